@@ -0,0 +1,22 @@
+#!/bin/bash
+# tinymem notify program for Codex CLI: Codex runs it after every agent turn with a JSON
+# payload as the last argument. Maps the thread to a tinymem session and records the turn.
+
+payload="${!#}"
+[ "$(echo "$payload" | jq -r '.type // empty')" = "agent-turn-complete" ] || exit 0
+
+url="http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/v1"
+auth="Authorization: Bearer $TINYMEM_TOKEN"
+post() { curl -s --max-time 2 -X POST "$url$1" -H "$auth" -H "Content-Type: application/json" -d "$2" > /dev/null 2>&1; }
+
+# Older Codex versions send no thread id; fall back to one session per working directory
+start=$(echo "$payload" | jq -c --arg cwd "$PWD" '{provider: "codex", external_id: (.["thread-id"] // .cwd // $cwd), agent: "codex", cwd: (.cwd // $cwd)}')
+sid=$(curl -s --max-time 2 -X POST "$url/start" -H "$auth" -H "Content-Type: application/json" -d "$start" | jq -r '.id // empty')
+[ -z "$sid" ] && exit 0
+
+echo "$payload" | jq -c '.["input-messages"][]? | {role: "user", text: .}' | while read -r entry; do
+  post "/session/$sid/transcript" "$entry"
+done
+reply=$(echo "$payload" | jq -c '{role: "assistant", text: (.["last-assistant-message"] // "")}')
+[ "$(echo "$reply" | jq -r '.text')" != "" ] && post "/session/$sid/transcript" "$reply"
+post "/session/$sid/hook" '{"kind":"post","task":"turn complete"}'
@@ -0,0 +1,33 @@
+#!/bin/bash
+# tinymem hook for Cursor agent: maps each conversation to a tinymem session and forwards
+# prompts, replies, shell/MCP calls and file edits. Never blocks the agent.
+
+input=$(cat)
+event=$(echo "$input" | jq -r '.hook_event_name // empty')
+
+# before* hooks must answer with a permission decision
+case "$event" in
+  before*) echo '{"continue": true, "permission": "allow"}' ;;
+esac
+
+url="http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/v1"
+auth="Authorization: Bearer $TINYMEM_TOKEN"
+post() { curl -s --max-time 2 -X POST "$url$1" -H "$auth" -H "Content-Type: application/json" -d "$2" > /dev/null 2>&1; }
+
+conversation=$(echo "$input" | jq -r '.conversation_id // empty')
+[ -z "$conversation" ] && exit 0
+start=$(echo "$input" | jq -c '{provider: "cursor", external_id: .conversation_id, agent: "cursor", cwd: (.workspace_roots[0] // "")}')
+sid=$(curl -s --max-time 2 -X POST "$url/start" -H "$auth" -H "Content-Type: application/json" -d "$start" | jq -r '.id // empty')
+[ -z "$sid" ] && exit 0
+
+case "$event" in
+  beforeSubmitPrompt) post "/session/$sid/transcript" "$(echo "$input" | jq -c '{role: "user", text: (.prompt // "")}')" ;;
+  afterAgentResponse) post "/session/$sid/transcript" "$(echo "$input" | jq -c '{role: "assistant", text: (.text // "")}')" ;;
+  beforeShellExecution) post "/session/$sid/hook" "$(echo "$input" | jq -c '{kind: "pre", task: "Shell", meta: {command: .command}}')" ;;
+  afterShellExecution) post "/session/$sid/hook" "$(echo "$input" | jq -c '{kind: "post", task: "Shell", meta: {command: .command}}')" ;;
+  beforeMCPExecution) post "/session/$sid/hook" "$(echo "$input" | jq -c '{kind: "pre", task: (.tool_name // "mcp"), meta: (.tool_input // {})}')" ;;
+  afterMCPExecution) post "/session/$sid/hook" "$(echo "$input" | jq -c '{kind: "post", task: (.tool_name // "mcp"), meta: {}}')" ;;
+  afterFileEdit) post "/session/$sid/hook" "$(echo "$input" | jq -c '{kind: "post", task: "Edit", meta: {file_path: .file_path}}')" ;;
+  stop) post "/session/$sid/hook" '{"kind":"post","task":"turn complete"}' ;;
+esac
+exit 0
@@ -0,0 +1,35 @@
+//! `cargo bench`: search tokenizing and snippets in memory, and store latencies against a scratch
+//! Redis at TINYMEM_BENCH_REDIS (default redis://127.0.0.1:6379/15). The store benches are skipped
+//! when that Redis can't be reached; the synthetic data they add is removed afterwards.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tinymem::bench::{self, Sizes, QUERIES};
+use tinymem::{search, Store};
+
+fn text() -> String {
+    QUERIES.iter().cycle().take(400).copied().collect::<Vec<_>>().join(" ")
+}
+
+fn tokenizing(c: &mut Criterion) {
+    let text = text();
+    c.bench_function("tokenize", |b| b.iter(|| search::tokenize(&text)));
+    c.bench_function("snippet", |b| b.iter(|| search::snippet(&text, "redis cache latency", 200)));
+}
+
+fn store(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let url = std::env::var("TINYMEM_BENCH_REDIS").unwrap_or_else(|_| "redis://127.0.0.1:6379/15".into());
+    let Ok(store) = rt.block_on(Store::new(&url)) else {
+        eprintln!("skipping store benches: can't reach {url}");
+        return;
+    };
+    let fixture = rt.block_on(bench::populate(&store, &Sizes::default())).unwrap();
+    let chain = fixture.chains[0].clone();
+    c.bench_function("search", |b| b.to_async(&rt).iter(|| bench::search(&store, QUERIES[0])));
+    c.bench_function("chain-load", |b| b.to_async(&rt).iter(|| bench::chain_load(&store, &chain)));
+    c.bench_function("refresh", |b| b.to_async(&rt).iter(|| bench::refresh(&store)));
+    rt.block_on(fixture.remove(&store)).unwrap();
+}
+
+criterion_group!(benches, tokenizing, store);
+criterion_main!(benches);
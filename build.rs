@@ -0,0 +1,10 @@
+fn main() {
+    // Generated gRPC stubs only when the `grpc` feature is on; uses the vendored protoc
+    // so no system protobuf install is needed.
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/tinymem.proto");
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path().expect("vendored protoc"));
+        tonic_build::compile_protos("proto/tinymem.proto").expect("compile tinymem.proto");
+    }
+}
@@ -9,14 +9,16 @@ tool=$(echo "$input" | jq -r '.tool_name')
 # Only intercept AskUserQuestion
 [ "$tool" != "AskUserQuestion" ] && exit 0
 
-# Extract question from tool input
-question=$(echo "$input" | jq -r '.tool_input.question // .tool_input.text // "Approval needed"')
+# Extract question and any answer choices from tool input (options may be strings or {label} objects)
+payload=$(echo "$input" | jq -c '(.tool_input.questions[0]? // .tool_input) as $q
+  | {question: ($q.question // $q.text // "Approval needed"),
+     options: [($q.options // [])[] | if type == "object" then .label else . end | strings]}')
 
 # Send to tinymem and wait for answer
-response=$(curl -s -X POST "http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/session/$TINYMEM_SESSION/ask" \
+response=$(curl -s -X POST "http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/v1/session/$TINYMEM_SESSION/ask" \
   -H "Authorization: Bearer $TINYMEM_TOKEN" \
   -H "Content-Type: application/json" \
-  -d "{\"question\": \"$question\"}" \
+  -d "$payload" \
   --max-time 310)
 
 answer=$(echo "$response" | jq -r '.answer // "yes"')
@@ -20,10 +20,30 @@ event=$(echo "$input" | jq -r '.hook_event_name')
 
 kind=$([[ "$event" == "PreToolUse" ]] && echo "pre" || echo "post")
 
-# Build JSON payload properly using jq to avoid escaping issues
-payload=$(echo "$input" | jq -c --arg kind "$kind" --arg task "$tool" '{kind: $kind, task: $task, meta: (.tool_input // {})}')
+# Build JSON payload properly using jq to avoid escaping issues; tool_use_id pairs pre/post of parallel calls
+# and a failed tool's is_error flags the session in the TUI
+payload=$(echo "$input" | jq -c --arg kind "$kind" --arg task "$tool" \
+  '{kind: $kind, task: $task, meta: ((.tool_input // {})
+    + (if .tool_use_id then {invocation_id: .tool_use_id} else {} end)
+    + (if ((.tool_response | objects | .is_error) // false) then {is_error: true} else {} end))}')
+
+url="http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/v1/session/$TINYMEM_SESSION/hook"
+
+# Tools matching $TINYMEM_APPROVE (a regex, e.g. '^(Bash|Write)$') wait for the operator's decision;
+# anything but an explicit deny (server down, timeout) lets the call through
+if [ "$kind" = "pre" ] && [ -n "$TINYMEM_APPROVE" ] && [[ "$tool" =~ $TINYMEM_APPROVE ]]; then
+  reply=$(curl -s --max-time 310 -X POST "$url" \
+    -H "Authorization: Bearer $TINYMEM_TOKEN" \
+    -H "Content-Type: application/json" \
+    -d "$(echo "$payload" | jq -c '. + {requires_approval: true}')")
+  if [ "$(echo "$reply" | jq -r '.decision // empty' 2>/dev/null)" = "deny" ]; then
+    echo "$reply" | jq -c '{hookSpecificOutput: {hookEventName: "PreToolUse", permissionDecision: "deny",
+      permissionDecisionReason: ("tinymem operator: " + (.reason // "denied"))}}'
+  fi
+  exit 0
+fi
 
-curl -s --max-time 2 -X POST "http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/session/$TINYMEM_SESSION/hook" \
+curl -s --max-time 2 -X POST "$url" \
   -H "Authorization: Bearer $TINYMEM_TOKEN" \
   -H "Content-Type: application/json" \
   -d "$payload" > /dev/null 2>&1 &
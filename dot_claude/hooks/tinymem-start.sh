@@ -8,9 +8,9 @@ port="${TINYMEM_PORT:-3000}"
 auth="Authorization: Bearer $TINYMEM_TOKEN"
 
 # Call /start endpoint - handles mapping lookup/creation in Redis
-response=$(curl -s -X POST "http://$host:$port/start" \
+response=$(curl -s -X POST "http://$host:$port/v1/start" \
   -H "$auth" -H "Content-Type: application/json" \
-  -d "{\"claude_session_id\":\"$claude_sid\",\"agent\":\"claude-code\",\"cwd\":\"$cwd\"}")
+  -d "{\"provider\":\"claude\",\"external_id\":\"$claude_sid\",\"agent\":\"claude-code\",\"cwd\":\"$cwd\"}")
 
 tinymem_sid=$(echo "$response" | jq -r '.id')
 
@@ -8,7 +8,7 @@ fi
 
 [ -z "$TINYMEM_SESSION" ] && exit 0
 
-curl -s -X POST "http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/session/$TINYMEM_SESSION/done" \
+curl -s -X POST "http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/v1/session/$TINYMEM_SESSION/done" \
   -H "Authorization: Bearer $TINYMEM_TOKEN" >> /tmp/tinymem-stop-debug.log 2>&1
 
 # Clean up
@@ -0,0 +1,44 @@
+#!/bin/bash
+# tinymem transcript hook: user prompts (UserPromptSubmit) and assistant replies (Stop)
+
+input=$(cat)
+
+# Get tinymem session: prefer env, then session-specific file
+if [ -z "$TINYMEM_SESSION" ]; then
+  claude_sid=$(echo "$input" | jq -r '.session_id // empty')
+  if [ -n "$claude_sid" ] && [ -f ".claude/.tinymem_session_$claude_sid" ]; then
+    TINYMEM_SESSION=$(cat ".claude/.tinymem_session_$claude_sid")
+  fi
+fi
+
+[ -z "$TINYMEM_SESSION" ] && exit 0
+
+event=$(echo "$input" | jq -r '.hook_event_name')
+
+base="http://${TINYMEM_HOST:-localhost}:${TINYMEM_PORT:-3000}/v1/session/$TINYMEM_SESSION"
+
+case "$event" in
+  UserPromptSubmit)
+    payload=$(echo "$input" | jq -c '{role: "user", text: (.prompt // "")}')
+    # Unread operator broadcasts; stdout here is added to the agent's context
+    curl -s --max-time 2 "$base/inbox" -H "Authorization: Bearer $TINYMEM_TOKEN" 2>/dev/null \
+      | jq -r '(.messages // []) | select(length > 0) | "Messages from the tinymem operator:", (.[] | "- " + .text)' 2>/dev/null
+    ;;
+  Stop)
+    # The reply isn't in the hook input; take the last assistant text from the transcript file
+    transcript=$(echo "$input" | jq -r '.transcript_path // empty')
+    [ -f "$transcript" ] || exit 0
+    text=$(tail -n 200 "$transcript" | jq -rs '[.[] | select(.type == "assistant") | .message.content[]? | select(.type == "text") | .text] | last // empty' 2>/dev/null)
+    [ -z "$text" ] && exit 0
+    payload=$(jq -cn --arg text "$text" '{role: "assistant", text: $text}')
+    ;;
+  *)
+    exit 0
+    ;;
+esac
+
+curl -s --max-time 2 -X POST "$base/transcript" \
+  -H "Authorization: Bearer $TINYMEM_TOKEN" \
+  -H "Content-Type: application/json" \
+  -d "$payload" > /dev/null 2>&1 &
+disown
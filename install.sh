@@ -34,6 +34,7 @@ cp "$SOURCE_DIR/hooks/tinymem-start.sh" "$HOOKS_DIR/"
 cp "$SOURCE_DIR/hooks/tinymem-hook.sh" "$HOOKS_DIR/"
 cp "$SOURCE_DIR/hooks/tinymem-stop.sh" "$HOOKS_DIR/"
 cp "$SOURCE_DIR/hooks/tinymem-ask-intercept.sh" "$HOOKS_DIR/"
+cp "$SOURCE_DIR/hooks/tinymem-transcript.sh" "$HOOKS_DIR/"
 chmod +x "$HOOKS_DIR"/tinymem-*.sh
 
 # Copy skills
@@ -67,8 +68,8 @@ if [ -f "$SETTINGS_FILE" ] && [ -s "$SETTINGS_FILE" ]; then
         .hooks.SessionStart = ((.hooks.SessionStart // []) | remove_tinymem) + $new.hooks.SessionStart |
         .hooks.PreToolUse = ((.hooks.PreToolUse // []) | remove_tinymem) + $new.hooks.PreToolUse |
         .hooks.PostToolUse = ((.hooks.PostToolUse // []) | remove_tinymem) + $new.hooks.PostToolUse |
-        # Remove any old tinymem Stop hooks (no longer used)
-        .hooks.Stop = ((.hooks.Stop // []) | remove_tinymem)
+        .hooks.UserPromptSubmit = ((.hooks.UserPromptSubmit // []) | remove_tinymem) + $new.hooks.UserPromptSubmit |
+        .hooks.Stop = ((.hooks.Stop // []) | remove_tinymem) + $new.hooks.Stop
       ' "$SETTINGS_FILE.bak" > "$SETTINGS_FILE.tmp" 2>/dev/null; then
         mv "$SETTINGS_FILE.tmp" "$SETTINGS_FILE"
         echo "Merged successfully (backup: settings.json.bak)"
@@ -0,0 +1,61 @@
+//! Glue for feeding agents other than Claude Code into tinymem (`tinymem adapt --agent ...`).
+//!
+//! Every adapter speaks the same HTTP ingest format, which custom integrations can use directly:
+//!
+//! - `POST /start` `{"provider": "<agent>", "external_id": "<native id>", "agent": "<agent>", "cwd": "..."}`
+//!   returns `{"id": "<tinymem session>", "reused": bool}`; repeated calls with the same provider and
+//!   id resume the same session, so adapters may call it on every event.
+//! - `POST /session/:id/hook` `{"kind": "pre"|"post"|"error"|"notification"|"user_prompt"|"stop", "task": "<tool>",
+//!   "meta": {...}}` (Claude Code event names like `PreToolUse` work too; other kinds are stored as sent); a
+//!   `meta.invocation_id` shared by a call's pre and post hooks keeps parallel calls of one tool apart; a pre
+//!   hook with `"requires_approval": true` blocks until the operator answers `{"decision": "approve"|"deny", "reason"}`
+//! - `POST /session/:id/transcript` `{"role": "user"|"assistant", "text": "..."}`
+//! - `POST /session/:id/usage` `{"input_tokens": n, "output_tokens": n, "model": "..."}`
+//! - `POST /session/:id/done`
+
+use std::path::{Path, PathBuf};
+use anyhow::{Context, Result};
+use crate::install::{bake_defaults, write_script};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Agent { Aider, Cursor, Codex }
+
+// (path relative to the output directory, template, executable)
+const AIDER: &[(&str, &str, bool)] = &[("tinymem-aider", include_str!("../adapters/aider/tinymem-aider"), true)];
+const CURSOR: &[(&str, &str, bool)] = &[
+    (".cursor/hooks.json", include_str!("../adapters/cursor/hooks.json"), false),
+    (".cursor/hooks/tinymem-cursor.sh", include_str!("../adapters/cursor/tinymem-cursor.sh"), true),
+];
+const CODEX: &[(&str, &str, bool)] = &[("tinymem-codex-notify.sh", include_str!("../adapters/codex/tinymem-codex-notify.sh"), true)];
+
+impl Agent {
+    fn files(self) -> &'static [(&'static str, &'static str, bool)] {
+        match self { Agent::Aider => AIDER, Agent::Cursor => CURSOR, Agent::Codex => CODEX }
+    }
+
+    /// What the user still has to do after the files are written
+    pub fn instructions(self, dir: &Path) -> String {
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+        match self {
+            Agent::Aider => format!("Run {} instead of aider (arguments are passed through)", dir.join("tinymem-aider").display()),
+            Agent::Cursor => format!("Cursor picks up {} on restart (merge by hand if the project already had hooks)", dir.join(".cursor/hooks.json").display()),
+            Agent::Codex => format!("Add to ~/.codex/config.toml:\n  notify = [\"bash\", \"{}\"]", dir.join("tinymem-codex-notify.sh").display()),
+        }
+    }
+}
+
+/// Writes the agent's glue under `dir` with the server address baked in and returns the files
+/// written; TINYMEM_HOST/PORT/TOKEN still override at runtime. Existing files are replaced.
+pub fn write_glue(agent: Agent, dir: &Path, host: &str, port: u16, token: &str) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for (rel, template, executable) in agent.files() {
+        let path = dir.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+        }
+        let contents = bake_defaults(template, host, port, token)?;
+        if *executable { write_script(&path, &contents)?; } else { std::fs::write(&path, contents)?; }
+        written.push(path);
+    }
+    Ok(written)
+}
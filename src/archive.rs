@@ -0,0 +1,108 @@
+//! Cold archive: finished sessions that have been quiet for long enough are written to one
+//! zstd-compressed JSONL file each under the archive directory and then deleted from Redis.
+//! `tinymem archive show <id>` reads them back without touching Redis. Chains retired with
+//! `DELETE /chain/delete/:name?archive=true` land under `chains/` the same way, for
+//! `tinymem archive chain <name>`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::models::{ChainLink, Hook, Session};
+use crate::store::Store;
+
+/// One line of an archive file: the session record first, then its hooks oldest first; a chain's
+/// file only has links
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Line {
+    Session(Session),
+    Hook(Hook),
+    Link(ChainLink),
+}
+
+/// `{dir}/{id}.jsonl.zst`; ids that could escape `dir` are refused
+pub fn path(dir: &Path, id: &str) -> Result<PathBuf> {
+    if id.is_empty() || id.starts_with('.') || id.contains(['/', '\\']) { bail!("session id '{id}' can't be used as a file name"); }
+    Ok(dir.join(format!("{id}.jsonl.zst")))
+}
+
+/// Archives and deletes every finished session last active before `ts`, returning their ids.
+/// A session is only deleted once its file is fully written.
+pub async fn compact(store: &Store, dir: &Path, ts: i64) -> Result<Vec<String>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating archive directory {}", dir.display()))?;
+    let mut archived = Vec::new();
+    for id in store.finished_before(ts).await? {
+        let Ok(file) = path(dir, &id) else { eprintln!("archive: skipping session {id}: not a usable file name"); continue };
+        let Some(session) = store.get_session(&id).await? else { continue };
+        let mut jsonl = serde_json::to_string(&Line::Session(session))? + "\n";
+        for hook in store.get_hooks(&id, isize::MAX).await? {
+            jsonl += &(serde_json::to_string(&Line::Hook(hook))? + "\n");
+        }
+        let tmp = file.with_extension("zst.tmp");
+        let mut out = std::fs::File::create(&tmp).with_context(|| format!("writing {}", tmp.display()))?;
+        out.write_all(&zstd::encode_all(jsonl.as_bytes(), 3)?)?;
+        out.sync_all()?;
+        std::fs::rename(&tmp, &file)?;
+        store.delete_session(&id).await?;
+        archived.push(id);
+    }
+    Ok(archived)
+}
+
+/// The archived session `id` and its hooks
+pub fn read(dir: &Path, id: &str) -> Result<(Session, Vec<Hook>)> {
+    let file = path(dir, id)?;
+    let bytes = std::fs::read(&file).with_context(|| format!("no archive for session {id} at {}", file.display()))?;
+    parse(&String::from_utf8(zstd::decode_all(&bytes[..])?)?).with_context(|| file.display().to_string())
+}
+
+/// The session and hooks in decompressed archive JSONL
+pub fn parse(text: &str) -> Result<(Session, Vec<Hook>)> {
+    let (mut session, mut hooks) = (None, Vec::new());
+    for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        match serde_json::from_str(line).with_context(|| format!("line {}", i + 1))? {
+            Line::Session(s) => session = Some(s),
+            Line::Hook(h) => hooks.push(h),
+            Line::Link(_) => bail!("line {}: chain link in a session archive", i + 1),
+        }
+    }
+    Ok((session.context("no session record")?, hooks))
+}
+
+/// `{dir}/chains/{name}.jsonl.zst`, percent-encoded so any chain name is one file name
+pub fn chain_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join("chains").join(format!("{}.jsonl.zst", urlencoding::encode(name)))
+}
+
+/// Appends chain `name`'s links to its archive file and returns how many there were. The caller deletes
+/// the chain afterwards; a chain archived under the same name again adds to the same file.
+pub async fn write_chain(store: &Store, dir: &Path, name: &str) -> Result<(PathBuf, usize)> {
+    let file = chain_path(dir, name);
+    std::fs::create_dir_all(dir.join("chains")).with_context(|| format!("creating archive directory {}", dir.display()))?;
+    let links = store.get_chain_links(name).await?;
+    let mut jsonl = String::new();
+    for link in &links {
+        jsonl += &(serde_json::to_string(&Line::Link(link.clone()))? + "\n");
+    }
+    // Each write is its own zstd frame, which decoding reads back to back
+    let mut out = std::fs::OpenOptions::new().create(true).append(true).open(&file).with_context(|| format!("writing {}", file.display()))?;
+    out.write_all(&zstd::encode_all(jsonl.as_bytes(), 3)?)?;
+    out.sync_all()?;
+    Ok((file, links.len()))
+}
+
+/// The links of archived chain `name`, in the order they were archived
+pub fn read_chain(dir: &Path, name: &str) -> Result<Vec<ChainLink>> {
+    let file = chain_path(dir, name);
+    let bytes = std::fs::read(&file).with_context(|| format!("no archive for chain {name} at {}", file.display()))?;
+    let text = String::from_utf8(zstd::decode_all(&bytes[..])?)?;
+    let mut links = Vec::new();
+    for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        match serde_json::from_str(line).with_context(|| format!("{} line {}", file.display(), i + 1))? {
+            Line::Link(link) => links.push(link),
+            _ => bail!("{} line {}: not a chain link", file.display(), i + 1),
+        }
+    }
+    Ok(links)
+}
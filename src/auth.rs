@@ -0,0 +1,245 @@
+//! Bearer auth beyond the static `--token`: with `--auth jwt`, HS256 JWTs signed with `--jwt-secret` are
+//! accepted too, for as long as their `exp` allows and for what their `scope` claim grants. Tokens can
+//! be minted and rotated by whatever holds the secret without restarting the server. Over HTTPS with
+//! `--tls-client-ca`, a verified client certificate authenticates on its own, scoped by its CN.
+
+use std::collections::HashMap;
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as B64, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+// Clock skew tolerated on exp and nbf
+const LEEWAY_SECS: i64 = 30;
+const MIN_SECRET_BYTES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mode {
+    /// Only the static --token
+    Static,
+    /// The static --token, plus JWTs signed with --jwt-secret
+    Jwt,
+}
+
+/// What a request needs from a JWT's or client certificate's scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// GET /export/corpus
+    Export,
+    /// Any other GET, or a POST that only reads
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// Who the token was issued to; audit entries, quotas and the access log record it as `jwt:<sub>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    pub exp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+    /// Space-separated: `read`, `write` and/or `export`
+    #[serde(default)]
+    pub scope: String,
+}
+
+/// Whether space-separated `scope` covers `access`: `write` allows everything, `read` everything but
+/// writes, `export` only the corpus export
+pub fn scope_allows(scope: &str, access: Access) -> bool {
+    scope.split_whitespace().any(|s| match s {
+        "write" => true,
+        "read" => access != Access::Write,
+        "export" => access == Access::Export,
+        _ => false,
+    })
+}
+
+impl Claims {
+    pub fn allows(&self, access: Access) -> bool { scope_allows(&self.scope, access) }
+}
+
+/// Subject CN of the client certificate a connection presented, set by [`crate::tls::serve`] once
+/// rustls has verified it
+#[derive(Debug, Clone)]
+pub struct ClientCert(pub String);
+
+/// `--client-cert-scope CN=SCOPES` rules. A CN without a rule only gets `read`, so whatever else the
+/// CA ever signed can't write; writers need a rule of their own.
+#[derive(Debug, Clone, Default)]
+pub struct CertScopes(HashMap<String, String>);
+
+impl CertScopes {
+    pub fn parse(specs: &[String]) -> Result<Self> {
+        let mut scopes = HashMap::new();
+        for spec in specs {
+            let Some((cn, scope)) = spec.split_once('=') else { bail!("invalid client cert scope '{spec}', expected CN=SCOPES"); };
+            if let Some(bad) = scope.split_whitespace().find(|s| !["read", "write", "export"].contains(s)) {
+                bail!("unknown scope '{bad}' in '{spec}' (read, write or export)");
+            }
+            scopes.insert(cn.trim().to_string(), scope.to_string());
+        }
+        Ok(Self(scopes))
+    }
+
+    pub fn allows(&self, cn: &str, access: Access) -> bool {
+        scope_allows(self.0.get(cn).map_or("read", String::as_str), access)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Header { alg: String, #[serde(default)] typ: Option<String> }
+
+/// Signs and verifies HS256 JWTs with one shared secret
+#[derive(Clone)]
+pub struct Jwt { mac: Hmac<Sha256> }
+
+impl Jwt {
+    pub fn new(secret: &str) -> Result<Self> {
+        if secret.len() < MIN_SECRET_BYTES { bail!("--jwt-secret must be at least {MIN_SECRET_BYTES} bytes"); }
+        Ok(Self { mac: Hmac::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length") })
+    }
+
+    fn sign(&self, signing_input: &str) -> Hmac<Sha256> {
+        let mut mac = self.mac.clone();
+        mac.update(signing_input.as_bytes());
+        mac
+    }
+
+    /// Checks the signature, `exp` and `nbf` (at `now`, in unix seconds); scopes are left to the caller
+    pub fn verify(&self, token: &str, now: i64) -> Result<Claims> {
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(sig), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            bail!("not a JWT");
+        };
+        let alg = serde_json::from_slice::<Header>(&B64.decode(header)?)?.alg;
+        // Only HS256, so a token can't pick "none" or an algorithm the secret wasn't meant for
+        if alg != "HS256" { bail!("unsupported JWT algorithm {alg}"); }
+        self.sign(&format!("{header}.{payload}")).verify_slice(&B64.decode(sig)?).map_err(|_| anyhow!("bad JWT signature"))?;
+        let claims: Claims = serde_json::from_slice(&B64.decode(payload)?)?;
+        if claims.exp + LEEWAY_SECS <= now { bail!("JWT expired"); }
+        if claims.nbf.is_some_and(|nbf| nbf > now + LEEWAY_SECS) { bail!("JWT not valid yet"); }
+        Ok(claims)
+    }
+
+    pub fn mint(&self, claims: &Claims) -> Result<String> {
+        let header = B64.encode(serde_json::to_vec(&Header { alg: "HS256".into(), typ: Some("JWT".into()) })?);
+        let payload = B64.encode(serde_json::to_vec(claims)?);
+        let sig = B64.encode(self.sign(&format!("{header}.{payload}")).finalize().into_bytes());
+        Ok(format!("{header}.{payload}.{sig}"))
+    }
+}
+
+/// The `sub` of a JWT-shaped bearer token, without verifying it. Only for labelling a caller the auth
+/// middleware has already let through.
+pub(crate) fn unverified_subject(token: &str) -> Option<String> {
+    let payload = token.split('.').nth(1)?;
+    serde_json::from_slice::<Claims>(&B64.decode(payload).ok()?).ok()?.sub
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn jwt() -> Jwt { Jwt::new("0123456789abcdef0123456789abcdef").unwrap() }
+
+    fn claims(exp: i64, nbf: Option<i64>, scope: &str) -> Claims {
+        Claims { sub: Some("test".into()), exp, nbf, iat: None, scope: scope.into() }
+    }
+
+    // A token with `alg` in its header, signed with the right secret
+    fn signed_with_alg(alg: &str) -> String {
+        let jwt = jwt();
+        let header = B64.encode(serde_json::to_vec(&Header { alg: alg.into(), typ: Some("JWT".into()) }).unwrap());
+        let payload = B64.encode(serde_json::to_vec(&claims(NOW + 60, None, "write")).unwrap());
+        let sig = B64.encode(jwt.sign(&format!("{header}.{payload}")).finalize().into_bytes());
+        format!("{header}.{payload}.{sig}")
+    }
+
+    #[test]
+    fn round_trip() {
+        let token = jwt().mint(&claims(NOW + 60, None, "read")).unwrap();
+        let verified = jwt().verify(&token, NOW).unwrap();
+        assert_eq!(verified.sub.as_deref(), Some("test"));
+        assert_eq!(verified.scope, "read");
+    }
+
+    #[test]
+    fn expiry_allows_leeway() {
+        let just_inside = jwt().mint(&claims(NOW - LEEWAY_SECS + 1, None, "read")).unwrap();
+        assert!(jwt().verify(&just_inside, NOW).is_ok());
+        let at_edge = jwt().mint(&claims(NOW - LEEWAY_SECS, None, "read")).unwrap();
+        assert!(jwt().verify(&at_edge, NOW).unwrap_err().to_string().contains("expired"));
+        let long_gone = jwt().mint(&claims(NOW - 3600, None, "read")).unwrap();
+        assert!(jwt().verify(&long_gone, NOW).is_err());
+    }
+
+    #[test]
+    fn not_before_allows_leeway() {
+        let at_edge = jwt().mint(&claims(NOW + 3600, Some(NOW + LEEWAY_SECS), "read")).unwrap();
+        assert!(jwt().verify(&at_edge, NOW).is_ok());
+        let future = jwt().mint(&claims(NOW + 3600, Some(NOW + LEEWAY_SECS + 1), "read")).unwrap();
+        assert!(jwt().verify(&future, NOW).unwrap_err().to_string().contains("not valid yet"));
+    }
+
+    #[test]
+    fn only_hs256_is_accepted() {
+        assert!(jwt().verify(&signed_with_alg("HS256"), NOW).is_ok());
+        for alg in ["none", "HS512", "RS256"] {
+            assert!(jwt().verify(&signed_with_alg(alg), NOW).unwrap_err().to_string().contains("unsupported"), "{alg}");
+        }
+        let header = B64.encode(br#"{"alg":"none"}"#);
+        let payload = B64.encode(serde_json::to_vec(&claims(NOW + 60, None, "write")).unwrap());
+        assert!(jwt().verify(&format!("{header}.{payload}."), NOW).is_err());
+    }
+
+    #[test]
+    fn tampering_is_rejected() {
+        let token = jwt().mint(&claims(NOW + 60, None, "read")).unwrap();
+        let [header, payload, sig]: [&str; 3] = token.split('.').collect::<Vec<_>>().try_into().unwrap();
+        let escalated = B64.encode(serde_json::to_vec(&claims(NOW + 60, None, "write")).unwrap());
+        assert!(jwt().verify(&format!("{header}.{escalated}.{sig}"), NOW).unwrap_err().to_string().contains("signature"));
+
+        let mut flipped = B64.decode(sig).unwrap();
+        flipped[0] ^= 1;
+        let flipped = B64.encode(flipped);
+        assert!(jwt().verify(&format!("{header}.{payload}.{flipped}"), NOW).unwrap_err().to_string().contains("signature"));
+
+        let other = Jwt::new("another secret that is long enough!!").unwrap().mint(&claims(NOW + 60, None, "read")).unwrap();
+        assert!(jwt().verify(&other, NOW).is_err());
+        assert!(jwt().verify("not.a.jwt.at-all", NOW).is_err());
+    }
+
+    #[test]
+    fn scopes() {
+        use Access::*;
+        let allows = |scope: &str| [Export, Read, Write].map(|a| claims(NOW, None, scope).allows(a));
+        assert_eq!(allows("write"), [true, true, true]);
+        assert_eq!(allows("read"), [true, true, false]);
+        assert_eq!(allows("export"), [true, false, false]);
+        assert_eq!(allows("read export"), [true, true, false]);
+        assert_eq!(allows(""), [false, false, false]);
+        assert_eq!(allows("admin"), [false, false, false]);
+    }
+
+    #[test]
+    fn unlisted_certs_only_read() {
+        use Access::*;
+        let scopes = CertScopes::parse(&["ci-bot=write".into(), "etl=export".into()]).unwrap();
+        assert!(scopes.allows("ci-bot", Write));
+        assert!(!scopes.allows("etl", Read) && scopes.allows("etl", Export));
+        assert!(scopes.allows("stranger", Read));
+        assert!(!scopes.allows("stranger", Write));
+        assert!(CertScopes::parse(&["x=admin".into()]).is_err());
+    }
+
+    #[test]
+    fn short_secrets_are_refused() {
+        assert!(Jwt::new("too short").is_err());
+    }
+}
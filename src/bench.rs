@@ -0,0 +1,147 @@
+//! `tinymem bench` and `benches/store.rs`: fill the store with synthetic sessions, chains and
+//! artifacts, time the reads the server and TUI repeat most (search, loading a chain, a full TUI
+//! refresh), then remove everything that was added.
+
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use crate::models::{now, short_id, Artifact, ChainLink, Hook, HookKind, SearchFilters, Session, Status};
+use crate::store::Store;
+
+// Content is drawn from a small vocabulary so queries match a realistic share of documents
+const WORDS: &[&str] = &[
+    "auth", "token", "refresh", "session", "cache", "redis", "index", "search", "query", "latency",
+    "migration", "schema", "deploy", "rollback", "config", "parser", "error", "retry", "timeout", "queue",
+    "worker", "batch", "report", "metrics", "trace", "span", "handler", "router", "client", "server",
+];
+const TOOLS: &[&str] = &["Read", "Edit", "Bash", "Grep", "Write"];
+pub const QUERIES: &[&str] = &["auth token", "redis cache latency", "migration rollback", "retry timeout queue", "parser error"];
+
+/// How much synthetic data to add
+#[derive(Debug, Clone, Copy)]
+pub struct Sizes { pub sessions: usize, pub hooks: usize, pub chains: usize, pub links: usize, pub artifacts: usize }
+
+impl Default for Sizes {
+    fn default() -> Self { Self { sessions: 50, hooks: 40, chains: 20, links: 10, artifacts: 50 } }
+}
+
+/// What [`populate`] added, so it can be read back and removed
+pub struct Fixture { pub sessions: Vec<String>, pub chains: Vec<String>, pub artifacts: Vec<String> }
+
+// Deterministic xorshift, so runs with the same sizes store the same content
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+
+    fn text(&mut self, words: usize) -> String {
+        (0..words).map(|_| WORDS[self.next() % WORDS.len()]).collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Adds `sizes` worth of sessions (with hooks), chains (with links) and artifacts (with extracted
+/// text), all named `bench-<run>-...` so they can't collide with real data
+pub async fn populate(store: &Store, sizes: &Sizes) -> Result<Fixture> {
+    let run = short_id();
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let ts = now();
+    let mut fixture = Fixture { sessions: vec![], chains: vec![], artifacts: vec![] };
+    for i in 0..sizes.sessions {
+        let id = format!("bench-{run}-s{i}");
+        let session = Session { id: id.clone(), name: None, agent: "bench".into(), cwd: "/tmp/bench".into(), status: Status::Active, created: ts, last_activity: ts, last_heartbeat: 0, git: None, project: None, summary: None };
+        store.create_session(&session).await?;
+        for h in 0..sizes.hooks {
+            let tool = TOOLS[rng.next() % TOOLS.len()];
+            let kind = if h % 2 == 0 { HookKind::Pre } else { HookKind::Post };
+            let meta = serde_json::json!({ "file_path": format!("src/{}.rs", WORDS[rng.next() % WORDS.len()]) });
+            store.add_hook(&id, &Hook { ts: ts + h as i64, kind, task: tool.into(), meta }).await?;
+        }
+        fixture.sessions.push(id);
+    }
+    for c in 0..sizes.chains {
+        let name = format!("bench-{run}-c{c}");
+        for l in 0..sizes.links {
+            let session_id = fixture.sessions.get(l % fixture.sessions.len().max(1)).cloned().unwrap_or_default();
+            store.save_chain_link(&ChainLink {
+                chain_name: name.clone(), session_id, slug: format!("step-{l}"), content: rng.text(120), ts: ts + l as i64,
+                redacted: false, pinned: false, importance: 0, artifact_ids: vec![], git: None, project: None,
+                completed: vec![], next_steps: vec![], decisions: vec![], files_touched: vec![], status: None,
+            }).await?;
+        }
+        fixture.chains.push(name);
+    }
+    for a in 0..sizes.artifacts {
+        let session_id = fixture.sessions.get(a % fixture.sessions.len().max(1)).cloned().unwrap_or_default();
+        let artifact = Artifact::new(format!("/tmp/bench/report-{a}.md"), format!("bench-{run} report {a}"), rng.text(12), session_id, vec!["bench".into()]);
+        let artifact = Artifact { id: format!("bench-{run}-a{a}"), ..artifact };
+        // Text first, so saving the artifact indexes it
+        store.set_artifact_text(&artifact.id, &rng.text(600)).await?;
+        store.save_artifact(&artifact).await?;
+        fixture.artifacts.push(artifact.id);
+    }
+    Ok(fixture)
+}
+
+impl Fixture {
+    /// Deletes everything [`populate`] added
+    pub async fn remove(&self, store: &Store) -> Result<()> {
+        for id in &self.sessions { store.delete_session(id).await?; }
+        for name in &self.chains { store.delete_chain(name).await?; }
+        for id in &self.artifacts { store.delete_artifact(id).await?; }
+        Ok(())
+    }
+}
+
+pub async fn search(store: &Store, query: &str) -> Result<()> {
+    store.global_search(query, 25, &SearchFilters::default()).await.map(|_| ())
+}
+
+pub async fn chain_load(store: &Store, chain: &str) -> Result<()> {
+    store.get_chain_links(chain).await.map(|_| ())
+}
+
+/// Everything the TUI reads when it reloads
+pub async fn refresh(store: &Store) -> Result<()> { crate::tui::reload(store).await }
+
+/// Samples of one operation
+pub struct Timing { pub name: &'static str, pub samples: Vec<Duration> }
+
+impl Timing {
+    /// `p` in 0..=100, by nearest rank
+    pub fn percentile(&self, p: usize) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort();
+        sorted.get((sorted.len() * p / 100).min(sorted.len().saturating_sub(1))).copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        self.samples.iter().sum::<Duration>() / self.samples.len().max(1) as u32
+    }
+}
+
+/// Times `iterations` rounds of search (cycling through [`QUERIES`]), chain loads and TUI refreshes
+pub async fn measure(store: &Store, fixture: &Fixture, iterations: usize) -> Result<Vec<Timing>> {
+    let mut timings = vec![
+        Timing { name: "search", samples: vec![] },
+        Timing { name: "chain-load", samples: vec![] },
+        Timing { name: "refresh", samples: vec![] },
+    ];
+    for i in 0..iterations {
+        let started = Instant::now();
+        search(store, QUERIES[i % QUERIES.len()]).await?;
+        timings[0].samples.push(started.elapsed());
+        if let Some(chain) = fixture.chains.get(i % fixture.chains.len().max(1)) {
+            let started = Instant::now();
+            chain_load(store, chain).await?;
+            timings[1].samples.push(started.elapsed());
+        }
+        let started = Instant::now();
+        refresh(store).await?;
+        timings[2].samples.push(started.elapsed());
+    }
+    Ok(timings)
+}
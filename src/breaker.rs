@@ -0,0 +1,233 @@
+//! Redis connection used by [`crate::Store`]: a single node, a cluster, or the master behind Sentinel.
+//! Connection errors are retried with backoff while the connection reconnects, and after repeated
+//! failures a circuit breaker fails every call fast for a while so the server can answer 503 instead
+//! of piling up slow errors.
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use anyhow::{bail, Context, Result};
+use redis::aio::{ConnectionLike, ConnectionManager};
+use redis::cluster_async::ClusterConnection;
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+use redis::{ClientTlsConfig, Cmd, ErrorKind, IntoConnectionInfo, Pipeline, RedisConnectionInfo, RedisError, RedisFuture, RedisResult, TlsCertificates, TlsMode, Value};
+use crate::store::RedisOptions;
+
+const RETRIES: u32 = 2;
+const FIRST_BACKOFF: Duration = Duration::from_millis(100);
+// Consecutive failed calls (after retries) that open the circuit, and how long it stays open
+const OPEN_AFTER: u32 = 3;
+const OPEN_FOR: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct Breaker { failures: u32, open_until: Option<Instant> }
+
+#[allow(clippy::large_enum_variant)] // one per store, cloned per call either way
+#[derive(Clone)]
+enum Backend { Single(ConnectionManager), Cluster(ClusterConnection) }
+
+impl ConnectionLike for Backend {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Backend::Single(c) => c.req_packed_command(cmd),
+            Backend::Cluster(c) => c.req_packed_command(cmd),
+        }
+    }
+
+    // A cluster pipeline goes to the node owning its first key, so store pipelines that touch
+    // several entities are sent command by command instead, each routed by its own key
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Backend::Single(c) => c.req_packed_commands(cmd, offset, count),
+            Backend::Cluster(c) => {
+                // offset is only nonzero for MULTI/EXEC transactions, which must stay one request
+                if offset > 0 { return c.req_packed_commands(cmd, offset, count); }
+                let mut c = c.clone();
+                Box::pin(async move {
+                    let mut out = Vec::with_capacity(count);
+                    for single in cmd.cmd_iter().take(count) { out.push(c.req_packed_command(single).await?); }
+                    Ok(out)
+                })
+            }
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self { Backend::Single(c) => c.get_db(), Backend::Cluster(c) => c.get_db() }
+    }
+}
+
+// Where to ask for the current master again after a failover
+struct SentinelTarget { sentinel: tokio::sync::Mutex<Sentinel>, service: String, node: SentinelNodeConnectionInfo }
+
+impl SentinelTarget {
+    async fn master(&self) -> RedisResult<ConnectionManager> {
+        let client = self.sentinel.lock().await.async_master_for(&self.service, Some(&self.node)).await?;
+        ConnectionManager::new(client).await
+    }
+}
+
+#[derive(Clone)]
+pub struct GuardedConn { backend: Arc<RwLock<Backend>>, sentinel: Option<Arc<SentinelTarget>>, breaker: Arc<Mutex<Breaker>> }
+
+fn is_connection_error(e: &RedisError) -> bool {
+    e.is_io_error() || e.is_connection_dropped() || e.is_connection_refusal() || e.is_timeout()
+}
+
+/// Whether a store call failed because Redis couldn't be reached (or the circuit is open), rather than
+/// because of what was sent
+pub fn is_unreachable(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<RedisError>().is_some_and(is_connection_error)
+}
+
+// `user:pass` / `:pass` from a URL's userinfo
+fn credentials(auth: &str) -> (Option<String>, Option<String>) {
+    let decode = |s: &str| (!s.is_empty()).then(|| urlencoding::decode(s).map(|d| d.into_owned()).unwrap_or_else(|_| s.to_string()));
+    match auth.split_once(':') {
+        Some((user, pass)) => (decode(user), decode(pass)),
+        None => (decode(auth), None),
+    }
+}
+
+// Custom CA and client certificate, when configured
+fn tls_certificates(opts: &RedisOptions) -> Result<Option<TlsCertificates>> {
+    let read = |p: &PathBuf| std::fs::read(p).with_context(|| format!("reading {}", p.display()));
+    let client_tls = match (&opts.client_cert, &opts.client_key) {
+        (Some(cert), Some(key)) => Some(ClientTlsConfig { client_cert: read(cert)?, client_key: read(key)? }),
+        (None, None) => None,
+        _ => bail!("a Redis client certificate needs its key and vice versa"),
+    };
+    let root_cert = opts.ca_cert.as_ref().map(read).transpose()?;
+    Ok((client_tls.is_some() || root_cert.is_some()).then_some(TlsCertificates { client_tls, root_cert }))
+}
+
+fn single_client(url: &str, opts: &RedisOptions) -> Result<redis::Client> {
+    let mut info = url.into_connection_info()?;
+    if opts.username.is_some() { info.redis.username = opts.username.clone(); }
+    if opts.password.is_some() { info.redis.password = opts.password.clone(); }
+    Ok(match tls_certificates(opts)? {
+        Some(certs) => redis::Client::build_with_tls(info, certs).context("custom Redis certificates need a rediss:// URL")?,
+        None => redis::Client::open(info)?,
+    })
+}
+
+impl GuardedConn {
+    /// Connects to `redis://` / `rediss://` (single node), `redis[s]+cluster://[auth@]host:port,host:port`
+    /// or `redis[s]+sentinel://[auth@]host:port,host:port/<service>[/db]`; auth applies to the data nodes
+    pub async fn connect(url: &str, opts: &RedisOptions) -> Result<Self> {
+        let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+        let (backend, sentinel) = if let Some(tls) = scheme.strip_suffix("+cluster") {
+            let (auth, hosts) = rest.rsplit_once('@').map_or((None, rest), |(a, h)| (Some(a), h));
+            let nodes: Vec<String> = hosts.trim_end_matches('/').split(',').filter(|h| !h.is_empty())
+                .map(|h| auth.map_or_else(|| format!("{tls}://{h}"), |a| format!("{tls}://{a}@{h}"))).collect();
+            if nodes.is_empty() { bail!("redis+cluster URL lists no nodes"); }
+            let mut builder = redis::cluster::ClusterClient::builder(nodes);
+            if let Some(u) = &opts.username { builder = builder.username(u.clone()); }
+            if let Some(p) = &opts.password { builder = builder.password(p.clone()); }
+            if let Some(certs) = tls_certificates(opts)? { builder = builder.certs(certs); }
+            (Backend::Cluster(builder.build()?.get_async_connection().await.context("connecting to redis cluster")?), None)
+        } else if let Some(tls) = scheme.strip_suffix("+sentinel") {
+            let (auth, rest) = rest.rsplit_once('@').map_or((None, rest), |(a, r)| (Some(a), r));
+            let (hosts, path) = rest.split_once('/').unwrap_or((rest, ""));
+            let mut path = path.split('/').filter(|p| !p.is_empty());
+            let service = path.next().context("redis+sentinel URL needs the master's service name: redis+sentinel://host:26379/mymaster")?;
+            let db = path.next().map(|d| d.parse()).transpose().context("invalid db in redis+sentinel URL")?.unwrap_or(0);
+            let (username, password) = auth.map(credentials).unwrap_or_default();
+            let (username, password) = (opts.username.clone().or(username), opts.password.clone().or(password));
+            let target = SentinelTarget {
+                sentinel: tokio::sync::Mutex::new(Sentinel::build(hosts.split(',').map(|h| format!("redis://{h}")).collect())?),
+                service: service.to_string(),
+                // Sentinels are always asked over plain TCP; rediss+sentinel only secures the master
+                node: SentinelNodeConnectionInfo {
+                    tls_mode: (tls == "rediss").then_some(TlsMode::Secure),
+                    redis_connection_info: Some(RedisConnectionInfo { db, username, password, ..Default::default() }),
+                },
+            };
+            let master = target.master().await.with_context(|| format!("resolving master {service} via sentinel"))?;
+            (Backend::Single(master), Some(Arc::new(target)))
+        } else {
+            (Backend::Single(ConnectionManager::new(single_client(url, opts)?).await?), None)
+        };
+        Ok(Self { backend: Arc::new(RwLock::new(backend)), sentinel, breaker: Arc::new(Mutex::new(Breaker::default())) })
+    }
+
+    /// Keys in different hash slots can't be used together in one script or transaction on a cluster
+    pub fn is_cluster(&self) -> bool { matches!(*self.backend.read().unwrap(), Backend::Cluster(_)) }
+
+    /// Time left before calls are let through again, while the circuit is open
+    pub fn open_for(&self) -> Option<Duration> {
+        let until = self.breaker.lock().unwrap().open_until?;
+        until.checked_duration_since(Instant::now())
+    }
+
+    // Once OPEN_FOR has passed the next call goes through as a probe; one more failure reopens it
+    fn record(&self, ok: bool) {
+        let mut b = self.breaker.lock().unwrap();
+        if ok { *b = Breaker::default(); return; }
+        b.failures += 1;
+        if b.failures >= OPEN_AFTER {
+            if b.open_until.is_none() { eprintln!("redis unavailable, failing fast for {}s", OPEN_FOR.as_secs()); }
+            b.open_until = Some(Instant::now() + OPEN_FOR);
+        }
+    }
+
+    // Behind Sentinel a dead or read-only node usually means the master moved; switch to the new one
+    async fn failover(&self) -> bool {
+        let Some(target) = &self.sentinel else { return false };
+        match target.master().await {
+            Ok(master) => {
+                *self.backend.write().unwrap() = Backend::Single(master);
+                true
+            }
+            Err(e) => {
+                eprintln!("sentinel: {e}");
+                false
+            }
+        }
+    }
+
+    async fn guarded<T, F: Future<Output = RedisResult<T>>>(&self, mut send: impl FnMut(Backend) -> F) -> RedisResult<T> {
+        if let Some(wait) = self.open_for() {
+            return Err(RedisError::from((ErrorKind::IoError, "redis unavailable", format!("circuit open, retry in {}s", wait.as_secs() + 1))));
+        }
+        let (mut attempt, mut delay, mut failed_over) = (0, FIRST_BACKOFF, false);
+        loop {
+            let backend = self.backend.read().unwrap().clone();
+            match send(backend).await {
+                Err(e) if is_connection_error(&e) && attempt < RETRIES => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                    delay *= 2;
+                }
+                Err(e) if is_connection_error(&e) || e.kind() == ErrorKind::ReadOnly => {
+                    if !failed_over && self.failover().await {
+                        failed_over = true;
+                        continue;
+                    }
+                    if is_connection_error(&e) { self.record(false); }
+                    return Err(e);
+                }
+                // Server-side errors (WRONGTYPE etc.) mean Redis is up
+                r => {
+                    self.record(true);
+                    return r;
+                }
+            }
+        }
+    }
+}
+
+impl ConnectionLike for GuardedConn {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        let this = &*self;
+        Box::pin(this.guarded(move |mut c| async move { c.req_packed_command(cmd).await }))
+    }
+
+    fn req_packed_commands<'a>(&'a mut self, cmd: &'a Pipeline, offset: usize, count: usize) -> RedisFuture<'a, Vec<Value>> {
+        let this = &*self;
+        Box::pin(this.guarded(move |mut c| async move { c.req_packed_commands(cmd, offset, count).await }))
+    }
+
+    fn get_db(&self) -> i64 { self.backend.read().unwrap().get_db() }
+}
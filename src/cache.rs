@@ -0,0 +1,89 @@
+//! Short-lived in-process cache for the reads the TUI and `list_chains` repeat every refresh: session
+//! records, the chain name list and artifact metadata. [`crate::Store`] drops entries on its own writes;
+//! [`ReadCache::listen`] also drops them on bus events, and the TTL bounds staleness from writes made
+//! by other processes sharing the Redis.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use crate::events::{Event, EventKind};
+use crate::models::{Artifact, Session};
+
+// Misses are cached too (None), so polling for a session that doesn't exist yet is also one round trip per TTL
+type Entry<T> = (Instant, Option<T>);
+
+pub struct ReadCache {
+    ttl: Duration,
+    sessions: Mutex<HashMap<String, Entry<Session>>>,
+    chain_names: Mutex<Option<(Instant, Vec<String>)>>,
+    artifacts: Mutex<HashMap<String, Entry<Artifact>>>,
+}
+
+impl ReadCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, sessions: Mutex::default(), chain_names: Mutex::default(), artifacts: Mutex::default() }
+    }
+
+    fn fresh(&self, at: Instant) -> bool { at.elapsed() < self.ttl }
+
+    /// `Some(None)` is a cached miss
+    pub fn session(&self, id: &str) -> Option<Option<Session>> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions.get(id).filter(|(at, _)| self.fresh(*at)).map(|(_, s)| s.clone())
+    }
+
+    pub fn put_session(&self, id: &str, session: Option<Session>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        // Expired entries are swept on insert so sessions that come and go don't accumulate
+        sessions.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        sessions.insert(id.to_string(), (Instant::now(), session));
+    }
+
+    pub fn forget_session(&self, id: &str) { self.sessions.lock().unwrap().remove(id); }
+
+    pub fn chain_names(&self) -> Option<Vec<String>> {
+        self.chain_names.lock().unwrap().as_ref().filter(|(at, _)| self.fresh(*at)).map(|(_, n)| n.clone())
+    }
+
+    pub fn put_chain_names(&self, names: Vec<String>) { *self.chain_names.lock().unwrap() = Some((Instant::now(), names)); }
+
+    pub fn forget_chain_names(&self) { *self.chain_names.lock().unwrap() = None; }
+
+    pub fn artifact(&self, id: &str) -> Option<Option<Artifact>> {
+        let artifacts = self.artifacts.lock().unwrap();
+        artifacts.get(id).filter(|(at, _)| self.fresh(*at)).map(|(_, a)| a.clone())
+    }
+
+    pub fn put_artifact(&self, id: &str, artifact: Option<Artifact>) {
+        let mut artifacts = self.artifacts.lock().unwrap();
+        artifacts.retain(|_, (at, _)| at.elapsed() < self.ttl);
+        artifacts.insert(id.to_string(), (Instant::now(), artifact));
+    }
+
+    pub fn forget_artifact(&self, id: &str) { self.artifacts.lock().unwrap().remove(id); }
+
+    fn forget_all(&self) {
+        self.sessions.lock().unwrap().clear();
+        self.artifacts.lock().unwrap().clear();
+        self.forget_chain_names();
+    }
+
+    /// Drops entries that `events` say have changed. A subscriber that falls behind can't tell what
+    /// it missed, so it clears everything.
+    pub fn listen(self: Arc<Self>, mut events: broadcast::Receiver<Event>) {
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(ev) => match ev.kind {
+                        EventKind::ChainLinkSaved => self.forget_chain_names(),
+                        EventKind::TodoChanged | EventKind::HandoffPending | EventKind::HandoffAccepted | EventKind::ArtifactIndexed | EventKind::SessionRejected => {}
+                        _ => self.forget_session(&ev.id),
+                    },
+                    Err(RecvError::Lagged(_)) => self.forget_all(),
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,133 @@
+//! Checkpoint reminders (`--checkpoint-after`): an active session that keeps working without saving a
+//! chain link is reminded through its inbox to save one, or with `--checkpoint-mode draft` gets a
+//! draft link written from its recent tool calls, so a long session doesn't end with nothing preserved.
+//! The server checks from its background sweep.
+
+use std::collections::HashMap;
+use anyhow::{bail, Context, Result};
+use crate::git;
+use crate::models::{now, ChainLink, Hook, HookKind, LinkMode, Session, Status};
+use crate::retention::parse_age;
+use crate::store::{LinkSave, Store};
+
+// How much of the session's recent work a draft lists
+const DRAFT_HOOKS: isize = 200;
+const DRAFT_CALLS: usize = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Mode {
+    /// Leave a note in the session's inbox
+    Remind,
+    /// Save a draft chain link from the session's recent tool calls, and say so in its inbox
+    Draft,
+}
+
+/// How long a session may work without a chain link, by project
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    default: Option<i64>,
+    projects: HashMap<String, Option<i64>>,
+}
+
+impl Policy {
+    /// Takes `AGE` rules for every project and `PROJECT=AGE` rules for one, e.g. `45m` and
+    /// `github.com/acme/api=20m`. Ages are written as for retention, and `off` exempts a project.
+    pub fn parse(rules: &[String]) -> Result<Self> {
+        let mut policy = Self::default();
+        for rule in rules {
+            let (project, age) = match rule.rsplit_once('=') {
+                Some((project, age)) => (Some(project.trim()), age.trim()),
+                None => (None, rule.trim()),
+            };
+            let age = parse_age(age).with_context(|| format!("invalid age in checkpoint rule '{rule}'"))?;
+            if age == Some(0) { bail!("checkpoint rule '{rule}' has a zero age; use off to disable it"); }
+            match project {
+                Some("") => bail!("checkpoint rule '{rule}' names no project"),
+                Some(project) => { policy.projects.insert(project.to_string(), age); }
+                None => policy.default = age,
+            }
+        }
+        Ok(policy)
+    }
+
+    pub fn is_off(&self) -> bool { self.default.is_none() && self.projects.values().all(Option::is_none) }
+
+    /// Seconds a session in `project` may go without a checkpoint, None when it is never reminded
+    pub fn after(&self, project: Option<&str>) -> Option<i64> {
+        project.and_then(|p| self.projects.get(p).copied()).unwrap_or(self.default)
+    }
+}
+
+/// Sessions acted on in one check
+#[derive(Debug, Default)]
+pub struct Report {
+    pub reminded: Vec<String>,
+    pub drafts: Vec<(String, ChainLink)>, // key and link of each draft saved
+}
+
+/// Reminds (or drafts for) every active session that has been working longer than its project allows
+/// since its last chain link or reminder. A session with no activity since then is left alone.
+pub async fn run(store: &Store, policy: &Policy, mode: Mode) -> Result<Report> {
+    let mut report = Report::default();
+    if policy.is_off() { return Ok(report); }
+    let now = now();
+    let sessions = store.list_sessions(&Status::Active, usize::MAX, 0).await?;
+    let ids: Vec<String> = sessions.iter().map(|s| s.id.clone()).collect();
+    for (session, (last, chain)) in sessions.into_iter().zip(store.last_checkpoints(&ids).await?) {
+        let Some(after) = policy.after(session.project.as_deref()) else { continue };
+        let since = last.unwrap_or(session.created);
+        if now - since < after || session.last_activity <= since { continue; }
+        let minutes = (now - since) / 60;
+        if mode == Mode::Draft {
+            let hooks: Vec<Hook> = store.get_hooks(&session.id, DRAFT_HOOKS).await?.into_iter().filter(|h| h.ts > since).collect();
+            if hooks.iter().any(|h| matches!(h.kind, HookKind::Pre | HookKind::Post)) {
+                let mut link = draft(&session, chain, &hooks, minutes, now);
+                // The checkout as of now, as for links the agent saves itself
+                link.git = git::detect_async(&session.cwd).await;
+                if let LinkSave::Saved(key, link) = store.save_chain_link_as(link, LinkMode::Create).await? {
+                    store.notify_session(&session.id, &format!(
+                        "no chain link saved in {minutes} minutes, so tinymem saved draft checkpoint {key} from your recent tool calls; \
+                         replace it with a real one using tinymem_chain_link")).await?;
+                    report.drafts.push((key, *link));
+                    continue;
+                }
+            }
+        }
+        store.notify_session(&session.id, &format!(
+            "no chain link saved in {minutes} minutes: save a checkpoint with tinymem_chain_link so this work outlasts the session")).await?;
+        store.mark_reminded(&session.id, now).await?;
+        report.reminded.push(session.id);
+    }
+    Ok(report)
+}
+
+// A link on the chain the session last wrote to (or one named after it) listing what it has done since
+fn draft(session: &Session, chain: Option<String>, hooks: &[Hook], minutes: i64, ts: i64) -> ChainLink {
+    let chain_name = chain.unwrap_or_else(|| format!("session-{}", session.name.as_deref().unwrap_or(&session.id).replace(':', "-")));
+    let calls = Hook::calls(hooks);
+    let mut content = format!("Draft checkpoint saved by tinymem after {minutes} minutes without one, from {} tool calls and events.\n", calls.len());
+    if calls.len() > DRAFT_CALLS { content += &format!("Latest {DRAFT_CALLS}:\n"); }
+    for h in &calls[calls.len().saturating_sub(DRAFT_CALLS)..] { content += &format!("- {}\n", h.line()); }
+    let mut files_touched: Vec<String> = Vec::new();
+    for f in hooks.iter().filter_map(Hook::file) {
+        if !files_touched.iter().any(|seen| seen == f) { files_touched.push(f.to_string()); }
+    }
+    ChainLink {
+        chain_name,
+        session_id: session.id.clone(),
+        slug: format!("draft-checkpoint-{ts}"),
+        content,
+        ts,
+        redacted: false,
+        pinned: false,
+        importance: 0,
+        artifact_ids: vec![],
+        git: None,
+        project: session.project.clone(),
+        completed: vec![],
+        next_steps: vec![],
+        decisions: vec![],
+        files_touched,
+        status: None,
+    }
+}
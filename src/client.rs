@@ -0,0 +1,109 @@
+//! Typed async client for the tinymem HTTP API (feature `client`).
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use ureq::http::StatusCode;
+use crate::models::{ChainLink, ChainSaveReq, CreateSessionReq, GlobalSearchReq, HookReq, SearchResult, Session};
+
+/// A non-2xx answer from the server; the client's methods return it inside their `anyhow::Error`, so
+/// callers can `downcast_ref::<ApiError>()` to act on the status
+#[derive(Debug)]
+pub struct ApiError { pub status: StatusCode, pub message: String, pub request_id: Option<String> }
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.status.as_u16(), self.message)?;
+        if let Some(id) = &self.request_id { write!(f, " (request {id})")?; }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[derive(Clone)]
+pub struct TinymemClient { agent: ureq::Agent, base: String, token: String }
+
+impl TinymemClient {
+    /// `base_url` like `http://localhost:3000`; empty token skips the auth header
+    pub fn new(base_url: &str, token: &str) -> Self {
+        let agent = ureq::Agent::config_builder().http_status_as_error(false).build().new_agent();
+        Self { agent, base: base_url.trim_end_matches('/').to_string(), token: token.to_string() }
+    }
+
+    pub async fn create_session(&self, req: &CreateSessionReq) -> Result<String> {
+        let body = self.send("POST", "/session", Some(json!(req))).await?;
+        body.get("id").and_then(|v| v.as_str()).map(String::from).ok_or_else(|| anyhow!("missing id in response"))
+    }
+
+    pub async fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        match self.send("GET", &format!("/session/{id}"), None).await {
+            Ok(body) => Ok(Some(serde_json::from_value(body)?)),
+            Err(e) if e.downcast_ref::<ApiError>().is_some_and(|e| e.status == StatusCode::NOT_FOUND) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn add_hook(&self, session_id: &str, req: &HookReq) -> Result<()> {
+        self.send("POST", &format!("/session/{session_id}/hook"), Some(json!(req))).await.map(|_| ())
+    }
+
+    /// Keeps a session that is alive but not calling tools from showing as disconnected
+    pub async fn heartbeat(&self, session_id: &str) -> Result<()> {
+        self.send("POST", &format!("/session/{session_id}/heartbeat"), None).await.map(|_| ())
+    }
+
+    pub async fn mark_done(&self, session_id: &str) -> Result<()> {
+        self.send("POST", &format!("/session/{session_id}/done"), None).await.map(|_| ())
+    }
+
+    /// Returns the Redis key the link was stored under
+    pub async fn save_chain_link(&self, session_id: &str, req: &ChainSaveReq) -> Result<String> {
+        let body = self.send("POST", &format!("/chain/{session_id}"), Some(json!(req))).await?;
+        body.get("saved").and_then(|v| v.as_str()).map(String::from).ok_or_else(|| anyhow!("missing saved key in response"))
+    }
+
+    pub async fn chain_links(&self, chain_name: &str) -> Result<Vec<ChainLink>> {
+        let body = self.send("GET", &format!("/chain/get/{}", urlencoding::encode(chain_name)), None).await?;
+        Ok(serde_json::from_value(body.get("links").cloned().unwrap_or(json!([])))?)
+    }
+
+    pub async fn search(&self, req: &GlobalSearchReq) -> Result<Vec<SearchResult>> {
+        let body = self.send("POST", "/search", Some(json!(req))).await?;
+        Ok(serde_json::from_value(body.get("results").cloned().unwrap_or(json!([])))?)
+    }
+
+    // ureq is blocking, so each call hops onto the blocking pool. Non-2xx statuses become an
+    // ApiError, shown as "{status}: {server error message} (request {id})".
+    async fn send(&self, method: &'static str, path: &str, body: Option<Value>) -> Result<Value> {
+        let (agent, url) = (self.agent.clone(), format!("{}{}{}", self.base, crate::server::API_PREFIX, path));
+        let auth = (!self.token.is_empty()).then(|| format!("Bearer {}", self.token));
+        tokio::task::spawn_blocking(move || -> Result<Value> {
+            let mut resp = if method == "GET" {
+                let mut req = agent.get(&url);
+                if let Some(a) = &auth { req = req.header("Authorization", a); }
+                req.call()?
+            } else {
+                let mut req = agent.post(&url);
+                if let Some(a) = &auth { req = req.header("Authorization", a); }
+                req.send_json(body.unwrap_or(json!({})))?
+            };
+            let status = resp.status();
+            let request_id = resp.headers().get("x-request-id").and_then(|v| v.to_str().ok()).map(String::from);
+            let text = resp.body_mut().read_to_string()?;
+            let value: Value = if text.is_empty() { Value::Null } else { serde_json::from_str(&text)? };
+            if !status.is_success() {
+                // Validation failures are problem+json: list the bad fields instead of the summary
+                let fields: Vec<String> = value.get("errors").and_then(|v| v.as_array()).into_iter().flatten()
+                    .filter_map(|e| Some(format!("{}: {}", e.get("field")?.as_str()?, e.get("message")?.as_str()?)))
+                    .collect();
+                let message = if fields.is_empty() {
+                    value.get("error").or_else(|| value.get("detail")).and_then(|v| v.as_str()).unwrap_or(&text).to_string()
+                } else {
+                    fields.join("; ")
+                };
+                return Err(ApiError { status, message, request_id }.into());
+            }
+            Ok(value)
+        }).await?
+    }
+}
@@ -0,0 +1,78 @@
+//! Near-duplicate detection and merging for memories and chain links, within one project at a time.
+
+use std::collections::{HashMap, HashSet};
+use anyhow::Result;
+use crate::models::{DuplicatePair, Memory};
+use crate::search::similarity;
+use crate::store::Store;
+
+// One comparable item: id, text compared, and the rank used to pick which copy survives
+struct Item { id: String, text: String, rank: (bool, u8, usize, i64) }
+
+/// Pairs of memories (or of chain links) of the same project whose content similarity is at least
+/// `threshold`, most similar first. Pairs at or above `merge_above` are merged into the higher-ranked copy: pinned,
+/// then importance, then longer content, then older.
+pub async fn run(store: &Store, threshold: f64, merge_above: Option<f64>) -> Result<Vec<DuplicatePair>> {
+    // Grouped by kind and project, so one project's copy is never folded into another's
+    let mut groups: HashMap<(bool, Option<String>), Vec<Item>> = HashMap::new();
+    for m in store.list_memories().await? {
+        groups.entry((false, m.project)).or_default().push(Item {
+            id: format!("memory:{}", m.id), rank: (m.pinned, m.importance, m.content.len(), -m.ts), text: format!("{} {}", m.title, m.content),
+        });
+    }
+    for name in store.list_chain_names().await? {
+        for l in store.get_chain_links(&name).await? {
+            groups.entry((true, l.project)).or_default().push(Item {
+                id: format!("chain:{}:{}", l.chain_name, l.ts), rank: (l.pinned, l.importance, l.content.len(), -l.ts), text: l.content,
+            });
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for items in groups.values() {
+        for (i, a) in items.iter().enumerate() {
+            for b in &items[i + 1..] {
+                let sim = similarity(&a.text, &b.text);
+                if sim < threshold { continue; }
+                let (keep, dup) = if a.rank >= b.rank { (a, b) } else { (b, a) };
+                pairs.push(DuplicatePair { keep: keep.id.clone(), duplicate: dup.id.clone(), similarity: sim, merged: false });
+            }
+        }
+    }
+    pairs.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+    let Some(merge_above) = merge_above else { return Ok(pairs) };
+    let mut removed = HashSet::new();
+    for p in pairs.iter_mut().filter(|p| p.similarity >= merge_above) {
+        if removed.contains(&p.keep) || removed.contains(&p.duplicate) { continue; }
+        merge(store, &p.keep, &p.duplicate).await?;
+        removed.insert(p.duplicate.clone());
+        p.merged = true;
+    }
+    Ok(pairs)
+}
+
+// The survivor inherits the strongest priority of the pair; the duplicate is deleted
+async fn merge(store: &Store, keep: &str, dup: &str) -> Result<()> {
+    if let (Some(k), Some(d)) = (keep.strip_prefix("memory:"), dup.strip_prefix("memory:")) {
+        let (Some(mut k), Some(d)) = (store.get_memory(k).await?, store.get_memory(d).await?) else { return Ok(()) };
+        absorb(&mut k, &d);
+        store.save_memory(&k).await?;
+        return store.delete_memory(&d.id).await;
+    }
+    let split = |id: &str| id.strip_prefix("chain:").and_then(|r| r.rsplit_once(':')).map(|(n, ts)| (n.to_string(), ts.to_string()));
+    let (Some((kn, kts)), Some((dn, dts))) = (split(keep), split(dup)) else { return Ok(()) };
+    let (Some(mut k), Some(d)) = (store.get_chain_link(&kn, &kts).await?, store.get_chain_link(&dn, &dts).await?) else { return Ok(()) };
+    k.pinned |= d.pinned;
+    k.importance = k.importance.max(d.importance);
+    store.save_chain_link(&k).await?;
+    store.delete_chain_link(&d.chain_name, d.ts).await
+}
+
+fn absorb(keep: &mut Memory, dup: &Memory) {
+    keep.pinned |= dup.pinned;
+    keep.importance = keep.importance.max(dup.importance);
+    // Keep the more lenient lifetime: no TTL beats any TTL, later beats earlier
+    keep.expires_at = keep.expires_at.zip(dup.expires_at).map(|(a, b)| a.max(b));
+    keep.decay_days = keep.decay_days.zip(dup.decay_days).map(|(a, b)| a.max(b));
+}
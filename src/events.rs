@@ -0,0 +1,85 @@
+//! In-process event bus. Handlers publish one [`Event`] per change and every consumer (the TUI, the
+//! gRPC change feed, webhook dispatch) subscribes on its own, so adding a consumer only needs a
+//! [`EventBus::subscribe`] call rather than another channel threaded through [`crate::AppState`].
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+use crate::models::now;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    SessionStarted,
+    SessionResumed,
+    SessionDone,
+    SessionDeleted,
+    Heartbeat,
+    Hook,
+    Transcript,
+    Usage,
+    AskPending,
+    AskClosed, // answered or timed out
+    ApprovalPending,
+    ApprovalClosed, // decided or timed out
+    ChainLinkSaved,
+    TodoChanged, // added, completed, reopened, assigned or deleted
+    HandoffPending,
+    HandoffAccepted,
+    ArtifactIndexed, // background text extraction finished or failed
+    SessionRejected, // a new session was refused by --max-active-sessions
+    SessionSummarized, // --summarize-url wrote a finished session's summary
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::SessionStarted => "session.started",
+            EventKind::SessionResumed => "session.resumed",
+            EventKind::SessionDone => "session.done",
+            EventKind::SessionDeleted => "session.deleted",
+            EventKind::Heartbeat => "session.heartbeat",
+            EventKind::Hook => "hook",
+            EventKind::Transcript => "transcript",
+            EventKind::Usage => "usage",
+            EventKind::AskPending => "ask.pending",
+            EventKind::AskClosed => "ask.closed",
+            EventKind::ApprovalPending => "approval.pending",
+            EventKind::ApprovalClosed => "approval.closed",
+            EventKind::ChainLinkSaved => "chain_link.saved",
+            EventKind::TodoChanged => "todo.changed",
+            EventKind::HandoffPending => "handoff.pending",
+            EventKind::HandoffAccepted => "handoff.accepted",
+            EventKind::ArtifactIndexed => "artifact.indexed",
+            EventKind::SessionRejected => "session.rejected",
+            EventKind::SessionSummarized => "session.summarized",
+        }
+    }
+}
+
+/// `id` is the session id, except for:
+/// - `chain_link.saved`: the link's key
+/// - `todo.changed`, `handoff.*` and `artifact.indexed`: the todo's, handoff's or artifact's id
+/// - `session.rejected`: the name the session asked for (empty when none)
+///
+/// `data` is the payload webhooks deliver for their events (`{"kind", "task"}` for hooks, `null` when
+/// there is nothing more to say)
+#[derive(Debug, Clone)]
+pub struct Event { pub kind: EventKind, pub id: String, pub ts: i64, pub data: Value }
+
+#[derive(Clone)]
+pub struct EventBus { tx: broadcast::Sender<Event> }
+
+impl EventBus {
+    /// `capacity` events are buffered per subscriber; slower ones see `RecvError::Lagged`
+    pub fn new(capacity: usize) -> Self {
+        Self { tx: broadcast::channel(capacity).0 }
+    }
+
+    /// No-op when nobody is subscribed
+    pub fn publish(&self, kind: EventKind, id: &str, data: Value) {
+        let _ = self.tx.send(Event { kind, id: id.to_string(), ts: now(), data });
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.tx.subscribe()
+    }
+}
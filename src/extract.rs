@@ -0,0 +1,115 @@
+//! Text extraction for artifacts, run on the blocking pool when an artifact is saved or reindexed.
+//! PDFs go through MuPDF, plain text and source files are read as is, and tables (CSV/TSV, plus
+//! spreadsheets with the `xlsx` feature) are summarized as their header and a sample of rows.
+
+use crate::models::DocMeta;
+
+// Extracted text is capped so one huge file can't dominate the index
+const MAX_CHARS: usize = 50000;
+// Tables keep their first rows plus up to this many more, spread evenly over the rest
+const HEAD_ROWS: usize = 20;
+const SAMPLED_ROWS: usize = 80;
+
+/// Extracted text, plus title, author and page offsets for PDFs
+pub fn extract_file_text(file_path: &str, file_type: &str) -> (String, Option<DocMeta>) {
+    match file_type {
+        "pdf" => {
+            match mupdf::Document::open(file_path) {
+                Ok(doc) => {
+                    let mut text = String::new();
+                    let page_count = doc.page_count().unwrap_or(0);
+                    let mut page_offsets = Vec::new();
+                    for i in 0..page_count {
+                        page_offsets.push(text.len());
+                        if let Ok(page) = doc.load_page(i) {
+                            if let Ok(tp) = page.to_text_page(mupdf::TextPageFlags::empty()) {
+                                for block in tp.blocks() {
+                                    for line in block.lines() {
+                                        for ch in line.chars() {
+                                            if let Some(c) = ch.char() {
+                                                text.push(c);
+                                            }
+                                        }
+                                        text.push('\n');
+                                    }
+                                }
+                            }
+                        }
+                        if text.len() > MAX_CHARS { break; }
+                    }
+                    let text: String = text.chars().take(MAX_CHARS).collect();
+                    page_offsets.retain(|&o| o < text.len());
+                    let info = |name| doc.metadata(name).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+                    let meta = DocMeta {
+                        title: info(mupdf::MetadataName::Title),
+                        author: info(mupdf::MetadataName::Author),
+                        page_count: page_count.max(0) as usize,
+                        page_offsets,
+                    };
+                    (text, Some(meta))
+                }
+                Err(_) => (String::new(), None)
+            }
+        }
+        "txt" | "md" | "json" | "yaml" | "yml" | "toml" | "rs" | "py" | "js" | "ts" => {
+            let text = std::fs::read_to_string(file_path)
+                .map(|s| s.chars().take(MAX_CHARS).collect())
+                .unwrap_or_default();
+            (text, None)
+        }
+        "csv" => (delimited_text(file_path, b','), None),
+        "tsv" => (delimited_text(file_path, b'\t'), None),
+        #[cfg(feature = "xlsx")]
+        "xlsx" | "xls" | "ods" => (spreadsheet_text(file_path), None),
+        _ => (String::new(), None)
+    }
+}
+
+fn delimited_text(file_path: &str, delimiter: u8) -> String {
+    let Ok(mut reader) = csv::ReaderBuilder::new().delimiter(delimiter).flexible(true).has_headers(false).from_path(file_path) else {
+        return String::new();
+    };
+    let rows = reader.records().filter_map(|r| r.ok()).map(|r| r.iter().map(str::to_string).collect());
+    table_text(rows)
+}
+
+#[cfg(feature = "xlsx")]
+fn spreadsheet_text(file_path: &str) -> String {
+    use calamine::Reader;
+    let Ok(mut workbook) = calamine::open_workbook_auto(file_path) else { return String::new() };
+    let mut text = String::new();
+    for name in workbook.sheet_names() {
+        let Ok(range) = workbook.worksheet_range(&name) else { continue };
+        text.push_str(&format!("Sheet: {name}\n"));
+        text.push_str(&table_text(range.rows().map(|row| row.iter().map(|c| c.to_string()).collect())));
+        text.push('\n');
+        if text.len() > MAX_CHARS { break; }
+    }
+    text.chars().take(MAX_CHARS).collect()
+}
+
+// The first row as column names, the row count, then the head rows and an evenly spaced sample of
+// the rest; the sample halves itself and doubles its stride whenever it fills, so one pass is enough
+fn table_text(mut rows: impl Iterator<Item = Vec<String>>) -> String {
+    let Some(header) = rows.next() else { return String::new() };
+    let (mut head, mut sample, mut stride, mut count) = (Vec::new(), Vec::new(), 1, 0);
+    for row in rows {
+        if head.len() < HEAD_ROWS {
+            head.push(row);
+        } else if (count - HEAD_ROWS).is_multiple_of(stride) {
+            sample.push(row);
+            if sample.len() > SAMPLED_ROWS {
+                sample = sample.into_iter().step_by(2).collect();
+                stride *= 2;
+            }
+        }
+        count += 1;
+    }
+    let line = |row: &[String]| row.iter().map(|c| c.trim()).collect::<Vec<_>>().join(" | ");
+    let mut text = format!("Columns: {}\nRows: {count}\n", line(&header));
+    for row in head.iter().chain(&sample) {
+        text.push_str(&line(row));
+        text.push('\n');
+    }
+    text.chars().take(MAX_CHARS).collect()
+}
@@ -0,0 +1,223 @@
+//! GraphQL endpoint (feature `graphql`) - lets dashboards fetch nested data in one round trip,
+//! e.g. `{ sessions { id agent hooks(limit: 5) { task } chains } }`.
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Enum, Json as GqlJson, Object, Result, Schema};
+use axum::{Extension, Json};
+use crate::models::{Artifact, ChainLink, Hook, SearchFilters, SearchResult, Session, Status};
+use crate::store::Store;
+
+pub type TinymemSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+// The schema has cycles (a link's session's chains' links...), so one read-only query could otherwise
+// fan out into unbounded Redis reads
+const MAX_DEPTH: usize = 8;
+const MAX_COMPLEXITY: usize = 250;
+
+pub fn schema(store: Store) -> TinymemSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription).data(store)
+        .limit_depth(MAX_DEPTH).limit_complexity(MAX_COMPLEXITY).finish()
+}
+
+pub async fn handler(Extension(schema): Extension<TinymemSchema>, Json(req): Json<async_graphql::Request>) -> Json<async_graphql::Response> {
+    Json(schema.execute(req).await)
+}
+
+#[derive(Enum, Copy, Clone, PartialEq, Eq)]
+enum SessionStatus { Active, Done }
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Active sessions by default; `status: DONE` walks history (newest first)
+    async fn sessions(&self, ctx: &Context<'_>, status: Option<SessionStatus>, #[graphql(default = 50)] limit: i32) -> Result<Vec<SessionNode>> {
+        let store = ctx.data::<Store>()?;
+        let ids = match status.unwrap_or(SessionStatus::Active) {
+            SessionStatus::Active => store.list_active().await?,
+            SessionStatus::Done => store.list_history(limit as isize).await?,
+        };
+        let mut sessions = Vec::new();
+        for id in ids.iter().take(limit.max(0) as usize) {
+            if let Some(s) = store.get_session(id).await? { sessions.push(SessionNode(s)); }
+        }
+        Ok(sessions)
+    }
+
+    async fn session(&self, ctx: &Context<'_>, id: String) -> Result<Option<SessionNode>> {
+        Ok(ctx.data::<Store>()?.get_session(&id).await?.map(SessionNode))
+    }
+
+    async fn chains(&self, ctx: &Context<'_>) -> Result<Vec<ChainNode>> {
+        Ok(ctx.data::<Store>()?.list_chain_names().await?.into_iter().map(ChainNode).collect())
+    }
+
+    async fn chain(&self, ctx: &Context<'_>, name: String) -> Result<Option<ChainNode>> {
+        let names = ctx.data::<Store>()?.list_chain_names().await?;
+        Ok(names.contains(&name).then_some(ChainNode(name)))
+    }
+
+    async fn artifacts(&self, ctx: &Context<'_>) -> Result<Vec<ArtifactNode>> {
+        Ok(ctx.data::<Store>()?.list_artifacts().await?.into_iter().map(ArtifactNode).collect())
+    }
+
+    /// `types` takes "chain_link" / "artifact"; `after` is inclusive and `before` exclusive (unix seconds)
+    #[allow(clippy::too_many_arguments)]
+    async fn search(
+        &self, ctx: &Context<'_>, query: String, #[graphql(default = 25)] limit: i32, types: Option<Vec<String>>,
+        session_id: Option<String>, chain_name: Option<String>, after: Option<i64>, before: Option<i64>,
+        tag: Option<String>, file_type: Option<String>, project: Option<String>,
+    ) -> Result<Vec<SearchResultNode>> {
+        let filters = SearchFilters { types, session_id, chain_name, after, before, tag, file_type, project };
+        let results = ctx.data::<Store>()?.global_search(&query, limit.max(0) as usize, &filters).await?;
+        Ok(results.into_iter().map(SearchResultNode).collect())
+    }
+}
+
+struct SessionNode(Session);
+
+#[Object(name = "Session")]
+impl SessionNode {
+    async fn id(&self) -> &str { &self.0.id }
+    async fn name(&self) -> Option<&str> { self.0.name.as_deref() }
+    async fn agent(&self) -> &str { &self.0.agent }
+    async fn cwd(&self) -> &str { &self.0.cwd }
+    async fn status(&self) -> SessionStatus {
+        match self.0.status { Status::Active => SessionStatus::Active, Status::Done => SessionStatus::Done }
+    }
+    async fn created(&self) -> i64 { self.0.created }
+    async fn last_activity(&self) -> i64 { self.0.last_activity }
+    async fn last_heartbeat(&self) -> i64 { self.0.last_heartbeat }
+    /// Written by --summarize-url once the session is done
+    async fn summary(&self) -> Option<&str> { self.0.summary.as_deref() }
+
+    /// Most recently started of the running tools
+    async fn active_tool(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        Ok(ctx.data::<Store>()?.get_active_tools(&self.0.id).await?.pop().map(|t| t.tool))
+    }
+
+    /// Every tool call in flight, longest-running first
+    async fn active_tools(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        Ok(ctx.data::<Store>()?.get_active_tools(&self.0.id).await?.into_iter().map(|t| t.tool).collect())
+    }
+
+    /// Most recent hooks, oldest first
+    async fn hooks(&self, ctx: &Context<'_>, #[graphql(default = 5)] limit: i32) -> Result<Vec<HookNode>> {
+        let hooks = ctx.data::<Store>()?.get_hooks(&self.0.id, limit.max(1) as isize).await?;
+        Ok(hooks.into_iter().map(HookNode).collect())
+    }
+
+    /// Names of chains this session saved links to
+    async fn chains(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        Ok(ctx.data::<Store>()?.session_related(&self.0.id).await?.0)
+    }
+
+    /// Artifacts this session saved, newest first
+    async fn artifacts(&self, ctx: &Context<'_>) -> Result<Vec<ArtifactNode>> {
+        Ok(ctx.data::<Store>()?.session_related(&self.0.id).await?.1.into_iter().map(ArtifactNode).collect())
+    }
+}
+
+struct HookNode(Hook);
+
+#[Object(name = "Hook")]
+impl HookNode {
+    async fn ts(&self) -> i64 { self.0.ts }
+    async fn kind(&self) -> &str { self.0.kind.as_str() }
+    async fn task(&self) -> &str { &self.0.task }
+    async fn meta(&self) -> GqlJson<serde_json::Value> { GqlJson(self.0.meta.clone()) }
+}
+
+struct ChainNode(String);
+
+#[Object(name = "Chain")]
+impl ChainNode {
+    async fn name(&self) -> &str { &self.0 }
+
+    /// Newest first
+    async fn links(&self, ctx: &Context<'_>, limit: Option<i32>) -> Result<Vec<ChainLinkNode>> {
+        let links = ctx.data::<Store>()?.get_chain_links(&self.0).await?;
+        let limit = limit.map_or(links.len(), |l| l.max(0) as usize);
+        Ok(links.into_iter().take(limit).map(ChainLinkNode).collect())
+    }
+
+    /// "active", "blocked" or "done"
+    async fn status(&self, ctx: &Context<'_>) -> Result<&'static str> {
+        Ok(ctx.data::<Store>()?.chain_status(&self.0).await?.as_str())
+    }
+
+    async fn link_count(&self, ctx: &Context<'_>) -> Result<usize> {
+        Ok(ctx.data::<Store>()?.get_chain_links(&self.0).await?.len())
+    }
+}
+
+struct ChainLinkNode(ChainLink);
+
+#[Object(name = "ChainLink")]
+impl ChainLinkNode {
+    async fn chain_name(&self) -> &str { &self.0.chain_name }
+    async fn session_id(&self) -> &str { &self.0.session_id }
+    async fn slug(&self) -> &str { &self.0.slug }
+    async fn content(&self) -> &str { &self.0.content }
+    async fn ts(&self) -> i64 { self.0.ts }
+    async fn redacted(&self) -> bool { self.0.redacted }
+    async fn pinned(&self) -> bool { self.0.pinned }
+    async fn importance(&self) -> u8 { self.0.importance }
+    async fn artifact_ids(&self) -> &[String] { &self.0.artifact_ids }
+    async fn completed(&self) -> &[String] { &self.0.completed }
+    async fn next_steps(&self) -> &[String] { &self.0.next_steps }
+    async fn decisions(&self) -> &[String] { &self.0.decisions }
+    async fn files_touched(&self) -> &[String] { &self.0.files_touched }
+    async fn status(&self) -> Option<&'static str> { self.0.status.map(|s| s.as_str()) }
+
+    async fn session(&self, ctx: &Context<'_>) -> Result<Option<SessionNode>> {
+        Ok(ctx.data::<Store>()?.get_session(&self.0.session_id).await?.map(SessionNode))
+    }
+
+    async fn artifacts(&self, ctx: &Context<'_>) -> Result<Vec<ArtifactNode>> {
+        let store = ctx.data::<Store>()?;
+        let mut out = Vec::new();
+        for id in &self.0.artifact_ids {
+            if let Some(a) = store.get_artifact(id).await? { out.push(ArtifactNode(a)); }
+        }
+        Ok(out)
+    }
+}
+
+struct ArtifactNode(Artifact);
+
+#[Object(name = "Artifact")]
+impl ArtifactNode {
+    async fn id(&self) -> &str { &self.0.id }
+    async fn file_path(&self) -> &str { &self.0.file_path }
+    async fn title(&self) -> &str { &self.0.title }
+    async fn description(&self) -> &str { &self.0.description }
+    async fn session_id(&self) -> &str { &self.0.session_id }
+    async fn file_type(&self) -> &str { &self.0.file_type }
+    async fn ts(&self) -> i64 { self.0.ts }
+    async fn tags(&self) -> &[String] { &self.0.tags }
+    async fn redacted(&self) -> bool { self.0.redacted }
+
+    async fn text(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        Ok(ctx.data::<Store>()?.get_artifact_text(&self.0.id).await?)
+    }
+
+    /// Chain links (chain:name:slug) whose content mentions this artifact's id
+    async fn mentioned_in(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        Ok(ctx.data::<Store>()?.artifact_mentions(&self.0.id).await?)
+    }
+}
+
+struct SearchResultNode(SearchResult);
+
+#[Object(name = "SearchResult")]
+impl SearchResultNode {
+    async fn result_type(&self) -> &str { &self.0.result_type }
+    async fn id(&self) -> &str { &self.0.id }
+    async fn title(&self) -> &str { &self.0.title }
+    async fn score(&self) -> f64 { self.0.score }
+    async fn preview(&self) -> &str { &self.0.preview }
+    /// `[start, end]` byte ranges of matched terms within `preview`
+    async fn highlights(&self) -> Vec<Vec<usize>> { self.0.highlights.iter().map(|h| h.to_vec()).collect() }
+    /// PDF page the preview was cut from
+    async fn page(&self) -> Option<usize> { self.0.page }
+}
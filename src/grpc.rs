@@ -0,0 +1,207 @@
+//! gRPC API (feature `grpc`) - the core HTTP operations over tonic, served on its own port.
+//! Schema: `proto/tinymem.proto`.
+
+use std::pin::Pin;
+use futures::Stream;
+use serde_json::json;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+use tonic::{metadata::MetadataValue, Request, Response, Status};
+use crate::events::EventKind;
+use crate::git;
+use crate::project::project_id;
+use crate::models::{self, now, short_id, ChainLink, Hook, HookKind, LinkMode, QuotaKind};
+use crate::server::{claim_session_slot, reserve_quota, token_subject, AppState};
+use crate::store::{LinkSave, Slot};
+
+pub mod pb { tonic::include_proto!("tinymem.v1"); }
+use pb::tinymem_server::{Tinymem, TinymemServer};
+
+pub struct GrpcService { state: AppState }
+
+fn internal(e: anyhow::Error) -> Status { Status::internal(e.to_string()) }
+
+impl From<models::Session> for pb::Session {
+    fn from(s: models::Session) -> Self {
+        let status = match s.status { models::Status::Active => "Active", models::Status::Done => "Done" };
+        Self { id: s.id, name: s.name, agent: s.agent, cwd: s.cwd, status: status.into(), created: s.created, last_activity: s.last_activity }
+    }
+}
+
+impl From<Hook> for pb::Hook {
+    fn from(h: Hook) -> Self { Self { ts: h.ts, kind: h.kind.as_str().to_string(), task: h.task, meta_json: h.meta.to_string() } }
+}
+
+impl From<ChainLink> for pb::ChainLink {
+    fn from(l: ChainLink) -> Self {
+        Self {
+            chain_name: l.chain_name, session_id: l.session_id, slug: l.slug, content: l.content, ts: l.ts,
+            completed: l.completed, next_steps: l.next_steps, decisions: l.decisions, files_touched: l.files_touched,
+        }
+    }
+}
+
+impl From<models::SearchResult> for pb::SearchResult {
+    fn from(r: models::SearchResult) -> Self {
+        let highlights = r.highlights.iter().map(|h| pb::Highlight { start: h[0] as u32, end: h[1] as u32 }).collect();
+        let page = r.page.map(|p| p as u32);
+        Self { result_type: r.result_type, id: r.id, title: r.title, score: r.score, preview: r.preview, highlights, page }
+    }
+}
+
+#[tonic::async_trait]
+impl Tinymem for GrpcService {
+    async fn create_session(&self, req: Request<pb::CreateSessionRequest>) -> Result<Response<pb::SessionId>, Status> {
+        let r = req.into_inner();
+        let id = r.name.clone().unwrap_or_else(short_id);
+        let slot = claim_session_slot(&self.state, &id, r.name.as_deref(), &r.agent).await.map_err(internal)?;
+        if let Slot::Full(active) = slot {
+            return Err(Status::resource_exhausted(format!("{active} sessions are active, the limit is {}", self.state.max_active_sessions)));
+        }
+        let ts = now();
+        let git = git::detect_async(&r.cwd).await;
+        let project = project_id(&r.cwd, git.as_ref());
+        let session = models::Session { id: id.clone(), name: r.name, agent: r.agent, cwd: r.cwd, status: models::Status::Active, created: ts, last_activity: ts, last_heartbeat: 0, git, project, summary: None };
+        if let Err(e) = self.state.store.create_session(&session).await {
+            if slot == Slot::Taken { let _ = self.state.store.release_active(&id).await; }
+            return Err(internal(e));
+        }
+        self.state.events.publish(EventKind::SessionStarted, &id, json!(session));
+        Ok(Response::new(pb::SessionId { id }))
+    }
+
+    async fn get_session(&self, req: Request<pb::SessionId>) -> Result<Response<pb::Session>, Status> {
+        match self.state.store.get_session(&req.into_inner().id).await.map_err(internal)? {
+            Some(s) => Ok(Response::new(s.into())),
+            None => Err(Status::not_found("session not found")),
+        }
+    }
+
+    async fn list_sessions(&self, _: Request<pb::Empty>) -> Result<Response<pb::SessionIds>, Status> {
+        let ids = self.state.store.list_active().await.map_err(internal)?;
+        Ok(Response::new(pb::SessionIds { ids }))
+    }
+
+    async fn mark_done(&self, req: Request<pb::SessionId>) -> Result<Response<pb::Empty>, Status> {
+        let id = req.into_inner().id;
+        self.state.store.mark_done(&id).await.map_err(internal)?;
+        self.state.chat.send(format!("✅ tinymem session `{id}` finished"));
+        self.state.events.publish(EventKind::SessionDone, &id, json!({ "id": id }));
+        Ok(Response::new(pb::Empty {}))
+    }
+
+    async fn add_hook(&self, req: Request<pb::AddHookRequest>) -> Result<Response<pb::Empty>, Status> {
+        let r = req.into_inner();
+        let meta = if r.meta_json.is_empty() { json!({}) } else {
+            serde_json::from_str(&r.meta_json).map_err(|e| Status::invalid_argument(format!("meta_json: {e}")))?
+        };
+        let hook = Hook { ts: now(), kind: HookKind::parse(&r.kind), task: r.task.clone(), meta };
+        let _ = self.state.store.track_tools(&r.session_id, &hook).await;
+        let journal = &self.state.journal;
+        if journal.backlog() > 0 || self.state.store.add_hook(&r.session_id, &hook).await.is_err() {
+            journal.append(&r.session_id, &hook).await.map_err(internal)?;
+        }
+        self.state.events.publish(EventKind::Hook, &r.session_id, json!({ "kind": r.kind, "task": r.task, "error": hook.is_error() }));
+        Ok(Response::new(pb::Empty {}))
+    }
+
+    async fn get_hooks(&self, req: Request<pb::GetHooksRequest>) -> Result<Response<pb::Hooks>, Status> {
+        let r = req.into_inner();
+        let limit = if r.limit > 0 { r.limit as isize } else { 50 };
+        let hooks = self.state.store.get_hooks(&r.session_id, limit).await.map_err(internal)?;
+        Ok(Response::new(pb::Hooks { hooks: hooks.into_iter().map(Into::into).collect() }))
+    }
+
+    async fn save_chain_link(&self, req: Request<pb::SaveChainLinkRequest>) -> Result<Response<pb::SaveChainLinkResponse>, Status> {
+        let subjects = [format!("session:{}", req.get_ref().session_id), token_subject(req.metadata().get("authorization").and_then(|v| v.to_str().ok()))];
+        let r = req.into_inner();
+        let mode = match r.mode.as_str() {
+            "" => LinkMode::Create,
+            m => LinkMode::parse(m).ok_or_else(|| Status::invalid_argument(format!("unknown mode '{m}' (expected create, upsert or append)")))?,
+        };
+        let (git, project) = match self.state.store.get_session(&r.session_id).await.map_err(internal)? {
+            Some(session) => (git::detect_async(&session.cwd).await, session.project),
+            None => (None, None),
+        };
+        if let Some(why) = reserve_quota(&self.state, &subjects, QuotaKind::Links, "", 1).await.map_err(internal)? {
+            return Err(Status::resource_exhausted(why));
+        }
+        let link = ChainLink {
+            chain_name: r.chain_name, session_id: r.session_id, slug: r.slug, content: r.content, ts: now(), redacted: false, pinned: false,
+            importance: 0, artifact_ids: vec![], git, project,
+            completed: r.completed, next_steps: r.next_steps, decisions: r.decisions, files_touched: r.files_touched, status: None,
+        };
+        let saved = self.state.store.save_chain_link_as(link, mode).await;
+        if !matches!(saved, Ok(LinkSave::Saved(..))) { let _ = self.state.store.release_reservation(&subjects, QuotaKind::Links, "", 1).await; }
+        let (key, link) = match saved.map_err(internal)? {
+            LinkSave::Saved(key, link) => (key, link),
+            LinkSave::Taken(key) => return Err(Status::already_exists(format!("slug is taken by {key}; use mode upsert or append"))),
+            LinkSave::Ambiguous(keys) => return Err(Status::failed_precondition(format!("slug is shared by {}", keys.join(", ")))),
+        };
+        self.state.events.publish(EventKind::ChainLinkSaved, &key, json!({ "key": key, "link": link }));
+        Ok(Response::new(pb::SaveChainLinkResponse { key }))
+    }
+
+    async fn get_chain_links(&self, req: Request<pb::ChainName>) -> Result<Response<pb::ChainLinks>, Status> {
+        let links = self.state.store.get_chain_links(&req.into_inner().name).await.map_err(internal)?;
+        Ok(Response::new(pb::ChainLinks { links: links.into_iter().map(Into::into).collect() }))
+    }
+
+    async fn list_chains(&self, _: Request<pb::Empty>) -> Result<Response<pb::ChainNames>, Status> {
+        let names = self.state.store.list_chain_names().await.map_err(internal)?;
+        Ok(Response::new(pb::ChainNames { names }))
+    }
+
+    async fn search(&self, req: Request<pb::SearchRequest>) -> Result<Response<pb::SearchResults>, Status> {
+        let r = req.into_inner();
+        let limit = if r.limit > 0 { r.limit as usize } else { 25 };
+        let filters = models::SearchFilters {
+            types: (!r.types.is_empty()).then_some(r.types), session_id: r.session_id, chain_name: r.chain_name, after: r.after, before: r.before,
+            tag: r.tag, file_type: r.file_type, project: r.project,
+        };
+        let results = self.state.store.global_search(&r.query, limit, &filters).await.map_err(internal)?;
+        Ok(Response::new(pb::SearchResults { results: results.into_iter().map(Into::into).collect() }))
+    }
+
+    type WatchEventsStream = Pin<Box<dyn Stream<Item = Result<pb::Event, Status>> + Send>>;
+
+    async fn watch_events(&self, req: Request<pb::WatchRequest>) -> Result<Response<Self::WatchEventsStream>, Status> {
+        let kinds = req.into_inner().kinds;
+        // Lagged subscribers silently skip what they missed rather than erroring the stream
+        let stream = BroadcastStream::new(self.state.events.subscribe()).filter_map(move |ev| match ev {
+            Ok(ev) if kinds.is_empty() || kinds.iter().any(|k| k == ev.kind.as_str()) => {
+                Some(Ok(pb::Event { kind: ev.kind.as_str().to_string(), id: ev.id, ts: ev.ts }))
+            }
+            _ => None,
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[allow(clippy::result_large_err)] // interceptor signature is fixed by tonic
+pub async fn run(state: AppState, port: u16) -> anyhow::Result<()> {
+    let expected: Option<MetadataValue<_>> = (!state.token.is_empty())
+        .then(|| format!("Bearer {}", state.token).parse()).transpose()?;
+    // The interceptor can't see which call it guards, so a JWT needs the write scope for any of them
+    let jwt = state.jwt.clone();
+    let open = expected.is_none() && jwt.is_none();
+    let auth = move |req: Request<()>| {
+        let a = req.metadata().get("authorization");
+        if open || (expected.is_some() && a == expected.as_ref()) { return Ok(req); }
+        let token = a.and_then(|v| v.to_str().ok()).and_then(|v| v.strip_prefix("Bearer "));
+        match (&jwt, token) {
+            (Some(jwt), Some(t)) => match jwt.verify(t, crate::models::now()) {
+                Ok(claims) if claims.allows(crate::auth::Access::Write) => Ok(req),
+                Ok(_) => Err(Status::permission_denied("token scope does not allow gRPC calls")),
+                Err(e) => Err(Status::unauthenticated(e.to_string())),
+            },
+            _ => Err(Status::unauthenticated("invalid token")),
+        }
+    };
+    let addr = format!("0.0.0.0:{port}").parse()?;
+    eprintln!("gRPC listening on {addr}");
+    tonic::transport::Server::builder()
+        .add_service(TinymemServer::with_interceptor(GrpcService { state }, auth))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
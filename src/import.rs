@@ -0,0 +1,159 @@
+//! `tinymem import`: brings memories exported from other agent-memory tools, or a directory of
+//! markdown notes, into the store as memories, or as the links of one chain with `--chain`.
+
+use std::collections::HashSet;
+use std::path::Path;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use crate::models::{now, truncate_chars, ChainLink, Memory};
+use crate::store::Store;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// mem0 export JSON: a list of memories or `{"results": [...]}`
+    Mem0,
+    /// Qdrant points, one JSON object per line, with the text in the payload
+    QdrantJsonl,
+    /// Markdown files anywhere under a directory, one note per file
+    PlainMdDir,
+}
+
+impl Format {
+    fn as_str(self) -> &'static str {
+        match self { Format::Mem0 => "mem0", Format::QdrantJsonl => "qdrant-jsonl", Format::PlainMdDir => "plain-md-dir" }
+    }
+}
+
+/// One note read from an export
+#[derive(Debug, Clone)]
+pub struct Note { pub source_id: String, pub title: String, pub content: String, pub ts: i64 }
+
+/// Where imported notes go: memories saved under `session_id`, or links of `chain`. A `project` of
+/// "." is the current directory's.
+#[derive(Debug, Clone)]
+pub struct Target { pub session_id: String, pub chain: Option<String>, pub project: Option<String> }
+
+// Payload keys other tools keep the text, title and time under, most specific first
+const TEXT_KEYS: &[&str] = &["memory", "text", "content", "page_content", "document", "data"];
+const TITLE_KEYS: &[&str] = &["title", "name"];
+const TIME_KEYS: &[&str] = &["updated_at", "created_at", "timestamp", "ts"];
+
+/// Reads every note in the export at `path`
+pub fn read(format: Format, path: &Path) -> Result<Vec<Note>> {
+    let text = |p: &Path| std::fs::read_to_string(p).with_context(|| format!("reading {}", p.display()));
+    match format {
+        Format::Mem0 => {
+            let v: Value = serde_json::from_str(&text(path)?).context("mem0 export is not JSON")?;
+            let items = v.as_array().or_else(|| v.get("results").and_then(|r| r.as_array()))
+                .or_else(|| v.get("memories").and_then(|r| r.as_array()))
+                .context("mem0 export should be a list of memories or {\"results\": [...]}")?;
+            Ok(items.iter().filter_map(json_note).collect())
+        }
+        Format::QdrantJsonl => {
+            let mut notes = Vec::new();
+            for (i, line) in text(path)?.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+                let point: Value = serde_json::from_str(line).with_context(|| format!("line {} is not JSON", i + 1))?;
+                let mut payload = point.get("payload").cloned().unwrap_or(Value::Null);
+                if let Some(id) = point.get("id") { payload["id"] = id.clone(); }
+                notes.extend(json_note(&payload));
+            }
+            Ok(notes)
+        }
+        Format::PlainMdDir => {
+            if !path.is_dir() { bail!("{} is not a directory", path.display()); }
+            let pattern = path.join("**/*.md");
+            let mut notes = Vec::new();
+            for file in glob::glob(&pattern.to_string_lossy())?.filter_map(|f| f.ok()) {
+                let content = text(&file)?;
+                let title = content.lines().find_map(|l| l.strip_prefix("# ")).map(|t| t.trim().to_string())
+                    .unwrap_or_else(|| file.file_stem().unwrap_or_default().to_string_lossy().into_owned());
+                let ts = std::fs::metadata(&file).and_then(|m| m.modified()).ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok()).map_or_else(now, |d| d.as_secs() as i64);
+                let source_id = file.strip_prefix(path).unwrap_or(&file).to_string_lossy().into_owned();
+                notes.push(Note { source_id, title, content, ts });
+            }
+            Ok(notes)
+        }
+    }
+}
+
+// A memory-like JSON object; None when it carries no text
+fn json_note(v: &Value) -> Option<Note> {
+    let field = |keys: &[&str]| keys.iter().find_map(|k| v.get(*k).and_then(|s| s.as_str()).filter(|s| !s.trim().is_empty()));
+    let content = field(TEXT_KEYS)?.to_string();
+    let source_id = match v.get("id") {
+        Some(Value::String(s)) => s.clone(),
+        Some(id @ Value::Number(_)) => id.to_string(),
+        _ => content.clone(),
+    };
+    let ts = TIME_KEYS.iter().find_map(|k| v.get(*k).and_then(parse_time)).unwrap_or_else(now);
+    Some(Note { source_id, title: field(TITLE_KEYS).unwrap_or_default().to_string(), content, ts })
+}
+
+// Unix seconds or milliseconds, RFC 3339, or a naive ISO date-time taken as UTC
+fn parse_time(v: &Value) -> Option<i64> {
+    if let Some(n) = v.as_i64() { return Some(if n > 100_000_000_000 { n / 1000 } else { n }); }
+    let s = v.as_str()?;
+    chrono::DateTime::parse_from_rfc3339(s).map(|t| t.timestamp()).ok()
+        .or_else(|| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").ok().map(|t| t.and_utc().timestamp()))
+}
+
+// Lowercase letters and digits joined by '-', as chain link slugs expect
+fn slugify(s: &str) -> String {
+    let slug: String = s.to_lowercase().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '-' }).collect();
+    let slug = slug.split('-').filter(|p| !p.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "note".into() } else { slug.chars().take(64).collect() }
+}
+
+/// Saves `notes` to `target` and returns how many were saved. Ids and timestamps are derived from the
+/// source, so importing the same export again overwrites the earlier copies instead of duplicating them.
+pub async fn save(store: &Store, format: Format, notes: Vec<Note>, target: &Target) -> Result<usize> {
+    let project = match target.project.as_deref() { Some(".") => crate::project::current(), p => p.map(String::from) };
+    let mut link_ts = HashSet::new();
+    for note in &notes {
+        let hash = Sha256::digest(format!("{}:{}", format.as_str(), note.source_id));
+        let hash: String = hash[..4].iter().map(|b| format!("{b:02x}")).collect();
+        match &target.chain {
+            None => store.save_memory(&Memory {
+                id: format!("{}_{hash}", note.ts),
+                session_id: target.session_id.clone(),
+                title: if note.title.is_empty() { truncate_chars(&note.content, 60).to_string() } else { note.title.clone() },
+                content: note.content.clone(),
+                ts: note.ts,
+                expires_at: None,
+                decay_days: None,
+                archived_at: None,
+                redacted: false,
+                pinned: false,
+                importance: 0,
+                project: project.clone(),
+            }).await?,
+            Some(chain) => {
+                // Links are keyed by chain and time, so notes from the same second are spread out
+                let mut ts = note.ts;
+                while !link_ts.insert(ts) { ts += 1; }
+                let title = if note.title.is_empty() { &note.source_id } else { &note.title };
+                store.save_chain_link(&ChainLink {
+                    chain_name: chain.clone(),
+                    session_id: target.session_id.clone(),
+                    slug: slugify(title),
+                    content: note.content.clone(),
+                    ts,
+                    redacted: false,
+                    pinned: false,
+                    importance: 0,
+                    artifact_ids: vec![],
+                    git: None,
+                    project: project.clone(),
+                    completed: vec![],
+                    next_steps: vec![],
+                    decisions: vec![],
+                    files_touched: vec![],
+                    status: None,
+                }).await?;
+            }
+        }
+    }
+    Ok(notes.len())
+}
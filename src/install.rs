@@ -0,0 +1,78 @@
+//! `tinymem install-hooks`: writes the Claude Code hook scripts and merges their entries into
+//! `.claude/settings.json`, with the server address and token baked in as defaults.
+
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+const SCRIPTS: &[(&str, &str)] = &[
+    ("tinymem-start.sh", include_str!("../dot_claude/hooks/tinymem-start.sh")),
+    ("tinymem-hook.sh", include_str!("../dot_claude/hooks/tinymem-hook.sh")),
+    ("tinymem-transcript.sh", include_str!("../dot_claude/hooks/tinymem-transcript.sh")),
+    ("tinymem-stop.sh", include_str!("../dot_claude/hooks/tinymem-stop.sh")),
+    ("tinymem-ask-intercept.sh", include_str!("../dot_claude/hooks/tinymem-ask-intercept.sh")),
+];
+const SETTINGS: &str = include_str!("../dot_claude/settings.json");
+
+/// Installs hooks into `project/.claude` and returns the files written. Environment variables
+/// (TINYMEM_HOST/PORT/TOKEN) still override the baked-in values when set.
+pub fn install_hooks(project: &Path, host: &str, port: u16, token: &str) -> Result<Vec<PathBuf>> {
+    let claude_dir = project.join(".claude");
+    let hooks_dir = claude_dir.join("hooks");
+    std::fs::create_dir_all(&hooks_dir).with_context(|| format!("creating {}", hooks_dir.display()))?;
+    let mut written = Vec::new();
+    for (name, script) in SCRIPTS {
+        let path = hooks_dir.join(name);
+        write_script(&path, &bake_defaults(script, host, port, token)?)?;
+        written.push(path);
+    }
+
+    let settings_path = claude_dir.join("settings.json");
+    let existing = match std::fs::read_to_string(&settings_path) {
+        Ok(s) if !s.trim().is_empty() => {
+            std::fs::write(settings_path.with_extension("json.bak"), &s)?;
+            serde_json::from_str(&s).with_context(|| format!("{} is not valid JSON", settings_path.display()))?
+        }
+        _ => serde_json::json!({}),
+    };
+    let merged = merge_settings(existing, serde_json::from_str(SETTINGS)?)?;
+    std::fs::write(&settings_path, serde_json::to_string_pretty(&merged)? + "\n")?;
+    written.push(settings_path);
+    Ok(written)
+}
+
+/// Makes the script's `${TINYMEM_HOST:-localhost}`, `${TINYMEM_PORT:-3000}` and `$TINYMEM_TOKEN`
+/// fall back to the given values instead
+pub(crate) fn bake_defaults(script: &str, host: &str, port: u16, token: &str) -> Result<String> {
+    if let Some(c) = host.chars().chain(token.chars()).find(|c| "\"'`$\\}".contains(*c) || c.is_whitespace()) {
+        bail!("host and token must not contain {c:?}: they are embedded in shell scripts");
+    }
+    Ok(script.replace("${TINYMEM_HOST:-localhost}", &format!("${{TINYMEM_HOST:-{host}}}"))
+        .replace("${TINYMEM_PORT:-3000}", &format!("${{TINYMEM_PORT:-{port}}}"))
+        .replace("$TINYMEM_TOKEN", &format!("${{TINYMEM_TOKEN:-{token}}}")))
+}
+
+pub(crate) fn write_script(path: &Path, contents: &str) -> Result<()> {
+    std::fs::write(path, contents).with_context(|| format!("writing {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+// Replace any earlier tinymem entries per hook event, leaving the user's own hooks in place
+fn merge_settings(mut settings: Value, ours: Value) -> Result<Value> {
+    if !settings.is_object() { bail!("settings.json must contain a JSON object"); }
+    if !settings["hooks"].is_object() { settings["hooks"] = serde_json::json!({}); }
+    let is_ours = |entry: &Value| entry["hooks"].as_array()
+        .is_some_and(|hs| hs.iter().any(|h| h["command"].as_str().is_some_and(|c| c.contains("tinymem"))));
+    for (event, entries) in ours["hooks"].as_object().into_iter().flatten() {
+        let mut kept: Vec<Value> = settings["hooks"][event].as_array().cloned().unwrap_or_default()
+            .into_iter().filter(|e| !is_ours(e)).collect();
+        kept.extend(entries.as_array().cloned().unwrap_or_default());
+        settings["hooks"][event] = Value::Array(kept);
+    }
+    Ok(settings)
+}
@@ -0,0 +1,122 @@
+//! Hook journal: hooks that can't be written to Redis are appended to a local JSONL file instead of
+//! being dropped, and replayed in order once Redis answers again. While anything is waiting in the
+//! journal new hooks queue behind it, so a session's hooks still land in the order they arrived.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use crate::breaker::is_unreachable;
+use crate::models::Hook;
+use crate::store::Store;
+
+const REPLAY_EVERY: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize)]
+struct Entry { session_id: String, hook: Hook }
+
+struct Inner { path: PathBuf, lock: Mutex<()>, backlog: AtomicUsize }
+
+/// The default journal is disabled: [`Journal::append`] fails and nothing is replayed
+#[derive(Clone, Default)]
+pub struct Journal(Option<Arc<Inner>>);
+
+impl Journal {
+    /// Journals to `path`, picking up whatever an earlier run left there
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let backlog = match std::fs::read_to_string(path) {
+            Ok(text) => text.lines().filter(|l| !l.trim().is_empty()).count(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e).with_context(|| format!("reading hook journal {}", path.display())),
+        };
+        Ok(Self(Some(Arc::new(Inner { path: path.to_path_buf(), lock: Mutex::new(()), backlog: AtomicUsize::new(backlog) }))))
+    }
+
+    /// `~/.tinymem/hook-journal.jsonl`
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+        PathBuf::from(home).join(".tinymem").join("hook-journal.jsonl")
+    }
+
+    /// Hooks waiting to be replayed
+    pub fn backlog(&self) -> usize { self.0.as_ref().map_or(0, |j| j.backlog.load(Ordering::Relaxed)) }
+
+    pub async fn append(&self, session_id: &str, hook: &Hook) -> Result<()> {
+        let Some(j) = &self.0 else { anyhow::bail!("hook journal is disabled") };
+        let line = serde_json::to_string(&Entry { session_id: session_id.to_string(), hook: hook.clone() })? + "\n";
+        let _guard = j.lock.lock().await;
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&j.path)
+            .with_context(|| format!("opening hook journal {}", j.path.display()))?;
+        file.write_all(line.as_bytes())?;
+        j.backlog.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Where hooks Redis rejected on replay are set aside, next to the journal
+    fn dead_letters(path: &Path) -> PathBuf { path.with_extension("dead.jsonl") }
+
+    /// Writes journaled hooks to Redis oldest first, stopping when Redis can't be reached; what is left
+    /// stays journaled for the next attempt. A hook Redis rejects would be rejected forever, so it is
+    /// moved to the dead-letter file instead of holding up the rest. Returns how many were written.
+    pub async fn replay(&self, store: &Store) -> Result<usize> {
+        let Some(j) = &self.0 else { return Ok(0) };
+        let _guard = j.lock.lock().await;
+        let text = match std::fs::read_to_string(&j.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+        let dead = Self::dead_letters(&j.path);
+        let mut written = 0;
+        for line in &lines {
+            // A line that doesn't parse (e.g. cut short by a crash) can never be written; skip it
+            if let Ok(entry) = serde_json::from_str::<Entry>(line) {
+                match store.add_hook(&entry.session_id, &entry.hook).await {
+                    Ok(()) => {}
+                    Err(e) if store.degraded().is_some() || is_unreachable(&e) => break,
+                    Err(e) => {
+                        let set_aside = std::fs::OpenOptions::new().create(true).append(true).open(&dead)
+                            .and_then(|mut f| f.write_all(format!("{line}\n").as_bytes()));
+                        if set_aside.is_err() { break; }
+                        eprintln!("hook journal: Redis rejected a hook for {} ({e}); moved it to {}", entry.session_id, dead.display());
+                    }
+                }
+            }
+            written += 1;
+        }
+        if written == lines.len() {
+            std::fs::remove_file(&j.path)?;
+        } else {
+            let tmp = j.path.with_extension("jsonl.tmp");
+            std::fs::write(&tmp, lines[written..].iter().map(|l| format!("{l}\n")).collect::<String>())?;
+            std::fs::rename(&tmp, &j.path)?;
+        }
+        j.backlog.store(lines.len() - written, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    /// Replays in the background whenever there is a backlog and Redis isn't known to be down
+    pub fn spawn_replay(self, store: Store) {
+        if self.0.is_none() { return; }
+        tokio::spawn(async move {
+            loop {
+                if self.backlog() > 0 && store.degraded().is_none() {
+                    match self.replay(&store).await {
+                        Ok(0) => {}
+                        Ok(n) => eprintln!("hook journal: replayed {n} hooks, {} left", self.backlog()),
+                        Err(e) => eprintln!("hook journal: {e}"),
+                    }
+                }
+                tokio::time::sleep(REPLAY_EVERY).await;
+            }
+        });
+    }
+}
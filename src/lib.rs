@@ -0,0 +1,50 @@
+//! tinymem - lightweight AI agent memory server.
+//!
+//! The binary wires these pieces together; embedders can use [`Store`] directly
+//! against Redis or mount [`server::router`] into their own axum app.
+
+pub mod adapters;
+pub mod archive;
+pub mod auth;
+pub mod bench;
+mod breaker;
+pub mod cache;
+pub mod checkpoint;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod dedupe;
+pub mod events;
+mod extract;
+mod git;
+pub mod import;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod install;
+pub mod journal;
+pub mod mcp;
+mod mcp_queue;
+mod mcp_tools;
+pub mod models;
+pub mod notify;
+mod preview;
+mod project;
+pub mod redact;
+pub mod replay;
+pub mod retention;
+pub mod search;
+pub mod server;
+pub mod store;
+pub mod summarize;
+mod systemd;
+pub mod tail;
+pub mod tls;
+pub mod tui;
+pub mod usage;
+pub mod validate;
+pub mod watcher;
+pub mod webhooks;
+
+pub use server::{router, AppState};
+pub use store::{RedisOptions, Store};
@@ -1,21 +1,37 @@
-mod models;
-mod server;
-mod store;
-mod tui;
-mod mcp;
-mod mcp_tools;
-
-use anyhow::Result;
-use clap::Parser;
-use tokio::sync::mpsc;
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use tinymem::{adapters, archive, auth, bench, checkpoint, dedupe, events, import, install, journal, mcp, notify, redact, replay, retention, server, store, summarize, tail, tui, watcher, webhooks};
 
 #[derive(Parser)]
 #[command(name = "tinymem", about = "AI Agent Coordination Framework")]
 struct Args {
-    /// Redis URL
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Redis URL (redis://, redis+cluster://host:port,host:port or redis+sentinel://host:port,.../service)
     #[arg(long, default_value = "redis://127.0.0.1:6379", env = "TINYMEM_REDIS")]
     redis: String,
 
+    /// Redis ACL username (overrides the URL's)
+    #[arg(long, env = "TINYMEM_REDIS_USERNAME")]
+    redis_username: Option<String>,
+
+    /// Redis password (overrides the URL's)
+    #[arg(long, env = "TINYMEM_REDIS_PASSWORD", hide_env_values = true)]
+    redis_password: Option<String>,
+
+    /// PEM CA bundle to trust for rediss:// instead of the system roots
+    #[arg(long, env = "TINYMEM_REDIS_CA")]
+    redis_ca: Option<std::path::PathBuf>,
+
+    /// PEM client certificate for mutual TLS (with --redis-key)
+    #[arg(long, env = "TINYMEM_REDIS_CERT")]
+    redis_cert: Option<std::path::PathBuf>,
+
+    /// PEM client key for mutual TLS (with --redis-cert)
+    #[arg(long, env = "TINYMEM_REDIS_KEY")]
+    redis_key: Option<std::path::PathBuf>,
+
     /// Server port
     #[arg(long, default_value = "3000", env = "TINYMEM_PORT")]
     port: u16,
@@ -28,6 +44,66 @@ struct Args {
     #[arg(long)]
     headless: bool,
 
+    /// Largest accepted HTTP request body in bytes
+    #[arg(long, default_value_t = 4 * 1024 * 1024, env = "TINYMEM_MAX_BODY_BYTES")]
+    max_body_bytes: usize,
+
+    /// Largest chain link, memory, transcript entry, artifact description or hook meta in bytes
+    #[arg(long, default_value_t = 1024 * 1024, env = "TINYMEM_MAX_CONTENT_BYTES")]
+    max_content_bytes: usize,
+
+    /// Seconds an HTTP handler may run (ask and approval holds get 330s, reindex and dedupe ten times this)
+    #[arg(long, default_value_t = 30, env = "TINYMEM_REQUEST_TIMEOUT_SECS")]
+    request_timeout_secs: u64,
+
+    /// Chain links each session and each token may save per day (0 = unlimited)
+    #[arg(long, default_value_t = 0, env = "TINYMEM_QUOTA_LINKS_PER_DAY")]
+    quota_links_per_day: u64,
+
+    /// Total bytes of artifact files each session and each token may save (0 = unlimited)
+    #[arg(long, default_value_t = 0, env = "TINYMEM_QUOTA_ARTIFACT_BYTES")]
+    quota_artifact_bytes: u64,
+
+    /// Memories each session and each token may keep (0 = unlimited)
+    #[arg(long, default_value_t = 0, env = "TINYMEM_QUOTA_MEMORIES")]
+    quota_memories: u64,
+
+    /// Sessions that may be active at once; /start and /session answer 429 beyond it (0 = unlimited)
+    #[arg(long, default_value_t = 0, env = "TINYMEM_MAX_ACTIVE_SESSIONS")]
+    max_active_sessions: usize,
+
+    /// static: only --token is accepted; jwt: JWTs signed with --jwt-secret are accepted too
+    #[arg(long, value_enum, default_value = "static", env = "TINYMEM_AUTH")]
+    auth: auth::Mode,
+
+    /// HS256 secret for --auth jwt and `tinymem token` (at least 32 bytes)
+    #[arg(long, default_value = "", env = "TINYMEM_JWT_SECRET", hide_env_values = true)]
+    jwt_secret: String,
+
+    /// PEM certificate chain to serve the API over HTTPS with (with --tls-key)
+    #[arg(long, env = "TINYMEM_TLS_CERT", requires = "tls_key")]
+    tls_cert: Option<std::path::PathBuf>,
+
+    /// PEM private key for --tls-cert
+    #[arg(long, env = "TINYMEM_TLS_KEY", requires = "tls_cert")]
+    tls_key: Option<std::path::PathBuf>,
+
+    /// Require HTTPS clients to present a certificate signed by this PEM CA; its CN identifies the caller
+    #[arg(long, env = "TINYMEM_TLS_CLIENT_CA", requires = "tls_cert")]
+    tls_client_ca: Option<std::path::PathBuf>,
+
+    /// What a client certificate may do, CN=SCOPES with scopes read, write, export (repeatable; CNs without a rule only read)
+    #[arg(long = "client-cert-scope", env = "TINYMEM_CLIENT_CERT_SCOPES", value_delimiter = ',')]
+    client_cert_scopes: Vec<String>,
+
+    /// Bearer token that only allows GET /export/corpus, for embedding and analytics pipelines (empty = none)
+    #[arg(long, default_value = "", env = "TINYMEM_EXPORT_TOKEN")]
+    export_token: String,
+
+    /// Append one line per HTTP request to this file (headless mode logs to stderr when unset)
+    #[arg(long, env = "TINYMEM_ACCESS_LOG")]
+    access_log: Option<std::path::PathBuf>,
+
     /// MCP server mode (stdio)
     #[arg(long)]
     mcp: bool,
@@ -35,6 +111,225 @@ struct Args {
     /// Host for MCP client to connect to
     #[arg(long, default_value = "localhost", env = "TINYMEM_HOST")]
     host: String,
+
+    /// Webhook target as EVENT=URL, or URL for all events (repeatable)
+    #[arg(long = "webhook", env = "TINYMEM_WEBHOOKS", value_delimiter = ',')]
+    webhooks: Vec<String>,
+
+    /// Secret for signing webhook payloads (HMAC-SHA256, empty = unsigned)
+    #[arg(long, default_value = "", env = "TINYMEM_WEBHOOK_SECRET")]
+    webhook_secret: String,
+
+    /// OpenAI-compatible chat completions URL; when set, finished sessions get an LLM-written summary
+    #[arg(long, env = "TINYMEM_SUMMARIZE_URL")]
+    summarize_url: Option<String>,
+
+    /// Model named in summary requests
+    #[arg(long, default_value = "gpt-4o-mini", env = "TINYMEM_SUMMARIZE_MODEL")]
+    summarize_model: String,
+
+    /// Bearer token for --summarize-url (empty for local servers)
+    #[arg(long, default_value = "", env = "TINYMEM_SUMMARIZE_API_KEY", hide_env_values = true)]
+    summarize_api_key: String,
+
+    /// Seconds a tool may run before the TUI shows it in red as possibly stuck
+    #[arg(long, default_value_t = 300, env = "TINYMEM_STUCK_TOOL_SECS")]
+    stuck_tool_secs: i64,
+
+    /// Seconds without tool activity before a session shows as idle
+    #[arg(long, default_value_t = 60, env = "TINYMEM_IDLE_AFTER_SECS")]
+    idle_after_secs: i64,
+
+    /// Seconds without activity or a heartbeat before a session shows as disconnected
+    #[arg(long, default_value_t = 120, env = "TINYMEM_DISCONNECT_AFTER_SECS")]
+    disconnect_after_secs: i64,
+
+    /// Seconds without activity or a heartbeat before a session is marked done
+    #[arg(long, default_value_t = 900, env = "TINYMEM_DONE_AFTER_SECS")]
+    done_after_secs: i64,
+
+    /// Retention rule FAMILY=AGE for hooks, sessions, memories or trash, e.g. hooks=14d (repeatable; only
+    /// the trash is emptied by default, after 7d)
+    #[arg(long = "retention", env = "TINYMEM_RETENTION", value_delimiter = ',')]
+    retention: Vec<String>,
+
+    /// Checkpoint rule AGE or PROJECT=AGE, e.g. 45m: how long an active session may work without saving a
+    /// chain link before it is reminded to (repeatable; off by default)
+    #[arg(long = "checkpoint-after", env = "TINYMEM_CHECKPOINT_AFTER", value_delimiter = ',')]
+    checkpoint_after: Vec<String>,
+
+    /// remind: note in the session's inbox; draft: save a draft link from its recent tool calls too
+    #[arg(long, value_enum, default_value = "remind", env = "TINYMEM_CHECKPOINT_MODE")]
+    checkpoint_mode: checkpoint::Mode,
+
+    /// Move finished sessions to compressed files in this directory once they are --archive-after-days old
+    #[arg(long, env = "TINYMEM_ARCHIVE_DIR")]
+    archive_dir: Option<std::path::PathBuf>,
+
+    /// Days since a finished session's last activity before it is archived
+    #[arg(long, default_value_t = 30, env = "TINYMEM_ARCHIVE_AFTER_DAYS")]
+    archive_after_days: i64,
+
+    /// File that hooks are journaled to while Redis is unreachable (default: ~/.tinymem/hook-journal.jsonl)
+    #[arg(long, env = "TINYMEM_HOOK_JOURNAL")]
+    hook_journal: Option<std::path::PathBuf>,
+
+    /// Answer 500 for hooks that can't be written to Redis instead of journaling them
+    #[arg(long, env = "TINYMEM_NO_HOOK_JOURNAL")]
+    no_hook_journal: bool,
+
+    /// Disable desktop notifications and terminal bell in TUI mode
+    #[arg(long, env = "TINYMEM_NO_DESKTOP_NOTIFY")]
+    no_desktop_notify: bool,
+
+    /// Chat notifier as slack:URL or discord:URL (repeatable)
+    #[arg(long = "notify", env = "TINYMEM_NOTIFY", value_delimiter = ',')]
+    notify: Vec<String>,
+
+    /// 32-byte AES key, as hex or base64, for encrypting chain links and artifact text at rest (empty = plaintext)
+    #[arg(long, default_value = "", env = "TINYMEM_ENCRYPT_KEY", hide_env_values = true)]
+    encrypt_key: String,
+
+    /// Extra regex to redact from stored content, on top of the built-in secret patterns (repeatable)
+    #[arg(long = "redact", env = "TINYMEM_REDACT")]
+    redact: Vec<String>,
+
+    /// Store content without secret redaction
+    #[arg(long, env = "TINYMEM_NO_REDACT")]
+    no_redact: bool,
+
+    /// How long session, chain name and artifact reads are served from memory before Redis is asked again (0 = off)
+    #[arg(long, default_value_t = 2000, env = "TINYMEM_READ_CACHE_MS")]
+    read_cache_ms: u64,
+
+    /// Save files that appear or change under this directory as artifacts (repeatable)
+    #[arg(long = "watch-dir", env = "TINYMEM_WATCH_DIR", value_delimiter = ',')]
+    watch_dirs: Vec<std::path::PathBuf>,
+
+    /// Which files under --watch-dir to save, matched against the path relative to it
+    #[arg(long, default_value = "*", env = "TINYMEM_WATCH_GLOB")]
+    watch_glob: String,
+
+    /// gRPC port (disabled when unset)
+    #[cfg(feature = "grpc")]
+    #[arg(long, env = "TINYMEM_GRPC_PORT")]
+    grpc_port: Option<u16>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Re-extract text for all artifacts whose source files still exist, then exit
+    Reindex,
+    /// Report near-duplicate memories and chain links, optionally merging the closest ones
+    Dedupe {
+        /// Minimum content similarity (0-1) to report a pair
+        #[arg(long, default_value = "0.85")]
+        threshold: f64,
+        /// Merge pairs at least this similar (report only when unset)
+        #[arg(long)]
+        merge_above: Option<f64>,
+    },
+    /// Import memories exported from another agent-memory tool, or a directory of markdown notes, then exit
+    Import {
+        #[arg(long, value_enum)]
+        format: import::Format,
+        /// Export file, or the directory for plain-md-dir
+        path: std::path::PathBuf,
+        /// Save the notes as links of this chain instead of as memories
+        #[arg(long)]
+        chain: Option<String>,
+        /// Session id the imported notes are recorded under
+        #[arg(long, default_value = "import")]
+        session: String,
+        /// Project id to file the notes under ("." for the current directory's)
+        #[arg(long)]
+        project: Option<String>,
+    },
+    /// Print a JWT signed with --jwt-secret for a server running with --auth jwt, then exit
+    Token {
+        /// Who the token is for; audit entries and quotas record it as jwt:<sub>
+        #[arg(long)]
+        sub: String,
+        /// Space-separated scopes: read, write, export
+        #[arg(long, default_value = "read")]
+        scope: String,
+        /// Seconds until the token expires
+        #[arg(long, default_value_t = 86400)]
+        ttl_secs: i64,
+    },
+    /// Print the running server's events (--host/--port/--token) as they happen, one per line
+    Tail {
+        /// Print each event as a JSON object instead of a colored summary
+        #[arg(long)]
+        json: bool,
+        /// Only these events, e.g. hook,session.done (repeatable)
+        #[arg(long = "kind", value_delimiter = ',')]
+        kinds: Vec<String>,
+    },
+    /// Post a recorded session's hooks to the running server (--host/--port/--token) as a new session
+    Replay {
+        /// GET /session/:id/export output, or a cold archive file
+        file: std::path::PathBuf,
+        /// How much faster than recorded to post hooks, e.g. 10x (0 = no waiting)
+        #[arg(long, default_value = "1x", value_parser = replay::parse_speed)]
+        speed: f64,
+        /// Longest wait between two hooks after scaling, in seconds
+        #[arg(long, default_value_t = 5.0)]
+        max_gap_secs: f64,
+        /// Replay this many copies at once, each as its own session
+        #[arg(long, default_value_t = 1)]
+        copies: usize,
+    },
+    /// Add synthetic sessions, chains and artifacts to --redis, time search, chain loads and TUI refreshes, then remove them
+    Bench {
+        #[arg(long, default_value_t = 50)]
+        sessions: usize,
+        /// Hooks per session
+        #[arg(long, default_value_t = 40)]
+        hooks: usize,
+        #[arg(long, default_value_t = 20)]
+        chains: usize,
+        /// Links per chain
+        #[arg(long, default_value_t = 10)]
+        links: usize,
+        #[arg(long, default_value_t = 50)]
+        artifacts: usize,
+        /// Timed rounds of each operation
+        #[arg(long, default_value_t = 50)]
+        iterations: usize,
+        /// Leave the synthetic data in place afterwards
+        #[arg(long)]
+        keep: bool,
+    },
+    /// Move old finished sessions to --archive-dir, or read one back
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveAction,
+    },
+    /// Write Claude Code hook scripts and settings.json entries pointing at --host/--port/--token, then exit
+    InstallHooks {
+        /// Project directory (hooks go to <dir>/.claude)
+        #[arg(default_value = ".")]
+        dir: std::path::PathBuf,
+    },
+    /// Write the glue that feeds another coding agent's events into --host/--port/--token, then exit
+    Adapt {
+        #[arg(long, value_enum)]
+        agent: adapters::Agent,
+        /// Output directory (the project root for cursor)
+        #[arg(long, default_value = ".")]
+        out: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ArchiveAction {
+    /// Archive every finished session older than --archive-after-days now, then exit
+    Run,
+    /// Print an archived session and its hooks
+    Show { id: String },
+    /// Print the links of a chain deleted with ?archive=true
+    Chain { name: String },
 }
 
 #[tokio::main]
@@ -47,23 +342,221 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    let store = store::Store::new(&args.redis).await?;
-    let (tui_tx, tui_rx) = mpsc::channel(100);
+    if let Some(Command::Token { sub, scope, ttl_secs }) = &args.command {
+        let iat = tinymem::models::now();
+        let claims = auth::Claims { sub: Some(sub.clone()), exp: iat + ttl_secs, nbf: None, iat: Some(iat), scope: scope.clone() };
+        println!("{}", auth::Jwt::new(&args.jwt_secret)?.mint(&claims)?);
+        return Ok(());
+    }
+
+    if let Some(Command::Tail { json, kinds }) = &args.command {
+        return tail::run(&args.host, args.port, &args.token, kinds, *json);
+    }
 
-    let server_store = store.clone();
-    let token = args.token.clone();
-    let port = args.port;
-    let server_handle = tokio::spawn(async move {
-        server::run(server_store, token, tui_tx, port).await
+    if let Some(Command::Replay { file, speed, max_gap_secs, copies }) = &args.command {
+        let rec = replay::load(file)?;
+        println!("Replaying {} hooks of {} session {} at {speed}x", rec.hooks.len(), rec.session.agent, rec.session.id);
+        let opts = replay::Options { speed: *speed, max_gap: std::time::Duration::from_secs_f64(max_gap_secs.max(0.0)), copies: *copies };
+        let started = std::time::Instant::now();
+        let ids = replay::run(&args.host, args.port, &args.token, &rec, &opts)?;
+        println!("Replayed as {} in {:.1}s", ids.join(", "), started.elapsed().as_secs_f64());
+        return Ok(());
+    }
+    if let Some(Command::InstallHooks { dir }) = &args.command {
+        for path in install::install_hooks(dir, &args.host, args.port, &args.token)? {
+            println!("wrote {}", path.display());
+        }
+        println!("Restart Claude Code in {} to pick up the hooks", dir.display());
+        return Ok(());
+    }
+    if let Some(Command::Adapt { agent, out }) = &args.command {
+        for path in adapters::write_glue(*agent, out, &args.host, args.port, &args.token)? {
+            println!("wrote {}", path.display());
+        }
+        println!("{}", agent.instructions(out));
+        return Ok(());
+    }
+
+    if let Some(Command::Archive { action: ArchiveAction::Show { id } }) = &args.command {
+        let dir = args.archive_dir.as_deref().context("--archive-dir (or TINYMEM_ARCHIVE_DIR) is required")?;
+        let (session, hooks) = archive::read(dir, id)?;
+        let time = |ts: i64| chrono::DateTime::from_timestamp(ts, 0).map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default();
+        println!("{} ({}) {}", session.name.as_deref().unwrap_or(&session.id), session.agent, session.cwd);
+        println!("started {}, last active {}", time(session.created), time(session.last_activity.max(session.created)));
+        if let Some(git) = &session.git { println!("git {git}"); }
+        println!("{} hooks", hooks.len());
+        for h in hooks {
+            let meta = if h.meta.is_null() { String::new() } else { format!("  {}", h.meta) };
+            println!("{}  {:<12} {}{}", time(h.ts), serde_json::to_value(&h.kind)?.as_str().unwrap_or_default(), h.task, meta);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Archive { action: ArchiveAction::Chain { name } }) = &args.command {
+        let dir = args.archive_dir.as_deref().context("--archive-dir (or TINYMEM_ARCHIVE_DIR) is required")?;
+        let links = archive::read_chain(dir, name)?;
+        println!("{name}: {} links", links.len());
+        for link in links {
+            let time = chrono::DateTime::from_timestamp(link.ts, 0).map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default();
+            println!("\n{time}  {}  (session {})", link.slug, link.session_id);
+            if !link.content.is_empty() { println!("{}", link.content); }
+            for (heading, items) in link.sections() {
+                if items.is_empty() { continue; }
+                println!("{heading}:");
+                for item in items { println!("  - {item}"); }
+            }
+        }
+        return Ok(());
+    }
+
+    let redis_opts = store::RedisOptions {
+        username: args.redis_username.clone(), password: args.redis_password.clone(),
+        ca_cert: args.redis_ca.clone(), client_cert: args.redis_cert.clone(), client_key: args.redis_key.clone(),
+    };
+    let mut store = store::Store::connect(&args.redis, &redis_opts).await?;
+    if !args.encrypt_key.is_empty() { store = store.with_encryption(&args.encrypt_key)?; }
+    if !args.no_redact { store = store.with_redactor(redact::Redactor::new(&args.redact)?); }
+    if args.read_cache_ms > 0 { store = store.with_read_cache(std::time::Duration::from_millis(args.read_cache_ms)); }
+
+    match args.command {
+        Some(Command::Bench { sessions, hooks, chains, links, artifacts, iterations, keep }) => {
+            let sizes = bench::Sizes { sessions, hooks, chains, links, artifacts };
+            let started = std::time::Instant::now();
+            let fixture = bench::populate(&store, &sizes).await?;
+            println!("Added {sessions} sessions, {chains} chains and {artifacts} artifacts in {:.1}s", started.elapsed().as_secs_f64());
+            let timings = bench::measure(&store, &fixture, iterations).await;
+            if !keep { fixture.remove(&store).await?; }
+            let ms = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+            println!("{:<12} {:>9} {:>9} {:>9} {:>9}", "", "mean ms", "p50 ms", "p95 ms", "max ms");
+            for t in timings? {
+                println!("{:<12} {:>9.2} {:>9.2} {:>9.2} {:>9.2}", t.name, ms(t.mean()), ms(t.percentile(50)), ms(t.percentile(95)), ms(t.percentile(100)));
+            }
+            return Ok(());
+        }
+        Some(Command::Reindex) => {
+            store.rebuild_links().await?;
+            let indexed = store.rebuild_search_index().await?;
+            let (reindexed, skipped) = server::reindex_artifacts(&store).await?;
+            println!("Reindexed {reindexed} artifacts ({skipped} skipped: file missing or no extractable text), {indexed} documents in the search index");
+            return Ok(());
+        }
+        Some(Command::Dedupe { threshold, merge_above }) => {
+            let pairs = dedupe::run(&store, threshold, merge_above).await?;
+            for p in &pairs {
+                println!("{:.2}  {}  {} <- {}", p.similarity, if p.merged { "merged" } else { "      " }, p.keep, p.duplicate);
+            }
+            println!("{} near-duplicate pairs, {} merged", pairs.len(), pairs.iter().filter(|p| p.merged).count());
+            return Ok(());
+        }
+        Some(Command::Import { format, path, chain, session, project }) => {
+            let notes = import::read(format, &path)?;
+            let into = chain.as_deref().map_or_else(|| "memories".to_string(), |c| format!("links of chain {c}"));
+            let saved = import::save(&store, format, notes, &import::Target { session_id: session, chain, project }).await?;
+            println!("Imported {saved} notes from {} as {into}", path.display());
+            return Ok(());
+        }
+        Some(Command::Archive { action: ArchiveAction::Run }) => {
+            let dir = args.archive_dir.as_deref().context("--archive-dir (or TINYMEM_ARCHIVE_DIR) is required")?;
+            let archived = archive::compact(&store, dir, tinymem::models::now() - args.archive_after_days * 86400).await?;
+            println!("Archived {} sessions to {}", archived.len(), dir.display());
+            return Ok(());
+        }
+        Some(Command::InstallHooks { .. } | Command::Adapt { .. } | Command::Archive { .. } | Command::Tail { .. } | Command::Replay { .. } | Command::Token { .. }) | None => {}
+    }
+    let hooks = webhooks::Webhooks::new(&args.webhooks, &args.webhook_secret)?;
+    let retention = retention::Policy::parse(&args.retention)?;
+    let checkpoints = checkpoint::Policy::parse(&args.checkpoint_after)?;
+    let chat = notify::ChatNotifiers::new(&args.notify)?;
+    let bus = events::EventBus::new(256);
+    hooks.listen(bus.subscribe());
+    if let Some(cache) = store.read_cache() { cache.listen(bus.subscribe()); }
+    if let Some(url) = &args.summarize_url {
+        summarize::Summarizer::new(url, &args.summarize_model, &args.summarize_api_key).listen(store.clone(), bus.clone(), bus.subscribe());
+    }
+    // Subscribe before the server starts so the TUI sees the first events
+    let tui_events = bus.subscribe();
+
+    let access_log = match &args.access_log {
+        Some(path) => server::AccessLog::file(path).with_context(|| format!("opening access log {}", path.display()))?,
+        None if args.headless => server::AccessLog::stderr(),
+        None => server::AccessLog::default(),
+    };
+    let journal = if args.no_hook_journal { journal::Journal::default() } else {
+        let path = args.hook_journal.clone().unwrap_or_else(journal::Journal::default_path);
+        journal::Journal::open(&path).with_context(|| format!("opening hook journal {}", path.display()))?
+    };
+    journal.clone().spawn_replay(store.clone());
+    // A missing search index is built in the background; search scans until it is ready
+    let index_store = store.clone();
+    tokio::spawn(async move {
+        if let Err(e) = index_store.ensure_search_index().await { eprintln!("search index: {e}"); }
     });
+    let jwt = match args.auth {
+        auth::Mode::Static => None,
+        auth::Mode::Jwt => Some(auth::Jwt::new(&args.jwt_secret)?),
+    };
+    let presence = tinymem::models::Presence { idle_after_secs: args.idle_after_secs, disconnect_after_secs: args.disconnect_after_secs };
+    let state = server::AppState {
+        store: store.clone(), token: args.token.clone(), chat: chat.clone(), events: bus.clone(), access_log,
+        limits: tinymem::validate::Limits { max_body_bytes: args.max_body_bytes, max_content_bytes: args.max_content_bytes },
+        timeout: std::time::Duration::from_secs(args.request_timeout_secs),
+        presence,
+        retention,
+        export_token: args.export_token.clone(),
+        journal,
+        jwt,
+        cert_scopes: auth::CertScopes::parse(&args.client_cert_scopes)?,
+        max_active_sessions: args.max_active_sessions,
+        archive_dir: args.archive_dir.clone(),
+        quotas: tinymem::models::Quotas { links_per_day: args.quota_links_per_day, artifact_bytes: args.quota_artifact_bytes, memories: args.quota_memories },
+    };
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_port) = args.grpc_port {
+        let grpc_state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = tinymem::grpc::run(grpc_state, grpc_port).await { eprintln!("gRPC server failed: {e}"); }
+        });
+    }
+    for dir in &args.watch_dirs {
+        watcher::watch(store.clone(), bus.clone(), dir, &args.watch_glob)?;
+    }
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => Some(tinymem::tls::acceptor(cert, key, args.tls_client_ca.as_deref())?),
+        _ => None,
+    };
+    let port = args.port;
+    let server_handle = tokio::spawn(async move { server::run(state, port, tls).await });
 
-    // Spawn cleanup task - mark sessions inactive after 2 minutes of no activity
+    // Spawn cleanup task - mark sessions done once they stop both working and heartbeating
     let cleanup_store = store.clone();
+    let done_after = args.done_after_secs;
+    let archive_to = args.archive_dir.clone().map(|dir| (dir, args.archive_after_days * 86400));
+    let checkpoint_mode = args.checkpoint_mode;
     tokio::spawn(async move {
         loop {
             tokio::time::sleep(std::time::Duration::from_secs(30)).await;
-            if let Ok(cleaned) = cleanup_store.cleanup_stale(120).await {
-                let _ = cleaned; // silence unused warning
+            if let Ok(cleaned) = cleanup_store.cleanup_stale(done_after).await {
+                for id in cleaned {
+                    bus.publish(events::EventKind::SessionDone, &id, serde_json::json!({ "id": id, "stale": true }));
+                    chat.send(format!("💤 tinymem session `{id}` marked done after {}m without activity", done_after / 60));
+                }
+            }
+            match checkpoint::run(&cleanup_store, &checkpoints, checkpoint_mode).await {
+                Ok(report) => for (key, link) in report.drafts {
+                    bus.publish(events::EventKind::ChainLinkSaved, &key, serde_json::json!({ "key": key, "link": link }));
+                },
+                Err(e) => eprintln!("checkpoint reminders: {e}"),
+            }
+            if let Some((dir, age)) = &archive_to {
+                match archive::compact(&cleanup_store, dir, tinymem::models::now() - age).await {
+                    Ok(ids) => for id in ids { bus.publish(events::EventKind::SessionDeleted, &id, serde_json::Value::Null); },
+                    Err(e) => eprintln!("archive: {e}"),
+                }
+            }
+            // Archive expired, decayed and aged memories and drop whatever else the retention policy has outlived
+            match retention::run(&cleanup_store, &retention, false).await {
+                Ok(report) => for id in report.sessions { bus.publish(events::EventKind::SessionDeleted, &id, serde_json::Value::Null); },
+                Err(e) => eprintln!("retention sweep: {e}"),
             }
         }
     });
@@ -73,7 +566,7 @@ async fn main() -> Result<()> {
         server_handle.await??;
     } else {
         let mut terminal = ratatui::init();
-        let mut app = tui::App::new(store, tui_rx);
+        let mut app = tui::App::new(store, tui_events, notify::Notifier::new(!args.no_desktop_notify), args.stuck_tool_secs, presence, args.max_active_sessions);
         let result = app.run(&mut terminal).await;
         ratatui::restore();
         result?;
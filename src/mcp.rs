@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::io::{self, BufRead, Write};
-use crate::mcp_tools;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::{mcp_queue, mcp_tools};
 
 #[derive(Deserialize)]
 struct Request { id: Option<Value>, method: String, params: Option<Value> }
@@ -16,8 +19,155 @@ struct Response {
     error: Option<Value>,
 }
 
+// MCP log levels, least to most severe; clients pick the minimum with logging/setLevel
+const LEVELS: &[&str] = &["debug", "info", "notice", "warning", "error", "critical", "alert", "emergency"];
+static LOG_LEVEL: AtomicUsize = AtomicUsize::new(1);
+
+// Notifications can come from the progress thread while a call is in flight; one locked write per message
+fn notify(method: &str, params: Value) {
+    let mut out = io::stdout().lock();
+    let _ = writeln!(out, "{}", json!({"jsonrpc": "2.0", "method": method, "params": params}));
+    let _ = out.flush();
+}
+
+fn log(level: &str, data: Value) {
+    if LEVELS.iter().position(|l| *l == level).unwrap_or(0) >= LOG_LEVEL.load(Ordering::Relaxed) {
+        notify("notifications/message", json!({"level": level, "logger": "tinymem", "data": data}));
+    }
+}
+
+/// Runs `f`, sending notifications/progress once a second until it returns, if the client asked for progress
+fn with_progress<T>(token: Option<&Value>, message: &str, f: impl FnOnce() -> T) -> T {
+    let Some(token) = token.cloned() else { return f() };
+    let done = Arc::new(AtomicBool::new(false));
+    let ticker = {
+        let (done, message) = (done.clone(), message.to_string());
+        std::thread::spawn(move || {
+            for secs in 1.. {
+                std::thread::park_timeout(Duration::from_secs(1));
+                if done.load(Ordering::Relaxed) { break; }
+                notify("notifications/progress", json!({"progressToken": token, "progress": secs, "message": format!("{message} ({secs}s)")}));
+            }
+        })
+    };
+    let out = f();
+    done.store(true, Ordering::Relaxed);
+    ticker.thread().unpark();
+    let _ = ticker.join();
+    out
+}
+
+// Read-only tools whose replies are reused for CACHE_TTL; agents often repeat a search within one turn.
+// Any write through this proxy clears the cache, writes from elsewhere show up once entries expire.
+const CACHED_TOOLS: &[&str] = &["tinymem_search", "tinymem_chain_list", "tinymem_get"];
+const WRITE_TOOLS: &[&str] = &["tinymem_chain_link", "tinymem_chain_delete", "tinymem_remember", "tinymem_prioritize", "tinymem_artifact_save"];
+const CACHE_TTL: Duration = Duration::from_secs(30);
+const CACHE_MAX: usize = 64;
+static CACHE: Mutex<Vec<(String, Instant, Value)>> = Mutex::new(Vec::new());
+
+fn cache_get(key: &str) -> Option<Value> {
+    let mut cache = CACHE.lock().unwrap();
+    cache.retain(|(_, at, _)| at.elapsed() < CACHE_TTL);
+    cache.iter().find(|(k, _, _)| k == key).map(|(_, _, v)| v.clone())
+}
+
+fn cache_put(key: String, value: Value) {
+    let mut cache = CACHE.lock().unwrap();
+    if cache.len() >= CACHE_MAX { cache.remove(0); }
+    cache.push((key, Instant::now(), value));
+}
+
+fn cache_clear() { CACHE.lock().unwrap().clear(); }
+
+const RETRIES: u32 = 3;
+
+// Connection-level failures mean the server is down or restarting; HTTP error statuses are real answers
+fn unreachable(e: &ureq::Error) -> bool {
+    matches!(e, ureq::Error::Io(_) | ureq::Error::ConnectionFailed | ureq::Error::HostNotFound | ureq::Error::Timeout(_))
+}
+
+/// Retries `f` with exponential backoff (200ms, 400ms, 800ms) while the server is unreachable
+fn with_retry<T>(mut f: impl FnMut() -> Result<T, ureq::Error>) -> Result<T, ureq::Error> {
+    let mut delay = Duration::from_millis(200);
+    for _ in 0..RETRIES {
+        match f() {
+            Err(e) if unreachable(&e) => {
+                log("warning", json!({"error": e.to_string(), "retrying_in_ms": delay.as_millis() as u64}));
+                std::thread::sleep(delay);
+                delay *= 2;
+            }
+            r => return r,
+        }
+    }
+    f()
+}
+
+/// POST for tools that write: retried, then queued on disk if the server stays unreachable (Ok(None))
+fn post_or_queue(base: &str, token: &str, path: &str, body: &Value) -> Result<Option<Value>, String> {
+    let sent = with_retry(|| {
+        ureq::post(&format!("{}{}", base, path))
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .send_json(body)
+    });
+    match sent {
+        Ok(mut resp) => resp.body_mut().read_json().map(Some).map_err(|e| e.to_string()),
+        Err(e) if unreachable(&e) => {
+            mcp_queue::push(&[mcp_queue::Entry { path: path.to_string(), body: body.clone(), queued_at: crate::models::now() }])?;
+            log("warning", json!({"queued": path, "error": e.to_string()}));
+            Ok(None)
+        }
+        Err(e) => Err(format!("request failed: {}", e)),
+    }
+}
+
+/// Replays queued writes in order; whatever can't be delivered yet goes back on the queue
+fn flush_queue(base: &str, token: &str) {
+    let mut pending = mcp_queue::take().into_iter();
+    let mut sent = 0;
+    while let Some(entry) = pending.next() {
+        let result = ureq::post(&format!("{}{}", base, entry.path))
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Content-Type", "application/json")
+            .send_json(&entry.body);
+        match result {
+            Ok(_) => sent += 1,
+            Err(e) if unreachable(&e) => {
+                let rest: Vec<_> = std::iter::once(entry).chain(pending.by_ref()).collect();
+                if let Err(e) = mcp_queue::push(&rest) { log("error", json!({"lost_queued_writes": rest.len(), "error": e})); }
+                break;
+            }
+            Err(e) => log("error", json!({"dropped_queued_write": entry.path, "error": e.to_string()})),
+        }
+    }
+    if sent > 0 {
+        cache_clear();
+        log("info", json!({"flushed_queued_writes": sent}));
+    }
+}
+
+// While the proxy runs, the agent is connected: heartbeat the last session a tool call named, so a
+// session thinking between tool calls shows as idle instead of disconnected. Missed beats aren't queued.
+const HEARTBEAT_EVERY: Duration = Duration::from_secs(30);
+static SESSION: Mutex<Option<String>> = Mutex::new(None);
+
+fn heartbeats(base: String, token: String) {
+    loop {
+        std::thread::sleep(HEARTBEAT_EVERY);
+        let Some(sid) = SESSION.lock().unwrap().clone() else { continue };
+        let sent = ureq::post(&format!("{}/session/{}/heartbeat", base, sid))
+            .header("Authorization", &format!("Bearer {}", token))
+            .send_empty();
+        if let Err(e) = sent { log("debug", json!({"heartbeat": sid, "error": e.to_string()})); }
+    }
+}
+
 pub fn run(host: &str, port: u16, token: &str) {
-    let base = format!("http://{}:{}", host, port);
+    let base = format!("http://{}:{}{}", host, port, crate::server::API_PREFIX);
+    {
+        let (base, token) = (base.clone(), token.to_string());
+        std::thread::spawn(move || heartbeats(base, token));
+    }
     let stdin = io::stdin();
     let mut stdout = io::stdout();
 
@@ -37,48 +187,135 @@ pub fn run(host: &str, port: u16, token: &str) {
     }
 }
 
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
 fn handle(method: &str, params: Option<Value>, base: &str, token: &str) -> Result<Value, String> {
     match method {
+        // Echo the client's version when we speak it; annotations and structured output need 2025-03-26+
         "initialize" => Ok(json!({
-            "protocolVersion": "2024-11-05",
-            "capabilities": { "tools": {} },
+            "protocolVersion": params.as_ref().and_then(|p| p.get("protocolVersion")).and_then(|v| v.as_str())
+                .filter(|v| PROTOCOL_VERSIONS.contains(v)).unwrap_or(PROTOCOL_VERSIONS[0]),
+            "capabilities": { "tools": {}, "logging": {} },
             "serverInfo": { "name": "tinymem", "version": "0.1.0" }
         })),
         "notifications/initialized" => Ok(Value::Null),
+        "logging/setLevel" => {
+            let level = params.as_ref().and_then(|p| p.get("level")).and_then(|v| v.as_str()).ok_or("missing level")?;
+            let rank = LEVELS.iter().position(|l| *l == level).ok_or_else(|| format!("unknown log level: {}", level))?;
+            LOG_LEVEL.store(rank, Ordering::Relaxed);
+            Ok(json!({}))
+        }
         "tools/list" => Ok(mcp_tools::tool_list()),
         "tools/call" => {
             let p = params.ok_or("missing params")?;
             let name = p.get("name").and_then(|v| v.as_str()).ok_or("missing tool name")?;
             let args = p.get("arguments").cloned().unwrap_or(json!({}));
-            call_tool(name, args, base, token)
+            let progress = p.get("_meta").and_then(|m| m.get("progressToken"));
+            log("debug", json!({"tool": name, "arguments": args}));
+            // tinymem_hooks names the session being inspected, usually an earlier one rather than the caller's
+            if let Some(sid) = args.get("session_id").and_then(|v| v.as_str()).filter(|_| name != "tinymem_hooks") { *SESSION.lock().unwrap() = Some(sid.to_string()); }
+            flush_queue(base, token);
+            if WRITE_TOOLS.contains(&name) { cache_clear(); }
+            let key = CACHED_TOOLS.contains(&name).then(|| format!("{} {}", name, args));
+            if let Some(hit) = key.as_deref().and_then(cache_get) {
+                log("debug", json!({"tool": name, "cached": true}));
+                return Ok(hit);
+            }
+            let result = call_tool(name, args, base, token, progress).inspect_err(|e| log("error", json!({"tool": name, "error": e})));
+            if let (Some(key), Ok(value)) = (key, &result) { cache_put(key, value.clone()); }
+            result
         }
         _ => Ok(Value::Null)
     }
 }
 
-fn call_tool(name: &str, args: Value, base: &str, token: &str) -> Result<Value, String> {
+// `project` argument, with "." meaning the checkout the proxy runs in
+fn project_arg(args: &Value) -> Option<String> {
+    let project = args.get("project").and_then(|v| v.as_str())?;
+    if project != "." { return Some(project.to_string()); }
+    crate::project::current()
+}
+
+// A handoff as the next agent should read it
+fn handoff_text(h: &Value) -> String {
+    let s = |v: &Value| v.as_str().unwrap_or_default().to_string();
+    let mut out = format!("handoff {} from session {} to {}\n", s(&h["id"]), s(&h["from_session"]), s(&h["to"]));
+    if !s(&h["note"]).is_empty() { out.push_str(&format!("\nNote: {}\n", s(&h["note"]))); }
+    if let Some(link) = h.get("link") {
+        out.push_str(&format!("\nLatest chain link: {}/{}\n", s(&link["chain_name"]), s(&link["slug"])));
+        if !s(&link["content"]).is_empty() { out.push_str(&format!("{}\n", s(&link["content"]))); }
+        for (key, heading) in [("completed", "Completed"), ("next_steps", "Next steps"), ("decisions", "Decisions"), ("files_touched", "Files touched")] {
+            let Some(items) = link.get(key).and_then(|v| v.as_array()) else { continue };
+            out.push_str(&format!("\n## {}\n", heading));
+            for item in items { out.push_str(&format!("- {}\n", item.as_str().unwrap_or_default())); }
+        }
+    }
+    if let Some(todos) = h.get("todos").and_then(|v| v.as_array()).filter(|t| !t.is_empty()) {
+        out.push_str("\nOpen todos:\n");
+        for t in todos { out.push_str(&format!("- {} ({})\n", s(&t["text"]), s(&t["id"]))); }
+    }
+    if let Some(artifacts) = h.get("artifacts").and_then(|v| v.as_array()).filter(|a| !a.is_empty()) {
+        out.push_str("\nArtifacts:\n");
+        for a in artifacts { out.push_str(&format!("- {} [artifact:{}] {}\n", s(&a["title"]), s(&a["id"]), s(&a["file_path"]))); }
+    }
+    out
+}
+
+// Wrap matched terms in **...** so the model can see why each result matched
+fn mark_highlights(results: &mut Value) {
+    for r in results.as_array_mut().into_iter().flatten() {
+        let preview = r.get("preview").and_then(|v| v.as_str()).unwrap_or("").to_string();
+        let ranges = r.get("highlights").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        let (mut marked, mut last) = (String::new(), 0);
+        for (s, e) in ranges.iter().filter_map(|h| Some((h.get(0)?.as_u64()? as usize, h.get(1)?.as_u64()? as usize))) {
+            if s < last || e > preview.len() || !preview.is_char_boundary(s) || !preview.is_char_boundary(e) { continue; }
+            marked.push_str(&preview[last..s]);
+            marked.push_str(&format!("**{}**", &preview[s..e]));
+            last = e;
+        }
+        marked.push_str(&preview[last..]);
+        r["preview"] = json!(marked);
+        if let Some(obj) = r.as_object_mut() { obj.remove("highlights"); }
+    }
+}
+
+// Text for older clients plus the same data as structuredContent, matching the tool's outputSchema
+fn reply(text: String, structured: Value) -> Value {
+    json!({"content": [{"type": "text", "text": text}], "structuredContent": structured})
+}
+
+fn call_tool(name: &str, args: Value, base: &str, token: &str, progress: Option<&Value>) -> Result<Value, String> {
     match name {
         "tinymem_search" => {
             let query = args.get("query").and_then(|v| v.as_str()).ok_or("missing query")?;
             let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(25) as usize;
+            let mut body = json!({"query": query, "limit": limit});
+            for key in ["types", "session_id", "chain_name", "after", "before"] {
+                if let Some(v) = args.get(key) { body[key] = v.clone(); }
+            }
+            // Searches stay in the caller's project unless scope is "global" or another project is named
+            let global = args.get("scope").and_then(|v| v.as_str()) == Some("global");
+            let project = project_arg(&args).or_else(|| if global { None } else { crate::project::current() });
+            if let Some(project) = project { body["project"] = json!(project); }
             let url = format!("{}/search", base);
-            let mut resp = ureq::post(&url)
-                .header("Authorization", &format!("Bearer {}", token))
-                .header("Content-Type", "application/json")
-                .send_json(&json!({"query": query, "limit": limit}))
-                .map_err(|e| format!("request failed: {}", e))?;
+            let mut resp = with_retry(|| {
+                ureq::post(&url)
+                    .header("Authorization", &format!("Bearer {}", token))
+                    .header("Content-Type", "application/json")
+                    .send_json(&body)
+            }).map_err(|e| format!("request failed: {}", e))?;
             let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
-            let results = body.get("results").cloned().unwrap_or(json!([]));
-            Ok(json!({"content": [{"type": "text", "text": serde_json::to_string_pretty(&results).unwrap()}]}))
+            let mut results = body.get("results").cloned().unwrap_or(json!([]));
+            mark_highlights(&mut results);
+            Ok(reply(serde_json::to_string_pretty(&results).unwrap(), json!({"results": results})))
         }
         "tinymem_get" => {
             let id = args.get("id").and_then(|v| v.as_str()).ok_or("missing id")?;
             let max_chars = args.get("max_chars").and_then(|v| v.as_u64()).unwrap_or(8000) as usize;
             let offset = args.get("offset").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
-            let url = format!("{}/get/{}", base, urlencoding::encode(id));
-            let mut resp = ureq::get(&url)
-                .header("Authorization", &format!("Bearer {}", token))
-                .call()
+            let mut url = format!("{}/get/{}", base, urlencoding::encode(id));
+            if let Some(pages) = args.get("pages").and_then(|v| v.as_str()) { url.push_str(&format!("?pages={}", urlencoding::encode(pages))); }
+            let mut resp = with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
                 .map_err(|e| format!("request failed: {}", e))?;
             let mut body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
             // Truncate text field for artifacts to avoid context overflow
@@ -93,75 +330,305 @@ fn call_tool(name: &str, args: Value, base: &str, token: &str) -> Result<Value,
                     body["next_offset"] = json!(end_offset);
                 }
             }
-            Ok(json!({"content": [{"type": "text", "text": serde_json::to_string_pretty(&body).unwrap()}]}))
+            Ok(reply(serde_json::to_string_pretty(&body).unwrap(), body))
         }
         "tinymem_artifact_save" => {
             let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
             let file_path = args.get("file_path").and_then(|v| v.as_str()).ok_or("missing file_path")?;
             let title = args.get("title").and_then(|v| v.as_str()).ok_or("missing title")?;
             let description = args.get("description").and_then(|v| v.as_str()).unwrap_or("");
-            let url = format!("{}/artifact/save/{}", base, sid);
+            let tags = args.get("tags").cloned().unwrap_or(json!([]));
+            let body = json!({"file_path": file_path, "title": title, "description": description, "tags": tags});
+            // Text extraction runs on the server after it answers; GET /artifact/:id/status tracks it
+            let Some(body) = post_or_queue(base, token, &format!("/artifact/save/{}", sid), &body)? else {
+                return Ok(reply(format!("tinymem unreachable: artifact {} queued, it will be saved when the server is back", file_path), json!({"id": "queued", "queued": true})));
+            };
+            let id = body.get("id").and_then(|v| v.as_str()).unwrap_or("unknown");
+            log("info", json!({"tool": "tinymem_artifact_save", "saved": id, "file_type": body.get("file_type")}));
+            let indexing = body.get("indexing").and_then(|v| v.as_bool()).unwrap_or(false);
+            let text = if indexing { format!("artifact saved: {} (text is being indexed for search)", id) } else { format!("artifact saved: {}", id) };
+            Ok(reply(text, json!({"id": format!("artifact:{}", id), "file_type": body.get("file_type"), "indexing": indexing})))
+        }
+        "tinymem_artifact_list" => {
+            let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+            let mut query = Vec::new();
+            for (arg, param) in [("tag", "tag"), ("type", "type")] {
+                if let Some(v) = args.get(arg).and_then(|v| v.as_str()) { query.push(format!("{}={}", param, urlencoding::encode(v))); }
+            }
+            if let Some(project) = project_arg(&args) { query.push(format!("project={}", urlencoding::encode(&project))); }
+            let url = format!("{}/artifacts?{}", base, query.join("&"));
+            let mut resp = with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
+                .map_err(|e| format!("request failed: {}", e))?;
+            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            let mut artifacts: Vec<Value> = body.get("artifacts").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            artifacts.sort_by_key(|a| std::cmp::Reverse(a.get("ts").and_then(|v| v.as_i64()).unwrap_or(0)));
+            let listed: Vec<Value> = artifacts.iter().take(limit).map(|a| json!({
+                "id": format!("artifact:{}", a.get("id").and_then(|v| v.as_str()).unwrap_or("")),
+                "title": a.get("title"),
+                "type": a.get("file_type"),
+                "tags": a.get("tags").cloned().unwrap_or(json!([])),
+                "description": a.get("description"),
+            })).collect();
+            Ok(reply(serde_json::to_string_pretty(&listed).unwrap(), json!({"artifacts": listed})))
+        }
+        "tinymem_artifact_search" => {
+            let query = args.get("query").and_then(|v| v.as_str()).ok_or("missing query")?;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+            let mut body = json!({"query": query, "limit": limit, "types": ["artifact"]});
+            if let Some(v) = args.get("tag") { body["tag"] = v.clone(); }
+            if let Some(v) = args.get("type") { body["file_type"] = v.clone(); }
+            if let Some(project) = project_arg(&args) { body["project"] = json!(project); }
+            let url = format!("{}/search", base);
+            let mut resp = with_retry(|| {
+                ureq::post(&url)
+                    .header("Authorization", &format!("Bearer {}", token))
+                    .header("Content-Type", "application/json")
+                    .send_json(&body)
+            }).map_err(|e| format!("request failed: {}", e))?;
+            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            let mut results = body.get("results").cloned().unwrap_or(json!([]));
+            mark_highlights(&mut results);
+            Ok(reply(serde_json::to_string_pretty(&results).unwrap(), json!({"results": results})))
+        }
+        "tinymem_remember" => {
+            let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
+            let content = args.get("content").and_then(|v| v.as_str()).ok_or("missing content")?;
+            let mut body = json!({"content": content});
+            for key in ["title", "ttl_secs", "decay_days", "pinned", "importance"] {
+                if let Some(v) = args.get(key) { body[key] = v.clone(); }
+            }
+            let Some(body) = post_or_queue(base, token, &format!("/memory/{}", sid), &body)? else {
+                return Ok(reply("tinymem unreachable: memory queued, it will be saved when the server is back".into(), json!({"id": "queued", "queued": true})));
+            };
+            let id = body.get("id").and_then(|v| v.as_str()).unwrap_or("unknown");
+            Ok(reply(format!("memory saved: memory:{}", id), json!({"id": format!("memory:{}", id)})))
+        }
+        "tinymem_prioritize" => {
+            let id = args.get("id").and_then(|v| v.as_str()).ok_or("missing id")?;
+            let mut body = json!({"id": id});
+            for key in ["pinned", "importance"] {
+                if let Some(v) = args.get(key) { body[key] = v.clone(); }
+            }
+            let Some(body) = post_or_queue(base, token, "/priority", &body)? else {
+                return Ok(reply(format!("tinymem unreachable: priority change for {} queued", id), json!({"updated": "queued", "queued": true})));
+            };
+            let updated = body.get("updated").and_then(|v| v.as_str()).unwrap_or("unknown");
+            Ok(reply(format!("updated: {}", updated), json!({"updated": updated})))
+        }
+        "tinymem_chain_status" => {
+            let chain_name = args.get("chain_name").and_then(|v| v.as_str()).ok_or("missing chain_name")?;
+            let status = args.get("status").and_then(|v| v.as_str()).ok_or("missing status")?;
+            let mut body = json!({"status": status});
+            if let Some(slug) = args.get("slug") { body["slug"] = slug.clone(); }
+            let path = format!("/chain/status/{}", urlencoding::encode(chain_name));
+            let Some(_) = post_or_queue(base, token, &path, &body)? else {
+                return Ok(reply(format!("tinymem unreachable: status change for {} queued", chain_name), json!({"status": status, "queued": true})));
+            };
+            Ok(reply(format!("{} is now {}", chain_name, status), json!({"status": status})))
+        }
+        "tinymem_ask" => {
+            let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
+            let question = args.get("question").and_then(|v| v.as_str()).ok_or("missing question")?;
+            let url = format!("{}/session/{}/ask", base, sid);
             let mut resp = ureq::post(&url)
                 .header("Authorization", &format!("Bearer {}", token))
                 .header("Content-Type", "application/json")
-                .send_json(&json!({"file_path": file_path, "title": title, "description": description}))
+                .send_json(json!({"question": question, "options": args.get("options").cloned().unwrap_or(json!([]))}))
+                .map_err(|e| format!("request failed: {}", e))?;
+            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            let answer = body.get("answer").and_then(|v| v.as_str()).ok_or("no answer from operator")?;
+            Ok(reply(answer.to_string(), json!({"answer": answer})))
+        }
+        "tinymem_inbox" => {
+            let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
+            let url = format!("{}/session/{}/inbox", base, sid);
+            let mut resp = with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
+                .map_err(|e| format!("request failed: {}", e))?;
+            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            let messages = body.get("messages").cloned().unwrap_or(json!([]));
+            let text = match messages.as_array().map(|m| m.iter().filter_map(|m| m.get("text")?.as_str()).collect::<Vec<_>>()) {
+                Some(notes) if !notes.is_empty() => notes.iter().map(|n| format!("- {}", n)).collect::<Vec<_>>().join("\n"),
+                _ => "no new messages".to_string(),
+            };
+            Ok(reply(text, json!({"messages": messages})))
+        }
+        "tinymem_hooks" => {
+            let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
+            let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(50);
+            let mut query = vec![format!("limit={}", limit)];
+            for key in ["kind", "task"] {
+                if let Some(v) = args.get(key).and_then(|v| v.as_str()) { query.push(format!("{}={}", key, urlencoding::encode(v))); }
+            }
+            if args.get("errors").and_then(|v| v.as_bool()) == Some(true) { query.push("errors=true".into()); }
+            let url = format!("{}/session/{}/hooks?{}", base, urlencoding::encode(sid), query.join("&"));
+            let mut resp = with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
                 .map_err(|e| format!("request failed: {}", e))?;
             let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            // Meta can carry whole files; keep the argument that identifies each call
+            let hooks: Vec<Value> = body["hooks"].as_array().map(|hooks| hooks.iter().map(|h| {
+                // A hook that doesn't parse is still listed, just without its target
+                let parsed = serde_json::from_value::<crate::models::Hook>(h.clone()).ok();
+                let target = parsed.as_ref().and_then(|p| p.target()).map(|t| crate::models::truncate_chars(t, 200));
+                let failed = parsed.as_ref().is_some_and(|p| p.is_error());
+                json!({"ts": h["ts"], "kind": h["kind"], "task": h["task"], "target": target, "error": failed})
+            }).collect()).unwrap_or_default();
+            let lines: Vec<String> = hooks.iter().map(|h| {
+                let time = chrono::DateTime::from_timestamp(h["ts"].as_i64().unwrap_or_default(), 0)
+                    .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default();
+                let target = h["target"].as_str().map(|t| format!(" {}", t)).unwrap_or_default();
+                let failed = if h["error"] == true { " (failed)" } else { "" };
+                format!("{} {} {}{}{}", time, h["kind"].as_str().unwrap_or_default(), h["task"].as_str().unwrap_or_default(), target, failed)
+            }).collect();
+            let text = if lines.is_empty() { format!("no matching hooks for session {}", sid) } else { lines.join("\n") };
+            Ok(reply(text, json!({"session_id": sid, "hooks": hooks})))
+        }
+        // Todo tools
+        "tinymem_todo_add" => {
+            let text = args.get("text").and_then(|v| v.as_str()).ok_or("missing text")?;
+            let mut body = json!({"text": text});
+            for key in ["chain_name", "session_id", "assignee"] {
+                if let Some(v) = args.get(key) { body[key] = v.clone(); }
+            }
+            let Some(body) = post_or_queue(base, token, "/todo", &body)? else {
+                return Ok(reply("tinymem unreachable: todo queued, it will be added when the server is back".to_string(), json!({"id": "queued", "queued": true})));
+            };
             let id = body.get("id").and_then(|v| v.as_str()).unwrap_or("unknown");
-            Ok(json!({"content": [{"type": "text", "text": format!("artifact saved: {}", id)}]}))
+            Ok(reply(format!("todo added: {}", id), json!({"id": id})))
+        }
+        "tinymem_todo_list" => {
+            let mut query = Vec::new();
+            for (arg, param) in [("chain_name", "chain"), ("session_id", "session_id"), ("assignee", "assignee")] {
+                if let Some(v) = args.get(arg).and_then(|v| v.as_str()) { query.push(format!("{}={}", param, urlencoding::encode(v))); }
+            }
+            if let Some(done) = args.get("done").and_then(|v| v.as_bool()) { query.push(format!("done={}", done)); }
+            let url = format!("{}/todos?{}", base, query.join("&"));
+            let mut resp = with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
+                .map_err(|e| format!("request failed: {}", e))?;
+            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            let todos = body.get("todos").cloned().unwrap_or(json!([]));
+            let lines: Vec<String> = todos.as_array().map(|todos| todos.iter().map(|t| {
+                let mark = if t.get("completed_at").is_some() { "x" } else { " " };
+                let assignee = t.get("assignee").and_then(|a| a.as_str()).map(|a| format!(" (@{})", a)).unwrap_or_default();
+                format!("[{}] {}{} - {}", mark, t["text"].as_str().unwrap_or_default(), assignee, t["id"].as_str().unwrap_or_default())
+            }).collect()).unwrap_or_default();
+            let text = if lines.is_empty() { "no todos".to_string() } else { lines.join("\n") };
+            Ok(reply(text, json!({"todos": todos})))
+        }
+        "tinymem_todo_complete" | "tinymem_todo_assign" => {
+            let id = args.get("id").and_then(|v| v.as_str()).ok_or("missing id")?;
+            let (path, body) = match name {
+                "tinymem_todo_assign" => (format!("/todo/{}/assign", id), json!({"assignee": args.get("assignee")})),
+                _ if args.get("reopen").and_then(|v| v.as_bool()).unwrap_or(false) => (format!("/todo/{}/reopen", id), json!({})),
+                _ => (format!("/todo/{}/complete", id), json!({})),
+            };
+            let Some(todo) = post_or_queue(base, token, &path, &body)? else {
+                return Ok(reply(format!("tinymem unreachable: change to todo {} queued", id), json!({"id": id, "text": "", "created": 0, "queued": true})));
+            };
+            Ok(reply(serde_json::to_string_pretty(&todo).unwrap(), todo))
+        }
+        // Handoff tools
+        "tinymem_handoff" | "tinymem_handoff_accept" => {
+            let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
+            let (path, mut body) = match name {
+                "tinymem_handoff" => ("/handoff", json!({"session_id": sid, "to": args.get("to").and_then(|v| v.as_str()).ok_or("missing to")?})),
+                _ => ("/handoff/accept", json!({"session_id": sid})),
+            };
+            for key in ["note", "chain_name", "id"] {
+                if let Some(v) = args.get(key) { body[key] = v.clone(); }
+            }
+            let url = format!("{}{}", base, path);
+            let mut resp = with_retry(|| {
+                ureq::post(&url)
+                    .header("Authorization", &format!("Bearer {}", token))
+                    .header("Content-Type", "application/json")
+                    .send_json(&body)
+            }).map_err(|e| format!("request failed: {}", e))?;
+            let handoff: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            Ok(reply(handoff_text(&handoff), handoff))
         }
         // Chain tools
         "tinymem_chain_link" => {
             let sid = args.get("session_id").and_then(|v| v.as_str()).ok_or("missing session_id")?;
             let chain_name = args.get("chain_name").and_then(|v| v.as_str()).ok_or("missing chain_name")?;
             let slug = args.get("slug").and_then(|v| v.as_str()).ok_or("missing slug")?;
-            let content = args.get("content").and_then(|v| v.as_str()).ok_or("missing content")?;
-            let url = format!("{}/chain/{}", base, sid);
-            let mut resp = ureq::post(&url)
-                .header("Authorization", &format!("Bearer {}", token))
-                .header("Content-Type", "application/json")
-                .send_json(&json!({"chain_name": chain_name, "slug": slug, "content": content}))
-                .map_err(|e| format!("request failed: {}", e))?;
-            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            let content = args.get("content").and_then(|v| v.as_str()).unwrap_or_default();
+            let mut body = json!({"chain_name": chain_name, "slug": slug, "content": content});
+            for key in ["pinned", "importance", "artifact_ids", "completed", "next_steps", "decisions", "files_touched", "status", "mode"] {
+                if let Some(v) = args.get(key) { body[key] = v.clone(); }
+            }
+            // The proxy runs in the agent's checkout; capturing here also keeps a queued link's commit
+            // the one it was written against rather than whatever is checked out at replay
+            if let Some(git) = std::env::current_dir().ok().and_then(|d| crate::git::detect(&d.to_string_lossy())) {
+                body["git"] = json!(git);
+            }
+            let Some(body) = post_or_queue(base, token, &format!("/chain/{}", sid), &body)? else {
+                return Ok(reply(format!("tinymem unreachable: chain link {}/{} queued, it will be saved when the server is back", chain_name, slug), json!({"saved": "queued", "queued": true})));
+            };
             let saved = body.get("saved").and_then(|v| v.as_str()).unwrap_or("unknown");
-            Ok(json!({"content": [{"type": "text", "text": format!("chain link saved: {}", saved)}]}))
+            Ok(reply(format!("chain link saved: {}", saved), json!({"saved": saved})))
         }
         "tinymem_chain_load" => {
             let chain_name = args.get("chain_name").and_then(|v| v.as_str()).ok_or("missing chain_name")?;
             let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(5) as usize;
             let url = format!("{}/chain/get/{}", base, chain_name);
-            let mut resp = ureq::get(&url)
-                .header("Authorization", &format!("Bearer {}", token))
-                .call()
-                .map_err(|e| format!("request failed: {}", e))?;
+            let mut resp = with_progress(progress, &format!("loading chain {}", chain_name), || {
+                with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
+            }).map_err(|e| format!("request failed: {}", e))?;
             let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
             let links = body.get("links").cloned().unwrap_or(json!([]));
             // Limit results
             let limited: Vec<Value> = links.as_array().map(|arr| arr.iter().take(limit).cloned().collect()).unwrap_or_default();
-            Ok(json!({"content": [{"type": "text", "text": serde_json::to_string_pretty(&limited).unwrap()}]}))
+            log("info", json!({"tool": "tinymem_chain_load", "chain": chain_name, "returned": limited.len(), "total": body.get("count")}));
+            Ok(reply(serde_json::to_string_pretty(&limited).unwrap(), json!({"chain": chain_name, "links": limited})))
         }
         "tinymem_chain_list" => {
-            let url = format!("{}/chains", base);
-            let mut resp = ureq::get(&url)
-                .header("Authorization", &format!("Bearer {}", token))
-                .call()
+            let url = match project_arg(&args) {
+                Some(project) => format!("{}/chains?project={}", base, urlencoding::encode(&project)),
+                None => format!("{}/chains", base),
+            };
+            let mut resp = with_retry(|| ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call())
                 .map_err(|e| format!("request failed: {}", e))?;
             let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
             let chains = body.get("chains").cloned().unwrap_or(json!([]));
-            Ok(json!({"content": [{"type": "text", "text": serde_json::to_string_pretty(&chains).unwrap()}]}))
+            Ok(reply(serde_json::to_string_pretty(&chains).unwrap(), json!({"chains": chains})))
+        }
+        // Never queued: a delete replayed later could remove links saved in the meantime
+        "tinymem_chain_delete" => {
+            let chain_name = args.get("chain_name").and_then(|v| v.as_str()).ok_or("missing chain_name")?;
+            if args.get("confirm").and_then(|v| v.as_str()) != Some(chain_name) {
+                return Err("not deleted: set confirm to the chain name to delete it".into());
+            }
+            let archive = args.get("archive").and_then(|v| v.as_bool()).unwrap_or(false);
+            let url = format!("{}/chain/delete/{}?archive={}", base, urlencoding::encode(chain_name), archive);
+            // The server's reason (no such chain, no archive directory) is more use to the agent than the status
+            let mut resp = with_retry(|| {
+                ureq::delete(&url).config().http_status_as_error(false).build()
+                    .header("Authorization", &format!("Bearer {}", token)).call()
+            }).map_err(|e| format!("request failed: {}", e))?;
+            let ok = resp.status().is_success();
+            let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
+            if !ok { return Err(format!("not deleted: {}", body["error"].as_str().unwrap_or("request failed"))); }
+            log("info", json!({"tool": "tinymem_chain_delete", "chain": chain_name, "archived": body.get("archived")}));
+            let text = match body["archived"]["links"].as_u64() {
+                Some(links) => format!("chain {} archived ({} links) and deleted", chain_name, links),
+                None => format!("chain {} deleted", chain_name),
+            };
+            Ok(reply(text, json!({"deleted": chain_name, "archived": body["archived"]["links"].is_u64()})))
         }
         "tinymem_chain_search" => {
             let query = args.get("query").and_then(|v| v.as_str()).ok_or("missing query")?;
             let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
             let url = format!("{}/chain/search", base);
-            let mut resp = ureq::post(&url)
-                .header("Authorization", &format!("Bearer {}", token))
-                .header("Content-Type", "application/json")
-                .send_json(&json!({"query": query, "limit": limit}))
-                .map_err(|e| format!("request failed: {}", e))?;
+            let body = json!({"query": query, "limit": limit});
+            let mut resp = with_retry(|| {
+                ureq::post(&url)
+                    .header("Authorization", &format!("Bearer {}", token))
+                    .header("Content-Type", "application/json")
+                    .send_json(&body)
+            }).map_err(|e| format!("request failed: {}", e))?;
             let body: Value = resp.body_mut().read_json().map_err(|e| e.to_string())?;
             let chains = body.get("chains").cloned().unwrap_or(json!([]));
-            Ok(json!({"content": [{"type": "text", "text": serde_json::to_string_pretty(&chains).unwrap()}]}))
+            Ok(reply(serde_json::to_string_pretty(&chains).unwrap(), json!({"chains": chains})))
         }
         _ => Err(format!("unknown tool: {}", name))
     }
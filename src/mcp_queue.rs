@@ -0,0 +1,45 @@
+//! On-disk queue of MCP write calls made while the tinymem server was unreachable.
+//!
+//! One JSON object per line in `$TINYMEM_QUEUE` (default `~/.tinymem/mcp-queue.jsonl`). Several
+//! proxies may share the file; [`take`] renames it away first so each entry is replayed once.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub path: String, // e.g. /chain/{session_id}, POSTed to the server base URL
+    pub body: Value,
+    pub queued_at: i64,
+}
+
+fn queue_path() -> PathBuf {
+    if let Ok(p) = std::env::var("TINYMEM_QUEUE") { return PathBuf::from(p); }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+    PathBuf::from(home).join(".tinymem").join("mcp-queue.jsonl")
+}
+
+pub fn push(entries: &[Entry]) -> Result<(), String> {
+    let path = queue_path();
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path).map_err(|e| format!("queue {}: {}", path.display(), e))?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry).map_err(|e| e.to_string())?).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Removes and returns everything queued so far, oldest first
+pub fn take() -> Vec<Entry> {
+    let path = queue_path();
+    if !path.exists() { return vec![]; }
+    let claimed = path.with_extension(format!("flushing.{}", std::process::id()));
+    if std::fs::rename(&path, &claimed).is_err() { return vec![]; }
+    let entries = std::fs::read_to_string(&claimed).unwrap_or_default()
+        .lines().filter_map(|l| serde_json::from_str(l).ok()).collect();
+    let _ = std::fs::remove_file(&claimed);
+    entries
+}
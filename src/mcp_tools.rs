@@ -7,22 +7,115 @@ pub fn tool_list() -> Value {
             tool_search(),
             tool_get(),
             tool_artifact_save(),
+            tool_artifact_list(),
+            tool_artifact_search(),
+            tool_remember(),
+            tool_prioritize(),
+            tool_ask(),
+            tool_inbox(),
+            tool_hooks(),
             // Chain tools
             tool_chain_link(),
             tool_chain_load(),
             tool_chain_list(),
             tool_chain_search(),
+            tool_chain_status(),
+            tool_chain_delete(),
+            // Todo tools
+            tool_todo_add(),
+            tool_todo_list(),
+            tool_todo_complete(),
+            tool_todo_assign(),
+            // Handoff tools
+            tool_handoff(),
+            tool_handoff_accept(),
         ]
     })
 }
 
+// Annotations for tools that only read
+fn read_only() -> Value {
+    json!({"readOnlyHint": true, "openWorldHint": false})
+}
+
+// `project` filter shared by the listing and search tools
+fn project_property() -> Value {
+    json!({
+        "type": "string",
+        "description": "Only content from sessions in this project (e.g. github.com/acme/api); \".\" means the repository you are working in"
+    })
+}
+
+// Annotations for tools that add new content without touching existing content
+fn additive() -> Value {
+    json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": false, "openWorldHint": false})
+}
+
+fn status_property(description: &str) -> Value {
+    json!({"type": "string", "enum": ["active", "blocked", "done"], "description": description})
+}
+
+fn id_schema(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": {"id": {"type": "string", "description": description}, "queued": {"type": "boolean", "description": "True when the server was unreachable and the write was queued locally"}},
+        "required": ["id"]
+    })
+}
+
+fn results_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "results": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "result_type": {"type": "string", "enum": ["chain_link", "artifact", "memory"]},
+                        "id": {"type": "string", "description": "Pass to tinymem_get"},
+                        "title": {"type": "string"},
+                        "score": {"type": "number"},
+                        "preview": {"type": "string", "description": "Best matching region, query terms in **...**"},
+                        "pinned": {"type": "boolean"},
+                        "importance": {"type": "integer"}
+                    },
+                    "required": ["result_type", "id", "title", "score", "preview"]
+                }
+            }
+        },
+        "required": ["results"]
+    })
+}
+
+// {"chains": [{"name": ..., <field>: ...}]}
+fn chains_schema(field: &str, field_type: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "chains": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}, field: {"type": field_type}},
+                    "required": ["name", field]
+                }
+            }
+        },
+        "required": ["chains"]
+    })
+}
+
 fn tool_search() -> Value {
     json!({
         "name": "tinymem_search",
-        "description": r#"Global search across all tinymem content - chains and artifacts.
+        "description": r#"Search tinymem content - chains, artifacts and memories - in the repository you are
+working in, or across every project with scope "global".
 
-Searches chain links (name, slug, content) and artifacts (title, description, extracted text).
-Returns results sorted by relevance with type, id, title, score, and preview.
+Searches chain links (name, slug, content), artifacts (title, description, extracted text)
+and memories (title, content).
+Returns results sorted by relevance with type, id, title, score, and a preview of the best
+matching region with the query terms wrapped in **...**.
 
 Use tinymem_get with the returned id to retrieve full content."#,
         "inputSchema": {
@@ -36,10 +129,40 @@ Use tinymem_get with the returned id to retrieve full content."#,
                     "type": "integer",
                     "description": "Maximum results to return",
                     "default": 25
+                },
+                "types": {
+                    "type": "array",
+                    "items": {"type": "string", "enum": ["chain_link", "artifact", "memory"]},
+                    "description": "Only return these result types (default: all)"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Only content created by this tinymem session"
+                },
+                "chain_name": {
+                    "type": "string",
+                    "description": "Only links from this chain (excludes artifacts)"
+                },
+                "after": {
+                    "type": "integer",
+                    "description": "Only content saved at or after this unix timestamp (seconds)"
+                },
+                "before": {
+                    "type": "integer",
+                    "description": "Only content saved before this unix timestamp (seconds)"
+                },
+                "project": project_property(),
+                "scope": {
+                    "type": "string",
+                    "enum": ["project", "global"],
+                    "description": "\"project\" (default) searches only the repository you are working in; \"global\" searches every project",
+                    "default": "project"
                 }
             },
             "required": ["query"]
-        }
+        },
+        "annotations": read_only(),
+        "outputSchema": results_schema()
     })
 }
 
@@ -52,13 +175,16 @@ Supports two id formats:
 - chain:name:slug - retrieves specific chain link content
 - artifact:id - retrieves artifact with extracted text (for PDFs) or file content
 
+For PDFs, pass pages (e.g. "12" or "3-5") to read only those pages; search results cite the page
+their preview came from.
+
 Use tinymem_search first to find relevant ids."#,
         "inputSchema": {
             "type": "object",
             "properties": {
                 "id": {
                     "type": "string",
-                    "description": "Content id from search results (chain:name:slug or artifact:id)"
+                    "description": "Content id from search results (chain:name:slug, artifact:id or memory:id)"
                 },
                 "max_chars": {
                     "type": "integer",
@@ -69,10 +195,29 @@ Use tinymem_search first to find relevant ids."#,
                     "type": "integer",
                     "description": "Character offset to start from (default: 0). Use with max_chars to paginate through large content.",
                     "default": 0
+                },
+                "pages": {
+                    "type": "string",
+                    "description": "PDF artifacts only: a page or range such as \"12\", \"3-5\" or \"3-\" (to the end). offset/max_chars then apply within those pages."
                 }
             },
             "required": ["id"]
-        }
+        },
+        "annotations": read_only(),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {
+                "type": {"type": "string", "enum": ["chain_link", "artifact", "memory"]},
+                "content": {"type": "string", "description": "Chain link or memory content"},
+                "text": {"type": "string", "description": "Artifact text, windowed by offset/max_chars"},
+                "text_range": {"type": "object"},
+                "meta": {"type": "object", "description": "PDF title, author, page_count and page_offsets"},
+                "pages": {"type": "object", "description": "Pages returned: from, to, page_count"},
+                "has_more": {"type": "boolean"},
+                "next_offset": {"type": "integer"}
+            },
+            "required": ["type"]
+        })
     })
 }
 
@@ -112,10 +257,521 @@ The file stays on the filesystem - tinymem only stores the reference."#,
                 "description": {
                     "type": "string",
                     "description": "Key topics, authors, purpose - metadata that helps fuzzy search find this artifact later"
+                },
+                "tags": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Short labels for filtering later (e.g. 'paper', 'spec', 'config')"
                 }
             },
             "required": ["session_id", "file_path", "title"]
-        }
+        },
+        "annotations": additive(),
+        "outputSchema": id_schema("artifact:id of the saved artifact")
+    })
+}
+
+fn tool_artifact_list() -> Value {
+    json!({
+        "name": "tinymem_artifact_list",
+        "description": r#"List saved artifacts, newest first, optionally filtered by tag or file type.
+
+Returns id (ready for tinymem_get), title, type, tags and description for each artifact.
+Use this to see what reference material already exists before saving or searching."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "tag": {
+                    "type": "string",
+                    "description": "Only artifacts with this tag"
+                },
+                "type": {
+                    "type": "string",
+                    "description": "Only artifacts of this file type (pdf, md, txt, ...)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum artifacts to return",
+                    "default": 50
+                },
+                "project": project_property()
+            }
+        },
+        "annotations": read_only(),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {
+                "artifacts": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": {"type": "string"},
+                            "title": {"type": "string"},
+                            "type": {"type": "string"},
+                            "tags": {"type": "array", "items": {"type": "string"}},
+                            "description": {"type": "string"}
+                        },
+                        "required": ["id", "title"]
+                    }
+                }
+            },
+            "required": ["artifacts"]
+        })
+    })
+}
+
+fn tool_artifact_search() -> Value {
+    json!({
+        "name": "tinymem_artifact_search",
+        "description": r#"Full-text search over artifacts only (title, description, extracted text).
+
+Like tinymem_search restricted to artifacts, with optional tag and file type filters.
+Returns ids ready for tinymem_get, with a preview of the best matching region."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search query"
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "Only artifacts with this tag"
+                },
+                "type": {
+                    "type": "string",
+                    "description": "Only artifacts of this file type (pdf, md, txt, ...)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum results to return",
+                    "default": 10
+                },
+                "project": project_property()
+            },
+            "required": ["query"]
+        },
+        "annotations": read_only(),
+        "outputSchema": results_schema()
+    })
+}
+
+fn tool_remember() -> Value {
+    json!({
+        "name": "tinymem_remember",
+        "description": r#"Save a standalone fact to tinymem memory.
+
+Use for durable knowledge that does not belong to a chain: conventions, gotchas, decisions.
+Set ttl_secs for facts that go stale at a known time, or decay_days to let the memory be
+archived if nobody reads it for that long. Memories are found with tinymem_search."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID (from TINYMEM_SESSION env)"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "The fact to remember, self-contained"
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Short title with key search terms (default: start of content)"
+                },
+                "ttl_secs": {
+                    "type": "integer",
+                    "description": "Archive the memory after this many seconds"
+                },
+                "decay_days": {
+                    "type": "integer",
+                    "description": "Archive the memory after this many days without being read"
+                },
+                "pinned": {
+                    "type": "boolean",
+                    "description": "Pin the memory: never archived, ranks higher in search"
+                },
+                "importance": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 10,
+                    "description": "Search ranking boost, 0 (default) to 10"
+                }
+            },
+            "required": ["session_id", "content"]
+        },
+        "annotations": additive(),
+        "outputSchema": id_schema("memory:id of the saved memory")
+    })
+}
+
+fn tool_prioritize() -> Value {
+    json!({
+        "name": "tinymem_prioritize",
+        "description": r#"Pin or re-weight an existing chain link or memory.
+
+Pinned items are exempt from memory decay and rank higher in tinymem_search; importance
+(0-10) adds a further boost. Reserve this for knowledge that must not get lost."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "chain:name:slug or memory:id (as returned by tinymem_search)"
+                },
+                "pinned": {
+                    "type": "boolean",
+                    "description": "Pin (true) or unpin (false); omit to leave unchanged"
+                },
+                "importance": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 10,
+                    "description": "New importance; omit to leave unchanged"
+                }
+            },
+            "required": ["id"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": true, "openWorldHint": false}),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"updated": {"type": "string"}, "queued": {"type": "boolean", "description": "True when the server was unreachable and the write was queued locally"}},
+            "required": ["updated"]
+        })
+    })
+}
+
+fn tool_ask() -> Value {
+    json!({
+        "name": "tinymem_ask",
+        "description": r#"Ask the human operator a question and wait for the answer.
+
+The question appears in the tinymem TUI (with a desktop notification) and this call
+blocks until the operator answers, up to 5 minutes. Pass `options` for a multiple-choice
+question: the operator picks from a list and you get the chosen option back verbatim.
+
+Use sparingly - only for decisions you genuinely cannot make on your own."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID (from TINYMEM_SESSION env)"
+                },
+                "question": {
+                    "type": "string",
+                    "description": "Question for the operator, with enough context to answer without looking at your session"
+                },
+                "options": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Possible answers; the operator picks one and it is returned exactly as given. Omit for a free-text answer"
+                }
+            },
+            "required": ["session_id", "question"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": false, "openWorldHint": true}),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"answer": {"type": "string"}},
+            "required": ["answer"]
+        })
+    })
+}
+
+fn tool_inbox() -> Value {
+    json!({
+        "name": "tinymem_inbox",
+        "description": r#"Read notes the human operator broadcast to running sessions (e.g. "hold off on deploys").
+
+Each note is returned once. Check it before risky or long-running steps and follow what it says."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session ID (from TINYMEM_SESSION env)"
+                }
+            },
+            "required": ["session_id"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": false, "openWorldHint": false}),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"messages": {"type": "array", "items": {
+                "type": "object",
+                "properties": {"ts": {"type": "integer"}, "text": {"type": "string"}}
+            }}},
+            "required": ["messages"]
+        })
+    })
+}
+
+fn tool_hooks() -> Value {
+    json!({
+        "name": "tinymem_hooks",
+        "description": r#"List the tool calls a session actually made: its hooks, most recent last.
+
+Use this when resuming someone else's work to check what a previous session really ran (which files
+it edited, which commands it ran, what failed) instead of relying only on the prose in its chain
+links. Each entry shows the tool and the file, path or command it was called with."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Session whose hooks to list (e.g. the session_id of a chain link)"
+                },
+                "kind": {
+                    "type": "string",
+                    "description": "Only this kind of hook: pre, post, error, notification, user_prompt or stop"
+                },
+                "task": {
+                    "type": "string",
+                    "description": "Only tools whose name contains this (case-insensitive), e.g. Edit or Bash"
+                },
+                "errors": {
+                    "type": "boolean",
+                    "description": "Only calls that failed (default: false)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most recent matching hooks to return (default: 50)"
+                }
+            },
+            "required": ["session_id"]
+        },
+        "annotations": read_only(),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {
+                "session_id": {"type": "string"},
+                "hooks": {"type": "array", "items": {
+                    "type": "object",
+                    "properties": {
+                        "ts": {"type": "integer"}, "kind": {"type": "string"}, "task": {"type": "string"},
+                        "target": {"type": ["string", "null"], "description": "File, path or command the tool was called with"},
+                        "error": {"type": "boolean"}
+                    }
+                }}
+            },
+            "required": ["session_id", "hooks"]
+        })
+    })
+}
+
+// ============ Todo Tools ============
+
+fn todo_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "text": {"type": "string"},
+            "chain_name": {"type": "string"},
+            "session_id": {"type": "string"},
+            "assignee": {"type": "string"},
+            "created": {"type": "integer"},
+            "completed_at": {"type": "integer"}
+        },
+        "required": ["id", "text", "created"]
+    })
+}
+
+fn tool_todo_add() -> Value {
+    json!({
+        "name": "tinymem_todo_add",
+        "description": r#"Add a todo to a chain or session, shared with the operator and other agents.
+
+Use todos for next steps that someone has to pick up and finish; unlike next steps written into
+a chain link, they can be assigned, listed and ticked off."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "text": {
+                    "type": "string",
+                    "description": "What needs doing"
+                },
+                "chain_name": {
+                    "type": "string",
+                    "description": "Chain (workstream) the todo belongs to"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Session the todo belongs to (from TINYMEM_SESSION env); give this or chain_name"
+                },
+                "assignee": {
+                    "type": "string",
+                    "description": "Who should do it: a session id, agent name or \"operator\""
+                }
+            },
+            "required": ["text"]
+        },
+        "annotations": additive(),
+        "outputSchema": id_schema("Id of the new todo")
+    })
+}
+
+fn tool_todo_list() -> Value {
+    json!({
+        "name": "tinymem_todo_list",
+        "description": r#"List todos, open ones first. Check this when picking up a chain to see what is left."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "chain_name": {
+                    "type": "string",
+                    "description": "Only this chain's todos"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Only this session's todos"
+                },
+                "assignee": {
+                    "type": "string",
+                    "description": "Only todos assigned to this session, agent or \"operator\""
+                },
+                "done": {
+                    "type": "boolean",
+                    "description": "true for completed todos only, false for open ones only; omit for both"
+                }
+            }
+        },
+        "annotations": read_only(),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"todos": {"type": "array", "items": todo_schema()}},
+            "required": ["todos"]
+        })
+    })
+}
+
+fn tool_todo_complete() -> Value {
+    json!({
+        "name": "tinymem_todo_complete",
+        "description": r#"Mark a todo as done, or open it again with reopen: true."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Todo id (from tinymem_todo_list)"
+                },
+                "reopen": {
+                    "type": "boolean",
+                    "description": "Open a completed todo again",
+                    "default": false
+                }
+            },
+            "required": ["id"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": true, "openWorldHint": false}),
+        "outputSchema": todo_schema()
+    })
+}
+
+fn tool_todo_assign() -> Value {
+    json!({
+        "name": "tinymem_todo_assign",
+        "description": r#"Assign a todo to a session, agent or the operator; omit assignee to unassign it."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "Todo id (from tinymem_todo_list)"
+                },
+                "assignee": {
+                    "type": "string",
+                    "description": "Session id, agent name or \"operator\""
+                }
+            },
+            "required": ["id"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": true, "openWorldHint": false}),
+        "outputSchema": todo_schema()
+    })
+}
+
+// ============ Handoff Tools ============
+
+fn handoff_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id": {"type": "string"},
+            "from_session": {"type": "string"},
+            "to": {"type": "string"},
+            "note": {"type": "string"},
+            "link": {"type": "object", "description": "The sending session's latest chain link"},
+            "todos": {"type": "array", "items": todo_schema()},
+            "artifacts": {"type": "array", "items": {"type": "object"}},
+            "created": {"type": "integer"}
+        },
+        "required": ["id", "from_session", "to", "created"]
+    })
+}
+
+fn tool_handoff() -> Value {
+    json!({
+        "name": "tinymem_handoff",
+        "description": r#"Hand your work over to another agent or session.
+
+Packages your latest chain link, the open todos on that chain or session, and the artifacts you
+saved or attached into a handoff addressed to `to`, which picks it up with tinymem_handoff_accept.
+Save a chain link first so the handoff carries where you stopped."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Your session ID (from TINYMEM_SESSION env)"
+                },
+                "to": {
+                    "type": "string",
+                    "description": "Session id or agent name (e.g. 'codex') that should continue"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Anything the next agent should know that isn't in the chain link"
+                },
+                "chain_name": {
+                    "type": "string",
+                    "description": "Hand over this chain's latest link instead of your own latest link anywhere"
+                }
+            },
+            "required": ["session_id", "to"]
+        },
+        "annotations": additive(),
+        "outputSchema": handoff_schema()
+    })
+}
+
+fn tool_handoff_accept() -> Value {
+    json!({
+        "name": "tinymem_handoff_accept",
+        "description": r#"Accept a handoff and get the work another agent left for you: their note, latest chain link,
+open todos and artifacts.
+
+Without an id, takes the oldest pending handoff addressed to your session or your agent name.
+Call this at the start of a session when your inbox mentions a handoff."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "session_id": {
+                    "type": "string",
+                    "description": "Your session ID (from TINYMEM_SESSION env)"
+                },
+                "id": {
+                    "type": "string",
+                    "description": "Handoff id; omit for the oldest one addressed to you"
+                }
+            },
+            "required": ["session_id"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": false, "openWorldHint": false}),
+        "outputSchema": handoff_schema()
     })
 }
 
@@ -136,10 +792,15 @@ Use this when pausing work on a feature to preserve context for later sessions.
 The chain_name groups related links together (e.g., 'auth-feature', 'bug-fix-123').
 The slug describes this specific checkpoint (e.g., 'implement-jwt', 'fix-refresh-token').
 
+Prefer the structured fields (completed, next_steps, decisions, files_touched) over headings in
+content; the next session gets them back as separate lists. content holds anything else.
+
 Example usage:
 - chain_name: 'user-auth'
 - slug: 'jwt-middleware-complete'
-- content: '## Completed\n- JWT validation middleware\n- Token refresh logic\n\n## Next Steps\n- Add rate limiting'"#,
+- completed: ['JWT validation middleware', 'Token refresh logic']
+- next_steps: ['Add rate limiting']
+- files_touched: ['src/auth/jwt.rs']"#,
         "inputSchema": {
             "type": "object",
             "properties": {
@@ -157,11 +818,58 @@ Example usage:
                 },
                 "content": {
                     "type": "string",
-                    "description": "Chain link content: context, decisions, code changes, next steps"
+                    "description": "Free-form context not covered by the structured fields (optional when any of them is given)"
+                },
+                "completed": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "What was finished in this stretch of work"
+                },
+                "next_steps": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "What to do next, in order"
+                },
+                "decisions": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Technical decisions made, with their reasons"
+                },
+                "files_touched": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Paths of files created or changed"
+                },
+                "status": status_property("Where this step stands; omit for an ordinary checkpoint"),
+                "pinned": {
+                    "type": "boolean",
+                    "description": "Pin critical links (architectural decisions) so they rank above routine notes"
+                },
+                "importance": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": 10,
+                    "description": "Search ranking boost, 0 (default) to 10"
+                },
+                "artifact_ids": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Ids of saved artifacts this checkpoint discusses (without the artifact: prefix)"
+                },
+                "mode": {
+                    "type": "string",
+                    "enum": ["create", "upsert", "append"],
+                    "description": "When the chain already has a link with this slug: create (default) fails with 409, upsert replaces that link, append adds this content and these lists to it"
                 }
             },
-            "required": ["session_id", "chain_name", "slug", "content"]
-        }
+            "required": ["session_id", "chain_name", "slug"]
+        },
+        "annotations": additive(),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"saved": {"type": "string", "description": "Redis key of the saved link"}, "queued": {"type": "boolean", "description": "True when the server was unreachable and the write was queued locally"}},
+            "required": ["saved"]
+        })
     })
 }
 
@@ -171,7 +879,8 @@ fn tool_chain_load() -> Value {
         "description": r#"Load chain links to continue work from a previous session.
 
 Returns all links in the chain, sorted by timestamp (newest first).
-Each link contains the preserved context, decisions, and next steps.
+Each link contains the preserved context, decisions, and next steps, plus the titles
+of any artifacts attached to it (fetch their text with tinymem_get artifact:id).
 
 Use this at the start of a session to restore context from previous work.
 The most recent link typically contains the immediate next steps."#,
@@ -189,21 +898,114 @@ The most recent link typically contains the immediate next steps."#,
                 }
             },
             "required": ["chain_name"]
-        }
+        },
+        "annotations": read_only(),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {
+                "chain": {"type": "string"},
+                "links": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "slug": {"type": "string"},
+                            "content": {"type": "string"},
+                            "session_id": {"type": "string"},
+                            "ts": {"type": "integer"},
+                            "artifacts": {"type": "array", "items": {"type": "object"}}
+                        },
+                        "required": ["slug", "content", "ts"]
+                    }
+                }
+            },
+            "required": ["chain", "links"]
+        })
     })
 }
 
 fn tool_chain_list() -> Value {
     json!({
         "name": "tinymem_chain_list",
-        "description": r#"List all available chains with their link counts.
+        "description": r#"List all available chains with their link counts and status.
 
-Returns chain names with metadata about each chain.
+Returns chain names with metadata about each chain; status is active, blocked or done.
 Use this to discover what chains exist before loading one."#,
         "inputSchema": {
             "type": "object",
-            "properties": {}
-        }
+            "properties": {
+                "project": project_property()
+            }
+        },
+        "annotations": read_only(),
+        "outputSchema": chains_schema("links", "integer")
+    })
+}
+
+fn tool_chain_status() -> Value {
+    json!({
+        "name": "tinymem_chain_status",
+        "description": r#"Mark a chain (a workstream) as active, blocked or done, or set the status of one of its links.
+
+Set a chain to blocked when you cannot go on without something outside your control, and to done
+when the work is finished, so the operator can see at a glance which workstreams are stuck."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "chain_name": {
+                    "type": "string",
+                    "description": "Chain to update"
+                },
+                "status": status_property("New status"),
+                "slug": {
+                    "type": "string",
+                    "description": "Update only this link instead of the whole chain"
+                }
+            },
+            "required": ["chain_name", "status"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": false, "idempotentHint": true, "openWorldHint": false}),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"status": {"type": "string"}, "queued": {"type": "boolean", "description": "True when the server was unreachable and the write was queued locally"}},
+            "required": ["status"]
+        })
+    })
+}
+
+fn tool_chain_delete() -> Value {
+    json!({
+        "name": "tinymem_chain_delete",
+        "description": r#"Delete a chain and all of its links, optionally archiving them first.
+
+Use this to retire your own chains once the feature they tracked is finished and nobody will resume
+it. Prefer tinymem_chain_status with "done" when the history may still be useful to others. With
+archive, the links are kept in the server's cold archive (if it has one) instead of being lost. This
+cannot be undone from here: confirm must repeat the chain name exactly."#,
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "chain_name": {
+                    "type": "string",
+                    "description": "Chain to delete"
+                },
+                "confirm": {
+                    "type": "string",
+                    "description": "The chain name again, to confirm the deletion"
+                },
+                "archive": {
+                    "type": "boolean",
+                    "description": "Write the links to the server's archive directory before deleting them (default: false)"
+                }
+            },
+            "required": ["chain_name", "confirm"]
+        },
+        "annotations": json!({"readOnlyHint": false, "destructiveHint": true, "idempotentHint": false, "openWorldHint": false}),
+        "outputSchema": json!({
+            "type": "object",
+            "properties": {"deleted": {"type": "string"}, "archived": {"type": "boolean"}},
+            "required": ["deleted", "archived"]
+        })
     })
 }
 
@@ -228,6 +1030,8 @@ Use this to find chains when you don't remember the exact name."#,
                 }
             },
             "required": ["query"]
-        }
+        },
+        "annotations": read_only(),
+        "outputSchema": chains_schema("score", "number")
     })
 }
@@ -7,6 +7,26 @@ pub struct Session {
     pub id: String, pub name: Option<String>, pub agent: String,
     pub cwd: String, pub status: Status, pub created: i64,
     #[serde(default)] pub last_activity: i64, // defaults to 0 for old sessions
+    #[serde(default)] pub last_heartbeat: i64, // last sign of life without a tool call; 0 until the first heartbeat
+    #[serde(default, skip_serializing_if = "Option::is_none")] pub git: Option<GitContext>, // of `cwd` when the session started
+    #[serde(default, skip_serializing_if = "Option::is_none")] pub project: Option<String>, // see crate::project
+    #[serde(default, skip_serializing_if = "Option::is_none")] pub summary: Option<String>, // what happened, see crate::summarize
+}
+
+/// Where a session or checkpoint stood in git
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitContext {
+    pub repo: String,           // origin URL without credentials, else the repository's top-level directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>, // None on a detached HEAD
+    pub commit: String,         // full HEAD sha
+}
+
+impl std::fmt::Display for GitContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let short = self.commit.get(..7).unwrap_or(&self.commit);
+        write!(f, "{} @ {} ({})", self.branch.as_deref().unwrap_or("detached"), short, self.repo)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -16,23 +36,362 @@ pub enum Status {
     Done,
 }
 
+/// How alive an active session looks: working, connected but quiet, or silent long enough that the
+/// agent is probably gone (it is only marked done after the longer `--done-after-secs`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Liveness { Active, Idle, Disconnected }
+
+impl Liveness {
+    pub fn as_str(&self) -> &'static str {
+        match self { Liveness::Active => "active", Liveness::Idle => "idle", Liveness::Disconnected => "disconnected" }
+    }
+}
+
+/// Thresholds for [`Liveness`]: idle after this long without tool activity, disconnected after this
+/// long without activity or a heartbeat
+#[derive(Debug, Clone, Copy)]
+pub struct Presence { pub idle_after_secs: i64, pub disconnect_after_secs: i64 }
+
+impl Default for Presence {
+    fn default() -> Self { Self { idle_after_secs: 60, disconnect_after_secs: 120 } }
+}
+
+impl Presence {
+    /// None for finished sessions
+    pub fn liveness(&self, s: &Session, now: i64) -> Option<Liveness> {
+        if s.status != Status::Active { return None; }
+        let seen = s.last_activity.max(s.last_heartbeat).max(s.created);
+        Some(if now - seen > self.disconnect_after_secs {
+            Liveness::Disconnected
+        } else if now - s.last_activity.max(s.created) > self.idle_after_secs {
+            Liveness::Idle
+        } else {
+            Liveness::Active
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hook { pub ts: i64, pub kind: HookKind, pub task: String, #[serde(default)] pub meta: Value }
+
+/// What a hook reports. Claude Code event names are accepted as aliases; any other kind an adapter
+/// sends is kept as `Other` so older and custom adapters keep working
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookKind {
+    #[serde(alias = "PreToolUse")] Pre,
+    #[serde(alias = "PostToolUse")] Post,
+    Error,
+    #[serde(alias = "Notification")] Notification,
+    #[serde(alias = "UserPromptSubmit")] UserPrompt,
+    #[serde(alias = "Stop")] Stop,
+    #[serde(untagged)] Other(String),
+}
+
+impl Hook {
+    /// An `error` hook, or a post hook whose meta (or meta.tool_response) says `is_error`
+    pub fn is_error(&self) -> bool {
+        let flagged = |v: &Value| v.get("is_error").and_then(|e| e.as_bool()).unwrap_or(false);
+        self.kind == HookKind::Error
+            || (self.kind == HookKind::Post && (flagged(&self.meta) || self.meta.get("tool_response").is_some_and(flagged)))
+    }
+
+    /// The file, path, command, pattern or URL the tool was called with, out of a meta that can carry
+    /// whole files
+    pub fn target(&self) -> Option<&str> {
+        ["file_path", "notebook_path", "path", "command", "pattern", "url"].iter().find_map(|k| self.meta.get(*k)?.as_str())
+    }
+
+    /// The file the tool was called on, if any
+    pub fn file(&self) -> Option<&str> {
+        FILE_KEYS.iter().filter_map(|k| self.meta.get(*k)?.as_str()).find(|f| !f.is_empty())
+    }
+
+    /// `hooks` with one hook per tool call: agents that report both halves of a call also report its
+    /// pre hook, which is left out
+    pub fn calls(hooks: &[Hook]) -> Vec<&Hook> {
+        let has_post = hooks.iter().any(|h| h.kind == HookKind::Post);
+        hooks.iter().filter(|h| !(has_post && h.kind == HookKind::Pre)).collect()
+    }
+
+    /// One line for a prompt or draft: the tool (prefixed by the kind when it isn't a call), its target
+    /// cut to HOOK_LINE_CHARS, and whether it failed
+    pub fn line(&self) -> String {
+        let kind = match self.kind { HookKind::Pre | HookKind::Post => String::new(), ref k => format!("{} ", k.as_str()) };
+        let target = self.target().map(|t| format!(" {}", truncate_chars(t, HOOK_LINE_CHARS))).unwrap_or_default();
+        format!("{kind}{}{target}{}", self.task, if self.is_error() { " (failed)" } else { "" })
+    }
+}
+
+/// Longest hook target [`Hook::line`] keeps
+pub const HOOK_LINE_CHARS: usize = 300;
+
+// Kept per session (newest first) so failing agents can be spotted and inspected
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Hook { pub ts: i64, pub kind: String, pub task: String, #[serde(default)] pub meta: Value }
+pub struct HookError { pub ts: i64, pub task: String, pub message: String }
+
+// Tool input keys that name the file a call worked on
+const FILE_KEYS: &[&str] = &["file_path", "notebook_path", "path"];
+
+/// What a session did, for comparing two runs of the same task
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionProfile {
+    pub session: Session,
+    pub tools: std::collections::BTreeMap<String, usize>, // tool -> calls
+    pub files: std::collections::BTreeSet<String>,        // from hook meta
+    pub links: Vec<String>,                               // chain:name:slug of links it saved, oldest first
+    pub hooks: usize,
+    pub errors: usize,
+}
+
+impl SessionProfile {
+    /// Calls are counted from pre hooks, or from post hooks for agents that only report those
+    pub fn new(session: Session, hooks: &[Hook], links: Vec<String>) -> Self {
+        let calls = if hooks.iter().any(|h| h.kind == HookKind::Pre) { HookKind::Pre } else { HookKind::Post };
+        let mut tools = std::collections::BTreeMap::new();
+        let mut files = std::collections::BTreeSet::new();
+        for hook in hooks {
+            if hook.kind == calls { *tools.entry(hook.task.clone()).or_insert(0) += 1; }
+            files.extend(FILE_KEYS.iter().filter_map(|k| hook.meta.get(*k)?.as_str()).filter(|f| !f.is_empty()).map(String::from));
+        }
+        let errors = hooks.iter().filter(|h| h.is_error()).count();
+        Self { session, tools, files, links, hooks: hooks.len(), errors }
+    }
+}
 
 #[derive(Debug, Deserialize)]
+pub struct CompareQuery { pub a: String, pub b: String }
+
+impl HookKind {
+    pub fn parse(s: &str) -> Self {
+        serde_json::from_value(Value::String(s.to_string())).unwrap_or_else(|_| HookKind::Other(s.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            HookKind::Pre => "pre",
+            HookKind::Post => "post",
+            HookKind::Error => "error",
+            HookKind::Notification => "notification",
+            HookKind::UserPrompt => "user_prompt",
+            HookKind::Stop => "stop",
+            HookKind::Other(s) => s,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CreateSessionReq { pub agent: String, pub name: Option<String>, #[serde(default)] pub cwd: String }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HookReq {
+    pub kind: HookKind, pub task: String, #[serde(default)] pub meta: Value,
+    #[serde(default)] pub requires_approval: bool, // pre hooks only: hold the reply until the operator decides
+}
+
 #[derive(Debug, Deserialize)]
-pub struct HookReq { pub kind: String, pub task: String, #[serde(default)] pub meta: Value }
+pub struct StartReq {
+    #[serde(default)]
+    pub provider: String,           // e.g. claude, gemini, cursor; defaults to `agent`
+    #[serde(default)]
+    pub external_id: String,        // the provider's own session id, used to resume
+    pub claude_session_id: Option<String>, // legacy form of provider=claude
+    pub agent: String,
+    #[serde(default)]
+    pub cwd: String,
+}
+
+impl StartReq {
+    /// (provider, external id) to resume by, if the caller sent one
+    pub fn external_key(&self) -> Option<(String, String)> {
+        if !self.external_id.is_empty() {
+            let provider = if self.provider.is_empty() { &self.agent } else { &self.provider };
+            return Some((provider.clone(), self.external_id.clone()));
+        }
+        self.claude_session_id.clone().filter(|id| !id.is_empty()).map(|id| ("claude".to_string(), id))
+    }
+}
+
+// Transcript - user prompts and assistant responses forwarded by the hook adapter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptEntry { pub ts: i64, pub role: String, pub text: String }
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptReq { pub role: String, pub text: String }
+
+#[derive(Debug, Deserialize)]
+pub struct SessionsQuery {
+    pub status: Option<String>, // active (default) or done
+    #[serde(default)]
+    pub full: bool, // full session objects instead of ids
+    #[serde(default)]
+    pub last_hook: bool, // with full, each session's latest hook
+    pub project: Option<String>,
+    pub agent: Option<String>, // case-insensitive, applied before limit and offset
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(default)]
+    pub offset: usize,
+    pub after: Option<i64>,  // unix seconds, compared with last activity
+    pub before: Option<i64>,
+    pub agent: Option<String>,
+    pub name: Option<String>, // fuzzy, against the session's name (its id when unnamed)
+}
+
+impl HistoryQuery {
+    pub fn is_filtered(&self) -> bool {
+        self.after.is_some() || self.before.is_some() || self.agent.is_some() || self.name.as_deref().is_some_and(|n| !n.trim().is_empty())
+    }
+
+    pub fn matches(&self, s: &Session) -> bool {
+        let ts = if s.last_activity > 0 { s.last_activity } else { s.created };
+        let name_matches = |q: &str| {
+            let (q, name) = (q.trim().to_lowercase(), s.name.as_deref().unwrap_or(&s.id).to_lowercase());
+            q.is_empty() || name.contains(&q) || strsim::jaro_winkler(&name, &q) >= 0.8
+        };
+        self.after.is_none_or(|a| ts >= a) && self.before.is_none_or(|b| ts <= b)
+            && self.agent.as_ref().is_none_or(|a| s.agent.eq_ignore_ascii_case(a))
+            && self.name.as_deref().is_none_or(name_matches)
+    }
+}
 
+/// `GET /session/:id/hooks`: the most recent `limit` hooks that pass every filter given
 #[derive(Debug, Deserialize)]
-pub struct StartReq { pub claude_session_id: String, pub agent: String, #[serde(default)] pub cwd: String }
+pub struct HooksQuery {
+    pub kind: Option<HookKind>,
+    pub task: Option<String>, // case-insensitive substring of the tool name
+    #[serde(default)]
+    pub errors: bool, // only failed calls, as Hook::is_error sees them
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
 
-#[derive(Debug, Clone)]
-pub enum TuiEvent { NewSession, SessionDone, Refresh }
+impl HooksQuery {
+    pub fn is_filtered(&self) -> bool { self.kind.is_some() || self.task.is_some() || self.errors }
+
+    pub fn matches(&self, h: &Hook) -> bool {
+        self.kind.as_ref().is_none_or(|k| *k == h.kind)
+            && self.task.as_deref().is_none_or(|t| h.task.to_lowercase().contains(&t.to_lowercase()))
+            && (!self.errors || h.is_error())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TranscriptQuery {
+    #[serde(default = "default_audit_limit")]
+    pub limit: usize,
+}
+
+// Token usage - reported via /session/:id/usage or a `usage` object in hook meta
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageReq {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub model: String,
+    pub cost_usd: Option<f64>, // overrides the built-in price table
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage { pub input_tokens: u64, pub output_tokens: u64, pub cost_usd: f64 }
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    #[serde(default = "default_stats_days")]
+    pub days: i64,
+}
+
+fn default_stats_days() -> i64 { 30 }
+
+// Tool between a pre hook and its post/error hook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveTool { pub tool: String, pub since: i64 }
+
+/// Pairs a pre hook with its post/error hook: the agent's own call id (meta `invocation_id`, or Claude
+/// Code's `tool_use_id`) when it sends one, else the tool name
+pub fn invocation_id(task: &str, meta: &Value) -> String {
+    ["invocation_id", "tool_use_id"].iter()
+        .find_map(|k| meta.get(k).and_then(|v| v.as_str()))
+        .unwrap_or(task)
+        .to_string()
+}
+
+// Operator questions - agent blocks on /session/:id/ask until answered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingAsk {
+    pub question: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>, // multiple choice when set: the answer is one of these, verbatim
+    pub ts: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AskReq { pub question: String, #[serde(default)] pub options: Vec<String> }
+
+#[derive(Debug, Deserialize)]
+pub struct AnswerReq { pub answer: String }
+
+// Operator note waiting in a session's inbox until the agent reads it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboxMessage { pub ts: i64, pub text: String }
+
+#[derive(Debug, Deserialize)]
+pub struct BroadcastReq { pub text: String }
+
+// Tool call held at its pre hook until the operator approves or denies it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApproval { pub invocation_id: String, pub task: String, #[serde(default)] pub meta: Value, pub ts: i64 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalDecision { pub approve: bool, #[serde(default)] pub reason: String }
+
+#[derive(Debug, Deserialize)]
+pub struct ApproveReq {
+    pub invocation_id: Option<String>, // defaults to the session's oldest pending approval
+    pub approve: bool,
+    #[serde(default)]
+    pub reason: String,
+}
 
 fn default_limit() -> usize { 25 }
 
+// Audit log - one entry per mutating API request, newest first when queried
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditEntry {
+    #[serde(default)] pub id: String, // Redis stream id
+    pub ts: i64,
+    pub actor: String,           // "token:<fingerprint>" or "anonymous"
+    pub agent: String,           // X-Tinymem-Agent or User-Agent header
+    pub method: String,
+    pub endpoint: String,        // route template, e.g. /session/:id/done
+    pub path: String,            // concrete path, identifies the key touched
+    pub status: u16,
+    #[serde(default)] pub request_id: String, // matches the X-Request-Id response header and access log
+}
+
+fn default_audit_limit() -> usize { 100 }
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default = "default_audit_limit")]
+    pub limit: usize,
+    pub actor: Option<String>,
+    pub endpoint: Option<String>,
+    pub path: Option<String>,    // substring match
+    pub since: Option<i64>,
+}
+
 // Chain system - multi-session workflow chains
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChainLink {
@@ -41,15 +400,153 @@ pub struct ChainLink {
     pub slug: String,            // e.g., "implement-auth"
     pub content: String,         // the chain link content (analysis, context, next steps)
     pub ts: i64,                 // timestamp
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,          // secrets were stripped from content on ingest
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub importance: u8,          // 0 (default) ..= 10, boosts search ranking
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifact_ids: Vec<String>, // artifacts this checkpoint discusses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitContext>, // code state the checkpoint describes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>, // the saving session's
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub completed: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub next_steps: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decisions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files_touched: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<ChainStatus>,
+}
+
+/// Where a chain (or one step of it) stands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainStatus { #[default] Active, Blocked, Done }
+
+impl ChainStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self { ChainStatus::Active => "active", ChainStatus::Blocked => "blocked", ChainStatus::Done => "done" }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s { "active" => Some(ChainStatus::Active), "blocked" => Some(ChainStatus::Blocked), "done" => Some(ChainStatus::Done), _ => None }
+    }
+
+    /// active -> blocked -> done -> active
+    pub fn next(self) -> Self {
+        match self { ChainStatus::Active => ChainStatus::Blocked, ChainStatus::Blocked => ChainStatus::Done, ChainStatus::Done => ChainStatus::Active }
+    }
 }
 
+// Set a chain's status, or one link's when `slug` is given (`status: null` clears a link's)
 #[derive(Debug, Deserialize)]
+pub struct ChainStatusReq { pub status: Option<ChainStatus>, #[serde(default)] pub slug: Option<String> }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChainDeleteQuery {
+    #[serde(default)]
+    pub archive: bool, // write the links to --archive-dir before deleting them
+}
+
+impl ChainLink {
+    /// Structured fields with their headings, in display order
+    pub fn sections(&self) -> [(&'static str, &[String]); 4] {
+        [("Completed", &self.completed), ("Next steps", &self.next_steps), ("Decisions", &self.decisions), ("Files touched", &self.files_touched)]
+    }
+
+    /// Non-empty structured fields as markdown sections, empty when there are none
+    pub fn sections_text(&self) -> String {
+        let mut out = String::new();
+        for (heading, items) in self.sections().into_iter().filter(|(_, items)| !items.is_empty()) {
+            if !out.is_empty() { out.push('\n'); }
+            out.push_str(&format!("## {heading}\n"));
+            for item in items { out.push_str(&format!("- {item}\n")); }
+        }
+        out
+    }
+
+    /// Adds a later save of the same slug to this link: content and list entries are appended,
+    /// artifacts merged, and a newer status or git context wins
+    pub fn append(&mut self, later: ChainLink) {
+        if !later.content.trim().is_empty() {
+            self.content = if self.content.trim().is_empty() { later.content } else { format!("{}\n\n{}", self.content, later.content) };
+        }
+        for (mine, theirs) in [(&mut self.completed, later.completed), (&mut self.next_steps, later.next_steps),
+            (&mut self.decisions, later.decisions), (&mut self.files_touched, later.files_touched), (&mut self.artifact_ids, later.artifact_ids)] {
+            for item in theirs { if !mine.contains(&item) { mine.push(item); } }
+        }
+        self.pinned |= later.pinned;
+        self.importance = self.importance.max(later.importance);
+        self.status = later.status.or(self.status);
+        self.git = later.git.or(self.git.take());
+        self.redacted |= later.redacted;
+    }
+}
+
+/// What saving a link does when its chain already has a link with the same slug
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    #[default]
+    Create, // refuse with a conflict
+    Upsert, // replace that link, keeping its place in the chain
+    Append, // add to that link (see ChainLink::append)
+}
+
+impl LinkMode {
+    pub fn as_str(&self) -> &'static str {
+        match self { LinkMode::Create => "create", LinkMode::Upsert => "upsert", LinkMode::Append => "append" }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s { "create" => Some(LinkMode::Create), "upsert" => Some(LinkMode::Upsert), "append" => Some(LinkMode::Append), _ => None }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ChainSaveReq {
     pub chain_name: String,
     pub slug: String,
-    pub content: String,
+    #[serde(default)]
+    pub content: String, // may be empty when structured fields are given
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub importance: u8,
+    #[serde(default)]
+    pub artifact_ids: Vec<String>,
+    #[serde(default)]
+    pub git: Option<GitContext>, // detected from the session's cwd when omitted
+    #[serde(default)]
+    pub completed: Vec<String>,
+    #[serde(default)]
+    pub next_steps: Vec<String>,
+    #[serde(default)]
+    pub decisions: Vec<String>,
+    #[serde(default)]
+    pub files_touched: Vec<String>,
+    #[serde(default)]
+    pub status: Option<ChainStatus>,
+    #[serde(default)]
+    pub mode: LinkMode,
 }
 
+impl ChainSaveReq {
+    pub fn has_sections(&self) -> bool {
+        !(self.completed.is_empty() && self.next_steps.is_empty() && self.decisions.is_empty() && self.files_touched.is_empty())
+    }
+}
+
+// Pin or weight a chain link (chain:name:slug) or memory (memory:id)
+#[derive(Debug, Deserialize)]
+pub struct PriorityReq { pub id: String, pub pinned: Option<bool>, pub importance: Option<u8> }
+
 #[derive(Debug, Deserialize)]
 pub struct ChainSearchReq {
     pub query: String,
@@ -67,6 +564,74 @@ pub struct Artifact {
     pub session_id: String,      // session that created it
     pub file_type: String,       // pdf, txt, md, etc.
     pub ts: i64,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,          // secrets were stripped from extracted text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,       // lowercased, e.g. "paper", "spec"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>, // the saving session's, or the watched directory's
+}
+
+impl Artifact {
+    /// A new artifact for `file_path`, typed by its extension (txt when it has none)
+    pub fn new(file_path: String, title: String, description: String, session_id: String, tags: Vec<String>) -> Self {
+        let file_type = std::path::Path::new(&file_path).extension().and_then(|e| e.to_str()).unwrap_or("txt").to_lowercase();
+        let ts = now();
+        let sanitized_title: String = title.chars().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_').take(50).collect();
+        Self { id: format!("{ts}_{sanitized_title}"), file_path, title, description, session_id, file_type, ts, redacted: false, tags, project: None }
+    }
+}
+
+// Background text extraction of an artifact's file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexState { Indexing, Ready, Failed }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexStatus {
+    pub state: IndexState,
+    #[serde(default)]
+    pub chars: usize, // extracted text length once ready; 0 for file types without an extractor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub updated: i64,
+}
+
+impl IndexStatus {
+    pub fn new(state: IndexState, chars: usize, error: Option<String>) -> Self { Self { state, chars, error, updated: now() } }
+}
+
+// Document metadata captured while extracting a PDF
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DocMeta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    pub page_count: usize,
+    #[serde(default)]
+    pub page_offsets: Vec<usize>, // byte offset in the extracted text where each page starts; pages past the text cap are left out
+}
+
+impl DocMeta {
+    /// 1-based page holding byte `offset` of the extracted text
+    pub fn page_at(&self, offset: usize) -> Option<usize> {
+        (!self.page_offsets.is_empty()).then(|| self.page_offsets.partition_point(|&o| o <= offset).max(1))
+    }
+
+    /// Text of pages `from..=to` (1-based); empty past the extracted pages
+    pub fn page_text<'a>(&self, text: &'a str, from: usize, to: usize) -> &'a str {
+        let floor = |mut i: usize| { i = i.min(text.len()); while !text.is_char_boundary(i) { i -= 1; } i };
+        let start = floor(self.page_offsets.get(from.saturating_sub(1)).copied().unwrap_or(text.len()));
+        let end = self.page_offsets.get(to).map_or(text.len(), |&o| floor(o));
+        &text[start..end.max(start)]
+    }
+}
+
+/// `pages` is a 1-based page or range of a PDF artifact: `12`, `3-5` or `3-` (to the end)
+#[derive(Debug, Default, Deserialize)]
+pub struct GetQuery {
+    pub pages: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -75,27 +640,363 @@ pub struct ArtifactSaveReq {
     pub title: String,
     #[serde(default)]
     pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ArtifactQuery {
+    pub tag: Option<String>,
+    #[serde(rename = "type")]
+    pub file_type: Option<String>,
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ProjectQuery {
+    pub project: Option<String>,
+}
+
+// Memories - standalone facts; expired or decayed ones are archived, then purged after a grace period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Memory {
+    pub id: String,
+    pub session_id: String,
+    pub title: String,
+    pub content: String,
+    pub ts: i64,
+    #[serde(default)]
+    pub expires_at: Option<i64>,  // hard TTL
+    #[serde(default)]
+    pub decay_days: Option<i64>,  // archive after this many days without a read
+    #[serde(default)]
+    pub archived_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
+    #[serde(default)]
+    pub pinned: bool,             // exempt from TTL and decay
+    #[serde(default)]
+    pub importance: u8,           // 0 (default) ..= 10, boosts search ranking
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,  // the saving session's
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MemorySaveReq {
+    #[serde(default)]
+    pub title: String,
+    pub content: String,
+    pub ttl_secs: Option<i64>,
+    pub decay_days: Option<i64>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub importance: u8,
+}
+
+// Todos - next steps attached to a chain and/or session that agents and the operator both work off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Todo {
+    pub id: String,
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,  // session id, agent name or "operator"; free-form
+    pub created: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub redacted: bool,
+}
+
+impl Todo {
+    pub fn done(&self) -> bool { self.completed_at.is_some() }
 }
 
 #[derive(Debug, Deserialize)]
+pub struct TodoReq {
+    pub text: String,
+    pub chain_name: Option<String>,
+    pub session_id: Option<String>,
+    pub assignee: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TodoQuery {
+    pub chain: Option<String>,
+    pub session_id: Option<String>,
+    pub assignee: Option<String>,
+    pub done: Option<bool>, // both when unset
+}
+
+impl TodoQuery {
+    pub fn matches(&self, t: &Todo) -> bool {
+        (self.chain.is_none() || t.chain_name == self.chain)
+            && (self.session_id.is_none() || t.session_id == self.session_id)
+            && (self.assignee.is_none() || t.assignee == self.assignee)
+            && self.done.is_none_or(|d| d == t.done())
+    }
+}
+
+// `assignee: null` unassigns
+#[derive(Debug, Deserialize)]
+pub struct AssignReq { pub assignee: Option<String> }
+
+// Handoffs - what a session leaves for another agent or session to continue from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Handoff {
+    pub id: String,
+    pub from_session: String,
+    pub to: String,                  // session id or agent name
+    #[serde(default)]
+    pub note: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<ChainLink>,     // the session's latest checkpoint
+    #[serde(default)]
+    pub todos: Vec<Todo>,            // open ones on that chain or session
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,    // attached to the link or saved by the session
+    pub created: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_at: Option<i64>,
+}
+
+impl Handoff {
+    /// Addressed to this session, by id or by its agent's name
+    pub fn is_for(&self, session: &Session) -> bool {
+        self.to == session.id || self.to == session.agent
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HandoffReq {
+    pub session_id: String,
+    pub to: String,
+    #[serde(default)]
+    pub note: String,
+    pub chain_name: Option<String>, // latest link on this chain instead of the session's latest anywhere
+}
+
+// Accepts `id`, or the oldest pending handoff addressed to the session
+#[derive(Debug, Deserialize)]
+pub struct HandoffAcceptReq { pub session_id: String, pub id: Option<String> }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HandoffQuery {
+    pub to: Option<String>,
+    #[serde(default)]
+    pub all: bool, // accepted ones too
+}
+
+// Quotas
+/// Write limits applied to each session and to each API token separately; 0 means unlimited
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Quotas { pub links_per_day: u64, pub artifact_bytes: u64, pub memories: u64 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind { Links, ArtifactBytes, Memories }
+
+impl QuotaKind {
+    pub fn as_str(&self) -> &'static str {
+        match self { QuotaKind::Links => "links", QuotaKind::ArtifactBytes => "artifact_bytes", QuotaKind::Memories => "memories" }
+    }
+}
+
+/// What a session or token counts against its [`Quotas`]: links saved today (UTC), bytes of the
+/// artifacts it saved and memories it saved that still exist
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct QuotaUsage { pub links_today: u64, pub artifact_bytes: u64, pub memories: u64 }
+
+impl Quotas {
+    /// The limit on `kind`, 0 when there is none
+    pub fn limit(&self, kind: QuotaKind) -> u64 {
+        match kind { QuotaKind::Links => self.links_per_day, QuotaKind::ArtifactBytes => self.artifact_bytes, QuotaKind::Memories => self.memories }
+    }
+
+    /// Why adding `amount` of `kind` to `current` would go over the limit, if it would
+    pub fn exceeded(&self, current: u64, kind: QuotaKind, amount: u64) -> Option<String> {
+        let limit = self.limit(kind);
+        let what = match kind { QuotaKind::Links => "chain links today", QuotaKind::ArtifactBytes => "artifact bytes", QuotaKind::Memories => "memories" };
+        (limit > 0 && current + amount > limit).then(|| format!("{current} of {limit} {what} used"))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct QuotaQuery { pub session_id: Option<String> }
+
+// Near-duplicate consolidation
+fn default_dedupe_threshold() -> f64 { 0.85 }
+
+#[derive(Debug, Deserialize)]
+pub struct DedupeReq {
+    #[serde(default = "default_dedupe_threshold")]
+    pub threshold: f64,              // report pairs at least this similar
+    pub merge_above: Option<f64>,    // merge pairs at least this similar (unset = report only)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicatePair {
+    pub keep: String,                // memory:id or chain:name:ts
+    pub duplicate: String,
+    pub similarity: f64,
+    pub merged: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GlobalSearchReq {
     pub query: String,
     #[serde(default = "default_limit")]
     pub limit: usize,
+    #[serde(flatten)]
+    pub filters: SearchFilters,
+}
+
+// Optional search narrowing; unset fields match everything
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFilters {
+    pub types: Option<Vec<String>>,  // any of "chain_link", "artifact", "memory"
+    pub session_id: Option<String>,
+    pub chain_name: Option<String>,  // implies chain links only
+    pub after: Option<i64>,          // unix seconds, inclusive
+    pub before: Option<i64>,         // unix seconds, exclusive
+    pub tag: Option<String>,         // implies artifacts only
+    pub file_type: Option<String>,   // implies artifacts only
+    pub project: Option<String>,     // content saved before project ids were recorded never matches
 }
 
-#[derive(Debug, Serialize)]
+impl SearchFilters {
+    pub fn allows_type(&self, result_type: &str) -> bool {
+        self.types.as_ref().is_none_or(|t| t.iter().any(|x| x == result_type))
+            && (self.chain_name.is_none() || result_type == "chain_link")
+            && (self.tag.is_none() && self.file_type.is_none() || result_type == "artifact")
+    }
+
+    pub fn allows_artifact(&self, a: &Artifact) -> bool {
+        self.allows(&a.session_id, a.project.as_deref(), a.ts)
+            && self.tag.as_ref().is_none_or(|t| a.tags.iter().any(|x| x.eq_ignore_ascii_case(t)))
+            && self.file_type.as_ref().is_none_or(|t| a.file_type.eq_ignore_ascii_case(t))
+    }
+
+    pub fn allows(&self, session_id: &str, project: Option<&str>, ts: i64) -> bool {
+        self.session_id.as_ref().is_none_or(|s| s == session_id)
+            && self.project.as_ref().is_none_or(|p| Some(p.as_str()) == project)
+            && self.after.is_none_or(|a| ts >= a)
+            && self.before.is_none_or(|b| ts < b)
+    }
+}
+
+// Saved searches - named query + filters, re-run on demand and pinned in the TUI Search tab
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+    #[serde(flatten)]
+    pub filters: SearchFilters,
+    pub within_secs: Option<i64>,    // relative window, e.g. 604800 = "last week"; overrides `after`
+    #[serde(default)]
+    pub created: i64,
+}
+
+// Corpus export - `GET /export/corpus` streams one CorpusDoc per line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CorpusType { Chains, Artifacts, Memories }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    #[serde(rename = "type")]
+    pub doc_type: Option<CorpusType>, // all three when unset
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    pub kinds: Option<String>, // comma-separated event names, e.g. "hook,session.done"; every event when unset
+}
+
+/// A searchable document as search sees it: `text` is what search previews are cut from (a link's
+/// content and sections, an artifact's extracted text or description, a memory's content)
+#[derive(Debug, Clone, Serialize)]
+pub struct CorpusDoc {
+    #[serde(rename = "type")]
+    pub doc_type: String,
+    pub id: String,
+    pub title: String,
+    pub text: String,
+    pub ts: i64,
+    pub session_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+impl SavedSearch {
+    /// Filters with the relative window resolved against the current time
+    pub fn resolved_filters(&self) -> SearchFilters {
+        let mut filters = self.filters.clone();
+        if let Some(secs) = self.within_secs { filters.after = Some(now() - secs); }
+        filters
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SearchResult {
-    pub result_type: String,     // "chain_link" or "artifact"
-    pub id: String,              // chain:name:slug or artifact:id
+    pub result_type: String,     // "chain_link", "artifact" or "memory"
+    pub id: String,              // chain:name:slug, artifact:id or memory:id
     pub title: String,           // chain_name/slug or artifact title
     pub score: f64,
-    pub preview: String,         // first ~200 chars of content
+    pub preview: String,         // ~200 bytes around the best match (start of content if none)
+    #[serde(default)]
+    pub highlights: Vec<[usize; 2]>, // byte ranges of matched terms within preview
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub importance: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page: Option<usize>,     // PDF page the preview was cut from
+}
+
+/// The first `max` characters of `s`; slicing by bytes instead panics inside a multi-byte character
+pub fn truncate_chars(s: &str, max: usize) -> &str {
+    s.char_indices().nth(max).map_or(s, |(i, _)| &s[..i])
+}
+
+/// `s` cut to `max` characters, with "..." when anything was cut
+pub fn preview(s: &str, max: usize) -> String {
+    let cut = truncate_chars(s, max);
+    if cut.len() < s.len() { format!("{cut}...") } else { s.to_string() }
 }
 
+/// Characters of content the API's `preview` fields carry
+pub const PREVIEW_CHARS: usize = 200;
+
 pub fn now() -> i64 { SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64 }
 
 pub fn short_id() -> String {
     let t = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
-    format!("{:x}", t as u32 ^ (t >> 32) as u32)[..6].to_string()
+    six_hex(t as u32 ^ (t >> 32) as u32)
+}
+
+// The low 24 bits as exactly six hex digits, zero-padded so small values don't come out short
+fn six_hex(v: u32) -> String { format!("{:06x}", v & 0xff_ffff) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_ids_are_six_hex_digits() {
+        assert_eq!(six_hex(0), "000000");
+        assert_eq!(six_hex(0xab), "0000ab");
+        assert_eq!(six_hex(0xf_ffff), "0fffff");
+        assert_eq!(six_hex(u32::MAX), "ffffff");
+        let id = short_id();
+        assert_eq!(id.len(), 6);
+        assert!(id.bytes().all(|b| b.is_ascii_hexdigit()));
+    }
 }
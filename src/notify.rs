@@ -0,0 +1,57 @@
+use std::io::Write;
+
+// Attention alerts for the TUI operator: terminal bell + desktop notification
+#[derive(Clone)]
+pub struct Notifier { enabled: bool }
+
+impl Notifier {
+    pub fn new(enabled: bool) -> Self { Self { enabled } }
+
+    pub fn alert(&self, summary: &str, body: &str) {
+        if !self.enabled { return; }
+        let mut out = std::io::stdout();
+        let _ = out.write_all(b"\x07").and_then(|_| out.flush());
+        // D-Bus round trip can stall, keep it off the draw loop
+        let (summary, body) = (summary.to_string(), body.to_string());
+        std::thread::spawn(move || {
+            let _ = notify_rust::Notification::new().appname("tinymem").summary(&summary).body(&body).show();
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ChatKind { Slack, Discord }
+
+// Team channel notifiers (`--notify slack:URL`, `--notify discord:URL`)
+#[derive(Clone, Default)]
+pub struct ChatNotifiers { targets: Vec<(ChatKind, String)> }
+
+impl ChatNotifiers {
+    pub fn new(specs: &[String]) -> anyhow::Result<Self> {
+        let mut targets = Vec::new();
+        for spec in specs.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let kind = match spec.split_once(':') {
+                Some(("slack", url)) => (ChatKind::Slack, url),
+                Some(("discord", url)) => (ChatKind::Discord, url),
+                _ => anyhow::bail!("invalid notifier '{spec}' (expected slack:URL or discord:URL)"),
+            };
+            targets.push((kind.0, kind.1.to_string()));
+        }
+        Ok(Self { targets })
+    }
+
+    /// Fire-and-forget post of a one-line message to every configured channel
+    pub fn send(&self, text: String) {
+        for (kind, url) in self.targets.clone() {
+            let body = match kind {
+                ChatKind::Slack => serde_json::json!({ "text": text }),
+                ChatKind::Discord => serde_json::json!({ "content": text }),
+            };
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = ureq::post(&url).send_json(&body) {
+                    eprintln!("{kind:?} notify failed: {e}");
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,39 @@
+//! First-page thumbnails for the TUI Artifacts pane. MuPDF opens images as one-page documents, so PDFs
+//! and images take the same path: the page is rendered to fit and drawn two pixels per cell with the
+//! upper half block (`▀`, foreground = top pixel, background = bottom pixel). Half blocks work in any
+//! truecolor terminal and live in ratatui's buffer like other text, unlike sixel images, which the
+//! next redraw would paint over.
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+const IMAGE_TYPES: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "tif", "tiff", "webp"];
+
+pub fn supported(file_type: &str) -> bool {
+    file_type == "pdf" || IMAGE_TYPES.contains(&file_type)
+}
+
+/// The first page of `path` scaled to fit `cols` x `rows` cells; None when it can't be rendered
+pub fn render(path: &str, cols: u16, rows: u16) -> Option<Vec<Line<'static>>> {
+    let doc = mupdf::Document::open(path).ok()?;
+    let page = doc.load_page(0).ok()?;
+    let bounds = page.bounds().ok()?;
+    let (w, h) = (bounds.x1 - bounds.x0, bounds.y1 - bounds.y0);
+    if w <= 0.0 || h <= 0.0 { return None; }
+    // Two pixel rows per cell, which also evens out cells being about twice as tall as wide
+    let scale = (cols as f32 / w).min(rows as f32 * 2.0 / h);
+    let pixmap = page.to_pixmap(&mupdf::Matrix::new_scale(scale, scale), &mupdf::Colorspace::device_rgb(), false, false).ok()?;
+    let (width, height, n, stride) = (pixmap.width() as usize, pixmap.height() as usize, pixmap.n() as usize, pixmap.stride() as usize);
+    let samples = pixmap.samples();
+    if n < 3 || samples.len() < height * stride { return None; }
+    let px = |x: usize, y: usize| {
+        let i = y * stride + x * n;
+        Color::Rgb(samples[i], samples[i + 1], samples[i + 2])
+    };
+    Some((0..height).step_by(2).map(|y| {
+        Line::from((0..width).map(|x| {
+            let bottom = if y + 1 < height { px(x, y + 1) } else { Color::Reset };
+            Span::styled("▀", Style::default().fg(px(x, y)).bg(bottom))
+        }).collect::<Vec<_>>())
+    }).collect())
+}
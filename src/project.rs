@@ -0,0 +1,46 @@
+//! Project ids: a stable name for the repository a session works in, recorded on the session and on
+//! the chain links, artifacts and memories it saves so listings and search can be scoped to one
+//! project. A checkout with an origin remote is named after it (`github.com/acme/api`, the same in
+//! every clone); anything else after its top-level directory (`api-1f2e3d4c`: the directory name
+//! plus a hash of the full path, so two `api` checkouts stay apart).
+
+use std::path::Path;
+use sha2::{Digest, Sha256};
+use crate::models::GitContext;
+
+/// None without a working directory
+pub fn project_id(cwd: &str, git: Option<&GitContext>) -> Option<String> {
+    match git {
+        Some(g) if !Path::new(&g.repo).is_absolute() => Some(normalize_remote(&g.repo)),
+        Some(g) => Some(path_id(&g.repo)),
+        None if !cwd.is_empty() => Some(path_id(cwd)),
+        None => None,
+    }
+}
+
+/// The id of the directory this process runs in
+pub fn current() -> Option<String> {
+    let cwd = std::env::current_dir().ok()?.to_string_lossy().to_string();
+    project_id(&cwd, crate::git::detect(&cwd).as_ref())
+}
+
+// https://github.com/Acme/api.git, ssh://git@github.com:22/Acme/api and git@github.com:Acme/api.git
+// all become github.com/Acme/api
+fn normalize_remote(url: &str) -> String {
+    let (rest, scp) = match url.split_once("://") {
+        Some((_, rest)) => (rest, false),
+        None => (url, true),
+    };
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, r)| r);
+    let (host, path) = if scp { rest.split_once(':') } else { rest.split_once('/') }.unwrap_or((rest, ""));
+    let host = host.split(':').next().unwrap_or(host).to_lowercase();
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    if path.is_empty() { host } else { format!("{host}/{path}") }
+}
+
+fn path_id(path: &str) -> String {
+    let path = path.trim_end_matches('/');
+    let name = Path::new(path).file_name().map_or_else(|| "root".into(), |n| n.to_string_lossy().to_string());
+    format!("{name}-{}", &format!("{:x}", Sha256::digest(path.as_bytes()))[..8])
+}
@@ -0,0 +1,51 @@
+//! Secret redaction applied by [`crate::Store`] before content is persisted.
+
+use std::borrow::Cow;
+use anyhow::Result;
+use regex::Regex;
+use serde_json::Value;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+// Built-in patterns for credentials agents commonly paste into hooks and chain links
+const BUILTIN: &[&str] = &[
+    r"(?s)-----BEGIN [A-Z ]*PRIVATE KEY-----.*?-----END [A-Z ]*PRIVATE KEY-----", // PEM private keys
+    r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b",                                           // AWS access key id
+    r#"(?i)aws_secret_access_key["']?\s*[:=]\s*["']?[A-Za-z0-9/+=]{40}"#,       // AWS secret key assignment
+    r"\bgh[pousr]_[A-Za-z0-9]{36,}\b|\bgithub_pat_[A-Za-z0-9_]{22,}",           // GitHub tokens
+    r"\bxox[abposr]-[A-Za-z0-9-]{10,}",                                         // Slack tokens
+    r"\bsk-[A-Za-z0-9_-]{20,}",                                                 // OpenAI / Anthropic style API keys
+    r"\beyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}",            // JWTs
+    r"(?i)\bbearer\s+[A-Za-z0-9._~+/-]{20,}=*",                                 // bearer tokens in headers
+];
+
+#[derive(Clone)]
+pub struct Redactor { patterns: Vec<Regex> }
+
+impl Redactor {
+    /// Built-in patterns plus `extra` user regexes
+    pub fn new(extra: &[String]) -> Result<Self> {
+        let patterns = BUILTIN.iter().copied().chain(extra.iter().map(String::as_str))
+            .map(Regex::new).collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Returns the redacted text, or None when nothing matched
+    pub fn redact(&self, text: &str) -> Option<String> {
+        let mut out = Cow::Borrowed(text);
+        for re in &self.patterns {
+            if re.is_match(&out) { out = re.replace_all(&out, REDACTED).into_owned().into(); }
+        }
+        match out { Cow::Owned(s) => Some(s), Cow::Borrowed(_) => None }
+    }
+
+    /// Redacts every string in a JSON value in place; true if anything changed
+    pub fn redact_value(&self, value: &mut Value) -> bool {
+        match value {
+            Value::String(s) => match self.redact(s) { Some(r) => { *s = r; true } None => false },
+            Value::Array(items) => items.iter_mut().fold(false, |acc, v| self.redact_value(v) | acc),
+            Value::Object(map) => map.values_mut().fold(false, |acc, v| self.redact_value(v) | acc),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! `tinymem replay`: plays a recorded session's hooks back against a running server as a new session,
+//! keeping their order and (scaled) spacing, for demoing the TUI, load-testing and reproducing
+//! event-handling bugs. Recordings are `GET /session/:id/export` output or cold archive files.
+
+use std::path::Path;
+use std::time::Duration;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::models::{Hook, Session, Status};
+
+/// A session and its hooks, oldest first
+#[derive(Debug, Deserialize)]
+pub struct Recording { pub session: Session, pub hooks: Vec<Hook> }
+
+/// Reads an export (`.json`), or an archive file (`.jsonl.zst`, or `.jsonl` once decompressed)
+pub fn load(path: &Path) -> Result<Recording> {
+    let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let bytes = if path.extension().is_some_and(|e| e == "zst") { zstd::decode_all(&bytes[..])? } else { bytes };
+    let text = String::from_utf8(bytes)?;
+    if let Ok(recording) = serde_json::from_str::<Recording>(&text) { return Ok(recording); }
+    let (session, hooks) = crate::archive::parse(&text)
+        .with_context(|| format!("{} is neither a session export nor an archive file", path.display()))?;
+    Ok(Recording { session, hooks })
+}
+
+/// "10x", "10" or "0.5x"; 0 posts every hook without waiting
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s.trim().trim_end_matches(['x', 'X']).parse().map_err(|_| format!("invalid speed '{s}', expected e.g. 10x"))?;
+    if !speed.is_finite() || speed < 0.0 { return Err(format!("invalid speed '{s}'")); }
+    Ok(speed)
+}
+
+pub struct Options { pub speed: f64, pub max_gap: Duration, pub copies: usize }
+
+/// Replays `rec` `copies` times in parallel, each as its own session, and returns the new session ids
+pub fn run(host: &str, port: u16, token: &str, rec: &Recording, opts: &Options) -> Result<Vec<String>> {
+    let base = format!("http://{}:{}{}", host, port, crate::server::API_PREFIX);
+    std::thread::scope(|scope| {
+        let copies: Vec<_> = (0..opts.copies.max(1)).map(|_| scope.spawn(|| replay_one(&base, token, rec, opts))).collect();
+        copies.into_iter().map(|c| c.join().unwrap_or_else(|_| bail!("replay thread panicked"))).collect()
+    })
+}
+
+fn replay_one(base: &str, token: &str, rec: &Recording, opts: &Options) -> Result<String> {
+    let auth = format!("Bearer {token}");
+    let post = |path: &str, body: &Value| -> Result<Value> {
+        match ureq::post(&format!("{base}{path}")).header("Authorization", &auth).send_json(body) {
+            Ok(mut resp) => Ok(resp.body_mut().read_json().unwrap_or(Value::Null)),
+            Err(ureq::Error::StatusCode(401)) => bail!("the server rejected the token (set --token or TINYMEM_TOKEN)"),
+            Err(e) => bail!("POST {path}: {e}"),
+        }
+    };
+    // Without a name, so the recording's id (which a named session uses) isn't overwritten
+    let created = post("/session", &json!({ "agent": rec.session.agent, "cwd": rec.session.cwd }))?;
+    let id = created["id"].as_str().context("the server returned no session id")?.to_string();
+    let mut prev = rec.hooks.first().map_or(0, |h| h.ts);
+    for hook in &rec.hooks {
+        if opts.speed > 0.0 {
+            let gap = Duration::from_secs_f64((hook.ts - prev).max(0) as f64 / opts.speed);
+            std::thread::sleep(gap.min(opts.max_gap));
+        }
+        prev = hook.ts;
+        post(&format!("/session/{id}/hook"), &json!({ "kind": hook.kind, "task": hook.task, "meta": hook.meta }))?;
+    }
+    if rec.session.status == Status::Done { post(&format!("/session/{id}/done"), &Value::Null)?; }
+    Ok(id)
+}
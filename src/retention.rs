@@ -0,0 +1,95 @@
+//! Retention policy: how long each family of data is kept. The server applies it from its background
+//! sweep; `GET /retention` reports what the next sweep would remove without touching anything.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use crate::models::now;
+use crate::store::Store;
+
+/// Ages in seconds; None keeps the family forever
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Policy {
+    pub hooks: Option<i64>,    // hook events, by their own time
+    pub sessions: Option<i64>, // finished sessions and everything recorded under them, by last activity
+    pub memories: Option<i64>, // unpinned memories neither saved nor read for this long go to the trash
+    pub trash: Option<i64>,    // archived memories, by when they were archived
+}
+
+impl Default for Policy {
+    // Everything is kept except the trash, which has always been emptied after a week
+    fn default() -> Self { Self { hooks: None, sessions: None, memories: None, trash: Some(7 * 86400) } }
+}
+
+impl Policy {
+    /// Applies `family=age` rules such as `hooks=14d` over the defaults. Ages take an s, m, h or d
+    /// suffix (seconds without one), and `off` keeps the family forever.
+    pub fn parse(rules: &[String]) -> Result<Self> {
+        let mut policy = Self::default();
+        for rule in rules {
+            let (family, age) = rule.split_once('=').with_context(|| format!("retention rule '{rule}' should be family=age"))?;
+            let age = parse_age(age.trim()).with_context(|| format!("invalid age in retention rule '{rule}'"))?;
+            match family.trim() {
+                "hooks" => policy.hooks = age,
+                "sessions" => policy.sessions = age,
+                "memories" => policy.memories = age,
+                "trash" => policy.trash = age,
+                other => bail!("unknown retention family '{other}' (expected hooks, sessions, memories or trash)"),
+            }
+        }
+        Ok(policy)
+    }
+}
+
+pub(crate) fn parse_age(age: &str) -> Result<Option<i64>> {
+    if age == "off" { return Ok(None); }
+    let (n, unit) = age.split_at(age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len()));
+    let unit = match unit { "" | "s" => 1, "m" => 60, "h" => 3600, "d" => 86400, other => bail!("unknown unit '{other}'") };
+    Ok(Some(n.parse::<i64>()? * unit))
+}
+
+/// What a sweep removed, or would remove on a dry run
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub dry_run: bool,
+    pub hooks: usize,          // hook events trimmed, across sessions that are kept
+    pub sessions: Vec<String>, // finished sessions deleted
+    pub memories: Vec<String>, // moved to the trash, by age or by their own TTL or decay
+    pub trash: Vec<String>,    // archived memories purged
+}
+
+/// Applies `policy` once; with `dry_run` only reports
+pub async fn run(store: &Store, policy: &Policy, dry_run: bool) -> Result<Report> {
+    let now = now();
+    let mut report = Report { dry_run, ..Default::default() };
+
+    let memories = store.list_memories().await?;
+    let ids: Vec<String> = memories.iter().map(|m| format!("memory:{}", m.id)).collect();
+    for (mut m, (_, last_read)) in memories.into_iter().zip(store.access_stats(&ids).await?) {
+        let last_read = last_read.unwrap_or(m.ts);
+        let expired = m.expires_at.is_some_and(|at| at <= now);
+        let decayed = m.decay_days.is_some_and(|d| now - last_read > d * 86400);
+        let aged = policy.memories.is_some_and(|age| now - last_read.max(m.ts) > age);
+        if m.pinned || !(expired || decayed || aged) { continue; }
+        if !dry_run {
+            m.archived_at = Some(now);
+            store.save_memory(&m).await?;
+        }
+        report.memories.push(m.id);
+    }
+    if let Some(age) = policy.trash {
+        report.trash = store.archived_before(now - age).await?;
+        if !dry_run { for id in &report.trash { store.delete_memory(id).await?; } }
+    }
+    if let Some(age) = policy.sessions {
+        report.sessions = store.finished_before(now - age).await?;
+        if !dry_run { for id in &report.sessions { store.delete_session(id).await?; } }
+    }
+    if let Some(age) = policy.hooks {
+        for (id, count) in store.hooks_before(now - age).await? {
+            if report.sessions.contains(&id) { continue; }
+            if !dry_run { store.trim_hooks(&id, count).await?; }
+            report.hooks += count;
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,140 @@
+//! Tokenization and BM25 ranking used by [`crate::Store::global_search`].
+
+use std::collections::HashMap;
+
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "has", "in", "is", "it", "its",
+    "of", "on", "or", "that", "the", "this", "to", "was", "were", "will", "with",
+];
+
+/// Lowercased, stemmed terms with stopwords and single characters dropped
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1)
+        .map(|w| w.to_lowercase())
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .map(|w| stem(&w))
+        .collect()
+}
+
+// Light suffix stripping so "tokens"/"token" and "caching"/"cached"/"cache" meet
+fn stem(w: &str) -> String {
+    if !w.is_ascii() { return w.to_string(); }
+    for (suffix, replacement) in [("ies", "y"), ("sses", "ss"), ("ing", ""), ("ed", ""), ("ly", ""), ("es", ""), ("s", "")] {
+        if let Some(stem) = w.strip_suffix(suffix) {
+            if stem.len() < 3 || (suffix == "s" && stem.ends_with('s')) { continue; }
+            let mut out = format!("{stem}{replacement}");
+            // "caching" -> "cach" and "cached" -> "cach" should meet "cache" -> "cach"
+            if replacement.is_empty() && out.ends_with('e') { out.pop(); }
+            return out;
+        }
+    }
+    w.strip_suffix('e').filter(|s| s.len() >= 3).unwrap_or(w).to_string()
+}
+
+/// Count of each term in `text`, plus the total number of terms
+pub fn term_frequencies(text: &str) -> (HashMap<String, u32>, usize) {
+    let tokens = tokenize(text);
+    let mut tf: HashMap<String, u32> = HashMap::new();
+    for t in &tokens { *tf.entry(t.clone()).or_default() += 1; }
+    (tf, tokens.len())
+}
+
+/// The `width`-byte region of `text` with the most query-term hits, plus the byte ranges of those
+/// hits within the returned snippet and the byte offset in `text` of the hit it is anchored on. Falls
+/// back to the start of the text when nothing matches.
+pub fn snippet(text: &str, query: &str, width: usize) -> (String, Vec<[usize; 2]>, usize) {
+    let terms = tokenize(query);
+    let mut hits = Vec::new();
+    let mut word_start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_alphanumeric(), word_start) {
+            (true, None) => word_start = Some(i),
+            (false, Some(s)) => {
+                let word = &text[s..i];
+                if word.chars().count() > 1 && terms.contains(&stem(&word.to_lowercase())) { hits.push([s, i]); }
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+    // Anchor on the hit that starts the densest window, with a little leading context
+    let best = hits.iter().max_by_key(|h| (hits.iter().filter(|o| o[0] >= h[0] && o[1] <= h[0] + width).count(), std::cmp::Reverse(h[0])))
+        .map_or(0, |h| h[0]);
+    let anchor = best.saturating_sub(width / 5);
+    let floor = |mut i: usize| { i = i.min(text.len()); while !text.is_char_boundary(i) { i -= 1; } i };
+    let (start, end) = (floor(anchor), floor(anchor + width));
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < text.len() { "…" } else { "" };
+    let body: String = text[start..end].chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+    let highlights = hits.into_iter().filter(|h| h[0] >= start && h[1] <= end)
+        .map(|h| [h[0] - start + prefix.len(), h[1] - start + prefix.len()]).collect();
+    (format!("{prefix}{body}{suffix}"), highlights, best)
+}
+
+/// Cosine similarity of the two texts' term-frequency vectors, 0.0 ..= 1.0
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let tf = |text: &str| {
+        let mut tf: HashMap<String, f64> = HashMap::new();
+        for t in tokenize(text) { *tf.entry(t).or_default() += 1.0; }
+        tf
+    };
+    let (ta, tb) = (tf(a), tf(b));
+    let dot: f64 = ta.iter().filter_map(|(t, x)| Some(x * tb.get(t)?)).sum();
+    let norm = |v: &HashMap<String, f64>| v.values().map(|x| x * x).sum::<f64>().sqrt();
+    let denom = norm(&ta) * norm(&tb);
+    if denom == 0.0 { 0.0 } else { dot / denom }
+}
+
+/// Multiplier favouring content that is read often and was read recently: +10% per e-fold of reads,
+/// plus up to +25% decaying with a 30-day time constant since the last read
+pub fn usage_boost(reads: i64, last_read: Option<i64>, now: i64) -> f64 {
+    let frequency = 0.1 * (1.0 + reads.max(0) as f64).ln();
+    let recency = last_read.map_or(0.0, |t| 0.25 * (-((now - t).max(0) as f64) / (30.0 * 86400.0)).exp());
+    1.0 + frequency + recency
+}
+
+/// Multiplier for operator-assigned priority: +10% per importance level (capped at 10), x1.5 when pinned
+pub fn priority_boost(pinned: bool, importance: u8) -> f64 {
+    (if pinned { 1.5 } else { 1.0 }) * (1.0 + 0.1 * importance.min(10) as f64)
+}
+
+/// BM25 contribution of one query term to one document
+pub fn bm25(tf: f64, df: f64, docs: f64, doc_len: f64, avg_len: f64) -> f64 {
+    let idf = (1.0 + (docs - df + 0.5) / (df + 0.5)).ln();
+    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * doc_len / avg_len.max(1.0)))
+}
+
+/// In-memory inverted index over a batch of documents, scored with BM25. The store keeps a persistent
+/// one in Redis; this is used when it can't (encryption at rest)
+#[derive(Default)]
+pub struct Index { docs: Vec<(usize, HashMap<String, u32>)>, df: HashMap<String, usize>, total_len: usize }
+
+impl Index {
+    /// Adds a document and returns its position, used to map scores back
+    pub fn add(&mut self, text: &str) -> usize {
+        let (tf, len) = term_frequencies(text);
+        for t in tf.keys() { *self.df.entry(t.clone()).or_default() += 1; }
+        self.total_len += len;
+        self.docs.push((len, tf));
+        self.docs.len() - 1
+    }
+
+    /// (doc position, score) for every document matching at least one query term
+    pub fn search(&self, query: &str) -> Vec<(usize, f64)> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        let n = self.docs.len() as f64;
+        let avg_len = self.total_len as f64 / n.max(1.0);
+        self.docs.iter().enumerate().filter_map(|(i, (len, tf))| {
+            let score: f64 = terms.iter()
+                .filter_map(|t| Some(bm25(*tf.get(t)? as f64, self.df[t] as f64, n, *len as f64, avg_len)))
+                .sum();
+            (score > 0.0).then_some((i, score))
+        }).collect()
+    }
+}
@@ -1,82 +1,765 @@
 use anyhow::Result;
-use axum::{body::Body, extract::{Path, Request, State}, http::{HeaderMap, StatusCode},
+use axum::{body::Body, extract::{DefaultBodyLimit, FromRef, FromRequestParts, MatchedPath, Path, Query, Request, State},
+    http::{header, request::Parts, Extensions, HeaderMap, Method, StatusCode},
     middleware::{self, Next}, response::{IntoResponse, Response}, routing::post, Json, Router};
 use serde_json::json;
-use tokio::{net::TcpListener, sync::mpsc::Sender};
-use crate::models::{now, short_id, Artifact, ArtifactSaveReq, ChainLink, ChainSaveReq, ChainSearchReq, CreateSessionReq, GlobalSearchReq, Hook, HookReq, Session, StartReq, Status, TuiEvent};
-use crate::store::Store;
+use tokio::net::TcpListener;
+use crate::models::{invocation_id, now, preview, short_id, truncate_chars, PREVIEW_CHARS, AnswerReq, CorpusType, EventsQuery, ExportQuery, ApprovalDecision, ApproveReq, AuditEntry, AuditQuery, DedupeReq, Artifact, ArtifactQuery, ArtifactSaveReq, AskReq, BroadcastReq, ChainDeleteQuery, ChainLink, ChainSaveReq, ChainSearchReq, ChainStatusReq, CompareQuery, CreateSessionReq, DocMeta, GetQuery, GlobalSearchReq, ProjectQuery, SavedSearch, SearchFilters, SessionsQuery, HistoryQuery, Hook, HookKind, HookReq, IndexState, IndexStatus, Memory, MemorySaveReq, PriorityReq, PendingApproval, PendingAsk, Presence, QuotaKind, QuotaQuery, Quotas, Session, StartReq, StatsQuery, Status, HooksQuery, Todo, TodoQuery, TodoReq, AssignReq, Handoff, HandoffAcceptReq, HandoffQuery, HandoffReq, TranscriptEntry, TranscriptQuery, TranscriptReq, Usage, UsageReq};
+use crate::auth::{Access, CertScopes, ClientCert, Jwt};
+use crate::breaker::is_unreachable;
+use crate::events::{EventBus, EventKind};
+use crate::git;
+use crate::journal::Journal;
+use crate::project::project_id;
+use crate::retention;
+use crate::extract::extract_file_text;
+use crate::notify::ChatNotifiers;
+use crate::store::{LinkSave, Slot, Store};
+use crate::validate::{Limits, Problem, Valid};
+use std::io::Write;
 use std::path::Path as FilePath;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 #[derive(Clone)]
-pub struct AppState { pub store: Store, pub tui_tx: Sender<TuiEvent>, pub token: String }
+pub struct AppState {
+    pub store: Store, pub token: String, pub chat: ChatNotifiers, pub events: EventBus, pub access_log: AccessLog,
+    pub limits: Limits, pub timeout: Duration, pub presence: Presence, pub quotas: Quotas,
+    pub retention: retention::Policy, pub export_token: String, pub journal: Journal,
+    /// Set with `--auth jwt`: JWTs are accepted alongside `token`
+    pub jwt: Option<Jwt>,
+    pub cert_scopes: CertScopes,
+    /// `--max-active-sessions`; 0 = unlimited
+    pub max_active_sessions: usize,
+    /// `--archive-dir`, where `DELETE /chain/delete/:name?archive=true` writes the chain
+    pub archive_dir: Option<std::path::PathBuf>,
+}
+
+impl FromRef<AppState> for Limits {
+    fn from_ref(s: &AppState) -> Self { s.limits }
+}
+
+// Routes that hold the request open for the operator (5 min) get that long plus a margin; bulk work
+// (reindexing, dedupe) gets ten times the normal budget
+const HOLD_ROUTES: &[&str] = &["/session/:id/ask", "/session/:id/hook"];
+const HOLD_TIMEOUT: Duration = Duration::from_secs(330);
+const BULK_ROUTES: &[&str] = &["/artifacts/reindex", "/dedupe", "/retention"];
+
+fn route_timeout(endpoint: &str, default: Duration) -> Duration {
+    if HOLD_ROUTES.contains(&endpoint) { HOLD_TIMEOUT } else if BULK_ROUTES.contains(&endpoint) { default * 10 } else { default }
+}
+
+// A handler still running after its route's budget is dropped and the caller gets a 503
+async fn timeout(State(s): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    let endpoint = req.extensions().get::<MatchedPath>().map(|p| p.as_str()).unwrap_or_default();
+    let limit = route_timeout(endpoint.strip_prefix(API_PREFIX).unwrap_or(endpoint), s.timeout);
+    match tokio::time::timeout(limit, next.run(req)).await {
+        Ok(resp) => resp,
+        Err(_) => Problem::new(StatusCode::SERVICE_UNAVAILABLE, format!("request timed out after {}s", limit.as_secs())).into_response(),
+    }
+}
+
+/// Destination for one line per request; disabled by default (stderr would garble the TUI)
+#[derive(Clone, Default)]
+pub struct AccessLog(Option<Arc<Mutex<Box<dyn Write + Send>>>>);
+
+impl AccessLog {
+    pub fn stderr() -> Self { Self(Some(Arc::new(Mutex::new(Box::new(std::io::stderr()))))) }
+
+    /// Appends to `path`, creating it if needed
+    pub fn file(path: &FilePath) -> Result<Self> {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self(Some(Arc::new(Mutex::new(Box::new(file))))))
+    }
+
+    fn write(&self, line: &str) {
+        if let Some(out) = &self.0 { let _ = writeln!(out.lock().unwrap(), "{line}"); }
+    }
+}
+
+/// The id [`request_log`] gave the current request, for handlers that want to log it
+#[derive(Clone)]
+pub struct RequestId(pub String);
 
+// Callers may pass their own X-Request-Id (kept if short and plain); otherwise one is made up
+fn request_id(h: &HeaderMap) -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    h.get("x-request-id").and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 64 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+        .map(String::from)
+        .unwrap_or_else(|| format!("{}-{}", short_id(), NEXT.fetch_add(1, Ordering::Relaxed)))
+}
+
+// Outermost layer: tags the request and its response with X-Request-Id and logs
+// `ts id method path status latency actor` once it is answered (401s and 503s included)
+async fn request_log(State(s): State<AppState>, mut req: Request<Body>, next: Next) -> Response {
+    let id = request_id(req.headers());
+    let (method, path, actor) = (req.method().clone(), req.uri().path().to_string(), actor(req.headers(), req.extensions()));
+    req.extensions_mut().insert(RequestId(id.clone()));
+    let started = Instant::now();
+    let mut resp = next.run(req).await;
+    if let Ok(v) = header::HeaderValue::from_str(&id) { resp.headers_mut().insert("x-request-id", v); }
+    s.access_log.write(&format!("{} {id} {method} {path} {} {}ms {actor}", now(), resp.status().as_u16(), started.elapsed().as_millis()));
+    resp
+}
+
+// The export token, when set, only opens the corpus export, so a pipeline can pull content without write access.
+// A valid JWT that lacks the scope a request needs gets a 403 rather than a 401. A verified client certificate
+// stands in for a token altogether.
 async fn auth(State(s): State<AppState>, h: HeaderMap, req: Request<Body>, next: Next) -> Response {
     let a = h.get("authorization").and_then(|v| v.to_str().ok()).unwrap_or("");
-    if a == format!("Bearer {}", s.token) || s.token.is_empty() { next.run(req).await }
-    else { StatusCode::UNAUTHORIZED.into_response() }
+    let path = req.uri().path();
+    let path = path.strip_prefix(API_PREFIX).unwrap_or(path);
+    let access = if req.method() == Method::GET && path == "/export/corpus" { Access::Export }
+        else if req.method() == Method::GET || READ_ONLY_POSTS.contains(&path) { Access::Read }
+        else { Access::Write };
+    if let Some(ClientCert(cn)) = req.extensions().get::<ClientCert>() {
+        return if s.cert_scopes.allows(cn, access) { next.run(req).await }
+            else { Problem::new(StatusCode::FORBIDDEN, format!("certificate {cn} is not allowed this request")).into_response() };
+    }
+    let export_ok = access == Access::Export && !s.export_token.is_empty() && a == format!("Bearer {}", s.export_token);
+    let open = s.token.is_empty() && s.jwt.is_none();
+    if open || (!s.token.is_empty() && a == format!("Bearer {}", s.token)) || export_ok { return next.run(req).await; }
+    match (&s.jwt, a.strip_prefix("Bearer ")) {
+        (Some(jwt), Some(t)) => match jwt.verify(t, now()) {
+            Ok(claims) if claims.allows(access) => next.run(req).await,
+            Ok(_) => Problem::new(StatusCode::FORBIDDEN, "token scope does not allow this request").into_response(),
+            Err(e) => Problem::new(StatusCode::UNAUTHORIZED, e.to_string()).into_response(),
+        },
+        _ => Problem::new(StatusCode::UNAUTHORIZED, "missing or invalid bearer token").into_response(),
+    }
+}
+
+// While the Redis circuit is open every store call fails fast; say so with a 503 instead of a 500 or an
+// empty result from handlers that fall back to defaults
+async fn degraded(State(s): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    if let Some(wait) = s.store.degraded() { return unavailable(wait); }
+    let resp = next.run(req).await;
+    match s.store.degraded() { Some(wait) => unavailable(wait), None => resp }
+}
+
+fn unavailable(wait: Duration) -> Response {
+    let secs = wait.as_secs() + 1;
+    let mut resp = Problem::new(StatusCode::SERVICE_UNAVAILABLE, "redis unavailable").with("retry_after", secs).into_response();
+    resp.headers_mut().insert(header::RETRY_AFTER, secs.into());
+    resp
+}
+
+/// Every route is served under this prefix; the unprefixed paths are kept for older hook scripts and
+/// proxies and answer with a `Deprecation` header pointing at their successor
+pub const API_PREFIX: &str = "/v1";
+pub const API_VERSION: &str = "1";
+
+// Tags every response with the API version, and legacy unprefixed calls as deprecated
+async fn version(req: Request<Body>, next: Next) -> Response {
+    let path = req.uri().path().to_string();
+    let mut resp = next.run(req).await;
+    let h = resp.headers_mut();
+    h.insert("x-tinymem-api-version", header::HeaderValue::from_static(API_VERSION));
+    if !path.starts_with(&format!("{API_PREFIX}/")) {
+        h.insert("deprecation", header::HeaderValue::from_static("true"));
+        if let Ok(link) = header::HeaderValue::from_str(&format!("<{API_PREFIX}{path}>; rel=\"successor-version\"")) {
+            h.insert(header::LINK, link);
+        }
+    }
+    resp
+}
+
+async fn get_version() -> impl IntoResponse {
+    Json(json!({ "api": API_VERSION, "prefix": API_PREFIX, "server": env!("CARGO_PKG_VERSION") }))
+}
+
+// POST routes that only read; everything else except GET is audited and needs a JWT's write scope
+const READ_ONLY_POSTS: &[&str] = &["/chain/search", "/search", "/graphql"];
+
+/// Identifies the caller by its client certificate's CN, a JWT's subject or a fingerprint of the presented bearer
+/// token, never the token itself
+fn actor(h: &HeaderMap, ext: &Extensions) -> String {
+    match ext.get::<ClientCert>() {
+        Some(ClientCert(cn)) => format!("cert:{cn}"),
+        None => token_subject(h.get("authorization").and_then(|v| v.to_str().ok())),
+    }
+}
+
+/// The caller as [`actor`] names it, for handlers that charge quotas to it
+struct Caller(String);
+
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Caller {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        Ok(Caller(actor(&parts.headers, &parts.extensions)))
+    }
+}
+
+/// `jwt:<sub>` for a JWT carrying a subject, otherwise `token:<short hash>` of the bearer token, so logs,
+/// audit entries and quotas can tell callers apart without storing the token itself. A subject keeps
+/// the same quota across rotated tokens.
+pub(crate) fn token_subject(authorization: Option<&str>) -> String {
+    use sha2::{Digest, Sha256};
+    let token = authorization.and_then(|a| a.strip_prefix("Bearer "));
+    if let Some(sub) = token.and_then(crate::auth::unverified_subject) { return format!("jwt:{sub}"); }
+    match token {
+        Some(t) => format!("token:{}", Sha256::digest(t.as_bytes())[..4].iter().map(|b| format!("{b:02x}")).collect::<String>()),
+        None => "anonymous".into(),
+    }
 }
 
-async fn create_session(State(s): State<AppState>, Json(r): Json<CreateSessionReq>) -> impl IntoResponse {
+async fn audit(State(s): State<AppState>, req: Request<Body>, next: Next) -> Response {
+    // Recorded without the version prefix so /v1 and legacy calls audit alike
+    let endpoint = req.extensions().get::<MatchedPath>().map(|p| p.as_str()).unwrap_or_default();
+    let endpoint = endpoint.strip_prefix(API_PREFIX).unwrap_or(endpoint).to_string();
+    if req.method() == Method::GET || READ_ONLY_POSTS.contains(&endpoint.as_str()) { return next.run(req).await; }
+    let h = req.headers();
+    let agent = h.get("x-tinymem-agent").or_else(|| h.get("user-agent")).and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+    let (actor, method, path) = (actor(h, req.extensions()), req.method().to_string(), req.uri().path().to_string());
+    let request_id = req.extensions().get::<RequestId>().map(|r| r.0.clone()).unwrap_or_default();
+    let resp = next.run(req).await;
+    let entry = AuditEntry { id: String::new(), ts: now(), actor, agent, method, endpoint, path, status: resp.status().as_u16(), request_id };
+    if let Err(e) = s.store.append_audit(&entry).await { eprintln!("audit: {e}"); }
+    resp
+}
+
+async fn get_audit(State(s): State<AppState>, Query(q): Query<AuditQuery>) -> impl IntoResponse {
+    match s.store.query_audit(&q).await {
+        Ok(entries) => (StatusCode::OK, Json(json!({ "entries": entries, "count": entries.len() }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn create_session(State(s): State<AppState>, Valid(r): Valid<CreateSessionReq>) -> Response {
     let id = r.name.clone().unwrap_or_else(short_id);
+    let slot = match session_slot(&s, &id, r.name.as_deref(), &r.agent).await { Ok(slot) => slot, Err(resp) => return resp };
     let ts = now();
-    let session = Session { id: id.clone(), name: r.name, agent: r.agent, cwd: r.cwd, status: Status::Active, created: ts, last_activity: ts };
+    let git = git::detect_async(&r.cwd).await;
+    let project = project_id(&r.cwd, git.as_ref());
+    let session = Session { id: id.clone(), name: r.name, agent: r.agent, cwd: r.cwd, status: Status::Active, created: ts, last_activity: ts, last_heartbeat: 0, git, project, summary: None };
     match s.store.create_session(&session).await {
-        Ok(_) => { let _ = s.tui_tx.send(TuiEvent::NewSession).await; (StatusCode::OK, Json(json!({ "id": id }))) }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+        Ok(_) => {
+            s.events.publish(EventKind::SessionStarted, &id, json!(session));
+            (StatusCode::OK, Json(json!({ "id": id }))).into_response()
+        }
+        Err(e) => {
+            release_slot(&s, slot, &id).await;
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
     }
 }
 
 // Start/resume session with Claude session ID mapping (stored in Redis)
-async fn start_session(State(s): State<AppState>, Json(r): Json<StartReq>) -> impl IntoResponse {
+async fn start_session(State(s): State<AppState>, Valid(r): Valid<StartReq>) -> Response {
+    let external = r.external_key();
     // Check for existing mapping
-    if let Ok(Some(tinymem_id)) = s.store.get_claude_mapping(&r.claude_session_id).await {
-        // Check if session exists
-        if let Ok(Some(_)) = s.store.get_session(&tinymem_id).await {
-            // Reactivate and return existing session
-            let _ = s.store.touch_and_reactivate(&tinymem_id).await;
-            let _ = s.tui_tx.send(TuiEvent::Refresh).await;
-            return (StatusCode::OK, Json(json!({ "id": tinymem_id, "reused": true })));
+    if let Some((provider, external_id)) = &external {
+        if let Ok(Some(tinymem_id)) = s.store.get_external_mapping(provider, external_id).await {
+            // Check if session exists
+            if let Ok(Some(existing)) = s.store.get_session(&tinymem_id).await {
+                // Resuming a finished session makes one more active
+                let slot = if existing.status != Status::Active {
+                    match session_slot(&s, &tinymem_id, Some(&tinymem_id), &existing.agent).await { Ok(slot) => slot, Err(resp) => return resp }
+                } else { Slot::Held };
+                // Reactivate and return existing session
+                if s.store.touch_and_reactivate(&tinymem_id).await.is_err() { release_slot(&s, slot, &tinymem_id).await; }
+                s.events.publish(EventKind::SessionResumed, &tinymem_id, serde_json::Value::Null);
+                return (StatusCode::OK, Json(json!({ "id": tinymem_id, "reused": true }))).into_response();
+            }
         }
     }
     // Create new session
     let id = short_id();
+    let slot = match session_slot(&s, &id, None, &r.agent).await { Ok(slot) => slot, Err(resp) => return resp };
     let ts = now();
-    let session = Session { id: id.clone(), name: None, agent: r.agent, cwd: r.cwd, status: Status::Active, created: ts, last_activity: ts };
+    let git = git::detect_async(&r.cwd).await;
+    let project = project_id(&r.cwd, git.as_ref());
+    let session = Session { id: id.clone(), name: None, agent: r.agent, cwd: r.cwd, status: Status::Active, created: ts, last_activity: ts, last_heartbeat: 0, git, project, summary: None };
     match s.store.create_session(&session).await {
         Ok(_) => {
-            let _ = s.store.set_claude_mapping(&r.claude_session_id, &id).await;
-            let _ = s.tui_tx.send(TuiEvent::NewSession).await;
-            (StatusCode::OK, Json(json!({ "id": id, "reused": false })))
+            if let Some((provider, external_id)) = &external {
+                let _ = s.store.set_external_mapping(provider, external_id, &id).await;
+            }
+            s.events.publish(EventKind::SessionStarted, &id, json!(session));
+            (StatusCode::OK, Json(json!({ "id": id, "reused": false, "project": session.project }))).into_response()
+        }
+        Err(e) => {
+            release_slot(&s, slot, &id).await;
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
 }
 
-async fn add_hook(State(s): State<AppState>, Path(id): Path<String>, Json(r): Json<HookReq>) -> StatusCode {
+async fn add_hook(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<HookReq>) -> Response {
+    // Adapters may piggyback token counts on a hook as meta.usage
+    if let Some(usage) = r.meta.get("usage").and_then(|u| serde_json::from_value::<UsageReq>(u.clone()).ok()) {
+        let _ = s.store.record_usage(&id, &usage).await;
+    }
     let hook = Hook { ts: now(), kind: r.kind.clone(), task: r.task.clone(), meta: r.meta };
-    // Track active tool for TUI display
-    if r.kind == "pre" {
-        let _ = s.store.set_active_tool(&id, &r.task).await;
-    } else {
-        let _ = s.store.clear_active_tool(&id).await;
+    let _ = s.store.track_tools(&id, &hook).await; // in-flight tools for TUI display
+    s.events.publish(EventKind::Hook, &id, json!({ "kind": r.kind, "task": r.task, "error": hook.is_error() }));
+    // Queue behind anything already journaled so hooks keep their order; only an outage is worth
+    // journaling, since a hook Redis rejected would be rejected again on replay
+    let journaled = s.journal.backlog() > 0 || match s.store.add_hook(&id, &hook).await {
+        Ok(()) => false,
+        Err(e) if s.store.degraded().is_some() || is_unreachable(&e) => true,
+        Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    if journaled {
+        if let Err(e) = s.journal.append(&id, &hook).await { return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(); }
+    }
+    if r.requires_approval && hook.kind == HookKind::Pre { await_approval(&s, &id, &hook).await.into_response() }
+    else if journaled { (StatusCode::ACCEPTED, Json(json!({ "journaled": true }))).into_response() }
+    else { StatusCode::OK.into_response() }
+}
+
+// Holds a pre hook until the operator approves or denies the call (5 min, like /ask); silence means deny
+async fn await_approval(s: &AppState, id: &str, hook: &Hook) -> Response {
+    let pending = PendingApproval { invocation_id: invocation_id(&hook.task, &hook.meta), task: hook.task.clone(), meta: hook.meta.clone(), ts: now() };
+    if let Err(e) = s.store.set_pending_approval(id, &pending).await {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    s.chat.send(format!("🔐 tinymem session `{id}` wants to run {}", pending.task));
+    s.events.publish(EventKind::ApprovalPending, id, json!({ "session_id": id, "invocation_id": pending.invocation_id, "task": pending.task }));
+    for _ in 0..600 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        match s.store.take_approval_decision(id, &pending.invocation_id).await {
+            Ok(Some(d)) => return (StatusCode::OK, Json(json!({ "decision": if d.approve { "approve" } else { "deny" }, "reason": d.reason }))).into_response(),
+            Ok(None) => {}
+            Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+    let _ = s.store.clear_pending_approval(id, &pending.invocation_id).await;
+    s.events.publish(EventKind::ApprovalClosed, id, serde_json::Value::Null);
+    (StatusCode::OK, Json(json!({ "decision": "deny", "reason": "no decision from operator" }))).into_response()
+}
+
+async fn approve(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<ApproveReq>) -> Response {
+    let invocation = match r.invocation_id {
+        Some(i) => i,
+        None => match s.store.pending_approvals(&id).await {
+            Ok(pending) => match pending.into_iter().next() {
+                Some(p) => p.invocation_id,
+                None => return Problem::new(StatusCode::NOT_FOUND, "no pending approval").into_response(),
+            },
+            Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        },
+    };
+    match s.store.decide_approval(&id, &invocation, &ApprovalDecision { approve: r.approve, reason: r.reason }).await {
+        Ok(true) => {
+            s.events.publish(EventKind::ApprovalClosed, &id, serde_json::Value::Null);
+            (StatusCode::OK, Json(json!({ "decided": invocation, "approve": r.approve }))).into_response()
+        }
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "no pending approval").into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn add_transcript(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<TranscriptReq>) -> Response {
+    let entry = TranscriptEntry { ts: now(), role: r.role, text: r.text };
+    match s.store.add_transcript(&id, &entry).await {
+        Ok(_) => {
+            s.events.publish(EventKind::Transcript, &id, serde_json::Value::Null);
+            (StatusCode::OK, Json(json!({ "ok": true }))).into_response()
+        }
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn get_transcript(State(s): State<AppState>, Path(id): Path<String>, Query(q): Query<TranscriptQuery>) -> impl IntoResponse {
+    match s.store.get_transcript(&id, q.limit as isize).await {
+        Ok(entries) => (StatusCode::OK, Json(json!({ "session_id": id, "entries": entries, "count": entries.len() }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn record_usage(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<UsageReq>) -> Response {
+    if let Err(e) = s.store.record_usage(&id, &r).await {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    s.events.publish(EventKind::Usage, &id, serde_json::Value::Null);
+    match s.store.session_usage(&id).await {
+        Ok(total) => (StatusCode::OK, Json(json!(total))).into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn get_errors(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.recent_errors(&id, 20).await {
+        Ok(errors) => (StatusCode::OK, Json(json!({ "session_id": id, "errors": errors }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Recent hooks, oldest first, optionally only some kinds, tools or failures
+async fn get_hooks(State(s): State<AppState>, Path(id): Path<String>, Query(q): Query<HooksQuery>) -> impl IntoResponse {
+    match s.store.query_hooks(&id, &q).await {
+        Ok(hooks) => (StatusCode::OK, Json(json!({ "session_id": id, "hooks": hooks, "count": hooks.len() }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
-    let _ = s.tui_tx.send(TuiEvent::Refresh).await; // Notify TUI
-    s.store.add_hook(&id, &hook).await.map(|_| StatusCode::OK).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// The session record and every hook, oldest first: what `tinymem replay` plays back
+async fn export_session(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    let session = match s.store.get_session(&id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" }))),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    };
+    match s.store.get_hooks(&id, isize::MAX).await {
+        Ok(hooks) => (StatusCode::OK, Json(json!({ "session": session, "hooks": hooks }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn get_usage(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.session_usage(&id).await {
+        Ok(total) => (StatusCode::OK, Json(json!(total))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+/// Counts a write of `amount` against the session's and token's (`subjects`) quotas before it is made,
+/// or says why not when it would take one of them over. `id` is the artifact or memory written; if the
+/// write fails, the reservation is given back with `release_reservation`.
+pub(crate) async fn reserve_quota(s: &AppState, subjects: &[String], kind: QuotaKind, id: &str, amount: u64) -> Result<Option<String>> {
+    let Some((subject, used)) = s.store.reserve_quota(subjects, kind, id, amount, s.quotas.limit(kind)).await? else { return Ok(None) };
+    let why = s.quotas.exceeded(used, kind, amount).unwrap_or_else(|| format!("{used} {} used", kind.as_str()));
+    Ok(Some(format!("quota exceeded for {subject}: {why}")))
+}
+
+async fn over_quota(s: &AppState, subjects: &[String], kind: QuotaKind, id: &str, amount: u64) -> Option<Response> {
+    match reserve_quota(s, subjects, kind, id, amount).await {
+        Ok(None) => None,
+        Ok(Some(why)) => Some(Problem::new(StatusCode::TOO_MANY_REQUESTS, why).into_response()),
+        Err(e) => Some(Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    }
+}
+
+/// Takes one of the `--max-active-sessions` slots for session `id` (named `name`, if it asked for one).
+/// A refusal is published as `session.rejected` so the TUI can warn about it.
+pub(crate) async fn claim_session_slot(s: &AppState, id: &str, name: Option<&str>, agent: &str) -> Result<Slot> {
+    if s.max_active_sessions == 0 { return Ok(Slot::Held); }
+    let slot = s.store.claim_active(id, s.max_active_sessions).await?;
+    if let Slot::Full(active) = slot {
+        s.events.publish(EventKind::SessionRejected, name.unwrap_or_default(), json!({ "agent": agent, "active": active, "limit": s.max_active_sessions }));
+    }
+    Ok(slot)
+}
+
+async fn session_slot(s: &AppState, id: &str, name: Option<&str>, agent: &str) -> Result<Slot, Response> {
+    match claim_session_slot(s, id, name, agent).await {
+        Ok(Slot::Full(active)) => Err(
+            Problem::new(StatusCode::TOO_MANY_REQUESTS, format!("{active} sessions are active, the limit is {}", s.max_active_sessions))
+                .with("active", active).with("limit", s.max_active_sessions).into_response(),
+        ),
+        Ok(slot) => Ok(slot),
+        Err(e) => Err(Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()),
+    }
+}
+
+// A session that took a slot and then failed to be written gives the slot back
+async fn release_slot(s: &AppState, slot: Slot, id: &str) {
+    if slot == Slot::Taken { let _ = s.store.release_active(id).await; }
+}
+
+// JSONL of every searchable document, produced while the client reads so the corpus is never held in
+// memory; a failure midway ends the stream early
+async fn export_corpus(State(s): State<AppState>, Query(q): Query<ExportQuery>) -> Response {
+    use futures::SinkExt;
+    let (mut tx, rx) = futures::channel::mpsc::channel::<Result<String, std::io::Error>>(64);
+    let kinds = q.doc_type.map_or_else(|| vec![CorpusType::Chains, CorpusType::Artifacts, CorpusType::Memories], |t| vec![t]);
+    tokio::spawn(async move {
+        let export = async {
+            for kind in kinds {
+                for unit in s.store.corpus_units(kind).await? {
+                    for doc in s.store.corpus_docs(kind, &unit).await? {
+                        tx.send(Ok(format!("{}\n", serde_json::to_string(&doc)?))).await?;
+                    }
+                }
+            }
+            anyhow::Ok(())
+        };
+        if let Err(e) = export.await { let _ = tx.send(Err(std::io::Error::other(e.to_string()))).await; }
+    });
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(rx)).into_response()
+}
+
+// Bus events as JSONL, one `{"event", "id", "ts", "data"}` object per line, until the client goes away.
+// A client too slow to keep up skips what it missed.
+async fn stream_events(State(s): State<AppState>, Query(q): Query<EventsQuery>) -> Response {
+    use futures::SinkExt;
+    use tokio::sync::broadcast::error::RecvError;
+    let kinds: Vec<String> = q.kinds.iter().flat_map(|k| k.split(',')).map(|k| k.trim().to_string()).filter(|k| !k.is_empty()).collect();
+    let (mut tx, rx) = futures::channel::mpsc::channel::<Result<String, std::io::Error>>(64);
+    let mut events = s.events.subscribe();
+    tokio::spawn(async move {
+        loop {
+            let ev = match events.recv().await {
+                Ok(ev) => ev,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return,
+            };
+            if !kinds.is_empty() && !kinds.iter().any(|k| k == ev.kind.as_str()) { continue; }
+            let line = json!({ "event": ev.kind.as_str(), "id": ev.id, "ts": ev.ts, "data": ev.data });
+            if tx.send(Ok(format!("{line}\n"))).await.is_err() { return; }
+        }
+    });
+    ([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(rx)).into_response()
+}
+
+// What the next retention sweep would remove, without removing it
+async fn get_retention(State(s): State<AppState>) -> impl IntoResponse {
+    match retention::run(&s.store, &s.retention, true).await {
+        Ok(report) => (StatusCode::OK, Json(json!({ "policy": s.retention, "report": report }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Limits plus what the caller's token, and the session if given, has used of them
+async fn get_quota(State(s): State<AppState>, Caller(token): Caller, Query(q): Query<QuotaQuery>) -> impl IntoResponse {
+    let session = match &q.session_id {
+        Some(id) => s.store.quota_usage(&format!("session:{id}")).await.map(Some),
+        None => Ok(None),
+    };
+    match (s.store.quota_usage(&token).await, session) {
+        (Ok(used), Ok(session)) => (StatusCode::OK, Json(json!({ "limits": s.quotas, "token": token, "token_usage": used, "session_usage": session }))),
+        (Err(e), _) | (_, Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn get_stats(State(s): State<AppState>, Query(q): Query<StatsQuery>) -> impl IntoResponse {
+    let (active, daily) = match (s.store.list_active().await, s.store.daily_usage(q.days).await) {
+        (Ok(a), Ok(d)) => (a, d),
+        (Err(e), _) | (_, Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    };
+    let total = daily.iter().fold(Usage::default(), |t, (_, u)| Usage {
+        input_tokens: t.input_tokens + u.input_tokens,
+        output_tokens: t.output_tokens + u.output_tokens,
+        cost_usd: t.cost_usd + u.cost_usd,
+    });
+    let daily: Vec<_> = daily.into_iter().map(|(day, u)| json!({ "day": day, "input_tokens": u.input_tokens, "output_tokens": u.output_tokens, "cost_usd": u.cost_usd })).collect();
+    (StatusCode::OK, Json(json!({ "active_sessions": active.len(), "days": q.days, "usage": total, "daily": daily })))
 }
 
 async fn mark_done(State(s): State<AppState>, Path(id): Path<String>) -> StatusCode {
-    let _ = s.tui_tx.send(TuiEvent::SessionDone).await;
-    s.store.mark_done(&id).await.map(|_| StatusCode::OK).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    match s.store.mark_done(&id).await {
+        Ok(_) => {
+            s.events.publish(EventKind::SessionDone, &id, json!({ "id": id }));
+            s.chat.send(format!("✅ tinymem session `{id}` finished"));
+            StatusCode::OK
+        }
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+// Agent question for the operator - holds the request until answered or timed out (5 min)
+async fn ask(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<AskReq>) -> Response {
+    let pending = PendingAsk { question: r.question.clone(), options: r.options.clone(), ts: now() };
+    if let Err(e) = s.store.set_pending_ask(&id, &pending).await {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    let choices = if r.options.is_empty() { String::new() } else { format!(" ({})", r.options.join(" / ")) };
+    s.chat.send(format!("❓ tinymem session `{id}` asks: {}{choices}", r.question));
+    s.events.publish(EventKind::AskPending, &id, json!({ "session_id": id, "question": r.question, "options": r.options }));
+    for _ in 0..600 {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        match s.store.take_answer(&id).await {
+            Ok(Some(answer)) => return (StatusCode::OK, Json(json!({ "answer": answer }))).into_response(),
+            Ok(None) => {}
+            Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+    let _ = s.store.clear_pending_ask(&id).await;
+    s.events.publish(EventKind::AskClosed, &id, serde_json::Value::Null);
+    Problem::new(StatusCode::REQUEST_TIMEOUT, "no answer from operator").into_response()
+}
+
+async fn answer(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<AnswerReq>) -> Response {
+    match s.store.get_pending_ask(&id).await {
+        Ok(Some(ask)) if !ask.options.is_empty() && !ask.options.contains(&r.answer) => {
+            return Problem::field("answer", format!("must be one of: {}", ask.options.join(", "))).into_response();
+        }
+        Ok(Some(_)) => {}
+        Ok(None) => return Problem::new(StatusCode::NOT_FOUND, "no pending question").into_response(),
+        Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+    match s.store.set_answer(&id, &r.answer).await {
+        Ok(_) => { s.events.publish(EventKind::AskClosed, &id, serde_json::Value::Null); (StatusCode::OK, Json(json!({ "answered": id }))).into_response() }
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+// Operator note to every active session, read by agents from their inbox
+async fn broadcast(State(s): State<AppState>, Valid(r): Valid<BroadcastReq>) -> Response {
+    match s.store.broadcast(&r.text).await {
+        Ok(ids) => {
+            for id in &ids { s.events.publish(EventKind::Transcript, id, serde_json::Value::Null); }
+            (StatusCode::OK, Json(json!({ "delivered": ids.len(), "sessions": ids }))).into_response()
+        }
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn get_inbox(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.take_inbox(&id).await {
+        Ok(messages) => (StatusCode::OK, Json(json!({ "messages": messages }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn get_related(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.session_related(&id).await {
+        Ok((chains, artifacts)) => {
+            let mut mentioned = Vec::new();
+            for a in &artifacts {
+                mentioned.push(json!({ "artifact": a.id, "mentioned_in": s.store.artifact_mentions(&a.id).await.unwrap_or_default() }));
+            }
+            (StatusCode::OK, Json(json!({ "session_id": id, "chains": chains, "artifacts": artifacts, "mentions": mentioned })))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Two sessions' profiles side by side, with the tools and files both of them used
+async fn compare_sessions(State(s): State<AppState>, Query(q): Query<CompareQuery>) -> impl IntoResponse {
+    let (a, b) = match (s.store.session_profile(&q.a).await, s.store.session_profile(&q.b).await) {
+        (Ok(Some(a)), Ok(Some(b))) => (a, b),
+        (Ok(None), _) => return (StatusCode::NOT_FOUND, Json(json!({ "error": format!("session {} not found", q.a) }))),
+        (_, Ok(None)) => return (StatusCode::NOT_FOUND, Json(json!({ "error": format!("session {} not found", q.b) }))),
+        (Err(e), _) | (_, Err(e)) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    };
+    let tools: Vec<&String> = a.tools.keys().filter(|t| b.tools.contains_key(*t)).collect();
+    let files: Vec<&String> = a.files.intersection(&b.files).collect();
+    (StatusCode::OK, Json(json!({ "a": a, "b": b, "shared": { "tools": tools, "files": files } })))
+}
+
+// A session with its liveness computed from the server's thresholds
+fn session_json(s: &AppState, sess: &Session) -> serde_json::Value {
+    let mut v = json!(sess);
+    v["liveness"] = json!(s.presence.liveness(sess, now()));
+    v
 }
 
 async fn get_session(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     match s.store.get_session(&id).await {
-        Ok(Some(sess)) => (StatusCode::OK, Json(json!(sess))),
+        Ok(Some(sess)) => (StatusCode::OK, Json(session_json(&s, &sess))),
         Ok(None) => (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" }))),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
 }
 
+async fn list_sessions_full(State(s): State<AppState>, Query(q): Query<SessionsQuery>) -> impl IntoResponse {
+    let status = match q.status.as_deref() {
+        None | Some("active") => Status::Active,
+        Some("done") => Status::Done,
+        Some(other) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": format!("unknown status '{other}' (expected active or done)") }))),
+    };
+    let sessions = match (&q.project, q.agent.as_deref().map(str::trim).filter(|a| !a.is_empty())) {
+        (Some(project), None) => s.store.project_sessions(project, &status, q.limit, q.offset).await,
+        (None, None) => s.store.list_sessions(&status, q.limit, q.offset).await,
+        // History is scanned in batches rather than loaded whole
+        (None, Some(agent)) if status == Status::Done => {
+            s.store.history_page(&HistoryQuery { agent: Some(agent.to_string()), limit: q.limit, offset: q.offset, ..Default::default() }).await
+        }
+        // The active set and a project's sessions are loaded whole anyway, so filter before paging
+        (project, Some(agent)) => {
+            let all = match project {
+                Some(project) => s.store.project_sessions(project, &status, usize::MAX, 0).await,
+                None => s.store.list_sessions(&status, usize::MAX, 0).await,
+            };
+            all.map(|all| all.into_iter().filter(|sess| sess.agent.eq_ignore_ascii_case(agent)).skip(q.offset).take(q.limit).collect())
+        }
+    };
+    let sessions = match sessions {
+        Ok(sessions) => sessions,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    };
+    let count = sessions.len();
+    if !q.full {
+        let ids: Vec<_> = sessions.into_iter().map(|s| s.id).collect();
+        return (StatusCode::OK, Json(json!({ "sessions": ids, "count": count })));
+    }
+    if !q.last_hook {
+        let sessions: Vec<_> = sessions.iter().map(|sess| session_json(&s, sess)).collect();
+        return (StatusCode::OK, Json(json!({ "sessions": sessions, "count": count })));
+    }
+    let hooks = futures::future::join_all(sessions.iter().map(|sess| s.store.get_hooks(&sess.id, 1))).await;
+    let sessions: Vec<_> = sessions.into_iter().zip(hooks).map(|(sess, hooks)| {
+        let mut v = session_json(&s, &sess);
+        v["last_hook"] = json!(hooks.ok().and_then(|mut h| h.pop()));
+        v
+    }).collect();
+    (StatusCode::OK, Json(json!({ "sessions": sessions, "count": count })))
+}
+
+// Keeps an idle agent from showing as disconnected; does not count as activity or resume a finished session
+async fn heartbeat(State(s): State<AppState>, Path(id): Path<String>) -> Response {
+    match s.store.heartbeat(&id).await {
+        Ok(true) => {
+            s.events.publish(EventKind::Heartbeat, &id, serde_json::Value::Null);
+            (StatusCode::OK, Json(json!({ "heartbeat": id }))).into_response()
+        }
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+// Moves a finished session back to Active right away instead of waiting for its next hook
+async fn resume_session(State(s): State<AppState>, Path(id): Path<String>) -> Response {
+    let slot = match s.store.get_session(&id).await {
+        // Resuming a finished session makes one more active, as on /start
+        Ok(Some(existing)) if existing.status != Status::Active => {
+            match session_slot(&s, &id, Some(&id), &existing.agent).await { Ok(slot) => slot, Err(resp) => return resp }
+        }
+        Ok(Some(_)) => Slot::Held,
+        Ok(None) => return Problem::new(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    };
+    match s.store.touch_and_reactivate(&id).await {
+        Ok(_) => {
+            s.events.publish(EventKind::SessionResumed, &id, serde_json::Value::Null);
+            (StatusCode::OK, Json(json!({ "resumed": id }))).into_response()
+        }
+        Err(e) => {
+            release_slot(&s, slot, &id).await;
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+async fn delete_session(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.delete_session(&id).await {
+        Ok(true) => {
+            s.events.publish(EventKind::SessionDeleted, &id, serde_json::Value::Null);
+            (StatusCode::OK, Json(json!({ "deleted": id })))
+        }
+        Ok(false) => (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn get_history(State(s): State<AppState>, Query(q): Query<HistoryQuery>) -> impl IntoResponse {
+    match s.store.history_page(&q).await {
+        Ok(sessions) => (StatusCode::OK, Json(json!({ "sessions": sessions, "count": sessions.len(), "offset": q.offset }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn list_projects(State(s): State<AppState>) -> impl IntoResponse {
+    match s.store.list_projects().await {
+        Ok(projects) => {
+            let projects: Vec<_> = projects.into_iter().map(|(id, sessions)| json!({ "project": id, "sessions": sessions })).collect();
+            (StatusCode::OK, Json(json!({ "projects": projects })))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Project of the session saving something, if it has one
+async fn session_project(s: &AppState, session_id: &str) -> Option<String> {
+    s.store.get_session(session_id).await.ok().flatten().and_then(|s| s.project)
+}
+
 async fn list_sessions(State(s): State<AppState>) -> impl IntoResponse {
     match s.store.list_active().await {
         Ok(ids) => (StatusCode::OK, Json(json!({ "sessions": ids }))),
@@ -85,35 +768,95 @@ async fn list_sessions(State(s): State<AppState>) -> impl IntoResponse {
 }
 
 // Chain endpoints
-async fn save_chain_link(State(s): State<AppState>, Path(session_id): Path<String>, Json(r): Json<ChainSaveReq>) -> impl IntoResponse {
+async fn save_chain_link(State(s): State<AppState>, Caller(caller): Caller, Path(session_id): Path<String>, Valid(r): Valid<ChainSaveReq>) -> Response {
+    let subjects = [format!("session:{session_id}"), caller];
+    for id in &r.artifact_ids {
+        match s.store.get_artifact(id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Problem::field("artifact_ids", format!("unknown artifact id: {id}")).into_response(),
+            Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        }
+    }
+    if let Some(resp) = over_quota(&s, &subjects, QuotaKind::Links, "", 1).await { return resp; }
+    let session = s.store.get_session(&session_id).await.ok().flatten();
+    // The session's checkout as of now, which may have moved on since it started
+    let git = match (r.git, &session) {
+        (Some(git), _) => Some(git),
+        (None, Some(session)) => git::detect_async(&session.cwd).await,
+        (None, None) => None,
+    };
     let link = ChainLink {
         chain_name: r.chain_name.clone(),
         session_id,
         slug: r.slug.clone(),
         content: r.content,
         ts: now(),
+        redacted: false,
+        pinned: r.pinned,
+        importance: r.importance.min(10),
+        artifact_ids: r.artifact_ids,
+        git,
+        project: session.and_then(|s| s.project),
+        completed: r.completed,
+        next_steps: r.next_steps,
+        decisions: r.decisions,
+        files_touched: r.files_touched,
+        status: r.status,
     };
-    match s.store.save_chain_link(&link).await {
-        Ok(key) => (StatusCode::OK, Json(json!({ "saved": key, "chain": r.chain_name, "slug": r.slug }))),
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    let saved = s.store.save_chain_link_as(link, r.mode).await;
+    if !matches!(saved, Ok(LinkSave::Saved(..))) { let _ = s.store.release_reservation(&subjects, QuotaKind::Links, "", 1).await; }
+    match saved {
+        Ok(LinkSave::Saved(key, link)) => {
+            s.events.publish(EventKind::ChainLinkSaved, &key, json!({ "key": key, "link": link }));
+            (StatusCode::OK, Json(json!({ "saved": key, "chain": r.chain_name, "slug": r.slug, "mode": r.mode }))).into_response()
+        }
+        Ok(LinkSave::Taken(key)) => Problem::new(StatusCode::CONFLICT, format!(
+            "chain '{}' already has a link with slug '{}' ({key}); save with mode \"upsert\" to replace it or \"append\" to add to it",
+            r.chain_name, r.slug)).into_response(),
+        Ok(LinkSave::Ambiguous(keys)) => Problem::new(StatusCode::CONFLICT, format!(
+            "{} links in chain '{}' share slug '{}' ({}); delete the extra ones before updating it", keys.len(), r.chain_name, r.slug, keys.join(", ")
+        )).into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
     }
 }
 
 async fn get_chain_links(State(s): State<AppState>, Path(chain_name): Path<String>) -> impl IntoResponse {
     match s.store.get_chain_links(&chain_name).await {
-        Ok(links) => (StatusCode::OK, Json(json!({ "chain": chain_name, "links": links, "count": links.len() }))),
+        Ok(links) => {
+            // Inline titles of attached artifacts so readers don't need a lookup per id
+            let mut out = Vec::new();
+            for link in &links {
+                let mut v = json!(link);
+                v["preview"] = json!(preview(&link.content, PREVIEW_CHARS));
+                if !link.artifact_ids.is_empty() {
+                    let mut attached = Vec::new();
+                    for id in &link.artifact_ids {
+                        if let Ok(Some(a)) = s.store.get_artifact(id).await {
+                            attached.push(json!({ "id": a.id, "title": a.title, "file_type": a.file_type }));
+                        }
+                    }
+                    v["artifacts"] = json!(attached);
+                }
+                out.push(v);
+            }
+            let status = s.store.chain_status(&chain_name).await.unwrap_or_default();
+            (StatusCode::OK, Json(json!({ "chain": chain_name, "status": status, "links": out, "count": links.len() })))
+        }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
 }
 
-async fn list_chains(State(s): State<AppState>) -> impl IntoResponse {
+async fn list_chains(State(s): State<AppState>, Query(q): Query<ProjectQuery>) -> impl IntoResponse {
     match s.store.list_chain_names().await {
         Ok(names) => {
-            // Get link count for each chain
+            // Get link count for each chain (only the project's links when scoped, leaving out chains without any)
+            let statuses = s.store.chain_statuses().await.unwrap_or_default();
             let mut chains = Vec::new();
             for name in names {
-                let count = s.store.get_chain_links(&name).await.map(|l| l.len()).unwrap_or(0);
-                chains.push(json!({ "name": name, "links": count }));
+                let links = s.store.get_chain_links(&name).await.unwrap_or_default();
+                let count = links.iter().filter(|l| q.project.is_none() || l.project == q.project).count();
+                let status = statuses.get(&name).copied().unwrap_or_default();
+                if count > 0 || q.project.is_none() { chains.push(json!({ "name": name, "links": count, "status": status })); }
             }
             (StatusCode::OK, Json(json!({ "chains": chains })))
         }
@@ -121,27 +864,391 @@ async fn list_chains(State(s): State<AppState>) -> impl IntoResponse {
     }
 }
 
-async fn search_chains(State(s): State<AppState>, Json(r): Json<ChainSearchReq>) -> impl IntoResponse {
+// Removes a chain and all its links; with ?archive=true they are written to --archive-dir first
+async fn delete_chain(State(s): State<AppState>, Path(chain_name): Path<String>, Query(q): Query<ChainDeleteQuery>) -> impl IntoResponse {
+    match s.store.list_chain_names().await {
+        Ok(names) if names.contains(&chain_name) => {}
+        Ok(_) => return (StatusCode::NOT_FOUND, Json(json!({ "error": "not found" }))),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    }
+    let archived = match (q.archive, &s.archive_dir) {
+        (false, _) => None,
+        (true, None) => return (StatusCode::BAD_REQUEST, Json(json!({ "error": "archiving a chain needs the server's --archive-dir" }))),
+        (true, Some(dir)) => match crate::archive::write_chain(&s.store, dir, &chain_name).await {
+            Ok(written) => Some(written),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": format!("archiving failed, chain kept: {e}") }))),
+        },
+    };
+    match s.store.delete_chain(&chain_name).await {
+        Ok(_) => {
+            let mut body = json!({ "deleted": chain_name });
+            if let Some((file, links)) = archived { body["archived"] = json!({ "file": file, "links": links }); }
+            (StatusCode::OK, Json(body))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn set_chain_status(State(s): State<AppState>, Path(chain_name): Path<String>, Valid(r): Valid<ChainStatusReq>) -> Response {
+    let result = match (&r.slug, r.status) {
+        (Some(slug), status) => s.store.set_link_status(&chain_name, slug, status).await,
+        (None, Some(status)) => match s.store.list_chain_names().await {
+            Ok(names) if names.contains(&chain_name) => s.store.set_chain_status(&chain_name, status).await.map(|_| true),
+            Ok(_) => Ok(false),
+            Err(e) => Err(e),
+        },
+        (None, None) => return Problem::field("status", "must be active, blocked or done").into_response(),
+    };
+    match result {
+        Ok(true) => (StatusCode::OK, Json(json!({ "chain": chain_name, "slug": r.slug, "status": r.status }))).into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn search_chains(State(s): State<AppState>, Valid(r): Valid<ChainSearchReq>) -> Response {
     match s.store.search_chains(&r.query, r.limit).await {
         Ok(results) => {
             let chains: Vec<_> = results.into_iter().map(|(name, score)| json!({"name": name, "score": score})).collect();
-            (StatusCode::OK, Json(json!({ "chains": chains })))
+            (StatusCode::OK, Json(json!({ "chains": chains }))).into_response()
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
     }
 }
 
 // Global search endpoint
-async fn global_search(State(s): State<AppState>, Json(r): Json<GlobalSearchReq>) -> impl IntoResponse {
-    match s.store.global_search(&r.query, r.limit).await {
-        Ok(results) => (StatusCode::OK, Json(json!({ "results": results }))),
+async fn global_search(State(s): State<AppState>, Valid(r): Valid<GlobalSearchReq>) -> Response {
+    match s.store.global_search(&r.query, r.limit, &r.filters).await {
+        Ok(results) => (StatusCode::OK, Json(json!({ "results": results }))).into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+// Memory endpoints
+async fn save_memory(State(s): State<AppState>, Caller(caller): Caller, Path(session_id): Path<String>, Valid(r): Valid<MemorySaveReq>) -> Response {
+    let subjects = [format!("session:{session_id}"), caller];
+    let ts = now();
+    let title = if r.title.is_empty() { truncate_chars(&r.content, 60).to_string() } else { r.title };
+    let project = session_project(&s, &session_id).await;
+    let memory = Memory {
+        id: format!("{ts}_{}", short_id()),
+        session_id,
+        title,
+        content: r.content,
+        ts,
+        expires_at: r.ttl_secs.map(|t| ts + t),
+        decay_days: r.decay_days,
+        archived_at: None,
+        redacted: false,
+        pinned: r.pinned,
+        importance: r.importance.min(10),
+        project,
+    };
+    if let Some(resp) = over_quota(&s, &subjects, QuotaKind::Memories, &memory.id, 1).await { return resp; }
+    match s.store.save_memory(&memory).await {
+        Ok(_) => (StatusCode::OK, Json(json!({ "id": memory.id, "expires_at": memory.expires_at }))).into_response(),
+        Err(e) => {
+            let _ = s.store.release_reservation(&subjects, QuotaKind::Memories, &memory.id, 1).await;
+            Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+        }
+    }
+}
+
+async fn list_memories(State(s): State<AppState>, Query(q): Query<ProjectQuery>) -> impl IntoResponse {
+    let memories = match &q.project { Some(p) => s.store.project_memories(p).await, None => s.store.list_memories().await };
+    match memories {
+        Ok(memories) => {
+            let memories: Vec<_> = memories.iter().map(|m| {
+                let mut v = json!(m);
+                v["preview"] = json!(preview(&m.content, PREVIEW_CHARS));
+                v
+            }).collect();
+            (StatusCode::OK, Json(json!({ "memories": memories })))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn list_expired_memories(State(s): State<AppState>) -> impl IntoResponse {
+    match s.store.list_expired_memories().await {
+        Ok(memories) => (StatusCode::OK, Json(json!({ "memories": memories, "purge_after_days": s.retention.trash.map(|t| t / 86400) }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Bring an archived memory back; its TTL is dropped so it does not immediately expire again
+async fn restore_memory(State(s): State<AppState>, Path(id): Path<String>) -> Response {
+    match s.store.get_memory(&id).await {
+        Ok(Some(mut memory)) => {
+            memory.archived_at = None;
+            memory.expires_at = None;
+            match s.store.save_memory(&memory).await {
+                Ok(_) => (StatusCode::OK, Json(json!({ "restored": id }))).into_response(),
+                Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            }
+        }
+        Ok(None) => Problem::new(StatusCode::NOT_FOUND, "memory not found").into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn set_priority(State(s): State<AppState>, Valid(r): Valid<PriorityReq>) -> Response {
+    match s.store.set_priority(&r.id, r.pinned, r.importance).await {
+        Ok(true) => (StatusCode::OK, Json(json!({ "updated": r.id }))).into_response(),
+        Ok(false) => Problem::new(StatusCode::NOT_FOUND, "not found").into_response(),
+        Err(e) => Problem::field("id", e.to_string()).into_response()
+    }
+}
+
+// Todos
+async fn save_todo(State(s): State<AppState>, Valid(r): Valid<TodoReq>) -> Response {
+    let todo = Todo {
+        id: format!("{}_{}", now(), short_id()),
+        text: r.text,
+        chain_name: r.chain_name,
+        session_id: r.session_id,
+        assignee: r.assignee,
+        created: now(),
+        completed_at: None,
+        redacted: false,
+    };
+    match s.store.save_todo(&todo).await {
+        Ok(_) => {
+            s.events.publish(EventKind::TodoChanged, &todo.id, json!(todo));
+            (StatusCode::OK, Json(json!({ "id": todo.id }))).into_response()
+        }
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn list_todos(State(s): State<AppState>, Query(q): Query<TodoQuery>) -> impl IntoResponse {
+    match s.store.list_todos(&q).await {
+        Ok(todos) => (StatusCode::OK, Json(json!({ "todos": todos }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Loads a todo, applies `change` and saves it back; 404 when it doesn't exist
+async fn update_todo(s: &AppState, id: &str, change: impl FnOnce(&mut Todo)) -> Response {
+    let mut todo = match s.store.get_todo(id).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return Problem::new(StatusCode::NOT_FOUND, "todo not found").into_response(),
+        Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    change(&mut todo);
+    match s.store.save_todo(&todo).await {
+        Ok(_) => {
+            s.events.publish(EventKind::TodoChanged, id, json!(todo));
+            (StatusCode::OK, Json(json!(todo))).into_response()
+        }
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn complete_todo(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    update_todo(&s, &id, |t| { t.completed_at.get_or_insert_with(now); }).await
+}
+
+async fn reopen_todo(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    update_todo(&s, &id, |t| t.completed_at = None).await
+}
+
+async fn assign_todo(State(s): State<AppState>, Path(id): Path<String>, Valid(r): Valid<AssignReq>) -> impl IntoResponse {
+    update_todo(&s, &id, |t| t.assignee = r.assignee).await
+}
+
+async fn delete_todo(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.delete_todo(&id).await {
+        Ok(_) => {
+            s.events.publish(EventKind::TodoChanged, &id, json!({ "id": id, "deleted": true }));
+            (StatusCode::OK, Json(json!({ "deleted": id })))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Handoffs
+async fn create_handoff(State(s): State<AppState>, Valid(r): Valid<HandoffReq>) -> Response {
+    match handoff_package(&s.store, r).await {
+        Ok(Some(handoff)) => {
+            if let Err(e) = s.store.save_handoff(&handoff).await {
+                return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+            }
+            // A recipient that is already running hears about it through its inbox
+            if let Ok(Some(to)) = s.store.get_session(&handoff.to).await {
+                let note = format!("handoff {} from {} is waiting: accept it with tinymem_handoff_accept", handoff.id, handoff.from_session);
+                let _ = s.store.notify_session(&to.id, &note).await;
+            }
+            s.events.publish(EventKind::HandoffPending, &handoff.id, json!({ "from": handoff.from_session, "to": handoff.to }));
+            (StatusCode::OK, Json(json!(handoff))).into_response()
+        }
+        Ok(None) => Problem::new(StatusCode::NOT_FOUND, "session not found").into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+// The session's latest chain link, the open todos on that chain or session, and the artifacts the link
+// attaches or the session saved
+async fn handoff_package(store: &Store, r: HandoffReq) -> Result<Option<Handoff>> {
+    if store.get_session(&r.session_id).await?.is_none() { return Ok(None); }
+    let (chains, saved) = store.session_related(&r.session_id).await?;
+    let chains = match &r.chain_name { Some(name) => vec![name.clone()], None => chains };
+    let mut link: Option<ChainLink> = None;
+    for name in &chains {
+        for l in store.get_chain_links(name).await? {
+            let own = l.session_id == r.session_id || r.chain_name.is_some();
+            if own && link.as_ref().is_none_or(|best| l.ts > best.ts) { link = Some(l); }
+        }
+    }
+    let chain = link.as_ref().map(|l| l.chain_name.clone());
+    let todos = store.list_todos(&TodoQuery { done: Some(false), ..Default::default() }).await?.into_iter()
+        .filter(|t| t.session_id.as_deref() == Some(r.session_id.as_str()) || (chain.is_some() && t.chain_name == chain))
+        .collect();
+    let mut artifacts = Vec::new();
+    for id in link.iter().flat_map(|l| &l.artifact_ids) {
+        if let Some(a) = store.get_artifact(id).await? { artifacts.push(a); }
+    }
+    for a in saved {
+        if !artifacts.iter().any(|x: &Artifact| x.id == a.id) { artifacts.push(a); }
+    }
+    let created = now();
+    Ok(Some(Handoff {
+        id: format!("{created}_{}", short_id()),
+        from_session: r.session_id,
+        to: r.to,
+        note: r.note,
+        link,
+        todos,
+        artifacts,
+        created,
+        accepted_by: None,
+        accepted_at: None,
+    }))
+}
+
+async fn list_handoffs(State(s): State<AppState>, Query(q): Query<HandoffQuery>) -> impl IntoResponse {
+    match s.store.list_handoffs(q.all).await {
+        Ok(mut handoffs) => {
+            if let Some(to) = &q.to { handoffs.retain(|h| &h.to == to); }
+            (StatusCode::OK, Json(json!({ "handoffs": handoffs })))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn accept_handoff(State(s): State<AppState>, Valid(r): Valid<HandoffAcceptReq>) -> Response {
+    let session = match s.store.get_session(&r.session_id).await {
+        Ok(Some(session)) => session,
+        Ok(None) => return Problem::new(StatusCode::NOT_FOUND, "session not found").into_response(),
+        Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let id = match r.id {
+        Some(id) => id,
+        None => match s.store.list_handoffs(false).await {
+            Ok(pending) => match pending.into_iter().find(|h| h.is_for(&session)) {
+                Some(h) => h.id,
+                None => return Problem::new(StatusCode::NOT_FOUND, "no pending handoff for this session").into_response(),
+            },
+            Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+    };
+    match s.store.accept_handoff(&id, &session.id).await {
+        Ok(Some(handoff)) => {
+            s.events.publish(EventKind::HandoffAccepted, &handoff.id, json!({ "from": handoff.from_session, "by": session.id }));
+            (StatusCode::OK, Json(json!(handoff))).into_response()
+        }
+        Ok(None) => Problem::new(StatusCode::CONFLICT, "handoff not found or already accepted").into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn dedupe(State(s): State<AppState>, Valid(r): Valid<DedupeReq>) -> Response {
+    match crate::dedupe::run(&s.store, r.threshold, r.merge_above).await {
+        Ok(pairs) => (StatusCode::OK, Json(json!({ "pairs": pairs, "merged": pairs.iter().filter(|p| p.merged).count() }))).into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn delete_memory(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.delete_memory(&id).await {
+        Ok(_) => (StatusCode::OK, Json(json!({ "deleted": id }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+// Saved searches
+async fn save_search(State(s): State<AppState>, Valid(mut r): Valid<SavedSearch>) -> Response {
+    r.created = now();
+    match s.store.save_search(&r).await {
+        Ok(_) => (StatusCode::OK, Json(json!({ "saved": r.name }))).into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+async fn list_saved_searches(State(s): State<AppState>) -> impl IntoResponse {
+    match s.store.list_saved_searches().await {
+        Ok(searches) => (StatusCode::OK, Json(json!({ "searches": searches }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn run_saved_search(State(s): State<AppState>, Path(name): Path<String>) -> impl IntoResponse {
+    let search = match s.store.get_saved_search(&name).await {
+        Ok(Some(search)) => search,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({ "error": "saved search not found" }))),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() }))),
+    };
+    match s.store.global_search(&search.query, search.limit, &search.resolved_filters()).await {
+        Ok(results) => (StatusCode::OK, Json(json!({ "search": search, "results": results }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    }
+}
+
+async fn delete_saved_search(State(s): State<AppState>, Path(name): Path<String>) -> impl IntoResponse {
+    match s.store.delete_saved_search(&name).await {
+        Ok(true) => (StatusCode::OK, Json(json!({ "deleted": name }))),
+        Ok(false) => (StatusCode::NOT_FOUND, Json(json!({ "error": "saved search not found" }))),
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
 }
 
 // Global get endpoint - handles chain:name:slug and artifact:id
-async fn global_get(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+async fn global_get(State(s): State<AppState>, Path(id): Path<String>, Query(q): Query<GetQuery>) -> Response {
     let id = urlencoding::decode(&id).unwrap_or_default().to_string();
+    let (status, Json(mut body)) = get_by_id(&s, &id).await;
+    if !status.is_success() { return (status, Json(body)).into_response(); }
+    if let Some(spec) = &q.pages {
+        if let Err(p) = select_pages(&mut body, spec) { return p.into_response(); }
+    }
+    let _ = s.store.record_access(&id).await;
+    (status, Json(body)).into_response()
+}
+
+// Narrows an artifact's `text` to the pages in `spec` and says which ones were returned
+fn select_pages(body: &mut serde_json::Value, spec: &str) -> Result<(), Problem> {
+    let meta = serde_json::from_value::<Option<DocMeta>>(body["meta"].clone()).ok().flatten().filter(|m| !m.page_offsets.is_empty())
+        .ok_or_else(|| Problem::field("pages", "only PDF artifacts have pages (run `tinymem reindex` for PDFs saved before page offsets were recorded)"))?;
+    let (from, to) = parse_pages(spec).ok_or_else(|| Problem::field("pages", "must be a page or range: 12, 3-5 or 3-"))?;
+    let extracted = meta.page_offsets.len();
+    if from > extracted {
+        return Err(Problem::field("pages", format!("must start at or before page {extracted}, the last with extracted text")));
+    }
+    let to = to.min(extracted);
+    let text = meta.page_text(body["text"].as_str().unwrap_or_default(), from, to).to_string();
+    body["text"] = json!(text);
+    body["pages"] = json!({ "from": from, "to": to, "page_count": meta.page_count });
+    Ok(())
+}
+
+// "12", "3-5" or "3-" (through the last page), 1-based
+fn parse_pages(spec: &str) -> Option<(usize, usize)> {
+    let (from, to) = spec.split_once('-').unwrap_or((spec, spec));
+    let from: usize = from.trim().parse().ok()?;
+    let to = if to.trim().is_empty() { usize::MAX } else { to.trim().parse().ok()? };
+    (from >= 1 && from <= to).then_some((from, to))
+}
+
+async fn get_by_id(s: &AppState, id: &str) -> (StatusCode, Json<serde_json::Value>) {
     if id.starts_with("chain:") {
         // Parse chain:name:slug
         let parts: Vec<&str> = id.splitn(3, ':').collect();
@@ -161,11 +1268,22 @@ async fn global_get(State(s): State<AppState>, Path(id): Path<String>) -> impl I
                 Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
             }
         }
+    } else if let Some(memory_id) = id.strip_prefix("memory:") {
+        return match s.store.get_memory(memory_id).await {
+            Ok(Some(memory)) => {
+                let mut body = json!(memory);
+                body["type"] = json!("memory");
+                (StatusCode::OK, Json(body))
+            }
+            Ok(None) => (StatusCode::NOT_FOUND, Json(json!({ "error": "memory not found" }))),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+        };
     } else if id.starts_with("artifact:") {
         let artifact_id = &id[9..];
         match s.store.get_artifact(artifact_id).await {
             Ok(Some(artifact)) => {
                 let text = s.store.get_artifact_text(artifact_id).await.ok().flatten().unwrap_or_default();
+                let meta = s.store.get_artifact_meta(artifact_id).await.ok().flatten();
                 return (StatusCode::OK, Json(json!({
                     "type": "artifact",
                     "id": artifact.id,
@@ -174,6 +1292,7 @@ async fn global_get(State(s): State<AppState>, Path(id): Path<String>) -> impl I
                     "description": artifact.description,
                     "file_type": artifact.file_type,
                     "text": text,
+                    "meta": meta,
                     "session_id": artifact.session_id,
                     "ts": artifact.ts
                 })));
@@ -182,47 +1301,82 @@ async fn global_get(State(s): State<AppState>, Path(id): Path<String>) -> impl I
             Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
         }
     }
-    (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid id format, expected chain:name:slug or artifact:id" })))
+    (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid id format, expected chain:name:slug, artifact:id or memory:id" })))
 }
 
 // Artifact endpoints
-async fn save_artifact(State(s): State<AppState>, Path(session_id): Path<String>, Json(r): Json<ArtifactSaveReq>) -> impl IntoResponse {
+async fn save_artifact(State(s): State<AppState>, Caller(caller): Caller, Path(session_id): Path<String>, Valid(r): Valid<ArtifactSaveReq>) -> Response {
     let path = FilePath::new(&r.file_path);
     if !path.exists() {
-        return (StatusCode::BAD_REQUEST, Json(json!({ "error": "file not found" })));
+        return Problem::field("file_path", "file not found").into_response();
     }
-    let file_type = path.extension().and_then(|e| e.to_str()).unwrap_or("txt").to_lowercase();
-    let ts = now();
-    let sanitized_title: String = r.title.chars().filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_').take(50).collect();
-    let id = format!("{}_{}", ts, sanitized_title);
-
-    let artifact = Artifact {
-        id: id.clone(),
-        file_path: r.file_path.clone(),
-        title: r.title,
-        description: r.description,
-        session_id,
-        file_type: file_type.clone(),
-        ts,
-    };
+    let subjects = [format!("session:{session_id}"), caller];
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let project = session_project(&s, &session_id).await;
+    let artifact = Artifact { project, ..Artifact::new(r.file_path, r.title, r.description, session_id, normalize_tags(r.tags)) };
+    let (id, file_type) = (artifact.id.clone(), artifact.file_type.clone());
+    if let Some(resp) = over_quota(&s, &subjects, QuotaKind::ArtifactBytes, &id, size).await { return resp; }
 
-    // Extract text for indexing
-    let text = extract_file_text(&r.file_path, &file_type);
+    if let Err(e) = s.store.save_artifact(&artifact).await {
+        let _ = s.store.release_reservation(&subjects, QuotaKind::ArtifactBytes, &id, size).await;
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+    }
+    let _ = s.store.set_artifact_status(&id, &IndexStatus::new(IndexState::Indexing, 0, None)).await;
+    tokio::spawn(index_artifact(s.store.clone(), s.events.clone(), artifact));
+    (StatusCode::OK, Json(json!({ "id": id, "file_type": file_type, "indexing": true }))).into_response()
+}
 
-    match s.store.save_artifact(&artifact).await {
-        Ok(_) => {
-            if !text.is_empty() {
-                let _ = s.store.set_artifact_text(&id, &text).await;
-            }
-            (StatusCode::OK, Json(json!({ "id": id, "file_type": file_type })))
+// Extraction blocks (big PDFs take seconds), so it runs on the blocking pool after the save is answered;
+// progress is visible at GET /artifact/:id/status, and the outcome is published as artifact.indexed
+pub(crate) async fn index_artifact(store: Store, events: EventBus, artifact: Artifact) {
+    let (path, file_type) = (artifact.file_path.clone(), artifact.file_type.clone());
+    let result = match tokio::task::spawn_blocking(move || extract_file_text(&path, &file_type)).await {
+        Ok((text, meta)) => save_extraction(&store, &artifact.id, &text, meta.as_ref()).await.map(|_| text.chars().count()),
+        Err(e) => Err(anyhow::anyhow!("extraction failed: {e}")),
+    };
+    let status = match result {
+        Ok(chars) => IndexStatus::new(IndexState::Ready, chars, None),
+        Err(e) => {
+            eprintln!("indexing artifact {}: {e}", artifact.id);
+            IndexStatus::new(IndexState::Failed, 0, Some(e.to_string()))
         }
-        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    };
+    let _ = store.set_artifact_status(&artifact.id, &status).await;
+    events.publish(EventKind::ArtifactIndexed, &artifact.id, json!({ "title": artifact.title, "session_id": artifact.session_id, "status": status }));
+}
+
+async fn artifact_status(State(s): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match s.store.get_artifact(&id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({ "error": "artifact not found" }))),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
+    let status = match s.store.get_artifact_status(&id).await {
+        Ok(Some(status)) => status,
+        // Saved before background indexing: extraction finished inside the save request
+        Ok(None) => {
+            let chars = s.store.get_artifact_text(&id).await.ok().flatten().map(|t| t.chars().count()).unwrap_or(0);
+            IndexStatus { state: IndexState::Ready, chars, error: None, updated: 0 }
+        }
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
+    };
+    (StatusCode::OK, Json(json!({ "id": id, "status": status })))
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags.into_iter().map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
 }
 
-async fn list_artifacts(State(s): State<AppState>) -> impl IntoResponse {
+async fn list_artifacts(State(s): State<AppState>, Query(q): Query<ArtifactQuery>) -> impl IntoResponse {
+    let filters = SearchFilters { tag: q.tag, file_type: q.file_type, project: q.project, ..Default::default() };
     match s.store.list_artifacts().await {
-        Ok(artifacts) => (StatusCode::OK, Json(json!({ "artifacts": artifacts }))),
+        Ok(artifacts) => {
+            let artifacts: Vec<_> = artifacts.into_iter().filter(|a| filters.allows_artifact(a)).collect();
+            (StatusCode::OK, Json(json!({ "artifacts": artifacts })))
+        }
         Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": e.to_string() })))
     }
 }
@@ -234,68 +1388,140 @@ async fn delete_artifact(State(s): State<AppState>, Path(id): Path<String>) -> i
     }
 }
 
-fn extract_file_text(file_path: &str, file_type: &str) -> String {
-    match file_type {
-        "pdf" => {
-            match mupdf::Document::open(file_path) {
-                Ok(doc) => {
-                    let mut text = String::new();
-                    let page_count = doc.page_count().unwrap_or(0);
-                    for i in 0..page_count {
-                        if let Ok(page) = doc.load_page(i) {
-                            if let Ok(tp) = page.to_text_page(mupdf::TextPageFlags::empty()) {
-                                for block in tp.blocks() {
-                                    for line in block.lines() {
-                                        for ch in line.chars() {
-                                            if let Some(c) = ch.char() {
-                                                text.push(c);
-                                            }
-                                        }
-                                        text.push('\n');
-                                    }
-                                }
-                            }
-                        }
-                        if text.len() > 50000 { break; }
-                    }
-                    text.chars().take(50000).collect()
-                }
-                Err(_) => String::new()
-            }
-        }
-        "txt" | "md" | "json" | "yaml" | "yml" | "toml" | "rs" | "py" | "js" | "ts" => {
-            std::fs::read_to_string(file_path)
-                .map(|s| s.chars().take(50000).collect())
-                .unwrap_or_default()
-        }
-        _ => String::new()
+async fn reindex(State(s): State<AppState>) -> Response {
+    if let Err(e) = s.store.rebuild_links().await {
+        return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
     }
+    let indexed = match s.store.rebuild_search_index().await {
+        Ok(n) => n,
+        Err(e) => return Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    match reindex_artifacts(&s.store).await {
+        Ok((reindexed, skipped)) => (StatusCode::OK, Json(json!({ "reindexed": reindexed, "skipped": skipped, "indexed": indexed }))).into_response(),
+        Err(e) => Problem::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+    }
+}
+
+/// Re-run text extraction for every artifact whose source file still exists; returns (reindexed, skipped)
+pub async fn reindex_artifacts(store: &Store) -> Result<(usize, usize)> {
+    let (mut reindexed, mut skipped) = (0, 0);
+    for artifact in store.list_artifacts().await? {
+        if !FilePath::new(&artifact.file_path).exists() { skipped += 1; continue; }
+        let (path, file_type) = (artifact.file_path.clone(), artifact.file_type.clone());
+        let (text, meta) = tokio::task::spawn_blocking(move || extract_file_text(&path, &file_type)).await?;
+        if text.is_empty() && meta.is_none() { skipped += 1; continue; }
+        save_extraction(store, &artifact.id, &text, meta.as_ref()).await?;
+        store.set_artifact_status(&artifact.id, &IndexStatus::new(IndexState::Ready, text.chars().count(), None)).await?;
+        reindexed += 1;
+    }
+    Ok((reindexed, skipped))
 }
 
-pub async fn run(store: Store, token: String, tui_tx: Sender<TuiEvent>, port: u16) -> Result<()> {
-    let state = AppState { store, tui_tx, token: token.clone() };
+// Metadata first: a scanned PDF without a text layer still has a title and page count
+async fn save_extraction(store: &Store, id: &str, text: &str, meta: Option<&DocMeta>) -> Result<()> {
+    if let Some(meta) = meta { store.set_artifact_meta(id, meta).await?; }
+    if !text.is_empty() { store.set_artifact_text(id, text).await?; }
+    Ok(())
+}
+
+/// Full HTTP API under `/v1` (plus the legacy unprefixed paths) with auth applied, ready to be served or
+/// nested into another router
+pub fn router(state: AppState) -> Router {
     let app = Router::new()
         .route("/session", post(create_session).get(list_sessions))
+        .route("/sessions", axum::routing::get(list_sessions_full))
+        .route("/projects", axum::routing::get(list_projects))
+        .route("/history", axum::routing::get(get_history))
         .route("/start", post(start_session))
-        .route("/session/:id", axum::routing::get(get_session))
+        .route("/session/:id", axum::routing::get(get_session).delete(delete_session))
         .route("/session/:id/hook", post(add_hook))
+        .route("/session/:id/hooks", axum::routing::get(get_hooks))
+        .route("/session/:id/transcript", post(add_transcript).get(get_transcript))
+        .route("/session/:id/usage", post(record_usage).get(get_usage))
+        .route("/session/:id/errors", axum::routing::get(get_errors))
+        .route("/session/:id/export", axum::routing::get(export_session))
+        .route("/session/:id/related", axum::routing::get(get_related))
+        .route("/sessions/compare", axum::routing::get(compare_sessions))
         .route("/session/:id/done", post(mark_done))
+        .route("/session/:id/heartbeat", post(heartbeat))
+        .route("/session/:id/resume", post(resume_session))
+        .route("/session/:id/ask", post(ask))
+        .route("/session/:id/answer", post(answer))
+        .route("/session/:id/approve", post(approve))
+        .route("/session/:id/inbox", axum::routing::get(get_inbox))
+        .route("/broadcast", post(broadcast))
         // Chain endpoints
         .route("/chain/:session_id", post(save_chain_link))
         .route("/chain/get/:chain_name", axum::routing::get(get_chain_links))
         .route("/chains", axum::routing::get(list_chains))
+        .route("/chain/status/:chain_name", post(set_chain_status))
+        .route("/chain/delete/:chain_name", axum::routing::delete(delete_chain))
         .route("/chain/search", post(search_chains))
         // Global search and get
         .route("/search", post(global_search))
+        .route("/search/saved", post(save_search).get(list_saved_searches))
+        .route("/search/saved/:name", axum::routing::get(run_saved_search).delete(delete_saved_search))
         .route("/get/*id", axum::routing::get(global_get))
         // Artifact endpoints
         .route("/artifact/save/:session_id", post(save_artifact))
+        .route("/artifact/:id/status", axum::routing::get(artifact_status))
         .route("/artifacts", axum::routing::get(list_artifacts))
+        .route("/artifacts/reindex", post(reindex))
         .route("/artifact/delete/:id", axum::routing::delete(delete_artifact))
+        // Memory endpoints
+        .route("/memory/:session_id", post(save_memory))
+        .route("/memories", axum::routing::get(list_memories))
+        .route("/memories/expired", axum::routing::get(list_expired_memories))
+        .route("/memories/:id/restore", post(restore_memory))
+        .route("/memory/delete/:id", axum::routing::delete(delete_memory))
+        .route("/priority", post(set_priority))
+        // Todo endpoints
+        .route("/todo", post(save_todo))
+        .route("/todos", axum::routing::get(list_todos))
+        .route("/todo/:id/complete", post(complete_todo))
+        .route("/todo/:id/reopen", post(reopen_todo))
+        .route("/todo/:id/assign", post(assign_todo))
+        .route("/todo/:id", axum::routing::delete(delete_todo))
+        // Handoff endpoints
+        .route("/handoff", post(create_handoff))
+        .route("/handoffs", axum::routing::get(list_handoffs))
+        .route("/handoff/accept", post(accept_handoff))
+        .route("/dedupe", post(dedupe))
+        .route("/stats", axum::routing::get(get_stats))
+        .route("/quota", axum::routing::get(get_quota))
+        .route("/retention", axum::routing::get(get_retention))
+        .route("/export/corpus", axum::routing::get(export_corpus))
+        .route("/events", axum::routing::get(stream_events))
+        .route("/audit", axum::routing::get(get_audit))
+        .route("/version", axum::routing::get(get_version));
+    #[cfg(feature = "graphql")]
+    let app = app
+        .route("/graphql", post(crate::graphql::handler))
+        .layer(axum::Extension(crate::graphql::schema(state.store.clone())));
+    let app = app
+        .route_layer(middleware::from_fn_with_state(state.clone(), timeout))
+        .route_layer(middleware::from_fn_with_state(state.clone(), audit))
+        .layer(DefaultBodyLimit::max(state.limits.max_body_bytes));
+    Router::new().nest(API_PREFIX, app.clone()).merge(app)
+        .layer(middleware::from_fn_with_state(state.clone(), degraded))
         .layer(middleware::from_fn_with_state(state.clone(), auth))
-        .with_state(state);
-    let listener = TcpListener::bind(format!("0.0.0.0:{port}")).await?;
-    eprintln!("Server listening on 0.0.0.0:{port}");
-    axum::serve(listener, app).await?;
+        .layer(middleware::from_fn(version))
+        .layer(middleware::from_fn_with_state(state.clone(), request_log))
+        .with_state(state)
+}
+
+/// Serves the API on `port`, over HTTPS when given a `tls` acceptor
+pub async fn run(state: AppState, port: u16, tls: Option<tokio_rustls::TlsAcceptor>) -> Result<()> {
+    let app = router(state);
+    let listener = match crate::systemd::listener()? {
+        Some(socket) => TcpListener::from_std(socket)?,
+        None => TcpListener::bind(format!("0.0.0.0:{port}")).await?,
+    };
+    eprintln!("Server listening on {}{}", listener.local_addr()?, if tls.is_some() { " (HTTPS)" } else { "" });
+    crate::systemd::notify("READY=1");
+    match tls {
+        Some(acceptor) => crate::tls::serve(listener, app, acceptor).await?,
+        None => axum::serve(listener, app).await?,
+    }
     Ok(())
 }
@@ -1,90 +1,520 @@
-use anyhow::Result;
-use redis::AsyncCommands;
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use aes_gcm::{aead::{Aead, AeadCore, KeyInit, OsRng}, Aes256Gcm, Nonce};
+use anyhow::{anyhow, bail, Result};
+use base64::{engine::general_purpose::STANDARD as B64, Engine};
+use redis::{streams::{StreamMaxlen, StreamRangeReply}, AsyncCommands};
 use strsim::jaro_winkler;
-use crate::models::{Artifact, ChainLink, Hook, SearchResult, Session, Status};
+use crate::breaker::GuardedConn;
+use crate::cache::ReadCache;
+use crate::redact::Redactor;
+use crate::search::{bm25, priority_boost, snippet, term_frequencies, tokenize, usage_boost, Index};
+use crate::models::{ActiveTool, ApprovalDecision, Artifact, AuditEntry, AuditQuery, CorpusDoc, CorpusType, HistoryQuery, HooksQuery, Memory, QuotaKind, QuotaUsage, SavedSearch, SearchFilters, ChainLink, ChainStatus, DocMeta, Hook, HookError, HookKind, InboxMessage, IndexStatus, LinkMode, PendingApproval, PendingAsk, SearchResult, Session, SessionProfile, Status, Handoff, Todo, TodoQuery, TranscriptEntry, Usage, UsageReq};
+
+// Large values (chain links, hooks, extracted text) are zstd-compressed. Readers detect the
+// zstd frame magic, so plain values written before compression existed still load.
+const COMPRESS_MIN_BYTES: usize = 1024;
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+fn pack(value: &str) -> Vec<u8> {
+    if value.len() >= COMPRESS_MIN_BYTES {
+        if let Ok(z) = zstd::encode_all(value.as_bytes(), 3) { return z; }
+    }
+    value.as_bytes().to_vec()
+}
+
+fn unpack(bytes: Vec<u8>) -> Result<String> {
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        Ok(String::from_utf8(zstd::decode_all(&bytes[..])?)?)
+    } else {
+        Ok(String::from_utf8(bytes)?)
+    }
+}
+
+// Encrypted values are ENC_MAGIC + 12-byte nonce + AES-256-GCM ciphertext of the packed value
+const ENC_MAGIC: &[u8] = b"tmenc1:";
+const ACTIVITY_TTL_SECS: i64 = 3600;
+const RECENT_ERRORS: isize = 20; // error hooks kept per session
+const HISTORY_SCAN_BATCH: isize = 100; // sessions fetched per round trip when filtering history by date
+const HOOK_SCAN_BATCH: isize = 500; // hooks fetched per round trip when filtering a session's hooks
+const INBOX_MAX: isize = 50; // unread operator messages kept per session
+const TRANSITION_ATTEMPTS: usize = 5; // rereads when a session changes under a status transition
+const SEARCH_SLACK: usize = 4; // index hits loaded per search result, as filters drop some and boosts reorder the rest
+
+// Compare-and-set of a session record together with its place in `active` / `history`, so a crash can't
+// leave the JSON saying one thing and the set and list another. KEYS: session, active, history.
+// ARGV: JSON as read, new JSON, session id, and "done", "active" or "" for where the id belongs.
+static TRANSITION: std::sync::LazyLock<redis::Script> = std::sync::LazyLock::new(|| redis::Script::new(r"
+    if redis.call('GET', KEYS[1]) ~= ARGV[1] then return 0 end
+    redis.call('SET', KEYS[1], ARGV[2])
+    if ARGV[4] == 'done' then
+        redis.call('SREM', KEYS[2], ARGV[3])
+        redis.call('LREM', KEYS[3], 0, ARGV[3])
+        redis.call('LPUSH', KEYS[3], ARGV[3])
+    elseif ARGV[4] == 'active' then
+        redis.call('LREM', KEYS[3], 0, ARGV[3])
+        redis.call('SADD', KEYS[2], ARGV[3])
+    end
+    return 1
+"));
+
+// Compare-and-set of the session record alone, for Redis Cluster, where `active` and `history` sit in
+// other slots than the session. KEYS: session. ARGV: JSON as read, new JSON.
+static COMPARE_AND_SET: std::sync::LazyLock<redis::Script> = std::sync::LazyLock::new(|| redis::Script::new(r"
+    if redis.call('GET', KEYS[1]) ~= ARGV[1] then return 0 end
+    redis.call('SET', KEYS[1], ARGV[2])
+    return 1
+"));
+
+// Adds a session id to `active` unless the set already holds `limit` ids, so the check and the add
+// can't interleave with another session's. KEYS: active. ARGV: session id, limit. Returns -1 when the
+// id was already there, -2 when it was added, and the number of active sessions when there is no room.
+static CLAIM_ACTIVE: std::sync::LazyLock<redis::Script> = std::sync::LazyLock::new(|| redis::Script::new(r"
+    if redis.call('SISMEMBER', KEYS[1], ARGV[1]) == 1 then return -1 end
+    local n = redis.call('SCARD', KEYS[1])
+    if n >= tonumber(ARGV[2]) then return n end
+    redis.call('SADD', KEYS[1], ARGV[1])
+    return -2
+"));
+
+/// How a session fared when it asked for one of `--max-active-sessions` slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    Taken,       // newly added to `active`; give it back if the session isn't written after all
+    Held,        // already active, or there is no limit, so there is nothing to give back
+    Full(usize), // no room: this many sessions are active
+}
+
+// Counts a write against one quota counter unless that takes it past the limit, so concurrent writes
+// can't both pass the check. KEYS: the counter, a string when ARGV[3] is empty and a hash otherwise.
+// ARGV: amount, limit (0 for none), hash field, TTL for a string. Returns whether it was counted, and
+// the usage before it.
+static RESERVE_QUOTA: std::sync::LazyLock<redis::Script> = std::sync::LazyLock::new(|| redis::Script::new(r"
+    local used
+    if ARGV[3] == '' then used = tonumber(redis.call('GET', KEYS[1]) or '0')
+    else used = tonumber(redis.call('HGET', KEYS[1], ARGV[3]) or '0') end
+    local limit = tonumber(ARGV[2])
+    if limit > 0 and used + tonumber(ARGV[1]) > limit then return {0, used} end
+    if ARGV[3] == '' then
+        redis.call('INCRBY', KEYS[1], ARGV[1])
+        redis.call('EXPIRE', KEYS[1], ARGV[4])
+    else
+        redis.call('HINCRBY', KEYS[1], ARGV[3], ARGV[1])
+    end
+    return {1, used}
+"));
+
+// Where a status transition leaves the session id
+#[derive(Clone, Copy)]
+enum Lists { Keep, Done, Active }
+
+/// Connection settings beyond the URL, for managed Redis that requires ACL users or TLS
+#[derive(Clone, Debug, Default)]
+pub struct RedisOptions {
+    /// Override the URL's ACL username and password
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// PEM CA bundle to trust instead of the system roots (`rediss://` only)
+    pub ca_cert: Option<PathBuf>,
+    /// PEM client certificate and key for mutual TLS; both or neither
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+}
+
+/// Outcome of [`Store::save_chain_link_as`]
+pub enum LinkSave {
+    Saved(String, Box<ChainLink>), // key, and the link as saved (merged, for append)
+    Taken(String),                 // create: the key of the link that has the slug
+    Ambiguous(Vec<String>),        // upsert or append: several links share the slug
+}
 
 #[derive(Clone)]
-pub struct Store { conn: redis::aio::ConnectionManager }
+pub struct Store { conn: GuardedConn, cipher: Option<Arc<Aes256Gcm>>, redactor: Option<Arc<Redactor>>, cache: Option<Arc<ReadCache>> }
 
 impl Store {
     pub async fn new(url: &str) -> Result<Self> {
-        let client = redis::Client::open(url)?;
-        Ok(Self { conn: redis::aio::ConnectionManager::new(client).await? })
+        Self::connect(url, &RedisOptions::default()).await
+    }
+
+    pub async fn connect(url: &str, opts: &RedisOptions) -> Result<Self> {
+        Ok(Self { conn: GuardedConn::connect(url, opts).await?, cipher: None, redactor: None, cache: None })
+    }
+
+    /// While Redis is unreachable, how long until calls are attempted again; until then they fail fast
+    pub fn degraded(&self) -> Option<std::time::Duration> { self.conn.open_for() }
+
+    /// Encrypt sensitive content (chain links, memories, artifact text) at rest. `key` is the AES key
+    /// itself, 32 random bytes as 64 hex digits or base64: a passphrase would be cheap to guess offline.
+    pub fn with_encryption(mut self, key: &str) -> Result<Self> {
+        let key = key.trim();
+        let bytes = if key.len() == 64 && key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            (0..32).map(|i| u8::from_str_radix(&key[2 * i..2 * i + 2], 16)).collect::<Result<Vec<u8>, _>>()?
+        } else {
+            B64.decode(key).unwrap_or_default()
+        };
+        let cipher = Aes256Gcm::new_from_slice(&bytes)
+            .map_err(|_| anyhow!("--encrypt-key must be 32 random bytes as hex or base64, e.g. from `openssl rand -hex 32`"))?;
+        self.cipher = Some(Arc::new(cipher));
+        Ok(self)
+    }
+
+    /// Strip secrets from hook meta/task, chain link and memory content, and artifact text before they are written
+    pub fn with_redactor(mut self, redactor: Redactor) -> Self {
+        self.redactor = Some(Arc::new(redactor));
+        self
+    }
+
+    /// Serve repeated session, chain name and artifact reads from memory for up to `ttl`
+    pub fn with_read_cache(mut self, ttl: std::time::Duration) -> Self {
+        self.cache = Some(Arc::new(ReadCache::new(ttl)));
+        self
+    }
+
+    /// The read cache, if enabled, so bus events can invalidate it (see [`ReadCache::listen`])
+    pub fn read_cache(&self) -> Option<Arc<ReadCache>> { self.cache.clone() }
+
+    fn forget_session(&self, id: &str) {
+        if let Some(c) = &self.cache { c.forget_session(id); }
+    }
+
+    fn seal(&self, value: &str) -> Result<Vec<u8>> {
+        let packed = pack(value);
+        let Some(cipher) = &self.cipher else { return Ok(packed) };
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ct = cipher.encrypt(&nonce, packed.as_slice()).map_err(|_| anyhow!("encryption failed"))?;
+        Ok([ENC_MAGIC, nonce.as_slice(), &ct].concat())
+    }
+
+    // Plaintext values are still readable after enabling encryption
+    fn open(&self, bytes: Vec<u8>) -> Result<String> {
+        let Some(rest) = bytes.strip_prefix(ENC_MAGIC) else { return unpack(bytes) };
+        let Some(cipher) = &self.cipher else { bail!("value is encrypted but no --encrypt-key is set") };
+        if rest.len() < 12 { bail!("truncated encrypted value"); }
+        let (nonce, ct) = rest.split_at(12);
+        let packed = cipher.decrypt(Nonce::from_slice(nonce), ct).map_err(|_| anyhow!("decryption failed (wrong --encrypt-key?)"))?;
+        unpack(packed)
     }
 
     pub async fn create_session(&self, s: &Session) -> Result<()> {
         let mut conn = self.conn.clone();
         let json = serde_json::to_string(s)?;
-        redis::pipe().set(format!("sessions:{}", s.id), &json).sadd("active", &s.id)
-            .query_async::<()>(&mut conn).await?;
+        let mut pipe = redis::pipe();
+        pipe.set(format!("sessions:{}", s.id), &json).sadd("active", &s.id);
+        if let Some(p) = &s.project { pipe.sadd("projects", p).sadd(format!("projects:{p}:sessions"), &s.id); }
+        pipe.query_async::<()>(&mut conn).await?;
+        self.forget_session(&s.id);
         Ok(())
     }
 
+    /// Known project ids with how many sessions each has had, by id
+    pub async fn list_projects(&self) -> Result<Vec<(String, usize)>> {
+        let mut conn = self.conn.clone();
+        let mut projects: Vec<String> = conn.smembers("projects").await?;
+        if projects.is_empty() { return Ok(vec![]); }
+        projects.sort();
+        let mut pipe = redis::pipe();
+        for p in &projects { pipe.scard(format!("projects:{p}:sessions")); }
+        let counts: Vec<usize> = pipe.query_async(&mut conn).await?;
+        Ok(projects.into_iter().zip(counts).collect())
+    }
+
+    /// A project's sessions with `status`, most recently active first
+    pub async fn project_sessions(&self, project: &str, status: &Status, limit: usize, offset: usize) -> Result<Vec<Session>> {
+        let ids: Vec<String> = self.conn.clone().smembers(format!("projects:{project}:sessions")).await?;
+        let mut sessions: Vec<Session> = self.get_sessions(&ids).await?.into_iter().filter(|s| s.status == *status).collect();
+        sessions.sort_by_key(|s| Reverse(s.last_activity));
+        Ok(sessions.into_iter().skip(offset).take(limit).collect())
+    }
+
     pub async fn get_session(&self, id: &str) -> Result<Option<Session>> {
+        if let Some(s) = self.cache.as_ref().and_then(|c| c.session(id)) { return Ok(s); }
         let mut conn = self.conn.clone();
         let json: Option<String> = conn.get(format!("sessions:{id}")).await?;
-        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+        let session: Option<Session> = json.map(|j| serde_json::from_str(&j)).transpose()?;
+        if let Some(c) = &self.cache { c.put_session(id, session.clone()); }
+        Ok(session)
     }
 
-    pub async fn update_status(&self, id: &str, status: &Status) -> Result<()> {
-        if let Some(mut s) = self.get_session(id).await? {
-            s.status = status.clone();
-            let mut conn = self.conn.clone();
-            conn.set::<_, _, ()>(format!("sessions:{id}"), serde_json::to_string(&s)?).await?;
-        }
+    pub async fn mark_done(&self, id: &str) -> Result<()> {
+        self.transition(id, |s| {
+            s.status = Status::Done;
+            Some(Lists::Done)
+        }).await?;
         Ok(())
     }
 
-    pub async fn mark_done(&self, id: &str) -> Result<()> {
-        self.update_status(id, &Status::Done).await?;
+    /// Rewrites session `id` with `change` and moves it between `active` and `history` in one step.
+    /// `change` returns where the id belongs, or None to leave the session alone, and is run again on
+    /// a fresh read if the record changed in the meantime. Returns whether the session was rewritten.
+    async fn transition(&self, id: &str, change: impl Fn(&mut Session) -> Option<Lists>) -> Result<bool> {
+        let key = format!("sessions:{id}");
         let mut conn = self.conn.clone();
-        redis::pipe().srem("active", id).lpush("history", id).query_async::<()>(&mut conn).await?;
-        Ok(())
+        for _ in 0..TRANSITION_ATTEMPTS {
+            // Read past the cache: the script compares against exactly what Redis holds
+            let Some(old) = conn.get::<_, Option<String>>(&key).await? else { return Ok(false) };
+            let mut s: Session = serde_json::from_str(&old)?;
+            let Some(lists) = change(&mut s) else { return Ok(false) };
+            let new = serde_json::to_string(&s)?;
+            let applied = if self.conn.is_cluster() {
+                // The three keys sit in different slots: the record is still compared before it is
+                // written, and only the winner moves the id, but a crash in between can leave them apart
+                let applied = COMPARE_AND_SET.key(&key).arg(&old).arg(&new).invoke_async::<bool>(&mut conn).await?;
+                match lists {
+                    Lists::Done if applied => redis::pipe().srem("active", id).lrem("history", 0, id).lpush("history", id).query_async::<()>(&mut conn).await?,
+                    Lists::Active if applied => redis::pipe().lrem("history", 0, id).sadd("active", id).query_async::<()>(&mut conn).await?,
+                    _ => {}
+                }
+                applied
+            } else {
+                let target = match lists { Lists::Keep => "", Lists::Done => "done", Lists::Active => "active" };
+                TRANSITION.key(&key).key("active").key("history")
+                    .arg(&old).arg(&new).arg(id).arg(target)
+                    .invoke_async::<bool>(&mut conn).await?
+            };
+            if applied {
+                self.forget_session(id);
+                return Ok(true);
+            }
+        }
+        bail!("session {id} kept changing during a status update; gave up after {TRANSITION_ATTEMPTS} attempts")
     }
 
     pub async fn add_hook(&self, id: &str, hook: &Hook) -> Result<()> {
+        let mut hook = hook.clone();
+        if let Some(r) = &self.redactor {
+            let task = r.redact(&hook.task);
+            let meta = r.redact_value(&mut hook.meta);
+            if let Some(t) = &task { hook.task = t.clone(); }
+            if task.is_some() || meta {
+                if !hook.meta.is_object() { hook.meta = serde_json::json!({}); }
+                hook.meta["redacted"] = true.into();
+            }
+        }
+        // Per-minute activity counters expire on their own once outside the sparkline window
+        let bucket = format!("activity:{{{id}}}:{}", hook.ts / 60);
         let mut conn = self.conn.clone();
-        conn.rpush::<_, _, ()>(format!("sessions:{id}:hooks"), serde_json::to_string(hook)?).await?;
+        let mut pipe = redis::pipe();
+        pipe.rpush(format!("sessions:{id}:hooks"), pack(&serde_json::to_string(&hook)?))
+            .incr(&bucket, 1).expire(&bucket, ACTIVITY_TTL_SECS);
+        if hook.is_error() {
+            let message = ["error", "message"].iter()
+                .find_map(|k| hook.meta.get(k).or_else(|| hook.meta.get("tool_response")?.get(k))?.as_str())
+                .unwrap_or_default().to_string();
+            let error = HookError { ts: hook.ts, task: hook.task.clone(), message };
+            let key = format!("sessions:{id}:errors");
+            pipe.lpush(&key, serde_json::to_string(&error)?).ltrim(&key, 0, RECENT_ERRORS - 1);
+        }
+        pipe.query_async::<()>(&mut conn).await?;
         self.touch_and_reactivate(id).await?;
         Ok(())
     }
 
+    /// Latest error hooks, newest first
+    pub async fn recent_errors(&self, id: &str, limit: isize) -> Result<Vec<HookError>> {
+        let items: Vec<String> = self.conn.clone().lrange(format!("sessions:{id}:errors"), 0, limit - 1).await?;
+        Ok(items.iter().filter_map(|e| serde_json::from_str(e).ok()).collect())
+    }
+
+    pub async fn add_transcript(&self, id: &str, entry: &TranscriptEntry) -> Result<()> {
+        let mut entry = entry.clone();
+        if let Some(t) = self.redactor.as_ref().and_then(|r| r.redact(&entry.text)) { entry.text = t; }
+        self.conn.clone().rpush::<_, _, ()>(format!("sessions:{id}:transcript"), self.seal(&serde_json::to_string(&entry)?)?).await?;
+        self.touch_and_reactivate(id).await
+    }
+
+    /// Leaves `text` in the inbox of every active session (and in its transcript as an `operator` entry);
+    /// returns the sessions reached
+    pub async fn broadcast(&self, text: &str) -> Result<Vec<String>> {
+        let ids: Vec<String> = self.conn.clone().smembers("active").await?;
+        for id in &ids { self.notify_session(id, text).await?; }
+        Ok(ids)
+    }
+
+    /// Leaves an operator note in one session's inbox (and transcript)
+    pub async fn notify_session(&self, id: &str, text: &str) -> Result<()> {
+        let msg = InboxMessage { ts: crate::models::now(), text: text.to_string() };
+        let key = format!("sessions:{id}:inbox");
+        redis::pipe().rpush(&key, serde_json::to_string(&msg)?).ltrim(&key, -INBOX_MAX, -1)
+            .query_async::<()>(&mut self.conn.clone()).await?;
+        self.add_transcript(id, &TranscriptEntry { ts: msg.ts, role: "operator".into(), text: msg.text }).await
+    }
+
+    /// Unread operator messages, oldest first; reading empties the inbox
+    pub async fn take_inbox(&self, id: &str) -> Result<Vec<InboxMessage>> {
+        let key = format!("sessions:{id}:inbox");
+        let (items,): (Vec<String>,) = redis::pipe().atomic()
+            .lrange(&key, 0, -1)
+            .del(&key).ignore()
+            .query_async(&mut self.conn.clone()).await?;
+        Ok(items.iter().filter_map(|m| serde_json::from_str(m).ok()).collect())
+    }
+
+    /// Last `limit` transcript entries, oldest first
+    pub async fn get_transcript(&self, id: &str, limit: isize) -> Result<Vec<TranscriptEntry>> {
+        let items: Vec<Vec<u8>> = self.conn.clone().lrange(format!("sessions:{id}:transcript"), -limit, -1).await?;
+        Ok(items.into_iter().filter_map(|b| serde_json::from_str(&self.open(b).ok()?).ok()).collect())
+    }
+
+    /// Hook counts for the last `minutes` minutes, oldest first (current minute last)
+    pub async fn activity(&self, id: &str, minutes: i64) -> Result<Vec<u64>> {
+        let current = crate::models::now() / 60;
+        // {id} is a hash tag so the MGET below stays on one slot in Redis Cluster
+        let keys: Vec<String> = (current - minutes + 1..=current).map(|m| format!("activity:{{{id}}}:{m}")).collect();
+        if keys.is_empty() { return Ok(vec![]); }
+        let counts: Vec<Option<u64>> = redis::cmd("MGET").arg(&keys).query_async(&mut self.conn.clone()).await?;
+        Ok(counts.into_iter().map(Option::unwrap_or_default).collect())
+    }
+
     pub async fn touch_and_reactivate(&self, id: &str) -> Result<()> {
-        if let Some(mut s) = self.get_session(id).await? {
+        self.transition(id, |s| {
             s.last_activity = crate::models::now();
-            if s.status == crate::models::Status::Done {
-                s.status = crate::models::Status::Active;
-                let mut conn = self.conn.clone();
-                redis::pipe()
-                    .lrem::<_, _>("history", 1, id)
-                    .sadd::<_, _>("active", id)
-                    .query_async::<()>(&mut conn).await?;
-            }
-            let mut conn = self.conn.clone();
-            conn.set::<_, _, ()>(format!("sessions:{id}"), serde_json::to_string(&s)?).await?;
-        }
+            if s.status != Status::Done { return Some(Lists::Keep); }
+            s.status = Status::Active;
+            Some(Lists::Active)
+        }).await?;
         Ok(())
     }
 
+    /// Records that the agent is still connected without counting as activity, so a quiet session shows
+    /// as idle rather than disconnected. Returns false if there was no such session.
+    pub async fn heartbeat(&self, id: &str) -> Result<bool> {
+        self.transition(id, |s| {
+            s.last_heartbeat = crate::models::now();
+            Some(Lists::Keep)
+        }).await
+    }
+
+    /// Stores what a finished session did; returns false if the session is gone
+    pub async fn set_summary(&self, id: &str, summary: &str) -> Result<bool> {
+        self.transition(id, |s| {
+            s.summary = Some(summary.to_string());
+            Some(Lists::Keep)
+        }).await
+    }
+
+    /// Sets or clears (None) the session's display name; false if there was no such session
+    pub async fn rename_session(&self, id: &str, name: Option<&str>) -> Result<bool> {
+        self.transition(id, |s| {
+            s.name = name.map(String::from);
+            Some(Lists::Keep)
+        }).await
+    }
+
+    /// Removes the session and everything recorded under it: hooks, transcript, usage, pending question,
+    /// external id mappings and activity counters. Chain links and artifacts it saved are shared and stay.
+    /// Returns false if there was no such session.
+    pub async fn delete_session(&self, id: &str) -> Result<bool> {
+        let mut conn = self.conn.clone();
+        let Some(session) = self.get_session(id).await? else { return Ok(false) };
+        let mut pipe = redis::pipe();
+        if let Some(p) = &session.project { pipe.srem(format!("projects:{p}:sessions"), id); }
+        // A mapping repointed to a newer session since is left alone
+        let mappings: Vec<String> = conn.smembers(format!("sessions:{id}:external")).await?;
+        for key in mappings {
+            if conn.get::<_, Option<String>>(&key).await?.as_deref() == Some(id) { pipe.del(key); }
+        }
+        for suffix in ["", ":hooks", ":errors", ":tools", ":active_tool", ":transcript", ":usage", ":ask", ":answer", ":approvals", ":inbox", ":chains", ":checkpoint", ":artifacts", ":external"] {
+            pipe.del(format!("sessions:{id}{suffix}"));
+        }
+        let current = crate::models::now() / 60;
+        for m in current - ACTIVITY_TTL_SECS / 60..=current { pipe.del(format!("activity:{{{id}}}:{m}")); }
+        pipe.srem("active", id).lrem("history", 0, id);
+        pipe.query_async::<()>(&mut conn).await?;
+        self.forget_session(id);
+        Ok(true)
+    }
+
+    /// Marks active sessions done once they have gone `max_inactive_secs` without activity or a heartbeat
     pub async fn cleanup_stale(&self, max_inactive_secs: i64) -> Result<Vec<String>> {
         let now = crate::models::now();
         let mut cleaned = Vec::new();
         for id in self.list_active().await? {
-            if let Ok(Some(s)) = self.get_session(&id).await {
-                let age = now - s.last_activity;
-                if age > max_inactive_secs && s.status == crate::models::Status::Active {
-                    self.mark_done(&id).await?;
-                    cleaned.push(id);
-                }
-            }
+            // Rechecked inside the transition, so activity that lands meanwhile keeps the session active
+            let stale = self.transition(&id, |s| {
+                if s.status != Status::Active || now - s.last_activity.max(s.last_heartbeat) <= max_inactive_secs { return None; }
+                s.status = Status::Done;
+                Some(Lists::Done)
+            }).await;
+            if let Ok(true) = stale { cleaned.push(id); }
         }
         Ok(cleaned)
     }
 
     pub async fn get_hooks(&self, id: &str, limit: isize) -> Result<Vec<Hook>> {
         let mut conn = self.conn.clone();
-        let items: Vec<String> = conn.lrange(format!("sessions:{id}:hooks"), -limit, -1).await?;
-        Ok(items.iter().filter_map(|j| serde_json::from_str(j).ok()).collect())
+        let items: Vec<Vec<u8>> = conn.lrange(format!("sessions:{id}:hooks"), -limit, -1).await?;
+        Ok(items.into_iter().filter_map(|b| serde_json::from_str(&unpack(b).ok()?).ok()).collect())
+    }
+
+    /// The latest `q.limit` hooks matching `q`, oldest first. Filtered queries scan back from the newest
+    /// hook a batch at a time until enough match.
+    pub async fn query_hooks(&self, id: &str, q: &HooksQuery) -> Result<Vec<Hook>> {
+        if q.limit == 0 { return Ok(vec![]); }
+        if !q.is_filtered() { return self.get_hooks(id, isize::try_from(q.limit).unwrap_or(isize::MAX)).await; }
+        let mut conn = self.conn.clone();
+        let (mut found, mut end) = (Vec::new(), -1isize);
+        while found.len() < q.limit {
+            let items: Vec<Vec<u8>> = conn.lrange(format!("sessions:{id}:hooks"), end - HOOK_SCAN_BATCH + 1, end).await?;
+            let fetched = items.len() as isize;
+            end -= fetched;
+            let hooks = items.into_iter().filter_map(|b| serde_json::from_str::<Hook>(&unpack(b).ok()?).ok());
+            found.extend(hooks.rev().filter(|h| q.matches(h)).take(q.limit - found.len()));
+            if fetched < HOOK_SCAN_BATCH { break; }
+        }
+        found.reverse();
+        Ok(found)
+    }
+
+    /// Finished sessions last active before `ts`, oldest first. History is in the order sessions ended,
+    /// so the scan starts at its old end and stops at the first session that is recent enough.
+    pub async fn finished_before(&self, ts: i64) -> Result<Vec<String>> {
+        let mut conn = self.conn.clone();
+        let (mut found, mut end) = (Vec::new(), -1isize);
+        loop {
+            let ids: Vec<String> = conn.lrange("history", end - HISTORY_SCAN_BATCH + 1, end).await?;
+            if ids.is_empty() { return Ok(found); }
+            end -= ids.len() as isize;
+            for s in self.get_sessions(&ids).await?.into_iter().rev() {
+                if s.last_activity.max(s.created) >= ts { return Ok(found); }
+                found.push(s.id);
+            }
+            if (ids.len() as isize) < HISTORY_SCAN_BATCH { return Ok(found); }
+        }
+    }
+
+    /// (session, how many of its oldest hooks are from before `ts`) for sessions that have any
+    pub async fn hooks_before(&self, ts: i64) -> Result<Vec<(String, usize)>> {
+        let mut conn = self.conn.clone();
+        let mut ids = self.list_active().await?;
+        ids.extend(conn.lrange::<_, Vec<String>>("history", 0, -1).await?);
+        let hook_ts = |b: &[u8]| unpack(b.to_vec()).ok().and_then(|j| serde_json::from_str::<Hook>(&j).ok()).map_or(i64::MAX, |h| h.ts);
+        // Hooks are appended in time order, so only sessions whose first hook is old need a closer look
+        let mut pipe = redis::pipe();
+        for id in &ids { pipe.lindex(format!("sessions:{id}:hooks"), 0); }
+        let heads: Vec<Option<Vec<u8>>> = pipe.query_async(&mut conn).await?;
+        let mut found = Vec::new();
+        for (id, head) in ids.into_iter().zip(heads) {
+            let Some(head) = head else { continue };
+            if hook_ts(&head) >= ts { continue; }
+            let mut count = 0;
+            loop {
+                let batch: Vec<Vec<u8>> = conn.lrange(format!("sessions:{id}:hooks"), count as isize, count as isize + HISTORY_SCAN_BATCH - 1).await?;
+                let len = batch.len();
+                let old = batch.into_iter().take_while(|b| hook_ts(b) < ts).count();
+                count += old;
+                if old < len || len < HISTORY_SCAN_BATCH as usize { break; }
+            }
+            found.push((id, count));
+        }
+        Ok(found)
+    }
+
+    /// Drops a session's `count` oldest hooks
+    pub async fn trim_hooks(&self, id: &str, count: usize) -> Result<()> {
+        self.conn.clone().ltrim::<_, ()>(format!("sessions:{id}:hooks"), count as isize, -1).await?;
+        Ok(())
     }
 
     pub async fn list_active(&self) -> Result<Vec<String>> { Ok(self.conn.clone().smembers("active").await?) }
@@ -93,53 +523,333 @@ impl Store {
         Ok(self.conn.clone().lrange("history", 0, limit - 1).await?)
     }
 
-    pub async fn set_active_tool(&self, id: &str, tool: &str) -> Result<()> {
-        self.conn.clone().set::<_, _, ()>(format!("sessions:{id}:active_tool"), tool).await?;
+    /// Full sessions in one round trip, most recent first: active ones by last activity, finished
+    /// ones in the order they ended
+    pub async fn list_sessions(&self, status: &Status, limit: usize, offset: usize) -> Result<Vec<Session>> {
+        if limit == 0 { return Ok(vec![]); }
+        let mut conn = self.conn.clone();
+        let ids: Vec<String> = match status {
+            Status::Active => conn.smembers("active").await?,
+            Status::Done => {
+                let index = |i: usize| isize::try_from(i).unwrap_or(isize::MAX);
+                conn.lrange("history", index(offset), index(offset.saturating_add(limit)) - 1).await?
+            }
+        };
+        if ids.is_empty() { return Ok(vec![]); }
+        let mut sessions = self.get_sessions(&ids).await?;
+        if *status == Status::Active {
+            sessions.sort_by_key(|s| Reverse(s.last_activity));
+            sessions = sessions.into_iter().skip(offset).take(limit).collect();
+        }
+        Ok(sessions)
+    }
+
+    /// When each of `ids` last saved a chain link or was reminded to, and the chain of its last link
+    pub async fn last_checkpoints(&self, ids: &[String]) -> Result<Vec<(Option<i64>, Option<String>)>> {
+        if ids.is_empty() { return Ok(vec![]); }
+        let mut pipe = redis::pipe();
+        for id in ids { pipe.cmd("HMGET").arg(format!("sessions:{id}:checkpoint")).arg("ts").arg("chain"); }
+        Ok(pipe.query_async(&mut self.conn.clone()).await?)
+    }
+
+    /// Restarts session `id`'s checkpoint clock without a link, after a reminder
+    pub async fn mark_reminded(&self, id: &str, ts: i64) -> Result<()> {
+        Ok(self.conn.clone().hset(format!("sessions:{id}:checkpoint"), "ts", ts).await?)
+    }
+
+    pub async fn active_count(&self) -> Result<usize> {
+        Ok(self.conn.clone().scard("active").await?)
+    }
+
+    /// Puts `id` in `active` if fewer than `limit` sessions are, checking and adding in one step
+    pub async fn claim_active(&self, id: &str, limit: usize) -> Result<Slot> {
+        let n: i64 = CLAIM_ACTIVE.key("active").arg(id).arg(limit).invoke_async(&mut self.conn.clone()).await?;
+        Ok(match n {
+            -1 => Slot::Held,
+            -2 => Slot::Taken,
+            n => Slot::Full(n as usize),
+        })
+    }
+
+    /// Gives back a slot from `claim_active` whose session was never written
+    pub async fn release_active(&self, id: &str) -> Result<()> {
+        Ok(self.conn.clone().srem("active", id).await?)
+    }
+
+    // Sessions that still exist among `ids`, in order
+    async fn get_sessions(&self, ids: &[String]) -> Result<Vec<Session>> {
+        if ids.is_empty() { return Ok(vec![]); }
+        let Some(cache) = &self.cache else {
+            let mut pipe = redis::pipe();
+            for id in ids { pipe.get(format!("sessions:{id}")); }
+            let jsons: Vec<Option<String>> = pipe.query_async(&mut self.conn.clone()).await?;
+            return Ok(jsons.into_iter().flatten().filter_map(|j| serde_json::from_str(&j).ok()).collect());
+        };
+        // Only the ids the cache can't answer go to Redis
+        let mut found: Vec<Option<Option<Session>>> = ids.iter().map(|id| cache.session(id)).collect();
+        let missing: Vec<usize> = (0..ids.len()).filter(|&i| found[i].is_none()).collect();
+        if !missing.is_empty() {
+            let mut pipe = redis::pipe();
+            for &i in &missing { pipe.get(format!("sessions:{}", ids[i])); }
+            let jsons: Vec<Option<String>> = pipe.query_async(&mut self.conn.clone()).await?;
+            for (i, json) in missing.into_iter().zip(jsons) {
+                let session: Option<Session> = json.and_then(|j| serde_json::from_str(&j).ok());
+                cache.put_session(&ids[i], session.clone());
+                found[i] = Some(session);
+            }
+        }
+        Ok(found.into_iter().flatten().flatten().collect())
+    }
+
+    /// Finished sessions, most recently ended first, keeping only those `q` matches; `offset` counts
+    /// matching sessions
+    pub async fn history_page(&self, q: &HistoryQuery) -> Result<Vec<Session>> {
+        let (limit, offset) = (q.limit, q.offset);
+        if !q.is_filtered() { return self.list_sessions(&Status::Done, limit, offset).await; }
+        let mut conn = self.conn.clone();
+        let (mut page, mut skip, mut start) = (Vec::new(), offset, 0isize);
+        while page.len() < limit {
+            let ids: Vec<String> = conn.lrange("history", start, start + HISTORY_SCAN_BATCH - 1).await?;
+            if ids.is_empty() { break; }
+            start += ids.len() as isize;
+            for s in self.get_sessions(&ids).await? {
+                if !q.matches(&s) { continue; }
+                if skip > 0 { skip -= 1; } else if page.len() < limit { page.push(s); }
+            }
+        }
+        Ok(page)
+    }
+
+    // In-flight tool calls: sessions:{id}:tools maps invocation id -> ActiveTool. The hash expires a day
+    // after the last call starts so a post hook that never arrives doesn't leave a tool running forever
+    pub async fn set_active_tool(&self, id: &str, invocation: &str, tool: &str) -> Result<()> {
+        let key = format!("sessions:{id}:tools");
+        let active = ActiveTool { tool: tool.to_string(), since: crate::models::now() };
+        redis::pipe().hset(&key, invocation, serde_json::to_string(&active)?).expire(&key, 86400)
+            .query_async::<()>(&mut self.conn.clone()).await?;
         Ok(())
     }
 
-    pub async fn clear_active_tool(&self, id: &str) -> Result<()> {
-        self.conn.clone().del::<_, ()>(format!("sessions:{id}:active_tool")).await?;
+    pub async fn clear_active_tool(&self, id: &str, invocation: &str) -> Result<()> {
+        self.conn.clone().hdel::<_, _, ()>(format!("sessions:{id}:tools"), invocation).await?;
         Ok(())
     }
 
-    pub async fn get_active_tool(&self, id: &str) -> Result<Option<String>> {
-        Ok(self.conn.clone().get(format!("sessions:{id}:active_tool")).await?)
+    /// Updates the in-flight tools for a hook: a pre hook starts its call, post/error ends it, and a
+    /// stop hook (the agent's turn is over) ends everything still listed
+    pub async fn track_tools(&self, id: &str, hook: &Hook) -> Result<()> {
+        let invocation = crate::models::invocation_id(&hook.task, &hook.meta);
+        match hook.kind {
+            HookKind::Pre => self.set_active_tool(id, &invocation, &hook.task).await,
+            HookKind::Post | HookKind::Error => self.clear_active_tool(id, &invocation).await,
+            HookKind::Stop => Ok(self.conn.clone().del::<_, ()>(format!("sessions:{id}:tools")).await?),
+            _ => Ok(()),
+        }
+    }
+
+    /// Running tools, longest-running first
+    pub async fn get_active_tools(&self, id: &str) -> Result<Vec<ActiveTool>> {
+        let raw: Vec<String> = self.conn.clone().hvals(format!("sessions:{id}:tools")).await?;
+        let mut tools: Vec<ActiveTool> = raw.iter().filter_map(|r| serde_json::from_str(r).ok()).collect();
+        tools.sort_by_key(|t| t.since);
+        Ok(tools)
     }
 
-    // Map Claude session ID to tinymem session ID
-    pub async fn set_claude_mapping(&self, claude_id: &str, tinymem_id: &str) -> Result<()> {
-        self.conn.clone().set::<_, _, ()>(format!("claude:{claude_id}"), tinymem_id).await?;
+    // Pending operator question and its answer (answer key is consumed by the waiting request)
+    pub async fn set_pending_ask(&self, id: &str, ask: &PendingAsk) -> Result<()> {
+        let mut conn = self.conn.clone();
+        redis::pipe()
+            .set(format!("sessions:{id}:ask"), serde_json::to_string(ask)?)
+            .del(format!("sessions:{id}:answer"))
+            .query_async::<()>(&mut conn).await?;
         Ok(())
     }
 
-    pub async fn get_claude_mapping(&self, claude_id: &str) -> Result<Option<String>> {
-        Ok(self.conn.clone().get(format!("claude:{claude_id}")).await?)
+    pub async fn get_pending_ask(&self, id: &str) -> Result<Option<PendingAsk>> {
+        let json: Option<String> = self.conn.clone().get(format!("sessions:{id}:ask")).await?;
+        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+    }
+
+    pub async fn set_answer(&self, id: &str, answer: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        redis::pipe()
+            .set(format!("sessions:{id}:answer"), answer)
+            .del(format!("sessions:{id}:ask"))
+            .query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    pub async fn take_answer(&self, id: &str) -> Result<Option<String>> {
+        Ok(self.conn.clone().get_del(format!("sessions:{id}:answer")).await?)
+    }
+
+    pub async fn clear_pending_ask(&self, id: &str) -> Result<()> {
+        self.conn.clone().del::<_, ()>(format!("sessions:{id}:ask")).await?;
+        Ok(())
+    }
+
+    // Tool calls awaiting approval: sessions:{id}:approvals maps invocation id -> PendingApproval, and the
+    // decision is left at sessions:{id}:approval:{invocation} for the held hook request to consume
+    pub async fn set_pending_approval(&self, id: &str, approval: &PendingApproval) -> Result<()> {
+        let mut approval = approval.clone();
+        if let Some(r) = &self.redactor {
+            if let Some(t) = r.redact(&approval.task) { approval.task = t; }
+            r.redact_value(&mut approval.meta);
+        }
+        redis::pipe()
+            .hset(format!("sessions:{id}:approvals"), &approval.invocation_id, serde_json::to_string(&approval)?)
+            .del(format!("sessions:{id}:approval:{}", approval.invocation_id))
+            .query_async::<()>(&mut self.conn.clone()).await?;
+        Ok(())
+    }
+
+    /// Oldest first
+    pub async fn pending_approvals(&self, id: &str) -> Result<Vec<PendingApproval>> {
+        let raw: Vec<String> = self.conn.clone().hvals(format!("sessions:{id}:approvals")).await?;
+        let mut pending: Vec<PendingApproval> = raw.iter().filter_map(|r| serde_json::from_str(r).ok()).collect();
+        pending.sort_by_key(|a| a.ts);
+        Ok(pending)
+    }
+
+    /// False if nothing with that invocation id is waiting
+    pub async fn decide_approval(&self, id: &str, invocation: &str, decision: &ApprovalDecision) -> Result<bool> {
+        let mut conn = self.conn.clone();
+        let removed: i64 = conn.hdel(format!("sessions:{id}:approvals"), invocation).await?;
+        if removed == 0 { return Ok(false); }
+        // Expires in case the waiting request already gave up
+        conn.set_ex::<_, _, ()>(format!("sessions:{id}:approval:{invocation}"), serde_json::to_string(decision)?, 600).await?;
+        Ok(true)
+    }
+
+    pub async fn take_approval_decision(&self, id: &str, invocation: &str) -> Result<Option<ApprovalDecision>> {
+        let json: Option<String> = self.conn.clone().get_del(format!("sessions:{id}:approval:{invocation}")).await?;
+        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+    }
+
+    pub async fn clear_pending_approval(&self, id: &str, invocation: &str) -> Result<()> {
+        self.conn.clone().hdel::<_, _, ()>(format!("sessions:{id}:approvals"), invocation).await?;
+        Ok(())
+    }
+
+    // Map an agent's own session ID to a tinymem session ID - external:{provider}:{external_id}
+    // sessions:{id}:external remembers the keys so deleting the session can drop them
+    pub async fn set_external_mapping(&self, provider: &str, external_id: &str, tinymem_id: &str) -> Result<()> {
+        let key = format!("external:{provider}:{external_id}");
+        redis::pipe().set(&key, tinymem_id).sadd(format!("sessions:{tinymem_id}:external"), &key)
+            .query_async::<()>(&mut self.conn.clone()).await?;
+        Ok(())
+    }
+
+    /// Also finds (and migrates) mappings written as claude:{id} before the registry existed
+    pub async fn get_external_mapping(&self, provider: &str, external_id: &str) -> Result<Option<String>> {
+        let key = format!("external:{provider}:{external_id}");
+        let mut conn = self.conn.clone();
+        let found: Option<String> = conn.get(&key).await?;
+        if found.is_some() || provider != "claude" { return Ok(found); }
+        let legacy_key = format!("claude:{external_id}");
+        let legacy: Option<String> = conn.get(&legacy_key).await?;
+        if let Some(id) = &legacy {
+            redis::pipe().set(&key, id).del(&legacy_key).sadd(format!("sessions:{id}:external"), &key).query_async::<()>(&mut conn).await?;
+        }
+        Ok(legacy)
     }
 
     // Chain operations - multi-session workflow chains
     pub async fn save_chain_link(&self, link: &ChainLink) -> Result<String> {
+        let mut link = link.clone();
+        if let Some(r) = &self.redactor {
+            if let Some(content) = r.redact(&link.content) {
+                link.content = content;
+                link.redacted = true;
+            }
+            for item in link.completed.iter_mut().chain(&mut link.next_steps).chain(&mut link.decisions) {
+                if let Some(redacted) = r.redact(item) {
+                    *item = redacted;
+                    link.redacted = true;
+                }
+            }
+        }
         let mut conn = self.conn.clone();
         // Key: chains:{chain_name}:{timestamp}
         let key = format!("chains:{}:{}", link.chain_name, link.ts);
-        redis::pipe()
-            .set(&key, serde_json::to_string(link)?)
+        let mut pipe = redis::pipe();
+        pipe.set(&key, self.seal(&serde_json::to_string(&link)?)?)
             .sadd("chain_names", &link.chain_name)
             .sadd(format!("chain:{}:links", link.chain_name), &key)
-            .query_async::<()>(&mut conn).await?;
+            .set(format!("chain:{}:slug:{}", link.chain_name, link.slug), &key)
+            .sadd(format!("sessions:{}:chains", link.session_id), &link.chain_name);
+        for id in self.artifact_refs(&link).await? {
+            pipe.sadd(format!("artifacts:{id}:mentions"), &key);
+        }
+        pipe.query_async::<()>(&mut conn).await?;
+        if let Some(c) = &self.cache { c.forget_chain_names(); }
+        self.index_doc(&key, &link_doc(link).indexed).await?;
         Ok(key)
     }
 
+    /// Saves a link according to `mode` against the chain's links with the same slug. A new link whose
+    /// second another link already holds moves to the next free second rather than overwriting it. Of
+    /// two saves racing to add the same new slug, one is told it is taken.
+    pub async fn save_chain_link_as(&self, mut link: ChainLink, mode: LinkMode) -> Result<LinkSave> {
+        let existing = self.links_with_slug(&link.chain_name, &link.slug).await?;
+        let key = |l: &ChainLink| format!("chains:{}:{}", l.chain_name, l.ts);
+        match (mode, existing.as_slice()) {
+            (_, []) => if let Some(taken) = self.claim_new_link(&mut link).await? { return Ok(LinkSave::Taken(taken)) },
+            (LinkMode::Create, [taken, ..]) => return Ok(LinkSave::Taken(key(taken))),
+            (_, [_, _, ..]) => return Ok(LinkSave::Ambiguous(existing.iter().map(key).collect())),
+            (LinkMode::Upsert, [old]) => link.ts = old.ts,
+            (LinkMode::Append, [old]) => {
+                let mut merged = old.clone();
+                merged.append(link);
+                link = merged;
+            }
+        }
+        let key = self.save_chain_link(&link).await?;
+        // A save the session made restarts its checkpoint clock (see crate::checkpoint); upserts and
+        // appends keep the old link's ts, so the clock goes by when the save happened
+        let checkpoint = [("ts", crate::models::now().to_string()), ("chain", link.chain_name.clone())];
+        self.conn.clone().hset_multiple::<_, _, _, ()>(format!("sessions:{}:checkpoint", link.session_id), &checkpoint).await?;
+        Ok(LinkSave::Saved(key, Box::new(link)))
+    }
+
+    // Claims a free second for a new link, then its slug, each with SET NX so concurrent saves can't
+    // both get them. Returns the key already holding the slug when another save got there first.
+    async fn claim_new_link(&self, link: &mut ChainLink) -> Result<Option<String>> {
+        let mut conn = self.conn.clone();
+        let key = |l: &ChainLink| format!("chains:{}:{}", l.chain_name, l.ts);
+        // A placeholder until save_chain_link writes the link; readers skip values that don't parse
+        while !conn.set_nx::<_, _, bool>(key(link), "").await? { link.ts += 1; }
+        let slug_key = format!("chain:{}:slug:{}", link.chain_name, link.slug);
+        if conn.set_nx::<_, _, bool>(&slug_key, key(link)).await? { return Ok(None); }
+        match conn.get::<_, Option<String>>(&slug_key).await? {
+            Some(holder) if conn.exists::<_, bool>(&holder).await? => {
+                conn.del::<_, ()>(key(link)).await?;
+                Ok(Some(holder))
+            }
+            // The index outlived the link it pointed at
+            _ => {
+                conn.set::<_, _, ()>(&slug_key, key(link)).await?;
+                Ok(None)
+            }
+        }
+    }
+
+    // The link the chain:{name}:slug:{slug} index points at, or every link with the slug when the chain
+    // was saved before the index existed
+    async fn links_with_slug(&self, chain_name: &str, slug: &str) -> Result<Vec<ChainLink>> {
+        let indexed: Option<String> = self.conn.clone().get(format!("chain:{chain_name}:slug:{slug}")).await?;
+        if let Some(key) = indexed {
+            if let Some(link) = self.link_at(&key).await?.filter(|l| l.slug == slug) { return Ok(vec![link]); }
+        }
+        Ok(self.get_chain_links(chain_name).await?.into_iter().filter(|l| l.slug == slug).collect())
+    }
+
     pub async fn get_chain_links(&self, chain_name: &str) -> Result<Vec<ChainLink>> {
         let mut conn = self.conn.clone();
         let keys: Vec<String> = conn.smembers(format!("chain:{}:links", chain_name)).await?;
         let mut links = Vec::new();
         for key in keys {
-            if let Ok(Some(json)) = conn.get::<_, Option<String>>(&key).await {
-                if let Ok(link) = serde_json::from_str::<ChainLink>(&json) {
-                    links.push(link);
-                }
-            }
+            if let Ok(Some(link)) = self.link_at(&key).await { links.push(link); }
         }
         // Sort by timestamp descending (newest first)
         links.sort_by(|a, b| b.ts.cmp(&a.ts));
@@ -147,7 +857,34 @@ impl Store {
     }
 
     pub async fn list_chain_names(&self) -> Result<Vec<String>> {
-        Ok(self.conn.clone().smembers("chain_names").await?)
+        if let Some(names) = self.cache.as_ref().and_then(|c| c.chain_names()) { return Ok(names); }
+        let names: Vec<String> = self.conn.clone().smembers("chain_names").await?;
+        if let Some(c) = &self.cache { c.put_chain_names(names.clone()); }
+        Ok(names)
+    }
+
+    // Chain status - hash of chain name -> status; chains without an entry are active
+    pub async fn set_chain_status(&self, chain_name: &str, status: ChainStatus) -> Result<()> {
+        self.conn.clone().hset::<_, _, _, ()>("chain_status", chain_name, status.as_str()).await?;
+        Ok(())
+    }
+
+    pub async fn chain_status(&self, chain_name: &str) -> Result<ChainStatus> {
+        let status: Option<String> = self.conn.clone().hget("chain_status", chain_name).await?;
+        Ok(status.and_then(|s| ChainStatus::parse(&s)).unwrap_or_default())
+    }
+
+    pub async fn chain_statuses(&self) -> Result<HashMap<String, ChainStatus>> {
+        let all: HashMap<String, String> = self.conn.clone().hgetall("chain_status").await?;
+        Ok(all.into_iter().filter_map(|(name, s)| Some((name, ChainStatus::parse(&s)?))).collect())
+    }
+
+    /// Sets or clears one link's status; false when the link doesn't exist
+    pub async fn set_link_status(&self, chain_name: &str, identifier: &str, status: Option<ChainStatus>) -> Result<bool> {
+        let Some(mut link) = self.get_chain_link(chain_name, identifier).await? else { return Ok(false) };
+        link.status = status;
+        self.save_chain_link(&link).await?;
+        Ok(true)
     }
 
     pub async fn search_chains(&self, query: &str, limit: usize) -> Result<Vec<(String, f64)>> {
@@ -171,21 +908,186 @@ impl Store {
     pub async fn delete_chain(&self, chain_name: &str) -> Result<()> {
         let mut conn = self.conn.clone();
         let link_keys: Vec<String> = conn.smembers(format!("chain:{}:links", chain_name)).await?;
+        let artifact_ids: HashSet<String> = conn.smembers("artifact_ids").await?;
         let mut pipe = redis::pipe();
+        for link in self.get_chain_links(chain_name).await? {
+            for id in artifact_ref_candidates(&link).into_iter().filter(|id| artifact_ids.contains(id)) {
+                pipe.srem(format!("artifacts:{id}:mentions"), format!("chains:{}:{}", link.chain_name, link.ts));
+            }
+            pipe.srem(format!("sessions:{}:chains", link.session_id), chain_name)
+                .del(format!("chain:{}:slug:{}", chain_name, link.slug));
+        }
         for key in &link_keys {
             pipe.del(key);
         }
         pipe.del(format!("chain:{}:links", chain_name));
-        pipe.srem("chain_names", chain_name);
+        pipe.srem("chain_names", chain_name).hdel("chain_status", chain_name);
         pipe.query_async::<()>(&mut conn).await?;
+        if let Some(c) = &self.cache { c.forget_chain_names(); }
+        for key in &link_keys { self.unindex_doc(key).await?; }
+        Ok(())
+    }
+
+    pub async fn delete_chain_link(&self, chain_name: &str, ts: i64) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let key = format!("chains:{chain_name}:{ts}");
+        let mut pipe = redis::pipe();
+        if let Some(link) = self.link_at(&key).await? {
+            self.unlink_chain_link(&mut pipe, &link).await?;
+            let slug_key = format!("chain:{chain_name}:slug:{}", link.slug);
+            if conn.get::<_, Option<String>>(&slug_key).await?.as_deref() == Some(key.as_str()) { pipe.del(&slug_key); }
+        }
+        pipe.del(&key).srem(format!("chain:{chain_name}:links"), &key).query_async::<()>(&mut conn).await?;
+        self.unindex_doc(&key).await
+    }
+
+    // Cross-links - sessions:{id}:chains, sessions:{id}:artifacts and artifacts:{id}:mentions (chain link keys)
+    // Artifacts a link refers to: attached via artifact_ids or mentioned by id in its content
+    async fn artifact_refs(&self, link: &ChainLink) -> Result<Vec<String>> {
+        let candidates = artifact_ref_candidates(link);
+        if candidates.is_empty() { return Ok(candidates); }
+        let mut pipe = redis::pipe();
+        for id in &candidates { pipe.sismember("artifact_ids", id); }
+        let known: Vec<bool> = pipe.query_async(&mut self.conn.clone()).await?;
+        Ok(candidates.into_iter().zip(known).filter_map(|(id, known)| known.then_some(id)).collect())
+    }
+
+    // Queue removal of a link from the mention index; the session keeps the chain unless this was its last link there
+    async fn unlink_chain_link(&self, pipe: &mut redis::Pipeline, link: &ChainLink) -> Result<()> {
+        let key = format!("chains:{}:{}", link.chain_name, link.ts);
+        for id in self.artifact_refs(link).await? {
+            pipe.srem(format!("artifacts:{id}:mentions"), &key);
+        }
+        let others = self.get_chain_links(&link.chain_name).await?.iter()
+            .any(|l| l.session_id == link.session_id && l.ts != link.ts);
+        if !others { pipe.srem(format!("sessions:{}:chains", link.session_id), &link.chain_name); }
         Ok(())
     }
 
-    // Get specific chain link by chain_name and slug or timestamp
+    // Usage totals - hashes of input_tokens/output_tokens/cost_micros at sessions:{id}:usage and usage:{YYYY-MM-DD} (UTC)
+    pub async fn record_usage(&self, id: &str, r: &UsageReq) -> Result<()> {
+        let cost = r.cost_usd.unwrap_or_else(|| crate::usage::estimate_cost(&r.model, r.input_tokens, r.output_tokens));
+        let micros = (cost * 1e6).round() as i64;
+        let mut pipe = redis::pipe();
+        for key in [format!("sessions:{id}:usage"), format!("usage:{}", chrono::Utc::now().format("%Y-%m-%d"))] {
+            pipe.hincr(&key, "input_tokens", r.input_tokens)
+                .hincr(&key, "output_tokens", r.output_tokens)
+                .hincr(&key, "cost_micros", micros);
+        }
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+        Ok(())
+    }
+
+    async fn usage_at(&self, key: &str) -> Result<Usage> {
+        let h: std::collections::HashMap<String, i64> = self.conn.clone().hgetall(key).await?;
+        let get = |f: &str| h.get(f).copied().unwrap_or(0);
+        Ok(Usage { input_tokens: get("input_tokens") as u64, output_tokens: get("output_tokens") as u64, cost_usd: get("cost_micros") as f64 / 1e6 })
+    }
+
+    pub async fn session_usage(&self, id: &str) -> Result<Usage> { self.usage_at(&format!("sessions:{id}:usage")).await }
+
+    /// (YYYY-MM-DD, totals) for the last `days` UTC days including today, oldest first
+    pub async fn daily_usage(&self, days: i64) -> Result<Vec<(String, Usage)>> {
+        let today = chrono::Utc::now().date_naive();
+        let mut out = Vec::new();
+        for back in (0..days.max(1)).rev() {
+            let day = (today - chrono::Duration::days(back)).format("%Y-%m-%d").to_string();
+            let usage = self.usage_at(&format!("usage:{day}")).await?;
+            out.push((day, usage));
+        }
+        Ok(out)
+    }
+
+    /// (chain names touched, artifacts produced) by a session
+    pub async fn session_related(&self, id: &str) -> Result<(Vec<String>, Vec<Artifact>)> {
+        let mut conn = self.conn.clone();
+        let mut chains: Vec<String> = conn.smembers(format!("sessions:{id}:chains")).await?;
+        chains.sort();
+        let artifact_ids: Vec<String> = conn.smembers(format!("sessions:{id}:artifacts")).await?;
+        let mut artifacts = Vec::new();
+        for aid in artifact_ids {
+            if let Some(a) = self.get_artifact(&aid).await? { artifacts.push(a); }
+        }
+        artifacts.sort_by_key(|a| Reverse(a.ts));
+        Ok((chains, artifacts))
+    }
+
+    /// Tools, files and chain links of session `id`, for `GET /sessions/compare`; None if there is no such session
+    pub async fn session_profile(&self, id: &str) -> Result<Option<SessionProfile>> {
+        let Some(session) = self.get_session(id).await? else { return Ok(None) };
+        let hooks = self.get_hooks(id, isize::MAX).await?;
+        let chains: Vec<String> = self.conn.clone().smembers(format!("sessions:{id}:chains")).await?;
+        let mut links = Vec::new();
+        for chain in chains {
+            links.extend(self.get_chain_links(&chain).await?.into_iter().filter(|l| l.session_id == id).map(|l| (l.ts, format!("chain:{}:{}", l.chain_name, l.slug))));
+        }
+        links.sort();
+        Ok(Some(SessionProfile::new(session, &hooks, links.into_iter().map(|(_, l)| l).collect())))
+    }
+
+    /// Chain links (as chain:name:slug ids) that attach or mention the artifact
+    pub async fn artifact_mentions(&self, id: &str) -> Result<Vec<String>> {
+        let mut conn = self.conn.clone();
+        let keys: Vec<String> = conn.smembers(format!("artifacts:{id}:mentions")).await?;
+        let mut mentions = Vec::new();
+        for key in keys {
+            if let Ok(Some(bytes)) = conn.get::<_, Option<Vec<u8>>>(&key).await {
+                if let Some(link) = self.open(bytes).ok().and_then(|j| serde_json::from_str::<ChainLink>(&j).ok()) {
+                    mentions.push(format!("chain:{}:{}", link.chain_name, link.slug));
+                }
+            }
+        }
+        mentions.sort();
+        Ok(mentions)
+    }
+
+    /// Rebuild every cross-link index from the stored chain links and artifacts, and each project's memory set
+    pub async fn rebuild_links(&self) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let mut pipe = redis::pipe();
+        for m in self.list_memories().await?.into_iter().chain(self.list_expired_memories().await?) {
+            if let Some(p) = &m.project { pipe.sadd(format!("projects:{p}:memories"), &m.id); }
+        }
+        let artifacts = self.list_artifacts().await?;
+        for a in &artifacts {
+            pipe.del(format!("artifacts:{}:mentions", a.id)).sadd(format!("sessions:{}:artifacts", a.session_id), &a.id);
+        }
+        let artifact_ids: HashSet<&str> = artifacts.iter().map(|a| a.id.as_str()).collect();
+        for name in self.list_chain_names().await? {
+            // Oldest first, so a slug that several links share points at the newest
+            for link in self.get_chain_links(&name).await?.into_iter().rev() {
+                let key = format!("chains:{}:{}", link.chain_name, link.ts);
+                pipe.sadd(format!("sessions:{}:chains", link.session_id), &link.chain_name)
+                    .set(format!("chain:{}:slug:{}", link.chain_name, link.slug), &key);
+                for id in artifact_ref_candidates(&link).into_iter().filter(|id| artifact_ids.contains(id.as_str())) {
+                    pipe.sadd(format!("artifacts:{id}:mentions"), &key);
+                }
+            }
+        }
+        pipe.query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    /// Finds a link by slug, or else by timestamp, through the chain:{name}:slug:{slug} index. Links
+    /// saved before the index existed are found by scanning the chain once and indexed on the way.
     pub async fn get_chain_link(&self, chain_name: &str, identifier: &str) -> Result<Option<ChainLink>> {
-        let links = self.get_chain_links(chain_name).await?;
-        // Try matching by slug first, then by timestamp
-        Ok(links.into_iter().find(|l| l.slug == identifier || l.ts.to_string() == identifier))
+        let slug_key = format!("chain:{chain_name}:slug:{identifier}");
+        let indexed: Option<String> = self.conn.clone().get(&slug_key).await?;
+        if let Some(key) = indexed {
+            if let Some(link) = self.link_at(&key).await?.filter(|l| l.slug == identifier) { return Ok(Some(link)); }
+        }
+        if let Ok(ts) = identifier.parse::<i64>() {
+            if let Some(link) = self.link_at(&format!("chains:{chain_name}:{ts}")).await? { return Ok(Some(link)); }
+        }
+        let Some(link) = self.get_chain_links(chain_name).await?.into_iter().find(|l| l.slug == identifier) else { return Ok(None) };
+        self.conn.clone().set::<_, _, ()>(&slug_key, format!("chains:{chain_name}:{}", link.ts)).await?;
+        Ok(Some(link))
+    }
+
+    // One link by its chains:{name}:{ts} key
+    async fn link_at(&self, key: &str) -> Result<Option<ChainLink>> {
+        let Some(bytes) = self.conn.clone().get::<_, Option<Vec<u8>>>(key).await? else { return Ok(None) };
+        Ok(self.open(bytes).ok().and_then(|j| serde_json::from_str(&j).ok()))
     }
 
     // Artifact operations
@@ -194,13 +1096,18 @@ impl Store {
         redis::pipe()
             .set(format!("artifacts:{}", artifact.id), serde_json::to_string(artifact)?)
             .sadd("artifact_ids", &artifact.id)
+            .sadd(format!("sessions:{}:artifacts", artifact.session_id), &artifact.id)
             .query_async::<()>(&mut conn).await?;
-        Ok(())
+        if let Some(c) = &self.cache { c.forget_artifact(&artifact.id); }
+        self.index_artifact(artifact).await
     }
 
     pub async fn get_artifact(&self, id: &str) -> Result<Option<Artifact>> {
+        if let Some(a) = self.cache.as_ref().and_then(|c| c.artifact(id)) { return Ok(a); }
         let json: Option<String> = self.conn.clone().get(format!("artifacts:{id}")).await?;
-        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+        let artifact: Option<Artifact> = json.map(|j| serde_json::from_str(&j)).transpose()?;
+        if let Some(c) = &self.cache { c.put_artifact(id, artifact.clone()); }
+        Ok(artifact)
     }
 
     pub async fn list_artifacts(&self) -> Result<Vec<Artifact>> {
@@ -219,90 +1126,698 @@ impl Store {
     }
 
     pub async fn delete_artifact(&self, id: &str) -> Result<()> {
+        let session_id = self.get_artifact(id).await?.map(|a| a.session_id).unwrap_or_default();
         let mut conn = self.conn.clone();
         redis::pipe()
+            .srem(format!("sessions:{session_id}:artifacts"), id)
+            .del(format!("artifacts:{id}:mentions"))
             .del(format!("artifacts:{id}"))
             .srem("artifact_ids", id)
             // Also delete cached text extraction if exists
             .del(format!("artifacts:{id}:text"))
+            .del(format!("artifacts:{id}:status"))
+            .del(format!("artifacts:{id}:meta"))
             .query_async::<()>(&mut conn).await?;
-        Ok(())
+        if let Some(c) = &self.cache { c.forget_artifact(id); }
+        self.release_quota(id).await?;
+        self.unindex_doc(&format!("artifact:{id}")).await
     }
 
     // Cache extracted text for artifact (for search)
     pub async fn set_artifact_text(&self, id: &str, text: &str) -> Result<()> {
-        self.conn.clone().set::<_, _, ()>(format!("artifacts:{id}:text"), text).await?;
+        let redacted = self.redactor.as_ref().and_then(|r| r.redact(text));
+        self.conn.clone().set::<_, _, ()>(format!("artifacts:{id}:text"), self.seal(redacted.as_deref().unwrap_or(text))?).await?;
+        if let Some(mut artifact) = self.get_artifact(id).await? {
+            if redacted.is_some() {
+                artifact.redacted = true;
+                self.save_artifact(&artifact).await?; // reindexes
+            } else {
+                self.index_artifact(&artifact).await?;
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn set_artifact_status(&self, id: &str, status: &IndexStatus) -> Result<()> {
+        self.conn.clone().set::<_, _, ()>(format!("artifacts:{id}:status"), serde_json::to_string(status)?).await?;
         Ok(())
     }
 
+    /// None for artifacts saved before extraction moved to the background
+    pub async fn get_artifact_status(&self, id: &str) -> Result<Option<IndexStatus>> {
+        let json: Option<String> = self.conn.clone().get(format!("artifacts:{id}:status")).await?;
+        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+    }
+
     pub async fn get_artifact_text(&self, id: &str) -> Result<Option<String>> {
-        Ok(self.conn.clone().get(format!("artifacts:{id}:text")).await?)
+        let bytes: Option<Vec<u8>> = self.conn.clone().get(format!("artifacts:{id}:text")).await?;
+        bytes.map(|b| self.open(b)).transpose()
     }
 
-    // Global search across chains and artifacts
-    pub async fn global_search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
-        let query_lower = query.to_lowercase();
-        let mut results = Vec::new();
+    // Sealed like the text, since titles and authors can be as sensitive
+    pub async fn set_artifact_meta(&self, id: &str, meta: &DocMeta) -> Result<()> {
+        self.conn.clone().set::<_, _, ()>(format!("artifacts:{id}:meta"), self.seal(&serde_json::to_string(meta)?)?).await?;
+        Ok(())
+    }
 
-        // Search chain links
-        for chain_name in self.list_chain_names().await? {
-            for link in self.get_chain_links(&chain_name).await? {
-                let searchable = format!("{} {} {}", chain_name, link.slug, link.content).to_lowercase();
-                let score = self.compute_search_score(&searchable, &query_lower);
-                if score > 0.3 {
-                    let preview = link.content.chars().take(200).collect::<String>();
-                    results.push(SearchResult {
-                        result_type: "chain_link".to_string(),
-                        id: format!("chain:{}:{}", chain_name, link.slug),
-                        title: format!("{}/{}", chain_name, link.slug),
-                        score,
-                        preview,
-                    });
+    /// None for non-PDF artifacts and PDFs indexed before metadata was captured
+    pub async fn get_artifact_meta(&self, id: &str) -> Result<Option<DocMeta>> {
+        let bytes: Option<Vec<u8>> = self.conn.clone().get(format!("artifacts:{id}:meta")).await?;
+        Ok(bytes.map(|b| self.open(b)).transpose()?.map(|j| serde_json::from_str(&j)).transpose()?)
+    }
+
+    // Audit log - append-only stream, trimmed to roughly the newest 100k entries
+    pub async fn append_audit(&self, e: &AuditEntry) -> Result<()> {
+        let fields = [
+            ("ts", e.ts.to_string()), ("actor", e.actor.clone()), ("agent", e.agent.clone()), ("method", e.method.clone()),
+            ("endpoint", e.endpoint.clone()), ("path", e.path.clone()), ("status", e.status.to_string()),
+            ("request_id", e.request_id.clone()),
+        ];
+        self.conn.clone().xadd_maxlen::<_, _, _, _, ()>("audit", StreamMaxlen::Approx(100_000), "*", &fields).await?;
+        Ok(())
+    }
+
+    pub async fn query_audit(&self, q: &AuditQuery) -> Result<Vec<AuditEntry>> {
+        let mut conn = self.conn.clone();
+        let mut entries = Vec::new();
+        let mut end = "+".to_string();
+        // Page backwards through the stream until enough entries match or we pass `since`
+        loop {
+            let reply: StreamRangeReply = conn.xrevrange_count("audit", &end, "-", 500).await?;
+            let page = reply.ids.len();
+            let prev_end = end.clone(); // XREVRANGE is inclusive, skip the last entry of the previous page
+            for sid in reply.ids.into_iter().filter(|sid| sid.id != prev_end) {
+                let field = |k: &str| sid.get::<String>(k).unwrap_or_default();
+                let e = AuditEntry {
+                    ts: sid.get("ts").unwrap_or(0), actor: field("actor"), agent: field("agent"), method: field("method"),
+                    endpoint: field("endpoint"), path: field("path"), status: sid.get("status").unwrap_or(0), id: sid.id.clone(),
+                    request_id: field("request_id"),
+                };
+                if q.since.is_some_and(|since| e.ts < since) { return Ok(entries); }
+                end = sid.id;
+                if q.actor.as_ref().is_some_and(|a| &e.actor != a) { continue; }
+                if q.endpoint.as_ref().is_some_and(|ep| &e.endpoint != ep) { continue; }
+                if q.path.as_ref().is_some_and(|p| !e.path.contains(p.as_str())) { continue; }
+                entries.push(e);
+                if entries.len() >= q.limit { return Ok(entries); }
+            }
+            if page < 500 { return Ok(entries); }
+        }
+    }
+
+    // Memories - live ids in `memory_ids`, archived ids in the `memories_expired` zset scored by archive time,
+    // and every id of a project's memories in `projects:{project}:memories`
+    pub async fn save_memory(&self, memory: &Memory) -> Result<()> {
+        let mut memory = memory.clone();
+        if let Some(r) = &self.redactor {
+            // Untitled memories are titled with the start of their content, secrets included
+            for field in [&mut memory.title, &mut memory.content] {
+                if let Some(redacted) = r.redact(field) {
+                    *field = redacted;
+                    memory.redacted = true;
                 }
             }
         }
+        let mut conn = self.conn.clone();
+        let mut pipe = redis::pipe();
+        pipe.set(format!("memories:{}", memory.id), self.seal(&serde_json::to_string(&memory)?)?);
+        match memory.archived_at {
+            Some(at) => pipe.srem("memory_ids", &memory.id).zadd("memories_expired", &memory.id, at),
+            None => pipe.sadd("memory_ids", &memory.id).zrem("memories_expired", &memory.id),
+        };
+        if let Some(p) = &memory.project { pipe.sadd(format!("projects:{p}:memories"), &memory.id); }
+        pipe.query_async::<()>(&mut conn).await?;
+        // Archived memories drop out of search until restored
+        let archived = memory.archived_at.is_some();
+        let doc = memory_doc(memory);
+        if archived { self.unindex_doc(&doc.key).await } else { self.index_doc(&doc.key, &doc.indexed).await }
+    }
 
-        // Search artifacts
-        for artifact in self.list_artifacts().await? {
-            let cached_text = self.get_artifact_text(&artifact.id).await?.unwrap_or_default();
-            let searchable = format!("{} {} {}", artifact.title, artifact.description, cached_text).to_lowercase();
-            let score = self.compute_search_score(&searchable, &query_lower);
-            if score > 0.3 {
-                let preview = if !cached_text.is_empty() {
-                    cached_text.chars().take(200).collect()
-                } else {
-                    artifact.description.chars().take(200).collect()
-                };
-                results.push(SearchResult {
-                    result_type: "artifact".to_string(),
-                    id: format!("artifact:{}", artifact.id),
-                    title: artifact.title.clone(),
-                    score,
-                    preview,
-                });
+    pub async fn get_memory(&self, id: &str) -> Result<Option<Memory>> {
+        let bytes: Option<Vec<u8>> = self.conn.clone().get(format!("memories:{id}")).await?;
+        bytes.map(|b| Ok(serde_json::from_str(&self.open(b)?)?)).transpose()
+    }
+
+    async fn load_memories(&self, ids: Vec<String>) -> Result<Vec<Memory>> {
+        let mut memories = Vec::new();
+        for id in ids {
+            if let Ok(Some(m)) = self.get_memory(&id).await { memories.push(m); }
+        }
+        Ok(memories)
+    }
+
+    /// Live memories, newest first
+    pub async fn list_memories(&self) -> Result<Vec<Memory>> {
+        let ids: Vec<String> = self.conn.clone().smembers("memory_ids").await?;
+        let mut memories = self.load_memories(ids).await?;
+        memories.sort_by_key(|m| Reverse(m.ts));
+        Ok(memories)
+    }
+
+    /// A project's live memories, newest first
+    pub async fn project_memories(&self, project: &str) -> Result<Vec<Memory>> {
+        let ids: Vec<String> = self.conn.clone().smembers(format!("projects:{project}:memories")).await?;
+        let mut memories: Vec<Memory> = self.load_memories(ids).await?.into_iter().filter(|m| m.archived_at.is_none()).collect();
+        memories.sort_by_key(|m| Reverse(m.ts));
+        Ok(memories)
+    }
+
+    /// Archived memories awaiting purge, most recently archived first
+    pub async fn list_expired_memories(&self) -> Result<Vec<Memory>> {
+        let ids: Vec<String> = self.conn.clone().zrevrange("memories_expired", 0, -1).await?;
+        self.load_memories(ids).await
+    }
+
+    pub async fn delete_memory(&self, id: &str) -> Result<()> {
+        let project = self.get_memory(id).await.ok().flatten().and_then(|m| m.project);
+        let mut conn = self.conn.clone();
+        let mut pipe = redis::pipe();
+        if let Some(p) = &project { pipe.srem(format!("projects:{p}:memories"), id); }
+        pipe
+            .del(format!("memories:{id}"))
+            .srem("memory_ids", id)
+            .zrem("memories_expired", id)
+            .hdel("access:count", format!("memory:{id}"))
+            .hdel("access:last", format!("memory:{id}"))
+            .query_async::<()>(&mut conn).await?;
+        self.release_quota(id).await?;
+        self.unindex_doc(&format!("memory:{id}")).await
+    }
+
+    // Quotas - `quota:{subject}` hashes (`session:{id}` or `token:{hash}`) hold artifact bytes and memories,
+    // `quota:{subject}:links:{day}` counts links and expires; `quota:owners` maps each charged artifact or
+    // memory to what it was charged against so deleting it gives the quota back
+    pub async fn quota_usage(&self, subject: &str) -> Result<QuotaUsage> {
+        let mut conn = self.conn.clone();
+        let ((bytes, memories), links): ((Option<u64>, Option<u64>), Option<u64>) = redis::pipe()
+            .hget(format!("quota:{subject}"), &[QuotaKind::ArtifactBytes.as_str(), QuotaKind::Memories.as_str()])
+            .get(format!("quota:{subject}:links:{}", crate::models::now() / 86400))
+            .query_async(&mut conn).await?;
+        Ok(QuotaUsage { links_today: links.unwrap_or(0), artifact_bytes: bytes.unwrap_or(0), memories: memories.unwrap_or(0) })
+    }
+
+    // The counter `kind` is kept in for `subject`, and its hash field ("" for the daily link count)
+    fn quota_counter(subject: &str, kind: QuotaKind) -> (String, &'static str) {
+        match kind {
+            QuotaKind::Links => (format!("quota:{subject}:links:{}", crate::models::now() / 86400), ""),
+            _ => (format!("quota:{subject}"), kind.as_str()),
+        }
+    }
+
+    /// Counts `amount` of `kind` against every subject before the write it is for, unless that takes one
+    /// of them past `limit` (0 for none); each subject is checked and counted in one step. Returns that
+    /// subject and its usage when there is no room, having given back what the others were counted.
+    /// `id` is the artifact or memory, unused for links; give the reservation back with
+    /// [`Store::release_reservation`] if the write fails.
+    pub async fn reserve_quota(&self, subjects: &[String], kind: QuotaKind, id: &str, amount: u64, limit: u64) -> Result<Option<(String, u64)>> {
+        let mut conn = self.conn.clone();
+        for (i, subject) in subjects.iter().enumerate() {
+            let (key, field) = Self::quota_counter(subject, kind);
+            let (counted, used): (bool, i64) = RESERVE_QUOTA.key(&key).arg(amount).arg(limit).arg(field).arg(2 * 86400)
+                .invoke_async(&mut conn).await?;
+            if !counted {
+                self.refund_quota(&subjects[..i], kind, amount).await?;
+                return Ok(Some((subject.clone(), used.max(0) as u64)));
             }
         }
+        if kind != QuotaKind::Links {
+            conn.hset::<_, _, _, ()>("quota:owners", id, serde_json::to_string(&(subjects, kind.as_str(), amount))?).await?;
+        }
+        Ok(None)
+    }
 
-        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
-        results.truncate(limit);
-        Ok(results)
+    /// Gives back a [`Store::reserve_quota`] reservation whose write failed
+    pub async fn release_reservation(&self, subjects: &[String], kind: QuotaKind, id: &str, amount: u64) -> Result<()> {
+        if kind == QuotaKind::Links { self.refund_quota(subjects, kind, amount).await } else { self.release_quota(id).await }
     }
 
-    fn compute_search_score(&self, text: &str, query: &str) -> f64 {
-        // Simple scoring: substring match gets high score, jaro-winkler for fuzzy
-        if text.contains(query) {
-            0.9 + (query.len() as f64 / text.len() as f64).min(0.1)
-        } else {
-            // Check individual words
-            let words: Vec<&str> = query.split_whitespace().collect();
-            let matches = words.iter().filter(|w| text.contains(*w)).count();
-            if matches > 0 {
-                0.5 + (matches as f64 / words.len() as f64) * 0.4
+    async fn refund_quota(&self, subjects: &[String], kind: QuotaKind, amount: u64) -> Result<()> {
+        if subjects.is_empty() { return Ok(()); }
+        let mut pipe = redis::pipe();
+        for subject in subjects {
+            let (key, field) = Self::quota_counter(subject, kind);
+            if field.is_empty() { pipe.decr(&key, amount).expire(&key, 2 * 86400); } else { pipe.hincr(&key, field, -(amount as i64)); }
+        }
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+        Ok(())
+    }
+
+    // Gives back what an artifact or memory was charged, if it was
+    async fn release_quota(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let Some(owner) = conn.hget::<_, _, Option<String>>("quota:owners", id).await? else { return Ok(()) };
+        let (subjects, field, amount): (Vec<String>, String, i64) = serde_json::from_str(&owner)?;
+        let mut pipe = redis::pipe();
+        for subject in subjects { pipe.hincr(format!("quota:{subject}"), &field, -amount); }
+        pipe.hdel("quota:owners", id).query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    // Todos - JSON per todo plus a zset of ids by creation time
+    pub async fn save_todo(&self, todo: &Todo) -> Result<()> {
+        let mut todo = todo.clone();
+        if let Some(text) = self.redactor.as_ref().and_then(|r| r.redact(&todo.text)) {
+            todo.text = text;
+            todo.redacted = true;
+        }
+        let mut conn = self.conn.clone();
+        redis::pipe()
+            .set(format!("todos:{}", todo.id), self.seal(&serde_json::to_string(&todo)?)?)
+            .zadd("todo_ids", &todo.id, todo.created)
+            .query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    pub async fn get_todo(&self, id: &str) -> Result<Option<Todo>> {
+        let bytes: Option<Vec<u8>> = self.conn.clone().get(format!("todos:{id}")).await?;
+        bytes.map(|b| Ok(serde_json::from_str(&self.open(b)?)?)).transpose()
+    }
+
+    /// Matching todos, open ones first, each group oldest first
+    pub async fn list_todos(&self, q: &TodoQuery) -> Result<Vec<Todo>> {
+        let ids: Vec<String> = self.conn.clone().zrange("todo_ids", 0, -1).await?;
+        let mut todos = Vec::new();
+        for id in ids {
+            if let Ok(Some(t)) = self.get_todo(&id).await {
+                if q.matches(&t) { todos.push(t); }
+            }
+        }
+        todos.sort_by_key(|t| t.done());
+        Ok(todos)
+    }
+
+    pub async fn delete_todo(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        redis::pipe().del(format!("todos:{id}")).zrem("todo_ids", id).query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    // Handoffs - JSON per handoff, all ids by creation time plus a zset of those not yet accepted
+    pub async fn save_handoff(&self, handoff: &Handoff) -> Result<()> {
+        let mut conn = self.conn.clone();
+        let mut pipe = redis::pipe();
+        pipe.set(format!("handoffs:{}", handoff.id), self.seal(&serde_json::to_string(handoff)?)?)
+            .zadd("handoff_ids", &handoff.id, handoff.created);
+        if handoff.accepted_at.is_none() { pipe.zadd("handoffs_pending", &handoff.id, handoff.created); }
+        pipe.query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    pub async fn get_handoff(&self, id: &str) -> Result<Option<Handoff>> {
+        let bytes: Option<Vec<u8>> = self.conn.clone().get(format!("handoffs:{id}")).await?;
+        bytes.map(|b| Ok(serde_json::from_str(&self.open(b)?)?)).transpose()
+    }
+
+    /// Pending handoffs (or all with `all`), oldest first
+    pub async fn list_handoffs(&self, all: bool) -> Result<Vec<Handoff>> {
+        let ids: Vec<String> = self.conn.clone().zrange(if all { "handoff_ids" } else { "handoffs_pending" }, 0, -1).await?;
+        let mut handoffs = Vec::new();
+        for id in ids {
+            if let Ok(Some(h)) = self.get_handoff(&id).await { handoffs.push(h); }
+        }
+        Ok(handoffs)
+    }
+
+    /// Claims a pending handoff for `session_id`; None when it doesn't exist or someone else got it first
+    pub async fn accept_handoff(&self, id: &str, session_id: &str) -> Result<Option<Handoff>> {
+        let claimed: i64 = self.conn.clone().zrem("handoffs_pending", id).await?;
+        if claimed == 0 { return Ok(None); }
+        let Some(mut handoff) = self.get_handoff(id).await? else { return Ok(None) };
+        handoff.accepted_by = Some(session_id.to_string());
+        handoff.accepted_at = Some(crate::models::now());
+        self.save_handoff(&handoff).await?;
+        Ok(Some(handoff))
+    }
+
+    /// Archived memories archived before `ts`
+    pub async fn archived_before(&self, ts: i64) -> Result<Vec<String>> {
+        Ok(self.conn.clone().zrangebyscore("memories_expired", "-inf", format!("({ts}")).await?)
+    }
+
+    /// Update pin/importance on a chain link (chain:name:slug) or memory (memory:id); false if not found
+    pub async fn set_priority(&self, id: &str, pinned: Option<bool>, importance: Option<u8>) -> Result<bool> {
+        let importance = importance.map(|i| i.min(10));
+        if let Some(memory_id) = id.strip_prefix("memory:") {
+            let Some(mut m) = self.get_memory(memory_id).await? else { return Ok(false) };
+            m.pinned = pinned.unwrap_or(m.pinned);
+            m.importance = importance.unwrap_or(m.importance);
+            self.save_memory(&m).await?;
+            return Ok(true);
+        }
+        if let Some((chain_name, slug)) = id.strip_prefix("chain:").and_then(|rest| rest.split_once(':')) {
+            let Some(mut link) = self.get_chain_link(chain_name, slug).await? else { return Ok(false) };
+            link.pinned = pinned.unwrap_or(link.pinned);
+            link.importance = importance.unwrap_or(link.importance);
+            self.save_chain_link(&link).await?;
+            return Ok(true);
+        }
+        bail!("invalid id format, expected chain:name:slug or memory:id")
+    }
+
+    // Saved searches - hash of name -> JSON
+    pub async fn save_search(&self, search: &SavedSearch) -> Result<()> {
+        self.conn.clone().hset::<_, _, _, ()>("saved_searches", &search.name, serde_json::to_string(search)?).await?;
+        Ok(())
+    }
+
+    pub async fn get_saved_search(&self, name: &str) -> Result<Option<SavedSearch>> {
+        let json: Option<String> = self.conn.clone().hget("saved_searches", name).await?;
+        Ok(json.map(|j| serde_json::from_str(&j)).transpose()?)
+    }
+
+    pub async fn list_saved_searches(&self) -> Result<Vec<SavedSearch>> {
+        let all: Vec<String> = self.conn.clone().hvals("saved_searches").await?;
+        let mut searches: Vec<SavedSearch> = all.iter().filter_map(|j| serde_json::from_str(j).ok()).collect();
+        searches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(searches)
+    }
+
+    pub async fn delete_saved_search(&self, name: &str) -> Result<bool> {
+        Ok(self.conn.clone().hdel::<_, _, i64>("saved_searches", name).await? > 0)
+    }
+
+    // Corpus export - streamed a unit at a time: a chain (all its links), an artifact or a live memory
+    pub async fn corpus_units(&self, kind: CorpusType) -> Result<Vec<String>> {
+        match kind {
+            CorpusType::Chains => self.list_chain_names().await,
+            CorpusType::Artifacts => Ok(self.conn.clone().smembers("artifact_ids").await?),
+            CorpusType::Memories => Ok(self.conn.clone().smembers("memory_ids").await?),
+        }
+    }
+
+    pub async fn corpus_docs(&self, kind: CorpusType, unit: &str) -> Result<Vec<CorpusDoc>> {
+        Ok(match kind {
+            CorpusType::Chains => self.get_chain_links(unit).await?.into_iter().map(|l| {
+                let (ts, session_id, project) = (l.ts, l.session_id.clone(), l.project.clone());
+                corpus_doc(link_doc(l), ts, session_id, project)
+            }).collect(),
+            CorpusType::Artifacts => {
+                let Some(a) = self.get_artifact(unit).await? else { return Ok(vec![]) };
+                let text = self.get_artifact_text(unit).await?.unwrap_or_default();
+                let (ts, session_id, project) = (a.ts, a.session_id.clone(), a.project.clone());
+                vec![corpus_doc(artifact_doc(a, text, None), ts, session_id, project)]
+            }
+            CorpusType::Memories => {
+                let Some(m) = self.get_memory(unit).await?.filter(|m| m.archived_at.is_none()) else { return Ok(vec![]) };
+                let (ts, session_id, project) = (m.ts, m.session_id.clone(), m.project.clone());
+                vec![corpus_doc(memory_doc(m), ts, session_id, project)]
+            }
+        })
+    }
+
+    // Access tracking - read count and last read time per content id (chain:name:slug, artifact:id)
+    pub async fn record_access(&self, id: &str) -> Result<()> {
+        let mut conn = self.conn.clone();
+        redis::pipe().hincr("access:count", id, 1).hset("access:last", id, crate::models::now())
+            .query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    /// (reads, last read) for each id, in order
+    pub async fn access_stats(&self, ids: &[String]) -> Result<Vec<(i64, Option<i64>)>> {
+        if ids.is_empty() { return Ok(Vec::new()); }
+        let mut conn = self.conn.clone();
+        let (counts, lasts): (Vec<Option<i64>>, Vec<Option<i64>>) = redis::pipe()
+            .cmd("HMGET").arg("access:count").arg(ids)
+            .cmd("HMGET").arg("access:last").arg(ids)
+            .query_async(&mut conn).await?;
+        Ok(counts.into_iter().zip(lasts).map(|(c, l)| (c.unwrap_or(0), l)).collect())
+    }
+
+    // Search index - idx:post:{term} hashes doc -> term count, idx:terms:{doc} the doc's own counts (for
+    // removal), idx:len doc -> length and idx:total the summed length. Docs are chain link keys
+    // (chains:{name}:{ts}), artifact:{id} and memory:{id}; idx:ready marks a completed build.
+    // Not kept with encryption at rest, where plaintext terms would leak the sealed content.
+    fn indexed(&self) -> bool { self.cipher.is_none() }
+
+    async fn index_doc(&self, doc: &str, text: &str) -> Result<()> {
+        if !self.indexed() { return Ok(()); }
+        self.unindex_doc(doc).await?;
+        let (tf, len) = term_frequencies(text);
+        let mut pipe = redis::pipe();
+        for (t, n) in &tf {
+            pipe.hset(format!("idx:post:{t}"), doc, n).hset(format!("idx:terms:{doc}"), t, n);
+        }
+        pipe.hset("idx:len", doc, len).incr("idx:total", len);
+        pipe.query_async::<()>(&mut self.conn.clone()).await?;
+        Ok(())
+    }
+
+    async fn unindex_doc(&self, doc: &str) -> Result<()> {
+        if !self.indexed() { return Ok(()); }
+        let mut conn = self.conn.clone();
+        let (terms, len): (Vec<String>, Option<i64>) = redis::pipe()
+            .hkeys(format!("idx:terms:{doc}")).hget("idx:len", doc).query_async(&mut conn).await?;
+        let Some(len) = len else { return Ok(()) };
+        let mut pipe = redis::pipe();
+        for t in terms { pipe.hdel(format!("idx:post:{t}"), doc); }
+        pipe.del(format!("idx:terms:{doc}")).hdel("idx:len", doc).decr("idx:total", len);
+        pipe.query_async::<()>(&mut conn).await?;
+        Ok(())
+    }
+
+    async fn index_artifact(&self, artifact: &Artifact) -> Result<()> {
+        if !self.indexed() { return Ok(()); }
+        let text = self.get_artifact_text(&artifact.id).await?.unwrap_or_default();
+        let doc = artifact_doc(artifact.clone(), text, None);
+        self.index_doc(&doc.key, &doc.indexed).await
+    }
+
+    /// Re-tokenizes every chain link, artifact and live memory into the search index; returns the
+    /// number of documents indexed (0 with encryption at rest, where search scans instead)
+    pub async fn rebuild_search_index(&self) -> Result<usize> {
+        if !self.indexed() { return Ok(0); }
+        let mut conn = self.conn.clone();
+        // Search scans instead of reading a half-built index
+        conn.del::<_, ()>("idx:ready").await?;
+        let old: Vec<String> = conn.hkeys("idx:len").await?;
+        for doc in &old { self.unindex_doc(doc).await?; }
+        conn.del::<_, ()>("idx:total").await?;
+        let docs = self.scan_docs(&SearchFilters::default()).await?;
+        for doc in &docs { self.index_doc(&doc.key, &doc.indexed).await?; }
+        conn.set::<_, _, ()>("idx:ready", crate::models::now()).await?;
+        Ok(docs.len())
+    }
+
+    /// Builds the search index unless a build has already completed (e.g. on first start after an
+    /// upgrade or a flush); returns the number of documents indexed, 0 when there was nothing to do
+    pub async fn ensure_search_index(&self) -> Result<usize> {
+        if !self.indexed() || self.conn.clone().exists::<_, bool>("idx:ready").await? { return Ok(0); }
+        self.rebuild_search_index().await
+    }
+
+    // BM25 over the stored postings; only documents sharing a term with the query are touched
+    async fn index_lookup(&self, query: &str) -> Result<Vec<(String, f64)>> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        if terms.is_empty() { return Ok(Vec::new()); }
+        let mut conn = self.conn.clone();
+        let mut pipe = redis::pipe();
+        for t in &terms { pipe.hgetall(format!("idx:post:{t}")); }
+        let postings: Vec<HashMap<String, u32>> = pipe.query_async(&mut conn).await?;
+        let mut docs: Vec<&String> = postings.iter().flat_map(|p| p.keys()).collect();
+        docs.sort();
+        docs.dedup();
+        if docs.is_empty() { return Ok(Vec::new()); }
+        let (n, total, lens): (usize, Option<i64>, Vec<Option<usize>>) = redis::pipe()
+            .hlen("idx:len").get("idx:total").cmd("HMGET").arg("idx:len").arg(&docs)
+            .query_async(&mut conn).await?;
+        let avg_len = total.unwrap_or(0) as f64 / (n as f64).max(1.0);
+        Ok(docs.into_iter().zip(lens).map(|(doc, len)| {
+            let score = postings.iter()
+                .filter_map(|p| Some(bm25(*p.get(doc)? as f64, p.len() as f64, n as f64, len.unwrap_or(0) as f64, avg_len)))
+                .sum();
+            (doc.clone(), score)
+        }).collect())
+    }
+
+    // Indexed documents, each if it still exists and passes the filters, read in one round trip
+    async fn load_docs(&self, docs: &[&str], filters: &SearchFilters) -> Result<Vec<Option<SearchDoc>>> {
+        let (chains, artifacts, memories) = (filters.allows_type("chain_link"), filters.allows_type("artifact"), filters.allows_type("memory"));
+        let mut pipe = redis::pipe();
+        let mut queued = 0;
+        for doc in docs {
+            if doc.starts_with("chains:") && chains {
+                pipe.get(*doc);
+                queued += 1;
+            } else if let Some(id) = doc.strip_prefix("artifact:").filter(|_| artifacts) {
+                pipe.get(format!("artifacts:{id}")).get(format!("artifacts:{id}:text")).get(format!("artifacts:{id}:meta"));
+                queued += 3;
+            } else if let Some(id) = doc.strip_prefix("memory:").filter(|_| memories) {
+                pipe.get(format!("memories:{id}"));
+                queued += 1;
+            }
+        }
+        let values: Vec<Option<Vec<u8>>> = if queued == 0 { Vec::new() } else { pipe.query_async(&mut self.conn.clone()).await? };
+        let mut values = values.into_iter();
+        let mut loaded = Vec::with_capacity(docs.len());
+        for doc in docs {
+            loaded.push(if doc.starts_with("chains:") && chains {
+                let link = values.next().flatten().and_then(|b| serde_json::from_str::<ChainLink>(&self.open(b).ok()?).ok());
+                link.filter(|l| filters.chain_name.as_ref().is_none_or(|n| *n == l.chain_name) && filters.allows(&l.session_id, l.project.as_deref(), l.ts))
+                    .map(link_doc)
+            } else if doc.starts_with("artifact:") && artifacts {
+                let (json, text, meta) = (values.next().flatten(), values.next().flatten(), values.next().flatten());
+                let artifact = json.and_then(|j| serde_json::from_slice::<Artifact>(&j).ok()).filter(|a| filters.allows_artifact(a));
+                match artifact {
+                    Some(a) => {
+                        let text = text.map(|b| self.open(b)).transpose()?.unwrap_or_default();
+                        let meta = meta.map(|b| self.open(b)).transpose()?.map(|j| serde_json::from_str(&j)).transpose()?;
+                        Some(artifact_doc(a, text, meta))
+                    }
+                    None => None,
+                }
+            } else if doc.starts_with("memory:") && memories {
+                let memory = values.next().flatten().map(|b| Ok::<Memory, anyhow::Error>(serde_json::from_str(&self.open(b)?)?)).transpose()?;
+                memory.filter(|m| m.archived_at.is_none() && filters.allows(&m.session_id, m.project.as_deref(), m.ts)).map(memory_doc)
             } else {
-                // Fall back to jaro-winkler on title-like portion
-                let first_100: String = text.chars().take(100).collect();
-                jaro_winkler(&first_100, query) * 0.5
+                None
+            });
+        }
+        Ok(loaded)
+    }
+
+    // Every searchable document passing the filters, loaded from the primary keys
+    async fn scan_docs(&self, filters: &SearchFilters) -> Result<Vec<SearchDoc>> {
+        let mut docs = Vec::new();
+        let chain_names = if !filters.allows_type("chain_link") { Vec::new() }
+            else if let Some(name) = &filters.chain_name { vec![name.clone()] }
+            else { self.list_chain_names().await? };
+        for chain_name in chain_names {
+            for link in self.get_chain_links(&chain_name).await? {
+                if filters.allows(&link.session_id, link.project.as_deref(), link.ts) { docs.push(link_doc(link)); }
+            }
+        }
+        let artifacts = if filters.allows_type("artifact") { self.list_artifacts().await? } else { Vec::new() };
+        for artifact in artifacts.into_iter().filter(|a| filters.allows_artifact(a)) {
+            let text = self.get_artifact_text(&artifact.id).await?.unwrap_or_default();
+            let meta = self.get_artifact_meta(&artifact.id).await?;
+            docs.push(artifact_doc(artifact, text, meta));
+        }
+        let memories = if !filters.allows_type("memory") { Vec::new() }
+            else if let Some(p) = &filters.project { self.project_memories(p).await? }
+            else { self.list_memories().await? };
+        docs.extend(memories.into_iter().filter(|m| filters.allows(&m.session_id, m.project.as_deref(), m.ts)).map(memory_doc));
+        Ok(docs)
+    }
+
+    // Global search across chains, artifacts and memories
+    pub async fn global_search(&self, query: &str, limit: usize, filters: &SearchFilters) -> Result<Vec<SearchResult>> {
+        // Until a build of the index completes (see `ensure_search_index`), search scans like it does with encryption
+        let (mut docs, mut scored) = if self.indexed() && self.conn.clone().exists::<_, bool>("idx:ready").await? {
+            let mut hits = self.index_lookup(query).await?;
+            hits.sort_by(|a, b| b.1.total_cmp(&a.1));
+            // Only the best hits are loaded, a batch at a time until enough of them pass the filters
+            let want = limit.saturating_mul(SEARCH_SLACK).max(1);
+            let (mut docs, mut scored) = (Vec::new(), Vec::new());
+            for batch in hits.chunks(want) {
+                let keys: Vec<&str> = batch.iter().map(|(doc, _)| doc.as_str()).collect();
+                for ((_, score), doc) in batch.iter().zip(self.load_docs(&keys, filters).await?) {
+                    let Some(d) = doc else { continue };
+                    scored.push((docs.len(), *score));
+                    docs.push(d);
+                }
+                if docs.len() >= want { break; }
             }
+            (docs, scored)
+        } else {
+            let docs = self.scan_docs(filters).await?;
+            let mut index = Index::default();
+            for d in &docs { index.add(&d.indexed); }
+            let scored = index.search(query);
+            (docs, scored)
+        };
+
+        let ids: Vec<String> = scored.iter().map(|(i, _)| docs[*i].result.id.clone()).collect();
+        let ts = crate::models::now();
+        for ((i, score), (reads, last)) in scored.iter_mut().zip(self.access_stats(&ids).await?) {
+            let c = &docs[*i].result;
+            *score *= usage_boost(reads, last, ts) * priority_boost(c.pinned, c.importance);
         }
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        Ok(scored.into_iter().map(|(i, score)| {
+            let (preview, highlights, start) = snippet(&docs[i].preview, query, 200);
+            let page = docs[i].pages.as_ref().and_then(|m| m.page_at(start));
+            SearchResult { score, preview, highlights, page, ..std::mem::take(&mut docs[i].result) }
+        }).collect())
+    }
+}
+
+// The artifact ids a link may refer to: its attached ones, plus each token of its content shaped like an
+// artifact id (`{ts}_{title}`), so only these need checking against artifact_ids
+fn artifact_ref_candidates(link: &ChainLink) -> Vec<String> {
+    let mut ids = link.artifact_ids.clone();
+    for token in link.content.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_')) {
+        let Some((ts, _)) = token.split_once('_') else { continue };
+        if !ts.is_empty() && ts.bytes().all(|b| b.is_ascii_digit()) && !ids.iter().any(|id| id == token) { ids.push(token.to_string()); }
+    }
+    ids
+}
+
+// A searchable document: its index key, the result it turns into, the text that is indexed,
+// the text its preview is cut from and, for PDFs, where that text's pages start
+struct SearchDoc { key: String, result: SearchResult, indexed: String, preview: String, pages: Option<DocMeta> }
+
+fn corpus_doc(doc: SearchDoc, ts: i64, session_id: String, project: Option<String>) -> CorpusDoc {
+    CorpusDoc { doc_type: doc.result.result_type, id: doc.result.id, title: doc.result.title, text: doc.preview, ts, session_id, project }
+}
+
+// Chain links: name, slug, content and structured sections
+fn link_doc(link: ChainLink) -> SearchDoc {
+    let sections = link.sections_text();
+    let preview = match (link.content.trim().is_empty(), sections.is_empty()) {
+        (_, true) => link.content,
+        (true, false) => sections,
+        (false, false) => format!("{}\n\n{}", link.content, sections),
+    };
+    SearchDoc {
+        key: format!("chains:{}:{}", link.chain_name, link.ts),
+        result: SearchResult {
+            result_type: "chain_link".to_string(),
+            id: format!("chain:{}:{}", link.chain_name, link.slug),
+            title: format!("{}/{}", link.chain_name, link.slug),
+            pinned: link.pinned,
+            importance: link.importance,
+            ..Default::default()
+        },
+        indexed: format!("{} {} {}", link.chain_name, link.slug, preview),
+        preview,
+        pages: None,
+    }
+}
+
+// Artifacts: title, description and extracted text
+fn artifact_doc(artifact: Artifact, text: String, meta: Option<DocMeta>) -> SearchDoc {
+    SearchDoc {
+        key: format!("artifact:{}", artifact.id),
+        result: SearchResult {
+            result_type: "artifact".to_string(),
+            id: format!("artifact:{}", artifact.id),
+            title: artifact.title.clone(),
+            ..Default::default()
+        },
+        indexed: format!("{} {} {}", artifact.title, artifact.description, text),
+        pages: meta.filter(|_| !text.is_empty()),
+        preview: if text.is_empty() { artifact.description } else { text },
+    }
+}
+
+// Memories: title and content
+fn memory_doc(memory: Memory) -> SearchDoc {
+    SearchDoc {
+        key: format!("memory:{}", memory.id),
+        result: SearchResult {
+            result_type: "memory".to_string(),
+            id: format!("memory:{}", memory.id),
+            title: memory.title.clone(),
+            pinned: memory.pinned,
+            importance: memory.importance,
+            ..Default::default()
+        },
+        indexed: format!("{} {}", memory.title, memory.content),
+        preview: memory.content,
+        pages: None,
     }
 }
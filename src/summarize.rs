@@ -0,0 +1,103 @@
+//! Optional session summaries (`--summarize-url`). When a session is marked done, its tool calls and
+//! transcript are condensed into a prompt for an OpenAI-compatible chat completions endpoint, and the
+//! reply is stored as the session's `summary`: shown in the TUI History tab and carried along by
+//! `GET /session/:id/export` and the cold archive.
+
+use std::time::Duration;
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast::{self, error::RecvError}, mpsc};
+use crate::events::{Event, EventBus, EventKind};
+use crate::models::{truncate_chars, Hook, Session, TranscriptEntry, HOOK_LINE_CHARS};
+use crate::store::Store;
+
+// What one prompt holds: the latest hooks and transcript entries, each line capped
+const MAX_HOOKS: isize = 300;
+const MAX_TRANSCRIPT: isize = 40;
+const TIMEOUT: Duration = Duration::from_secs(120);
+
+const INSTRUCTIONS: &str = "You summarize a finished coding agent session for whoever picks up its work later. \
+From its tool calls and conversation, say in at most five sentences of plain prose what it set out to do, \
+what it changed (name the files), and what failed or was left unfinished. No preamble, headings or lists.";
+
+/// Where prompts go; `api_key` may be empty for a local server
+#[derive(Clone)]
+pub struct Summarizer { url: String, model: String, api_key: String }
+
+impl Summarizer {
+    pub fn new(url: &str, model: &str, api_key: &str) -> Self {
+        Self { url: url.to_string(), model: model.to_string(), api_key: api_key.to_string() }
+    }
+
+    /// Summarizes each session the bus reports done, one at a time, so stale cleanup finishing many
+    /// sessions at once doesn't turn into as many concurrent requests
+    pub fn listen(self, store: Store, bus: EventBus, mut events: broadcast::Receiver<Event>) {
+        let (tx, mut done) = mpsc::unbounded_channel::<String>();
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(ev) if ev.kind == EventKind::SessionDone => { let _ = tx.send(ev.id); }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(n)) => eprintln!("summarizer: fell behind, {n} events missed"),
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+        tokio::spawn(async move {
+            while let Some(id) = done.recv().await {
+                match self.summarize(&store, &id).await {
+                    Ok(Some(summary)) => bus.publish(EventKind::SessionSummarized, &id, json!({ "summary": summary })),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("summarizer: session {id}: {e:#}"),
+                }
+            }
+        });
+    }
+
+    /// Generates and stores session `id`'s summary; None when there was nothing to summarize
+    pub async fn summarize(&self, store: &Store, id: &str) -> Result<Option<String>> {
+        let Some(session) = store.get_session(id).await? else { return Ok(None) };
+        let hooks = store.get_hooks(id, MAX_HOOKS).await?;
+        let transcript = store.get_transcript(id, MAX_TRANSCRIPT).await?;
+        if hooks.is_empty() && transcript.is_empty() { return Ok(None); }
+        let summary = self.complete(prompt(&session, &hooks, &transcript)).await?;
+        Ok(store.set_summary(id, &summary).await?.then_some(summary))
+    }
+
+    async fn complete(&self, prompt: String) -> Result<String> {
+        let body = json!({
+            "model": self.model,
+            "messages": [{ "role": "system", "content": INSTRUCTIONS }, { "role": "user", "content": prompt }],
+            "max_tokens": 400,
+        });
+        let (url, api_key) = (self.url.clone(), self.api_key.clone());
+        let reply = tokio::task::spawn_blocking(move || -> Result<Value> {
+            let agent: ureq::Agent = ureq::Agent::config_builder().timeout_global(Some(TIMEOUT)).build().into();
+            let mut req = agent.post(&url);
+            if !api_key.is_empty() { req = req.header("Authorization", &format!("Bearer {api_key}")); }
+            let mut resp = req.send_json(&body)?;
+            Ok(resp.body_mut().read_json()?)
+        }).await??;
+        let text = reply["choices"][0]["message"]["content"].as_str().context("no message in the completion")?.trim();
+        if text.is_empty() { bail!("empty completion"); }
+        Ok(text.to_string())
+    }
+}
+
+// The session's facts, then its tool calls and conversation oldest first, one line each
+fn prompt(session: &Session, hooks: &[Hook], transcript: &[TranscriptEntry]) -> String {
+    let mut out = format!("Agent: {}\nWorking directory: {}\n", session.agent, session.cwd);
+    if let Some(name) = &session.name { out += &format!("Name: {name}\n"); }
+    if let Some(git) = &session.git { out += &format!("Git: {git}\n"); }
+    out += &format!("Duration: {} minutes\n", (session.last_activity - session.created).max(0) / 60);
+    let calls = Hook::calls(hooks);
+    if !calls.is_empty() {
+        out += "\nTool calls:\n";
+        for h in calls { out += &format!("- {}\n", h.line()); }
+    }
+    if !transcript.is_empty() {
+        out += "\nConversation:\n";
+        for t in transcript { out += &format!("- {}: {}\n", t.role, truncate_chars(&t.text, HOOK_LINE_CHARS)); }
+    }
+    out
+}
@@ -0,0 +1,51 @@
+//! systemd integration: readiness notification for `Type=notify` units and socket activation, where a
+//! `.socket` unit binds the port and passes it in as fd 3. Outside systemd both do nothing.
+
+use anyhow::Result;
+
+#[cfg(unix)]
+const LISTEN_FDS_START: i32 = 3;
+
+/// The listening socket systemd passed in, if any. The LISTEN_* variables are cleared either way so
+/// processes tinymem starts don't think the socket is theirs.
+#[cfg(unix)]
+pub fn listener() -> Result<Option<std::net::TcpListener>> {
+    use std::os::fd::FromRawFd;
+    let (pid, fds) = (std::env::var("LISTEN_PID").ok(), std::env::var("LISTEN_FDS").ok());
+    for var in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] { std::env::remove_var(var); }
+    let (Some(pid), Some(fds)) = (pid, fds) else { return Ok(None) };
+    if pid.parse::<u32>().ok() != Some(std::process::id()) { return Ok(None); }
+    match fds.parse::<u32>() {
+        Ok(0) => return Ok(None),
+        Ok(1) => {}
+        _ => anyhow::bail!("LISTEN_FDS={fds}: tinymem takes exactly one socket"),
+    }
+    // SAFETY: systemd hands fd 3 to this process open and unused; nothing else in tinymem touches it
+    let listener = unsafe { std::net::TcpListener::from_raw_fd(LISTEN_FDS_START) };
+    listener.set_nonblocking(true)?;
+    Ok(Some(listener))
+}
+
+#[cfg(not(unix))]
+pub fn listener() -> Result<Option<std::net::TcpListener>> { Ok(None) }
+
+/// Sends `state` (e.g. "READY=1") to $NOTIFY_SOCKET; failures are logged, not fatal
+#[cfg(unix)]
+pub fn notify(state: &str) {
+    use std::os::unix::{ffi::OsStrExt, net::UnixDatagram};
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else { return };
+    let sent = UnixDatagram::unbound().and_then(|sock| match path.as_bytes().strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        Some(name) => {
+            use std::os::linux::net::SocketAddrExt;
+            sock.send_to_addr(state.as_bytes(), &std::os::unix::net::SocketAddr::from_abstract_name(name)?)
+        }
+        #[cfg(not(target_os = "linux"))]
+        Some(_) => Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "abstract socket addresses are Linux-only")),
+        None => sock.send_to(state.as_bytes(), &path),
+    });
+    if let Err(e) = sent { eprintln!("sd_notify {state}: {e}"); }
+}
+
+#[cfg(not(unix))]
+pub fn notify(_state: &str) {}
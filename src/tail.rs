@@ -0,0 +1,75 @@
+//! `tinymem tail`: follows a running server's `GET /events` stream and prints each event as a
+//! colored one-liner, or as the raw JSON line with `--json`, reconnecting when the server goes away.
+
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::time::Duration;
+use crossterm::style::{Color, Stylize};
+use serde_json::Value;
+
+const RECONNECT_AFTER: Duration = Duration::from_secs(2);
+
+/// Runs until interrupted or stdout closes (e.g. the other end of a pipe exits)
+pub fn run(host: &str, port: u16, token: &str, kinds: &[String], json: bool) -> anyhow::Result<()> {
+    let mut url = format!("http://{}:{}{}/events", host, port, crate::server::API_PREFIX);
+    if !kinds.is_empty() { url += &format!("?kinds={}", urlencoding::encode(&kinds.join(","))); }
+    let color = std::io::stdout().is_terminal();
+    let mut connected = true;
+    loop {
+        match ureq::get(&url).header("Authorization", &format!("Bearer {}", token)).call() {
+            Ok(resp) => {
+                if !connected { eprintln!("tail: reconnected"); }
+                connected = true;
+                for line in BufReader::new(resp.into_body().into_reader()).lines() {
+                    let Ok(line) = line else { break };
+                    let out = if json { line } else {
+                        match serde_json::from_str::<Value>(&line) { Ok(ev) => one_liner(&ev, color), Err(_) => continue }
+                    };
+                    let mut stdout = std::io::stdout().lock();
+                    if writeln!(stdout, "{out}").and_then(|_| stdout.flush()).is_err() { return Ok(()); }
+                }
+                eprintln!("tail: stream ended, reconnecting");
+            }
+            Err(ureq::Error::StatusCode(401)) => anyhow::bail!("the server rejected the token (set --token or TINYMEM_TOKEN)"),
+            Err(e) => {
+                if connected { eprintln!("tail: {e}, retrying every {}s", RECONNECT_AFTER.as_secs()); }
+                connected = false;
+            }
+        }
+        std::thread::sleep(RECONNECT_AFTER);
+    }
+}
+
+// "12:04:31 hook        a1b2c3  ✓ Edit src/main.rs"
+fn one_liner(ev: &Value, color: bool) -> String {
+    let s = |v: &Value| v.as_str().unwrap_or_default().to_string();
+    let (event, id, data) = (s(&ev["event"]), s(&ev["id"]), &ev["data"]);
+    let time = chrono::DateTime::from_timestamp(ev["ts"].as_i64().unwrap_or_default(), 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+        .unwrap_or_default();
+    let detail = match event.as_str() {
+        "hook" => format!("{} {}", s(&data["kind"]), s(&data["task"])),
+        "session.started" => format!("{} in {}", s(&data["agent"]), s(&data["cwd"])),
+        "ask.pending" => s(&data["question"]),
+        "approval.pending" => s(&data["task"]),
+        "chain_link.saved" => format!("{}/{}", s(&data["link"]["chain_name"]), s(&data["link"]["slug"])),
+        "todo.changed" => s(&data["text"]),
+        "handoff.pending" => format!("{} -> {}", s(&data["from"]), s(&data["to"])),
+        "artifact.indexed" => format!("{} {}", s(&data["status"]["state"]), s(&data["title"])),
+        "session.summarized" => crate::models::truncate_chars(&s(&data["summary"]).replace('\n', " "), 80).to_string(),
+        "session.rejected" => format!("{} ({} of {} active)", s(&data["agent"]), data["active"], data["limit"]),
+        _ => String::new(),
+    };
+    let line = format!("{time} {event:<17} {id}  {detail}");
+    if !color { return line; }
+    let hue = match event.as_str() {
+        "hook" if data["error"] == true => Color::Red,
+        "hook" => Color::Cyan,
+        "session.started" | "session.resumed" => Color::Green,
+        "session.done" | "session.deleted" => Color::DarkGrey,
+        "ask.pending" | "approval.pending" => Color::Yellow,
+        "session.rejected" => Color::Red,
+        "chain_link.saved" => Color::Magenta,
+        _ => Color::Reset,
+    };
+    line.with(hue).to_string()
+}
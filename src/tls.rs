@@ -0,0 +1,73 @@
+//! HTTPS for the API listener (`--tls-cert`/`--tls-key`). With `--tls-client-ca` every client must
+//! present a certificate signed by that CA, and the subject CN of the one it presented reaches the
+//! handlers as [`ClientCert`] for auth, auditing and quotas.
+
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::{bail, Context, Result};
+use axum::Router;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::{self, pki_types::CertificateDer, server::WebPkiClientVerifier, RootCertStore};
+use tokio_rustls::TlsAcceptor;
+use tower::Service;
+use crate::auth::ClientCert;
+
+fn certs(path: &Path) -> Result<Vec<CertificateDer<'static>>> {
+    let pem = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+    let certs = rustls_pemfile::certs(&mut &pem[..]).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() { bail!("no certificates in {}", path.display()); }
+    Ok(certs)
+}
+
+/// Server certificate chain and key, plus the CA client certificates must chain to when `client_ca` is set
+pub fn acceptor(cert: &Path, key: &Path, client_ca: Option<&Path>) -> Result<TlsAcceptor> {
+    let pem = std::fs::read(key).with_context(|| format!("reading {}", key.display()))?;
+    let key = rustls_pemfile::private_key(&mut &pem[..])?.with_context(|| format!("no private key in {}", key.display()))?;
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = rustls::ServerConfig::builder_with_provider(provider.clone()).with_safe_default_protocol_versions()?;
+    let builder = match client_ca {
+        Some(ca) => {
+            let mut roots = RootCertStore::empty();
+            for c in certs(ca)? { roots.add(c)?; }
+            builder.with_client_cert_verifier(WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider).build()?)
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut config = builder.with_single_cert(certs(cert)?, key)?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+fn common_name(cert: &CertificateDer) -> Option<String> {
+    let (_, cert) = x509_parser::parse_x509_certificate(cert).ok()?;
+    let cn = cert.subject().iter_common_name().next()?.as_str().ok()?;
+    Some(cn.to_string())
+}
+
+/// Serves `app` over TLS on `listener`, one task per connection
+pub async fn serve(listener: TcpListener, app: Router, acceptor: TlsAcceptor) -> Result<()> {
+    loop {
+        let (tcp, peer) = match listener.accept().await {
+            Ok(conn) => conn,
+            Err(e) => { eprintln!("accept failed: {e}"); continue; }
+        };
+        let (acceptor, app) = (acceptor.clone(), app.clone());
+        tokio::spawn(async move {
+            let tls = match acceptor.accept(tcp).await {
+                Ok(tls) => tls,
+                Err(e) => return eprintln!("TLS handshake with {peer} failed: {e}"),
+            };
+            // By now rustls has checked the chain against --tls-client-ca; all that's left is naming the caller
+            let cert = tls.get_ref().1.peer_certificates().and_then(|c| c.first()).and_then(common_name).map(ClientCert);
+            let svc = hyper::service::service_fn(move |mut req: hyper::Request<Incoming>| {
+                if let Some(cert) = &cert { req.extensions_mut().insert(cert.clone()); }
+                app.clone().call(req)
+            });
+            // Errors here are clients hanging up mid-request
+            let _ = hyper_util::server::conn::auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(tls), svc).await;
+        });
+    }
+}
@@ -1,91 +1,309 @@
 use anyhow::Result;
+use chrono::Datelike;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use strsim::jaro_winkler;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
-    text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Tabs, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap},
     Frame,
 };
-use std::time::Duration;
-use tokio::sync::mpsc::Receiver;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{error::TryRecvError, Receiver};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use crate::models::{Artifact, ChainLink, Session, Status, TuiEvent};
+use crate::events::{self, EventKind};
+use crate::models::{now, preview, short_id, truncate_chars, ActiveTool, Artifact, HookError, HookKind, ChainLink, ChainStatus, ApprovalDecision, Handoff, HistoryQuery, Hook, Liveness, Memory, Presence, Todo, TodoQuery, PendingApproval, PendingAsk, SavedSearch, SearchFilters, SearchResult, Session, SessionProfile, Status, Usage};
+use crate::notify::Notifier;
+use crate::preview;
 use crate::store::Store;
 
+const SPARKLINE_MINUTES: i64 = 15; // width of the per-session activity sparkline
+const ERROR_RECENT_SECS: i64 = 600; // a session with an error this recent is flagged red
+const HISTORY_PAGE: usize = 20; // finished sessions loaded at first and per PageDown
+const PREVIEW_COLS: u16 = 40; // artifact thumbnail size in cells
+const PREVIEW_ROWS: u16 = 12;
+const HOOK_FEED: isize = 50; // latest hooks kept per active session for the split view's feed
+const TOAST_TTL: Duration = Duration::from_secs(4);
+const TOASTS_SHOWN: usize = 4;
+
 #[derive(Default, Clone, Copy, PartialEq)]
 enum Tab {
     #[default]
     Active,
     Chains,
+    Todos,
+    Board,
     Artifacts,
+    Search,
     History,
 }
 
+// Which finished sessions the History tab shows, by last activity
+#[derive(Default, Clone, Copy, PartialEq)]
+enum Period {
+    #[default]
+    All,
+    Today,
+    Week,
+}
+
+impl Period {
+    fn next(self) -> Self {
+        match self { Period::All => Period::Today, Period::Today => Period::Week, Period::Week => Period::All }
+    }
+
+    fn label(self) -> &'static str {
+        match self { Period::All => "all time", Period::Today => "today", Period::Week => "this week" }
+    }
+}
+
+// What the background loader should re-fetch
+enum Load { All, Session(String), OlderHistory, History(HistoryQuery), Todos, Handoffs }
+
+// A Board column: (title, color, cards), where a card is (chain name or todo id, label)
+type BoardColumn = (&'static str, Color, Vec<(String, String)>);
+
+// What typed input is for in the Chains and Todos tabs
+enum TodoInput { New(String), Assign(String) } // (chain name), (todo id)
+
+// Less common actions, run from the `:` palette instead of each having a key
+#[derive(Clone, Copy, PartialEq)]
+enum PaletteCommand { MarkDone, Rename, OpenChain, SaveNote, Broadcast, FilterAgent }
+
+impl PaletteCommand {
+    const ALL: [PaletteCommand; 6] = [
+        PaletteCommand::MarkDone, PaletteCommand::Rename, PaletteCommand::OpenChain, PaletteCommand::SaveNote, PaletteCommand::Broadcast,
+        PaletteCommand::FilterAgent,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            PaletteCommand::MarkDone => "mark session done",
+            PaletteCommand::Rename => "rename session",
+            PaletteCommand::OpenChain => "open chain",
+            PaletteCommand::SaveNote => "save note",
+            PaletteCommand::Broadcast => "broadcast",
+            PaletteCommand::FilterAgent => "filter by agent",
+        }
+    }
+
+    // What to ask for before running, for commands that take text
+    fn prompt(self) -> Option<&'static str> {
+        match self {
+            PaletteCommand::Rename => Some("New name (empty clears it)"),
+            PaletteCommand::OpenChain => Some("Chain"),
+            PaletteCommand::SaveNote => Some("Note"),
+            PaletteCommand::FilterAgent => Some("Agent (empty shows all)"),
+            PaletteCommand::MarkDone | PaletteCommand::Broadcast => None,
+        }
+    }
+}
+
+// One session plus everything its row and detail pane show; history rows only carry the last hook
+struct SessionData {
+    session: Session,
+    active_tools: Option<Vec<ActiveTool>>,
+    pending_ask: Option<PendingAsk>,
+    approvals: Option<Vec<PendingApproval>>,
+    related: Option<String>,
+    activity: Option<Vec<u64>>,
+    usage: Option<Usage>,
+    transcript: Option<String>,
+    errors: Option<Vec<HookError>>,
+    last_hook: Option<(String, String)>, // (preview, detail)
+    hooks: Option<Vec<Hook>>, // latest HOOK_FEED, oldest first
+}
+
+struct Snapshot {
+    sessions: Vec<SessionData>,
+    history: Vec<SessionData>,
+    usage_today: Usage,
+    chains: Vec<(String, usize)>,
+    chain_statuses: std::collections::HashMap<String, ChainStatus>,
+    todos: Vec<Todo>,
+    handoffs: Vec<Handoff>,
+    artifacts: Vec<Artifact>,
+    saved_searches: Vec<SavedSearch>,
+}
+
+// A background event worth a glance, shown briefly over whichever tab is open
+struct Toast { text: String, color: Color, at: Instant }
+
+// Sent by the loader: a full reload, one session that changed (and whether it is still active), one that is
+// gone, or the next page of history
+enum Update { All(Box<Snapshot>), Session(Box<SessionData>, bool, Usage), Gone(String), History(Vec<SessionData>, bool), Todos(Vec<Todo>), Handoffs(Vec<Handoff>) }
+
 pub struct App {
     store: Store,
-    rx: Receiver<TuiEvent>,
+    events: Receiver<events::Event>,
+    load_tx: UnboundedSender<Load>,
+    updates: UnboundedReceiver<Update>,
     tab: Tab,
     sessions: Vec<Session>,
-    active_tools: std::collections::HashMap<String, String>, // session_id -> tool_name
+    active_tools: std::collections::HashMap<String, Vec<ActiveTool>>, // session_id -> running tools, oldest first
     last_msgs: std::collections::HashMap<String, String>, // session_id -> last message preview
     last_hook_details: std::collections::HashMap<String, String>, // session_id -> full hook detail (first 1k chars)
+    hooks: std::collections::HashMap<String, Vec<Hook>>, // session_id -> latest hooks for the split view
+    split_view: bool, // Active tab shows the selected session's hook feed under its detail
+    pending_asks: std::collections::HashMap<String, PendingAsk>, // session_id -> unanswered question
+    approvals: std::collections::HashMap<String, Vec<PendingApproval>>, // session_id -> tool calls awaiting approval, oldest first
+    dismissed_approvals: HashSet<String>, // invocation ids put off with Esc; [A] brings them back
+    related: std::collections::HashMap<String, String>, // session_id -> chains/artifacts summary
+    transcripts: std::collections::HashMap<String, String>, // session_id -> recent prompts/responses
+    errors: std::collections::HashMap<String, Vec<HookError>>, // session_id -> recent error hooks, newest first
+    usage: std::collections::HashMap<String, Usage>, // session_id -> token totals
+    activity: std::collections::HashMap<String, Vec<u64>>, // session_id -> hooks per minute, oldest first
+    usage_today: Usage,
     session_state: ListState,
+    session_search: String, // fuzzy name, agent, cwd or last hook
     history: Vec<Session>,
+    history_state: ListState,
+    history_period: Period,
+    agent_filter: Option<String>, // Active and History show only this agent's sessions
+    history_search: String, // fuzzy session name
+    agents: std::collections::BTreeSet<String>, // every agent seen, for cycling the agent filter
+    confirm_delete: Option<String>, // session awaiting y/n before deletion
+    handoffs: Vec<Handoff>, // pending, oldest first
     // Chains tab
     chains: Vec<(String, usize)>,     // (chain_name, link_count)
     chains_filtered: Vec<(String, usize, f64)>, // (name, count, score)
+    chain_statuses: std::collections::HashMap<String, ChainStatus>, // chain_name -> status, absent = active
     chain_state: ListState,
     chain_search: String,
     chain_content: Option<String>,
     chain_scroll: u16,
+    // Todos tab
+    todos: Vec<Todo>, // open first
+    todo_state: ListState,
+    // Board tab
+    board_todos: bool, // todos by open/done instead of chains by status
+    board_col: usize,
+    board_rows: [usize; 3], // selected card per column
     // Artifacts tab
     artifacts: Vec<Artifact>,
     artifacts_filtered: Vec<(Artifact, f64)>, // (artifact, score)
     artifact_state: ListState,
     artifact_search: String,
     artifact_content: Option<String>,
+    artifact_preview: Option<(String, Vec<Line<'static>>)>, // (artifact id, first page as half blocks)
     artifact_scroll: u16,
+    // Search tab
+    saved_searches: Vec<SavedSearch>,
+    saved_state: ListState,
+    search_query: String,
+    search_label: String, // what the current results came from
+    search_results: Vec<SearchResult>,
+    result_sel: usize, // highlighted result for pin/importance keys
     // Input
     input_mode: bool,
     input: String,
+    answer_target: Option<String>, // session whose question is being answered
+    answer_choice: Option<usize>, // highlighted option when that question is multiple choice
+    broadcasting: bool, // input goes to every active session's inbox
+    todo_input: Option<TodoInput>,
+    command_input: Option<(PaletteCommand, Option<String>)>, // command waiting for its text, with the session selected when it was picked
+    palette: Option<String>, // typed filter while the command palette is open
+    palette_sel: usize,
+    compare_pick: Option<String>, // first session picked with [c], waiting for the second
+    comparison: Option<Box<(SessionProfile, SessionProfile)>>,
+    toasts: Vec<Toast>, // oldest first, dropped after TOAST_TTL
     search_mode: bool,
+    notifier: Notifier,
+    stuck_tool_secs: i64, // running tools older than this show in red
+    presence: Presence,
+    max_active_sessions: usize, // 0 = unlimited
+    sessions_rejected: usize, // since the TUI started, by --max-active-sessions
 }
 
 impl App {
-    pub fn new(store: Store, rx: Receiver<TuiEvent>) -> Self {
+    pub fn new(store: Store, events: Receiver<events::Event>, notifier: Notifier, stuck_tool_secs: i64, presence: Presence, max_active_sessions: usize) -> Self {
+        let (load_tx, load_rx) = unbounded_channel();
+        let (update_tx, updates) = unbounded_channel();
+        tokio::spawn(load(store.clone(), load_rx, update_tx));
         Self {
             store,
-            rx,
+            events,
+            load_tx,
+            updates,
             tab: Tab::Active,
             sessions: vec![],
             active_tools: std::collections::HashMap::new(),
             last_msgs: std::collections::HashMap::new(),
             last_hook_details: std::collections::HashMap::new(),
+            hooks: std::collections::HashMap::new(),
+            split_view: false,
+            pending_asks: std::collections::HashMap::new(),
+            approvals: std::collections::HashMap::new(),
+            dismissed_approvals: HashSet::new(),
+            related: std::collections::HashMap::new(),
+            transcripts: std::collections::HashMap::new(),
+            errors: std::collections::HashMap::new(),
+            usage: std::collections::HashMap::new(),
+            activity: std::collections::HashMap::new(),
+            usage_today: Usage::default(),
             session_state: ListState::default(),
+            session_search: String::new(),
             history: vec![],
+            history_state: ListState::default(),
+            history_period: Period::All,
+            agent_filter: None,
+            history_search: String::new(),
+            agents: Default::default(),
+            confirm_delete: None,
+            handoffs: vec![],
             chains: vec![],
             chains_filtered: vec![],
+            chain_statuses: Default::default(),
             chain_state: ListState::default(),
             chain_search: String::new(),
             chain_content: None,
             chain_scroll: 0,
+            todos: vec![],
+            todo_state: ListState::default(),
+            board_todos: false,
+            board_col: 0,
+            board_rows: [0; 3],
             artifacts: vec![],
             artifacts_filtered: vec![],
             artifact_state: ListState::default(),
             artifact_search: String::new(),
             artifact_content: None,
+            artifact_preview: None,
             artifact_scroll: 0,
+            saved_searches: vec![],
+            saved_state: ListState::default(),
+            search_query: String::new(),
+            search_label: String::new(),
+            search_results: vec![],
+            result_sel: 0,
             input_mode: false,
             input: String::new(),
+            answer_target: None,
+            answer_choice: None,
+            broadcasting: false,
+            todo_input: None,
+            command_input: None,
+            palette: None,
+            palette_sel: 0,
+            compare_pick: None,
+            comparison: None,
+            toasts: vec![],
             search_mode: false,
+            notifier,
+            stuck_tool_secs,
+            presence,
+            max_active_sessions,
+            sessions_rejected: 0,
         }
     }
 
     pub async fn run(&mut self, terminal: &mut ratatui::DefaultTerminal) -> Result<()> {
-        self.refresh().await?;
+        self.request(Load::All);
         loop {
+            while let Ok(update) = self.updates.try_recv() { self.apply(update); }
             terminal.draw(|f| self.draw(f))?;
             if event::poll(Duration::from_millis(200))? {
                 if let Event::Key(key) = event::read()? {
@@ -94,82 +312,244 @@ impl App {
                     }
                 }
             }
-            while let Ok(ev) = self.rx.try_recv() {
-                match ev {
-                    TuiEvent::Refresh | TuiEvent::NewSession | TuiEvent::SessionDone => {
-                        self.refresh().await?;
+            loop {
+                match self.events.try_recv() {
+                    Ok(ev) => self.on_event(ev),
+                    // Missed some; the only safe catch-up is a full reload
+                    Err(TryRecvError::Lagged(_)) => self.request(Load::All),
+                    Err(_) => break,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn on_event(&mut self, ev: events::Event) {
+        let text = |key: &str| ev.data.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let name = self.sessions.iter().find(|s| s.id == ev.id).and_then(|s| s.name.clone()).unwrap_or_else(|| ev.id.clone());
+        match ev.kind {
+            EventKind::AskPending => {
+                self.notifier.alert(&format!("tinymem: {} asks", ev.id), &text("question"));
+                self.toast(Tab::Active, format!("{name} asks: {}", text("question")), Color::Yellow);
+            }
+            EventKind::ApprovalPending => {
+                self.notifier.alert(&format!("tinymem: {} needs approval", ev.id), &text("task"));
+                self.toast(Tab::Active, format!("{name} needs approval for {}", text("task")), Color::Yellow);
+            }
+            EventKind::Hook if ev.data["error"] == true => {
+                self.notifier.alert(&format!("tinymem: {} failed", ev.id), &text("task"));
+                self.toast(Tab::Active, format!("{name}: {} failed", text("task")), Color::Red);
+            }
+            EventKind::SessionDone if ev.data.get("stale").is_none() => {
+                self.notifier.alert("tinymem: session done", &ev.id);
+                self.toast(Tab::Active, format!("{name} is done"), Color::Green);
+            }
+            // Keyed by link, not session; chains reload with 'r'
+            EventKind::ChainLinkSaved => {
+                let chain = ev.data["link"]["chain_name"].as_str().unwrap_or_default();
+                self.toast(Tab::Chains, format!("chain '{chain}' updated"), Color::Magenta);
+                return;
+            }
+            EventKind::ArtifactIndexed => {
+                let (title, status) = (text("title"), &ev.data["status"]);
+                match status["state"].as_str() {
+                    Some("failed") => self.toast(Tab::Artifacts, format!("artifact '{title}' failed to index: {}", status["error"].as_str().unwrap_or_default()), Color::Red),
+                    _ => self.toast(Tab::Artifacts, format!("artifact '{title}' indexed"), Color::Cyan),
+                }
+                return;
+            }
+            // No session was created, so nothing to reload
+            EventKind::SessionRejected => {
+                self.sessions_rejected += 1;
+                let why = format!("{} rejected: {} of {} sessions active", text("agent"), ev.data["active"], ev.data["limit"]);
+                self.notifier.alert("tinymem: session limit reached", &why);
+                self.toast(Tab::Active, why, Color::Red);
+                return;
+            }
+            // Frequent and only moves a timestamp, so no reload
+            EventKind::Heartbeat => {
+                if let Some(s) = self.sessions.iter_mut().find(|s| s.id == ev.id) { s.last_heartbeat = ev.ts; }
+                return;
+            }
+            EventKind::TodoChanged => {
+                self.request(Load::Todos);
+                return;
+            }
+            EventKind::HandoffPending | EventKind::HandoffAccepted => {
+                if ev.kind == EventKind::HandoffPending { self.notifier.alert("tinymem: handoff", &format!("{} → {}", text("from"), text("to"))); }
+                self.request(Load::Handoffs);
+                return;
+            }
+            _ => {}
+        }
+        self.request(Load::Session(ev.id));
+    }
+
+    // Skipped when `home`, the tab that already shows the event, is open. Repeats refresh the existing toast.
+    fn toast(&mut self, home: Tab, text: String, color: Color) {
+        if self.tab == home { return; }
+        self.toasts.retain(|t| t.text != text);
+        self.toasts.push(Toast { text, color, at: Instant::now() });
+        if self.toasts.len() > TOASTS_SHOWN { self.toasts.remove(0); }
+    }
+
+    // Loading happens in the background; results arrive through `updates` on a later frame
+    fn request(&self, what: Load) {
+        let _ = self.load_tx.send(what);
+    }
+
+    fn apply(&mut self, update: Update) {
+        match update {
+            Update::All(snapshot) => {
+                let Snapshot { sessions, history, usage_today, chains, chain_statuses, todos, handoffs, artifacts, saved_searches } = *snapshot;
+                self.active_tools.clear();
+                self.pending_asks.clear();
+                self.approvals.clear();
+                self.related.clear();
+                self.activity.clear();
+                self.usage.clear();
+                self.transcripts.clear();
+                self.errors.clear();
+                self.last_msgs.clear();
+                self.last_hook_details.clear();
+                self.hooks.clear();
+                self.sessions = sessions.into_iter().map(|d| self.store_session(d)).collect();
+                self.history = history.into_iter().map(|d| self.store_session(d)).collect();
+                self.usage_today = usage_today;
+                self.chains = chains;
+                self.chain_statuses = chain_statuses;
+                self.filter_chains();
+                self.set_todos(todos);
+                self.handoffs = handoffs;
+                self.artifacts = artifacts;
+                self.filter_artifacts();
+                self.saved_searches = saved_searches;
+                if self.saved_state.selected().is_some_and(|i| i >= self.saved_searches.len()) {
+                    self.saved_state.select(if self.saved_searches.is_empty() { None } else { Some(0) });
+                }
+            }
+            Update::Session(data, active, usage_today) => {
+                let session = self.store_session(*data);
+                self.usage_today = usage_today;
+                self.history.retain(|s| s.id != session.id);
+                if active {
+                    match self.sessions.iter_mut().find(|s| s.id == session.id) {
+                        Some(s) => *s = session,
+                        None => self.sessions.push(session),
                     }
+                } else {
+                    self.sessions.retain(|s| s.id != session.id);
+                    if self.history_query().matches(&session) { self.history.insert(0, session); }
                 }
             }
+            Update::Gone(id) => {
+                self.sessions.retain(|s| s.id != id);
+                self.history.retain(|s| s.id != id);
+            }
+            Update::Todos(todos) => self.set_todos(todos),
+            Update::Handoffs(handoffs) => self.handoffs = handoffs,
+            Update::History(page, replace) => {
+                if replace {
+                    self.history.clear();
+                    self.history_state.select(if page.is_empty() { None } else { Some(0) });
+                }
+                for d in page {
+                    if self.history.iter().any(|s| s.id == d.session.id) { continue; }
+                    let session = self.store_session(d);
+                    self.history.push(session);
+                }
+            }
+        }
+    }
+
+    fn set_todos(&mut self, todos: Vec<Todo>) {
+        self.todos = todos;
+        if self.todo_state.selected().is_some_and(|i| i >= self.todos.len()) {
+            self.todo_state.select(if self.todos.is_empty() { None } else { Some(self.todos.len() - 1) });
         }
+    }
+
+    // Files one session's loaded details under its id and hands back the session itself
+    fn store_session(&mut self, d: SessionData) -> Session {
+        let id = &d.session.id;
+        set(&mut self.active_tools, id, d.active_tools);
+        set(&mut self.pending_asks, id, d.pending_ask);
+        set(&mut self.approvals, id, d.approvals);
+        set(&mut self.related, id, d.related);
+        set(&mut self.activity, id, d.activity);
+        set(&mut self.usage, id, d.usage);
+        set(&mut self.transcripts, id, d.transcript);
+        set(&mut self.errors, id, d.errors);
+        let (preview, detail) = d.last_hook.unzip();
+        set(&mut self.last_msgs, id, preview);
+        set(&mut self.last_hook_details, id, detail);
+        set(&mut self.hooks, id, d.hooks);
+        self.agents.insert(d.session.agent.clone());
+        d.session
+    }
+
+    // The History tab's filters as a history query; "today" and "this week" are local time
+    fn history_query(&self) -> HistoryQuery {
+        let today = chrono::Local::now().date_naive();
+        let start = match self.history_period {
+            Period::All => None,
+            Period::Today => Some(today),
+            Period::Week => Some(today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64)),
+        };
+        HistoryQuery {
+            after: start.and_then(|d| d.and_hms_opt(0, 0, 0)?.and_local_timezone(chrono::Local).earliest()).map(|t| t.timestamp()),
+            agent: self.agent_filter.clone(),
+            name: Some(self.history_search.clone()).filter(|n| !n.trim().is_empty()),
+            ..Default::default()
+        }
+    }
+
+    // No agent -> each agent seen, alphabetically -> no agent
+    fn cycle_agent_filter(&mut self) {
+        let next = match &self.agent_filter {
+            None => self.agents.iter().next().cloned(),
+            Some(a) => self.agents.range::<String, _>((std::ops::Bound::Excluded(a), std::ops::Bound::Unbounded)).next().cloned(),
+        };
+        self.set_agent_filter(next);
+    }
+
+    fn set_agent_filter(&mut self, agent: Option<String>) {
+        self.agent_filter = agent;
+        self.clamp_active_selection();
+        self.request(Load::History(self.history_query()));
+    }
+
+    async fn run_search(&mut self, label: String, query: &str, limit: usize, filters: &SearchFilters) {
+        self.search_results = self.store.global_search(query, limit, filters).await.unwrap_or_default();
+        self.search_label = label;
+        self.result_sel = 0;
+    }
+
+    async fn run_selected_saved_search(&mut self) {
+        if let Some(search) = self.saved_state.selected().and_then(|i| self.saved_searches.get(i)).cloned() {
+            self.run_search(format!("📌 {}", search.name), &search.query, search.limit, &search.resolved_filters()).await;
+        }
+    }
+
+    // Toggle pin or nudge importance on the highlighted result (chain links and memories only)
+    async fn prioritize_result(&mut self, toggle_pin: bool, importance_delta: i8) -> Result<()> {
+        let Some(r) = self.search_results.get_mut(self.result_sel) else { return Ok(()) };
+        if r.result_type == "artifact" { return Ok(()); }
+        if toggle_pin { r.pinned = !r.pinned; }
+        r.importance = r.importance.saturating_add_signed(importance_delta).min(10);
+        self.store.set_priority(&r.id, Some(r.pinned), Some(r.importance)).await?;
         Ok(())
     }
 
-    async fn refresh(&mut self) -> Result<()> {
-        let ids = self.store.list_active().await?;
-        self.sessions = futures::future::join_all(ids.iter().map(|id| self.store.get_session(id)))
-            .await
-            .into_iter()
-            .filter_map(|r| r.ok().flatten())
-            .collect();
-        // Fetch active tools for each session
-        self.active_tools.clear();
-        for s in &self.sessions {
-            if let Ok(Some(tool)) = self.store.get_active_tool(&s.id).await {
-                self.active_tools.insert(s.id.clone(), tool);
-            }
-        }
-        let hist_ids = self.store.list_history(20).await?;
-        self.history = futures::future::join_all(hist_ids.iter().map(|id| self.store.get_session(id)))
-            .await
-            .into_iter()
-            .filter_map(|r| r.ok().flatten())
-            .collect();
-        // Fetch last hook for all sessions (shows last activity with details)
-        self.last_msgs.clear();
-        self.last_hook_details.clear();
-        let all_sessions: Vec<&Session> = self.sessions.iter().chain(self.history.iter()).collect();
-        for s in all_sessions {
-            if let Ok(hooks) = self.store.get_hooks(&s.id, 1).await {
-                if let Some(hook) = hooks.last() {
-                    let kind = if hook.kind == "pre" { "→" } else { "✓" };
-                    let meta_str = if let Some(obj) = hook.meta.as_object() {
-                        let priority_keys = ["file_path", "command", "pattern", "query", "url", "skill", "prompt"];
-                        let mut found = None;
-                        for key in priority_keys {
-                            if let Some(serde_json::Value::String(val)) = obj.get(key) {
-                                let val = val.replace('\n', " ");
-                                found = Some(if val.len() > 45 { format!("{}...", &val[..42]) } else { val });
-                                break;
-                            }
-                        }
-                        found.unwrap_or_default()
-                    } else {
-                        String::new()
-                    };
-                    let preview = if meta_str.is_empty() {
-                        format!("{} {}", kind, hook.task)
-                    } else {
-                        format!("{} {} ({})", kind, hook.task, meta_str)
-                    };
-                    self.last_msgs.insert(s.id.clone(), preview);
-                    let full_meta = serde_json::to_string_pretty(&hook.meta).unwrap_or_default();
-                    let detail = format!("Last: {} {}\n\n{}", kind, hook.task,
-                        if full_meta.len() > 1000 { format!("{}...", &full_meta[..1000]) } else { full_meta });
-                    self.last_hook_details.insert(s.id.clone(), detail);
-                }
-            }
-        }
-        // Load chains with link counts
-        self.chains.clear();
-        for name in self.store.list_chain_names().await.unwrap_or_default() {
-            let count = self.store.get_chain_links(&name).await.map(|l| l.len()).unwrap_or(0);
-            self.chains.push((name, count));
-        }
-        self.filter_chains();
-        // Load artifacts
-        self.artifacts = self.store.list_artifacts().await.unwrap_or_default();
-        self.filter_artifacts();
+    // Save the current ad-hoc query, named after itself
+    async fn save_query(&mut self) -> Result<()> {
+        if self.search_query.is_empty() { return Ok(()); }
+        let search = SavedSearch {
+            name: self.search_query.clone(), query: self.search_query.clone(), limit: 25,
+            filters: SearchFilters::default(), within_secs: None, created: now(),
+        };
+        self.store.save_search(&search).await?;
+        self.request(Load::All);
         Ok(())
     }
 
@@ -239,28 +619,48 @@ impl App {
         if let Some(i) = self.artifact_state.selected() {
             if let Some((artifact, _)) = self.artifacts_filtered.get(i) {
                 let text = self.store.get_artifact_text(&artifact.id).await.ok().flatten();
+                let mentions = self.store.artifact_mentions(&artifact.id).await.unwrap_or_default();
+                let document = match self.store.get_artifact_meta(&artifact.id).await.ok().flatten() {
+                    Some(m) => format!("Pages: {}\n{}{}", m.page_count,
+                        m.title.map(|t| format!("Document title: {t}\n")).unwrap_or_default(),
+                        m.author.map(|a| format!("Author: {a}\n")).unwrap_or_default()),
+                    None => String::new(),
+                };
+                let related = if mentions.is_empty() { String::new() } else { format!("\n\nMentioned in:\n{}", mentions.join("\n")) };
                 let ts = chrono::DateTime::from_timestamp(artifact.ts, 0)
                     .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
                     .unwrap_or_else(|| artifact.ts.to_string());
                 let content = format!(
-                    "Title: {}\nType: {}\nPath: {}\nCreated: {}\nSession: {}\n\nDescription:\n{}\n\n{}",
+                    "Title: {}\nType: {}\nPath: {}\nCreated: {}\nSession: {}\n{}\nDescription:\n{}{}\n\n{}",
                     artifact.title,
                     artifact.file_type.to_uppercase(),
                     artifact.file_path,
                     ts,
                     artifact.session_id,
+                    document,
                     artifact.description,
+                    related,
                     if let Some(t) = text {
-                        format!("--- Extracted Text ---\n{}", if t.len() > 5000 { format!("{}...", &t[..5000]) } else { t })
+                        format!("--- Extracted Text ---\n{}", preview(&t, 5000))
                     } else {
                         "(no text extracted)".to_string()
                     }
                 );
                 self.artifact_content = Some(content);
+                if self.artifact_preview.as_ref().is_none_or(|(id, _)| *id != artifact.id) {
+                    self.artifact_preview = None;
+                    if preview::supported(&artifact.file_type) {
+                        let path = artifact.file_path.clone();
+                        if let Ok(Some(lines)) = tokio::task::spawn_blocking(move || preview::render(&path, PREVIEW_COLS, PREVIEW_ROWS)).await {
+                            self.artifact_preview = Some((artifact.id.clone(), lines));
+                        }
+                    }
+                }
                 return;
             }
         }
         self.artifact_content = None;
+        self.artifact_preview = None;
     }
 
     fn format_chain_links(&self, chain_name: &str, links: &[ChainLink]) -> String {
@@ -268,6 +668,14 @@ impl App {
             return format!("🔗 Chain: {}\n\n(no links yet)", chain_name);
         }
         let mut output = format!("🔗 Chain: {} ({} links)\n", chain_name, links.len());
+        let todos: Vec<&Todo> = self.todos.iter().filter(|t| t.chain_name.as_deref() == Some(chain_name)).collect();
+        if !todos.is_empty() {
+            output.push_str(&format!("Todos ({} open):\n", todos.iter().filter(|t| !t.done()).count()));
+            for t in todos {
+                let assignee = t.assignee.as_ref().map(|a| format!(" @{a}")).unwrap_or_default();
+                output.push_str(&format!("  [{}] {}{}\n", if t.done() { "x" } else { " " }, t.text, assignee));
+            }
+        }
         output.push_str("─".repeat(40).as_str());
         output.push('\n');
 
@@ -275,15 +683,21 @@ impl App {
             let ts = chrono::DateTime::from_timestamp(link.ts, 0)
                 .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
                 .unwrap_or_else(|| link.ts.to_string());
-            output.push_str(&format!("\n[{}] {} ({})\n", i + 1, link.slug, ts));
+            let status = link.status.map(|s| format!(" [{}]", s.as_str())).unwrap_or_default();
+            output.push_str(&format!("\n[{}] {} ({}){}\n", i + 1, link.slug, ts, status));
             output.push_str(&format!("Session: {}\n", link.session_id));
+            if let Some(git) = &link.git { output.push_str(&format!("⎇ {git}\n")); }
+            for id in &link.artifact_ids {
+                let title = self.artifacts.iter().find(|a| &a.id == id).map_or("(deleted)", |a| a.title.as_str());
+                output.push_str(&format!("📎 {} [{}]\n", title, id));
+            }
             // Show first 500 chars of content
-            let preview = if link.content.len() > 500 {
-                format!("{}...", &link.content[..500])
-            } else {
-                link.content.clone()
-            };
-            output.push_str(&format!("\n{}\n", preview));
+            let content = preview(&link.content, 500);
+            if !content.trim().is_empty() { output.push_str(&format!("\n{}\n", content)); }
+            for (heading, items) in link.sections().into_iter().filter(|(_, items)| !items.is_empty()) {
+                output.push_str(&format!("\n{}:\n", heading));
+                for item in items { output.push_str(&format!("  • {}\n", item)); }
+            }
             output.push_str("─".repeat(40).as_str());
             output.push('\n');
         }
@@ -291,13 +705,57 @@ impl App {
     }
 
     async fn handle_key(&mut self, code: KeyCode) -> Result<bool> {
+        if let Some(id) = self.confirm_delete.take() {
+            if code == KeyCode::Char('y') {
+                self.store.delete_session(&id).await?;
+                self.request(Load::Session(id));
+            }
+            return Ok(false);
+        }
+        if self.comparison.is_some() {
+            if matches!(code, KeyCode::Esc | KeyCode::Char('c' | 'q')) { self.comparison = None; }
+            return Ok(false);
+        }
+        if let Some((id, invocation)) = self.shown_approval().map(|(id, a)| (id.to_string(), a.invocation_id.clone())) {
+            match code {
+                KeyCode::Char(c @ ('y' | 'n')) => {
+                    let decision = ApprovalDecision { approve: c == 'y', reason: if c == 'y' { String::new() } else { "denied by operator".into() } };
+                    self.store.decide_approval(&id, &invocation, &decision).await?;
+                    self.request(Load::Session(id));
+                }
+                KeyCode::Esc => { self.dismissed_approvals.insert(invocation); }
+                _ => {}
+            }
+            return Ok(false);
+        }
+        if let Some(query) = &mut self.palette {
+            match code {
+                KeyCode::Esc => self.palette = None,
+                KeyCode::Down => self.palette_sel = (self.palette_sel + 1).min(self.palette_matches().len().saturating_sub(1)),
+                KeyCode::Up => self.palette_sel = self.palette_sel.saturating_sub(1),
+                KeyCode::Backspace => { query.pop(); self.palette_sel = 0; }
+                KeyCode::Char(c) => { query.push(c); self.palette_sel = 0; }
+                KeyCode::Enter => {
+                    let matches = self.palette_matches();
+                    self.palette = None;
+                    if let Some(command) = matches.get(self.palette_sel.min(matches.len().saturating_sub(1))) {
+                        self.start_command(*command).await?;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(false);
+        }
         if self.search_mode {
             match code {
                 KeyCode::Esc => {
                     self.search_mode = false;
                     match self.tab {
+                        Tab::Active => { self.session_search.clear(); self.clamp_active_selection(); }
                         Tab::Chains => { self.chain_search.clear(); self.filter_chains(); }
                         Tab::Artifacts => { self.artifact_search.clear(); self.filter_artifacts(); }
+                        Tab::Search => self.search_query.clear(),
+                        Tab::History => { self.history_search.clear(); self.request(Load::History(self.history_query())); }
                         _ => {}
                     }
                 }
@@ -306,20 +764,31 @@ impl App {
                     match self.tab {
                         Tab::Chains => self.load_selected_chain().await,
                         Tab::Artifacts => self.load_selected_artifact().await,
+                        Tab::Search => {
+                            let query = self.search_query.clone();
+                            self.run_search(format!("/{query}"), &query, 25, &SearchFilters::default()).await;
+                        }
+                        Tab::History => self.request(Load::History(self.history_query())),
                         _ => {}
                     }
                 }
                 KeyCode::Backspace => {
                     match self.tab {
+                        Tab::Active => { self.session_search.pop(); self.clamp_active_selection(); }
                         Tab::Chains => { self.chain_search.pop(); self.filter_chains(); }
                         Tab::Artifacts => { self.artifact_search.pop(); self.filter_artifacts(); }
+                        Tab::Search => { self.search_query.pop(); }
+                        Tab::History => { self.history_search.pop(); }
                         _ => {}
                     }
                 }
                 KeyCode::Char(c) => {
                     match self.tab {
+                        Tab::Active => { self.session_search.push(c); self.clamp_active_selection(); }
                         Tab::Chains => { self.chain_search.push(c); self.filter_chains(); }
                         Tab::Artifacts => { self.artifact_search.push(c); self.filter_artifacts(); }
+                        Tab::Search => self.search_query.push(c),
+                        Tab::History => self.history_search.push(c),
                         _ => {}
                     }
                 }
@@ -327,11 +796,38 @@ impl App {
             }
             return Ok(false);
         }
+        if let Some(i) = self.answer_choice {
+            let options = self.answer_target.as_ref().and_then(|id| self.pending_asks.get(id)).map(|a| a.options.clone()).unwrap_or_default();
+            match code {
+                KeyCode::Esc => { self.answer_choice = None; self.answer_target = None; }
+                KeyCode::Char('j') | KeyCode::Down => self.answer_choice = Some((i + 1).min(options.len().saturating_sub(1))),
+                KeyCode::Char('k') | KeyCode::Up => self.answer_choice = Some(i.saturating_sub(1)),
+                KeyCode::Enter => {
+                    self.answer_choice = None;
+                    if let (Some(id), Some(option)) = (self.answer_target.take(), options.get(i)) {
+                        self.store.set_answer(&id, option).await?;
+                        self.request(Load::Session(id));
+                    }
+                }
+                _ => {}
+            }
+            return Ok(false);
+        }
         if self.input_mode {
             match code {
-                KeyCode::Esc => self.input_mode = false,
+                KeyCode::Esc => { self.input_mode = false; self.answer_target = None; self.broadcasting = false; self.todo_input = None; self.command_input = None; }
                 KeyCode::Enter => {
                     self.input_mode = false;
+                    if let Some((command, session)) = self.command_input.take() {
+                        self.finish_command(command, session).await?;
+                    } else if let Some(target) = self.todo_input.take() {
+                        self.submit_todo_input(target).await?;
+                    } else if let Some(id) = self.answer_target.take() {
+                        self.store.set_answer(&id, &self.input).await?;
+                        self.request(Load::Session(id));
+                    } else if std::mem::take(&mut self.broadcasting) && !self.input.trim().is_empty() {
+                        for id in self.store.broadcast(&self.input).await? { self.request(Load::Session(id)); }
+                    }
                 }
                 KeyCode::Backspace => { self.input.pop(); }
                 KeyCode::Char(c) => self.input.push(c),
@@ -340,11 +836,15 @@ impl App {
         } else {
             match code {
                 KeyCode::Char('q') => return Ok(true),
+                KeyCode::Char(':') => { self.palette = Some(String::new()); self.palette_sel = 0; }
                 KeyCode::Tab => {
                     self.tab = match self.tab {
                         Tab::Active => Tab::Chains,
-                        Tab::Chains => Tab::Artifacts,
-                        Tab::Artifacts => Tab::History,
+                        Tab::Chains => Tab::Todos,
+                        Tab::Todos => Tab::Board,
+                        Tab::Board => Tab::Artifacts,
+                        Tab::Artifacts => Tab::Search,
+                        Tab::Search => Tab::History,
                         Tab::History => Tab::Active,
                     }
                 }
@@ -353,6 +853,7 @@ impl App {
                     match self.tab {
                         Tab::Chains => self.load_selected_chain().await,
                         Tab::Artifacts => self.load_selected_artifact().await,
+                        Tab::Search => self.run_selected_saved_search().await,
                         _ => {}
                     }
                 }
@@ -361,6 +862,7 @@ impl App {
                     match self.tab {
                         Tab::Chains => self.load_selected_chain().await,
                         Tab::Artifacts => self.load_selected_artifact().await,
+                        Tab::Search => self.run_selected_saved_search().await,
                         _ => {}
                     }
                 }
@@ -368,22 +870,95 @@ impl App {
                     match self.tab {
                         Tab::Chains => self.load_selected_chain().await,
                         Tab::Artifacts => self.load_selected_artifact().await,
+                        Tab::Search => self.run_selected_saved_search().await,
                         _ => {}
                     }
                 }
-                KeyCode::Char('/') if matches!(self.tab, Tab::Chains | Tab::Artifacts) => {
+                KeyCode::Char('/') if matches!(self.tab, Tab::Active | Tab::Chains | Tab::Artifacts | Tab::Search | Tab::History) => {
                     self.search_mode = true;
                     match self.tab {
+                        Tab::Active => self.session_search.clear(),
                         Tab::Chains => self.chain_search.clear(),
                         Tab::Artifacts => self.artifact_search.clear(),
+                        Tab::Search => self.search_query.clear(),
+                        Tab::History => self.history_search.clear(),
                         _ => {}
                     }
                 }
-                KeyCode::Char('r') => self.refresh().await?,
+                KeyCode::Char('s') if self.tab == Tab::Search => self.save_query().await?,
+                KeyCode::Char('s') if self.tab == Tab::Chains => self.cycle_chain_status().await?,
+                KeyCode::Char('t') if self.tab == Tab::Chains => {
+                    if let Some((name, _, _)) = self.chain_state.selected().and_then(|i| self.chains_filtered.get(i)) {
+                        self.todo_input = Some(TodoInput::New(name.clone()));
+                        self.input.clear();
+                        self.input_mode = true;
+                    }
+                }
+                KeyCode::Char('x') | KeyCode::Char(' ') if self.tab == Tab::Todos => self.toggle_selected_todo().await?,
+                KeyCode::Char('v') if self.tab == Tab::Active => self.split_view = !self.split_view,
+                KeyCode::Char('c') if matches!(self.tab, Tab::Active | Tab::History) => self.compare_selected().await?,
+                KeyCode::Esc if self.compare_pick.is_some() => self.compare_pick = None,
+                KeyCode::Char('v') if self.tab == Tab::Board => {
+                    self.board_todos = !self.board_todos;
+                    (self.board_col, self.board_rows) = (0, [0; 3]);
+                }
+                KeyCode::Char('h') | KeyCode::Left if self.tab == Tab::Board => self.board_col = self.board_col.saturating_sub(1),
+                KeyCode::Char('l') | KeyCode::Right if self.tab == Tab::Board => {
+                    self.board_col = (self.board_col + 1).min(self.board_columns().len() - 1);
+                }
+                KeyCode::Char('H') if self.tab == Tab::Board => self.move_board_card(false).await?,
+                KeyCode::Char('L') if self.tab == Tab::Board => self.move_board_card(true).await?,
+                KeyCode::Char('a') if self.tab == Tab::Todos => {
+                    if let Some(todo) = self.todo_state.selected().and_then(|i| self.todos.get(i)) {
+                        self.todo_input = Some(TodoInput::Assign(todo.id.clone()));
+                        self.input = todo.assignee.clone().unwrap_or_default();
+                        self.input_mode = true;
+                    }
+                }
+                KeyCode::Char('J') if self.tab == Tab::Search => {
+                    self.result_sel = (self.result_sel + 1).min(self.search_results.len().saturating_sub(1));
+                }
+                KeyCode::Char('K') if self.tab == Tab::Search => self.result_sel = self.result_sel.saturating_sub(1),
+                KeyCode::Char('p') if self.tab == Tab::Search => self.prioritize_result(true, 0).await?,
+                KeyCode::Char('+') if self.tab == Tab::Search => self.prioritize_result(false, 1).await?,
+                KeyCode::Char('-') if self.tab == Tab::Search => self.prioritize_result(false, -1).await?,
+                KeyCode::Char('r') => self.request(Load::All),
+                KeyCode::Char('a') if self.tab == Tab::Active => {
+                    if let Some(s) = self.selected_session().cloned() {
+                        if let Some(ask) = self.pending_asks.get(&s.id) {
+                            self.answer_target = Some(s.id.clone());
+                            if ask.options.is_empty() {
+                                self.input.clear();
+                                self.input_mode = true;
+                            } else {
+                                self.answer_choice = Some(0);
+                            }
+                        }
+                    }
+                }
+                KeyCode::Char('a') if self.tab == Tab::History => {
+                    if let Some(id) = self.selected_session().map(|s| s.id.clone()) {
+                        self.store.touch_and_reactivate(&id).await?;
+                        self.request(Load::Session(id));
+                    }
+                }
+                KeyCode::Char('B') => {
+                    self.broadcasting = true;
+                    self.input.clear();
+                    self.input_mode = true;
+                }
+                KeyCode::Char('A') => {
+                    if let Some(pending) = self.selected_session().and_then(|s| self.approvals.get(&s.id)) {
+                        for a in pending { self.dismissed_approvals.remove(&a.invocation_id); }
+                    }
+                }
+                KeyCode::Char('D') => self.confirm_delete = self.selected_session().map(|s| s.id.clone()),
                 KeyCode::Char('d') => {
                     match self.tab {
                         Tab::Chains => self.delete_selected_chain().await?,
+                        Tab::Todos => self.delete_selected_todo().await?,
                         Tab::Artifacts => self.delete_selected_artifact().await?,
+                        Tab::Search => self.delete_selected_saved_search().await?,
                         Tab::Active => self.archive_selected().await?,
                         _ => {}
                     }
@@ -398,6 +973,12 @@ impl App {
                 KeyCode::Char('l') | KeyCode::PageDown if self.tab == Tab::Artifacts => {
                     self.artifact_scroll = self.artifact_scroll.saturating_add(5);
                 }
+                KeyCode::PageDown if self.tab == Tab::History => self.request(Load::OlderHistory),
+                KeyCode::Char('f') if self.tab == Tab::History => {
+                    self.history_period = self.history_period.next();
+                    self.request(Load::History(self.history_query()));
+                }
+                KeyCode::Char('g') if matches!(self.tab, Tab::Active | Tab::History) => self.cycle_agent_filter(),
                 KeyCode::Char('h') | KeyCode::PageUp if self.tab == Tab::Artifacts => {
                     self.artifact_scroll = self.artifact_scroll.saturating_sub(5);
                 }
@@ -411,27 +992,135 @@ impl App {
         if let Some(i) = self.chain_state.selected() {
             if let Some((name, _, _)) = self.chains_filtered.get(i).cloned() {
                 self.store.delete_chain(&name).await?;
-                self.refresh().await?;
+                self.request(Load::All);
             }
         }
         Ok(())
     }
 
+    // active -> blocked -> done -> active
+    async fn cycle_chain_status(&mut self) -> Result<()> {
+        let Some((name, _, _)) = self.chain_state.selected().and_then(|i| self.chains_filtered.get(i)) else { return Ok(()) };
+        let status = self.chain_statuses.get(name).copied().unwrap_or_default().next();
+        self.store.set_chain_status(name, status).await?;
+        self.chain_statuses.insert(name.clone(), status);
+        Ok(())
+    }
+
+    async fn submit_todo_input(&mut self, target: TodoInput) -> Result<()> {
+        let input = self.input.trim().to_string();
+        match target {
+            TodoInput::New(chain_name) if !input.is_empty() => {
+                let todo = Todo {
+                    id: format!("{}_{}", now(), short_id()),
+                    text: input,
+                    chain_name: Some(chain_name),
+                    session_id: None,
+                    assignee: None,
+                    created: now(),
+                    completed_at: None,
+                    redacted: false,
+                };
+                self.store.save_todo(&todo).await?;
+            }
+            TodoInput::New(_) => return Ok(()),
+            TodoInput::Assign(id) => {
+                let Some(mut todo) = self.store.get_todo(&id).await? else { return Ok(()) };
+                todo.assignee = (!input.is_empty()).then_some(input);
+                self.store.save_todo(&todo).await?;
+            }
+        }
+        self.request(Load::Todos);
+        Ok(())
+    }
+
+    async fn toggle_selected_todo(&mut self) -> Result<()> {
+        let Some(mut todo) = self.todo_state.selected().and_then(|i| self.todos.get(i)).cloned() else { return Ok(()) };
+        todo.completed_at = if todo.done() { None } else { Some(now()) };
+        self.store.save_todo(&todo).await?;
+        self.request(Load::Todos);
+        Ok(())
+    }
+
+    async fn delete_selected_todo(&mut self) -> Result<()> {
+        if let Some(todo) = self.todo_state.selected().and_then(|i| self.todos.get(i)) {
+            self.store.delete_todo(&todo.id).await?;
+            self.request(Load::Todos);
+        }
+        Ok(())
+    }
+
+    fn board_columns(&self) -> Vec<BoardColumn> {
+        if self.board_todos {
+            let card = |t: &Todo| {
+                let assignee = t.assignee.as_ref().map(|a| format!(" @{a}")).unwrap_or_default();
+                (t.id.clone(), format!("{}{}", t.text, assignee))
+            };
+            vec![
+                ("Open", Color::Yellow, self.todos.iter().filter(|t| !t.done()).map(card).collect()),
+                ("Done", Color::DarkGray, self.todos.iter().filter(|t| t.done()).map(card).collect()),
+            ]
+        } else {
+            [ChainStatus::Active, ChainStatus::Blocked, ChainStatus::Done].into_iter().map(|status| {
+                let cards = self.chains.iter()
+                    .filter(|(name, _)| self.chain_statuses.get(name).copied().unwrap_or_default() == status)
+                    .map(|(name, count)| (name.clone(), format!("{} [{}]", name, count)))
+                    .collect();
+                let (title, color) = match status {
+                    ChainStatus::Active => ("Active", Color::Green),
+                    ChainStatus::Blocked => ("Blocked", Color::Red),
+                    ChainStatus::Done => ("Done", Color::DarkGray),
+                };
+                (title, color, cards)
+            }).collect()
+        }
+    }
+
+    // Moves the selected card one column left or right and keeps it selected there
+    async fn move_board_card(&mut self, right: bool) -> Result<()> {
+        let columns = self.board_columns();
+        let Some((key, _)) = columns.get(self.board_col).and_then(|(_, _, cards)| cards.get(self.board_rows[self.board_col])).cloned() else { return Ok(()) };
+        let to = if right { self.board_col + 1 } else { self.board_col.wrapping_sub(1) };
+        if to >= columns.len() { return Ok(()); }
+        if self.board_todos {
+            let Some(todo) = self.todos.iter_mut().find(|t| t.id == key) else { return Ok(()) };
+            todo.completed_at = (to == 1).then(now);
+            self.store.save_todo(todo).await?;
+            self.request(Load::Todos);
+        } else {
+            let status = [ChainStatus::Active, ChainStatus::Blocked, ChainStatus::Done][to];
+            self.store.set_chain_status(&key, status).await?;
+            self.chain_statuses.insert(key.clone(), status);
+        }
+        self.board_col = to;
+        self.board_rows[to] = self.board_columns()[to].2.iter().position(|(k, _)| *k == key).unwrap_or(0);
+        Ok(())
+    }
+
     async fn delete_selected_artifact(&mut self) -> Result<()> {
         if let Some(i) = self.artifact_state.selected() {
             if let Some((artifact, _)) = self.artifacts_filtered.get(i).cloned() {
                 self.store.delete_artifact(&artifact.id).await?;
-                self.refresh().await?;
+                self.request(Load::All);
             }
         }
         Ok(())
     }
 
+    async fn delete_selected_saved_search(&mut self) -> Result<()> {
+        if let Some(search) = self.saved_state.selected().and_then(|i| self.saved_searches.get(i)).cloned() {
+            self.store.delete_saved_search(&search.name).await?;
+            self.request(Load::All);
+        }
+        Ok(())
+    }
+
     fn next(&mut self) {
         match self.tab {
             Tab::Active => {
+                let shown = self.active_sessions().len();
                 let i = self.session_state.selected()
-                    .map(|i| (i + 1).min(self.sessions.len().saturating_sub(1)))
+                    .map(|i| (i + 1).min(shown.saturating_sub(1)))
                     .unwrap_or(0);
                 self.session_state.select(Some(i));
             }
@@ -441,13 +1130,34 @@ impl App {
                     .unwrap_or(0);
                 self.chain_state.select(Some(i));
             }
+            Tab::Todos => {
+                let i = self.todo_state.selected()
+                    .map(|i| (i + 1).min(self.todos.len().saturating_sub(1)))
+                    .unwrap_or(0);
+                self.todo_state.select(Some(i));
+            }
+            Tab::Board => {
+                let len = self.board_columns().get(self.board_col).map_or(0, |(_, _, cards)| cards.len());
+                self.board_rows[self.board_col] = (self.board_rows[self.board_col] + 1).min(len.saturating_sub(1));
+            }
             Tab::Artifacts => {
                 let i = self.artifact_state.selected()
                     .map(|i| (i + 1).min(self.artifacts_filtered.len().saturating_sub(1)))
                     .unwrap_or(0);
                 self.artifact_state.select(Some(i));
             }
-            Tab::History => {}
+            Tab::Search => {
+                let i = self.saved_state.selected()
+                    .map(|i| (i + 1).min(self.saved_searches.len().saturating_sub(1)))
+                    .unwrap_or(0);
+                self.saved_state.select(Some(i));
+            }
+            Tab::History => {
+                let i = self.history_state.selected()
+                    .map(|i| (i + 1).min(self.history.len().saturating_sub(1)))
+                    .unwrap_or(0);
+                self.history_state.select(Some(i));
+            }
         }
     }
 
@@ -461,22 +1171,161 @@ impl App {
                 let i = self.chain_state.selected().map(|i| i.saturating_sub(1)).unwrap_or(0);
                 self.chain_state.select(Some(i));
             }
+            Tab::Todos => {
+                let i = self.todo_state.selected().map(|i| i.saturating_sub(1)).unwrap_or(0);
+                self.todo_state.select(Some(i));
+            }
+            Tab::Board => self.board_rows[self.board_col] = self.board_rows[self.board_col].saturating_sub(1),
             Tab::Artifacts => {
                 let i = self.artifact_state.selected().map(|i| i.saturating_sub(1)).unwrap_or(0);
                 self.artifact_state.select(Some(i));
             }
-            Tab::History => {}
+            Tab::Search => {
+                let i = self.saved_state.selected().map(|i| i.saturating_sub(1)).unwrap_or(0);
+                self.saved_state.select(Some(i));
+            }
+            Tab::History => {
+                let i = self.history_state.selected().map(|i| i.saturating_sub(1)).unwrap_or(0);
+                self.history_state.select(Some(i));
+            }
         }
     }
 
-    async fn archive_selected(&mut self) -> Result<()> {
-        if self.tab == Tab::Active {
-            if let Some(i) = self.session_state.selected() {
-                if let Some(s) = self.sessions.get(i) {
+    // Oldest approval across active sessions that hasn't been put off, shown as a modal over every tab
+    // (held back while typing so it doesn't swallow keystrokes)
+    fn shown_approval(&self) -> Option<(&str, &PendingApproval)> {
+        if self.input_mode || self.search_mode || self.answer_choice.is_some() || self.palette.is_some() { return None; }
+        self.sessions.iter()
+            .filter_map(|s| self.approvals.get(&s.id).map(|pending| (s.id.as_str(), pending)))
+            .flat_map(|(id, pending)| pending.iter().map(move |a| (id, a)))
+            .filter(|(_, a)| !self.dismissed_approvals.contains(&a.invocation_id))
+            .min_by_key(|(_, a)| a.ts)
+    }
+
+    // The first [c] picks the selected session; the second compares it with the one selected then
+    async fn compare_selected(&mut self) -> Result<()> {
+        let Some(id) = self.selected_session().map(|s| s.id.clone()) else { return Ok(()) };
+        match self.compare_pick.take() {
+            None => self.compare_pick = Some(id),
+            Some(first) if first == id => {}
+            Some(first) => {
+                if let (Some(a), Some(b)) = (self.store.session_profile(&first).await?, self.store.session_profile(&id).await?) {
+                    self.comparison = Some(Box::new((a, b)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // The Active tab's list as shown: filtered by agent and the `/` search, best match first
+    fn active_sessions(&self) -> Vec<&Session> {
+        filter_sessions(&self.sessions, &self.last_msgs, self.agent_filter.as_deref(), &self.session_search)
+    }
+
+    fn clamp_active_selection(&mut self) {
+        let shown = self.active_sessions().len();
+        if self.session_state.selected().is_none_or(|i| i >= shown) {
+            self.session_state.select(if shown == 0 { None } else { Some(0) });
+        }
+    }
+
+    fn selected_session(&self) -> Option<&Session> {
+        match self.tab {
+            Tab::Active => self.session_state.selected().and_then(|i| self.active_sessions().get(i).copied()),
+            Tab::History => self.history_state.selected().and_then(|i| self.history.get(i)),
+            _ => None,
+        }
+    }
+
+    // Commands whose label fuzzy-matches the palette's text, best first
+    fn palette_matches(&self) -> Vec<PaletteCommand> {
+        let query = self.palette.as_deref().unwrap_or_default().trim().to_lowercase();
+        if query.is_empty() { return PaletteCommand::ALL.to_vec(); }
+        let mut scored: Vec<(PaletteCommand, f64)> = PaletteCommand::ALL.iter()
+            .map(|c| {
+                let boost = if c.label().contains(&query) { 0.3 } else { 0.0 };
+                (*c, (jaro_winkler(c.label(), &query) + boost).min(1.0))
+            })
+            .filter(|(_, score)| *score > 0.6)
+            .collect();
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        scored.into_iter().map(|(c, _)| c).collect()
+    }
+
+    // Runs a palette command, or asks for its text first
+    async fn start_command(&mut self, command: PaletteCommand) -> Result<()> {
+        let session = self.selected_session().cloned();
+        match command {
+            PaletteCommand::MarkDone => {
+                if let Some(s) = session.filter(|s| s.status == Status::Active) {
                     self.store.mark_done(&s.id).await?;
-                    self.refresh().await?;
+                    self.request(Load::Session(s.id));
                 }
             }
+            PaletteCommand::Broadcast => {
+                self.broadcasting = true;
+                self.input.clear();
+                self.input_mode = true;
+            }
+            PaletteCommand::Rename if session.is_none() => {}
+            _ => {
+                self.input = match (command, &session) {
+                    (PaletteCommand::Rename, Some(s)) => s.name.clone().unwrap_or_default(),
+                    (PaletteCommand::FilterAgent, _) => self.agent_filter.clone().unwrap_or_default(),
+                    _ => String::new(),
+                };
+                self.command_input = Some((command, session.map(|s| s.id)));
+                self.input_mode = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish_command(&mut self, command: PaletteCommand, session: Option<String>) -> Result<()> {
+        let text = self.input.trim().to_string();
+        match command {
+            PaletteCommand::Rename => {
+                let Some(id) = session else { return Ok(()) };
+                self.store.rename_session(&id, Some(text.as_str()).filter(|t| !t.is_empty())).await?;
+                self.request(Load::Session(id));
+            }
+            PaletteCommand::OpenChain => {
+                self.tab = Tab::Chains;
+                self.chain_search = text;
+                self.filter_chains();
+                self.chain_state.select(if self.chains_filtered.is_empty() { None } else { Some(0) });
+                self.load_selected_chain().await;
+            }
+            PaletteCommand::SaveNote if !text.is_empty() => {
+                let session = match &session { Some(id) => self.store.get_session(id).await?, None => None };
+                let ts = now();
+                self.store.save_memory(&Memory {
+                    id: format!("{ts}_{}", short_id()),
+                    session_id: session.as_ref().map_or_else(|| "operator".to_string(), |s| s.id.clone()),
+                    title: truncate_chars(&text, 60).to_string(),
+                    content: text,
+                    ts,
+                    expires_at: None,
+                    decay_days: None,
+                    archived_at: None,
+                    redacted: false,
+                    pinned: false,
+                    importance: 0,
+                    project: session.and_then(|s| s.project),
+                }).await?;
+            }
+            PaletteCommand::FilterAgent => self.set_agent_filter(Some(text).filter(|t| !t.is_empty())),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn archive_selected(&mut self) -> Result<()> {
+        if self.tab == Tab::Active {
+            if let Some(id) = self.selected_session().map(|s| s.id.clone()) {
+                self.store.mark_done(&id).await?;
+                self.request(Load::Session(id));
+            }
         }
         Ok(())
     }
@@ -492,15 +1341,25 @@ impl App {
             .split(f.area());
 
         let chains_title = format!("Chains ({})", self.chains.len());
+        let todos_title = format!("Todos ({})", self.todos.iter().filter(|t| !t.done()).count());
         let artifacts_title = format!("Artifacts ({})", self.artifacts.len());
-        let titles: Vec<&str> = vec!["Active", &chains_title, &artifacts_title, "History"];
+        let titles: Vec<&str> = vec!["Active", &chains_title, &todos_title, "Board", &artifacts_title, "Search", "History"];
+        // Data shown is stale while the Redis circuit is open
+        let header = match self.store.degraded() {
+            Some(wait) => Line::from(format!(" tinymem ⚠ redis unavailable, retrying in {}s ", wait.as_secs() + 1)).red().bold(),
+            None if !self.handoffs.is_empty() => Line::from(format!(" tinymem · ⇢ {} pending handoff{} ", self.handoffs.len(), if self.handoffs.len() == 1 { "" } else { "s" })).magenta(),
+            None => Line::from(" tinymem "),
+        };
         let tabs = Tabs::new(titles)
-            .block(Block::default().borders(Borders::ALL).title(" tinymem "))
+            .block(Block::default().borders(Borders::ALL).title(header))
             .select(match self.tab {
                 Tab::Active => 0,
                 Tab::Chains => 1,
-                Tab::Artifacts => 2,
-                Tab::History => 3,
+                Tab::Todos => 2,
+                Tab::Board => 3,
+                Tab::Artifacts => 4,
+                Tab::Search => 5,
+                Tab::History => 6,
             })
             .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
         f.render_widget(tabs, chunks[0]);
@@ -508,10 +1367,103 @@ impl App {
         match self.tab {
             Tab::Active => self.draw_active(f, chunks[1]),
             Tab::Chains => self.draw_chains(f, chunks[1]),
+            Tab::Todos => self.draw_todos(f, chunks[1]),
+            Tab::Board => self.draw_board(f, chunks[1]),
             Tab::Artifacts => self.draw_artifacts(f, chunks[1]),
+            Tab::Search => self.draw_search(f, chunks[1]),
             Tab::History => self.draw_history(f, chunks[1]),
         }
         self.draw_status(f, chunks[2]);
+        self.draw_toasts(f, chunks[1]);
+        self.draw_approval(f);
+        self.draw_palette(f);
+        self.draw_comparison(f);
+    }
+
+    // Stacked in the bottom-right corner of `area`, newest at the bottom
+    fn draw_toasts(&mut self, f: &mut Frame, area: Rect) {
+        self.toasts.retain(|t| t.at.elapsed() < TOAST_TTL);
+        if self.toasts.is_empty() { return; }
+        let w = area.width.min(60);
+        let lines: Vec<Line> = self.toasts.iter()
+            .map(|t| Line::styled(preview(&t.text, w.saturating_sub(5) as usize), Style::default().fg(t.color)))
+            .collect();
+        let h = (lines.len() as u16 + 2).min(area.height);
+        let rect = Rect { x: area.x + area.width - w, y: area.y + area.height - h, width: w, height: h };
+        f.render_widget(Clear, rect);
+        f.render_widget(Paragraph::new(lines).block(Block::default().borders(Borders::ALL).border_style(Style::default().dim())), rect);
+    }
+
+    fn draw_comparison(&self, f: &mut Frame) {
+        let Some(pair) = &self.comparison else { return };
+        let area = f.area();
+        let (w, h) = (area.width * 9 / 10, area.height * 8 / 10);
+        let modal = Rect { x: area.x + (area.width - w) / 2, y: area.y + (area.height - h) / 2, width: w, height: h };
+        f.render_widget(Clear, modal);
+        let block = Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Yellow)).title(" Compare sessions (yellow: only this one) ");
+        let inner = block.inner(modal);
+        f.render_widget(block, modal);
+        let columns = Layout::default().direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)]).split(inner);
+        for (col, (this, other)) in columns.iter().zip([(&pair.0, &pair.1), (&pair.1, &pair.0)]) {
+            let s = &this.session;
+            let only = |shared: bool| if shared { Style::default() } else { Style::default().fg(Color::Yellow) };
+            let mut lines = vec![
+                Line::styled(s.name.clone().unwrap_or_else(|| s.id.clone()), Style::default().add_modifier(Modifier::BOLD)),
+                Line::styled(format!("{} · {} · ran {}", s.agent, if s.status == Status::Done { "done" } else { "active" }, format_elapsed(s.last_activity - s.created)), Style::default().dim()),
+                Line::from(format!("{} hooks, {} errors", this.hooks, this.errors)),
+                Line::from(""),
+                Line::styled(format!("Tools ({})", this.tools.len()), Style::default().add_modifier(Modifier::BOLD)),
+            ];
+            lines.extend(this.tools.iter().map(|(tool, n)| Line::styled(format!("  {tool} ×{n}"), only(other.tools.contains_key(tool)))));
+            lines.push(Line::from(""));
+            lines.push(Line::styled(format!("Files ({})", this.files.len()), Style::default().add_modifier(Modifier::BOLD)));
+            lines.extend(this.files.iter().map(|file| Line::styled(format!("  {file}"), only(other.files.contains(file)))));
+            lines.push(Line::from(""));
+            lines.push(Line::styled(format!("Chain links ({})", this.links.len()), Style::default().add_modifier(Modifier::BOLD)));
+            lines.extend(this.links.iter().map(|l| Line::from(format!("  {l}"))));
+            f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(Block::default().borders(Borders::RIGHT)), *col);
+        }
+    }
+
+    fn draw_palette(&self, f: &mut Frame) {
+        let Some(query) = &self.palette else { return };
+        let matches = self.palette_matches();
+        let area = f.area();
+        let (w, h) = (area.width.min(50), area.height.min(PaletteCommand::ALL.len() as u16 + 4));
+        let modal = Rect { x: area.x + (area.width - w) / 2, y: area.y + area.height / 4, width: w, height: h };
+        let mut lines = vec![Line::from(format!(": {query}_")), Line::from("")];
+        lines.extend(matches.iter().enumerate().map(|(i, c)| {
+            if i == self.palette_sel { Line::styled(format!("▸ {}", c.label()), Style::default().add_modifier(Modifier::REVERSED)) } else { Line::from(format!("  {}", c.label())) }
+        }));
+        if matches.is_empty() { lines.push(Line::styled("  no matching command", Style::default().dim())); }
+        let p = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Yellow)).title(" Command "));
+        f.render_widget(Clear, modal);
+        f.render_widget(p, modal);
+    }
+
+    fn draw_approval(&self, f: &mut Frame) {
+        let Some((id, a)) = self.shown_approval() else { return };
+        let area = f.area();
+        let (w, h) = (area.width.min(70), area.height.min(12));
+        let modal = Rect { x: area.x + (area.width - w) / 2, y: area.y + (area.height - h) / 2, width: w, height: h };
+        let meta = if a.meta.is_null() { String::new() } else { a.meta.to_string() };
+        let meta = preview(&meta, 300);
+        let name = self.sessions.iter().find(|s| s.id == id).and_then(|s| s.name.as_deref()).unwrap_or(id);
+        let text = Text::from(vec![
+            Line::from(vec![Span::raw(format!("{name} wants to run ")), Span::styled(a.task.as_str(), Style::default().add_modifier(Modifier::BOLD))]),
+            Line::styled(format!("waiting {}", format_elapsed(now() - a.ts)), Style::default().dim()),
+            Line::from(""),
+            Line::from(meta),
+            Line::from(""),
+            Line::styled("[y] approve | [n] deny | [Esc] later", Style::default().fg(Color::Yellow)),
+        ]);
+        let p = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Yellow)).title(" Approval needed "))
+            .wrap(Wrap { trim: true });
+        f.render_widget(Clear, modal);
+        f.render_widget(p, modal);
     }
 
     fn draw_active(&mut self, f: &mut Frame, area: Rect) {
@@ -520,58 +1472,155 @@ impl App {
             .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
             .split(area);
 
-        let items: Vec<ListItem> = self
-            .sessions
+        let shown = filter_sessions(&self.sessions, &self.last_msgs, self.agent_filter.as_deref(), &self.session_search);
+        let items: Vec<ListItem> = shown
             .iter()
             .map(|s| {
-                let has_active_tool = self.active_tools.contains_key(&s.id);
+                let tools = self.active_tools.get(&s.id);
+                let failing = self.errors.get(&s.id).and_then(|e| e.first()).is_some_and(|e| now() - e.ts < ERROR_RECENT_SECS);
                 let (icon, color) = match &s.status {
                     Status::Done => ("○", Color::Gray),
-                    Status::Active if has_active_tool => ("⚙", Color::Cyan),
-                    Status::Active => ("●", Color::Green),
+                    Status::Active if self.approvals.contains_key(&s.id) => ("⏸", Color::Yellow),
+                    Status::Active if self.pending_asks.contains_key(&s.id) => ("?", Color::Yellow),
+                    Status::Active if failing => ("✗", Color::Red),
+                    Status::Active if tools.is_some_and(|t| t.iter().any(|t| is_stuck(t, self.stuck_tool_secs))) => ("⚙", Color::Red),
+                    Status::Active if tools.is_some() => ("⚙", Color::Cyan),
+                    Status::Active => match self.presence.liveness(s, now()) {
+                        Some(Liveness::Disconnected) => ("◌", Color::DarkGray),
+                        Some(Liveness::Idle) => ("◑", Color::Blue),
+                        _ => ("●", Color::Green),
+                    },
                 };
                 let name = s.name.as_deref().unwrap_or(&s.id);
                 let last_msg = self.last_msgs.get(&s.id).map(|m| m.as_str()).unwrap_or("");
+                let spark = self.activity.get(&s.id).map(|c| sparkline(c)).unwrap_or_default();
                 ListItem::new(vec![
                     Line::from(vec![
                         Span::styled(format!("{icon} "), Style::default().fg(color)),
+                        agent_span(&s.agent),
                         Span::raw(name),
+                        Span::styled(format!(" {spark}"), Style::default().fg(Color::Cyan)),
                     ]),
                     Line::from(Span::styled(last_msg, Style::default().dim())),
                 ])
             })
             .collect();
+        let sessions_title = if self.usage_today.input_tokens + self.usage_today.output_tokens > 0 {
+            format!(" Sessions · today {} ", format_usage(&self.usage_today))
+        } else {
+            " Sessions ".to_string()
+        };
+        let sessions_title = if self.max_active_sessions > 0 && self.sessions.len() >= self.max_active_sessions {
+            let rejected = if self.sessions_rejected > 0 { format!(", {} rejected", self.sessions_rejected) } else { String::new() };
+            format!("{sessions_title}· limit of {} reached{rejected} ", self.max_active_sessions)
+        } else {
+            sessions_title
+        };
+        let sessions_title = if self.search_mode {
+            format!("{sessions_title}[/{}] ", self.session_search)
+        } else if self.agent_filter.is_some() || !self.session_search.is_empty() {
+            let filters: Vec<&str> = self.agent_filter.as_deref().into_iter().chain(Some(self.session_search.as_str()).filter(|q| !q.is_empty())).collect();
+            format!("{sessions_title}(filter: {}, {} of {}) ", filters.join(", "), shown.len(), self.sessions.len())
+        } else {
+            sessions_title
+        };
         let list = List::new(items)
-            .block(Block::default().borders(Borders::ALL).title(" Sessions "))
+            .block(Block::default().borders(Borders::ALL).title(sessions_title))
             .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
         f.render_stateful_widget(list, chunks[0], &mut self.session_state);
 
         if let Some(i) = self.session_state.selected() {
-            if let Some(s) = self.sessions.get(i) {
-                let active_tool = self.active_tools.get(&s.id);
-                let (status_str, hint) = match (&s.status, active_tool) {
-                    (Status::Active, Some(tool)) => (
-                        format!("RUNNING: {tool}"),
-                        "\n\nTool in progress".to_string()
+            if let Some(s) = shown.get(i) {
+                let tools = self.active_tools.get(&s.id);
+                let status_style = match tools {
+                    Some(t) if t.iter().any(|t| is_stuck(t, self.stuck_tool_secs)) => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    _ => Style::default(),
+                };
+                let (status_str, hint) = match (&s.status, tools) {
+                    (Status::Active, Some(t)) => (
+                        format!("RUNNING: {}", t.iter().map(|t| format!("{} ({})", t.tool, format_elapsed(now() - t.since))).collect::<Vec<_>>().join(", ")),
+                        format!("\n\n{} in progress", if t.len() == 1 { "Tool".to_string() } else { format!("{} tools", t.len()) })
                     ),
-                    (Status::Active, None) => ("Active".into(), String::new()),
+                    (Status::Active, None) => match self.presence.liveness(s, now()) {
+                        Some(Liveness::Disconnected) => (format!("Disconnected (last seen {} ago)", format_elapsed(now() - s.last_activity.max(s.last_heartbeat).max(s.created))), String::new()),
+                        Some(Liveness::Idle) => (format!("Idle (no tool activity for {})", format_elapsed(now() - s.last_activity.max(s.created))), String::new()),
+                        _ => ("Active".into(), String::new()),
+                    },
                     (Status::Done, _) => ("Done".into(), String::new()),
                 };
                 let hook_detail = self.last_hook_details.get(&s.id)
                     .map(|d| format!("\n\n{}", d))
                     .unwrap_or_default();
-                let detail = format!(
-                    "Agent: {}\nCWD: {}\nID: {}\n\n{}{}{}",
-                    s.agent, s.cwd, s.id, status_str, hint, hook_detail
-                );
+                let choosing = self.answer_choice.filter(|_| self.answer_target.as_ref() == Some(&s.id));
+                let question = self.pending_asks.get(&s.id).map(|a| {
+                    if a.options.is_empty() { return format!("\n\nQUESTION: {}\n[a] answer", a.question); }
+                    let options: Vec<String> = a.options.iter().enumerate()
+                        .map(|(i, o)| format!("{} {o}", if choosing == Some(i) { "▸" } else { " " }))
+                        .collect();
+                    let hint = if choosing.is_some() { "[j/k] choose | [Enter] answer" } else { "[a] choose an answer" };
+                    format!("\n\nQUESTION: {}\n{}\n{hint}", a.question, options.join("\n"))
+                }).unwrap_or_default();
+                let approvals = self.approvals.get(&s.id).map(|pending| {
+                    let tools: Vec<&str> = pending.iter().map(|a| a.task.as_str()).collect();
+                    format!("\n\nAWAITING APPROVAL: {}\n[A] review", tools.join(", "))
+                }).unwrap_or_default();
+                let related = self.related.get(&s.id).map(|r| r.as_str()).unwrap_or("");
+                let transcript = self.transcripts.get(&s.id).map(|t| t.as_str()).unwrap_or("");
+                let errors = self.errors.get(&s.id).map(|errors| {
+                    let lines: Vec<String> = errors.iter().take(5).map(|e| {
+                        let ago = format_elapsed(now() - e.ts);
+                        if e.message.is_empty() { format!("✗ {} {ago} ago", e.task) } else { format!("✗ {} {ago} ago: {}", e.task, e.message) }
+                    }).collect();
+                    format!("\n\nRecent errors ({})\n{}", errors.len(), lines.join("\n"))
+                }).unwrap_or_default();
+                let usage = self.usage.get(&s.id).map(|u| format!("\n\nUsage: {}", format_usage(u))).unwrap_or_default();
+                let handoffs: Vec<String> = self.handoffs.iter().filter_map(|h| {
+                    let ago = format_elapsed(now() - h.created);
+                    if h.from_session == s.id { Some(format!("⇢ handed off to {} {ago} ago, not yet accepted", h.to)) }
+                    else if h.is_for(s) { Some(format!("⇠ handoff from {} waiting ({ago}){}", h.from_session, if h.note.is_empty() { String::new() } else { format!(": {}", h.note) })) }
+                    else { None }
+                }).collect();
+                let handoffs = if handoffs.is_empty() { String::new() } else { format!("\n\nHANDOFFS\n{}", handoffs.join("\n")) };
+                let git = s.git.as_ref().map(|g| format!("Git: {g}\n")).unwrap_or_default();
+                let (agent_icon, agent_color) = agent_badge(&s.agent);
+                let mut detail = Text::from(Line::from(vec![Span::raw("Agent: "), Span::styled(format!("{agent_icon} {}", s.agent), Style::default().fg(agent_color))]));
+                detail.extend(Text::from(format!("CWD: {}\n{git}ID: {}\n\n", s.cwd, s.id)));
+                detail.lines.push(Line::styled(status_str, status_style));
+                // Every section starts with a blank line; the first newline just ends the status line
+                let rest = format!("{hint}{question}{approvals}{handoffs}{errors}{usage}{related}{transcript}{hook_detail}");
+                detail.extend(Text::from(rest.strip_prefix('\n').unwrap_or(&rest).to_string()));
                 let p = Paragraph::new(detail)
                     .block(Block::default().borders(Borders::ALL).title(" Detail "))
                     .wrap(Wrap { trim: true });
-                f.render_widget(p, chunks[1]);
+                if self.split_view {
+                    let parts = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Percentage(40), Constraint::Min(5)])
+                        .split(chunks[1]);
+                    f.render_widget(p, parts[0]);
+                    self.draw_hook_feed(f, parts[1], &s.id);
+                } else {
+                    f.render_widget(p, chunks[1]);
+                }
             }
         }
     }
 
+    // The session's latest hooks, newest at the bottom, as many as fit
+    fn draw_hook_feed(&self, f: &mut Frame, area: Rect, id: &str) {
+        let hooks = self.hooks.get(id).map(Vec::as_slice).unwrap_or_default();
+        let rows = area.height.saturating_sub(2) as usize;
+        let lines: Vec<Line> = hooks[hooks.len().saturating_sub(rows)..].iter().map(|h| {
+            let time = chrono::DateTime::from_timestamp(h.ts, 0)
+                .map(|t| t.with_timezone(&chrono::Local).format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            let style = if h.kind == HookKind::Error { Style::default().fg(Color::Red) } else { Style::default() };
+            Line::from(vec![Span::styled(format!("{time} "), Style::default().dim()), Span::styled(hook_summary(h), style)])
+        }).collect();
+        let p = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(" Hooks (last {}) ", hooks.len())));
+        f.render_widget(p, area);
+    }
+
     fn draw_chains(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -589,10 +1638,13 @@ impl App {
         let items: Vec<ListItem> = self.chains_filtered.iter()
             .map(|(name, count, score)| {
                 let score_str = if *score < 1.0 { format!(" ({:.0}%)", score * 100.0) } else { String::new() };
+                let status = self.chain_statuses.get(name).copied().unwrap_or_default();
+                let color = match status { ChainStatus::Active => Color::Green, ChainStatus::Blocked => Color::Red, ChainStatus::Done => Color::DarkGray };
                 ListItem::new(Line::from(vec![
                     Span::styled("🔗 ", Style::default().fg(Color::Cyan)),
                     Span::raw(name),
                     Span::styled(format!(" [{}]", count), Style::default().dim()),
+                    Span::styled(format!(" {}", status.as_str()), Style::default().fg(color)),
                     Span::styled(score_str, Style::default().dim()),
                 ]))
             })
@@ -611,6 +1663,48 @@ impl App {
         f.render_widget(p, chunks[1]);
     }
 
+    fn draw_todos(&mut self, f: &mut Frame, area: Rect) {
+        let items: Vec<ListItem> = self.todos.iter()
+            .map(|t| {
+                let (mark, text_style) = if t.done() { ("[x] ", Style::default().dim()) } else { ("[ ] ", Style::default()) };
+                let owner = match (&t.chain_name, &t.session_id) {
+                    (Some(chain), _) => format!("  🔗 {}", chain),
+                    (None, Some(session)) => format!("  {}", session),
+                    (None, None) => String::new(),
+                };
+                let mut spans = vec![Span::styled(mark, Style::default().fg(Color::Cyan)), Span::styled(t.text.as_str(), text_style)];
+                if let Some(a) = &t.assignee { spans.push(Span::styled(format!("  @{}", a), Style::default().fg(Color::Yellow))); }
+                spans.push(Span::styled(owner, Style::default().dim()));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let open = self.todos.iter().filter(|t| !t.done()).count();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!(" Todos ({} open, {} done) ", open, self.todos.len() - open)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(list, area, &mut self.todo_state);
+    }
+
+    fn draw_board(&mut self, f: &mut Frame, area: Rect) {
+        let columns = self.board_columns();
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, columns.len() as u32); columns.len()])
+            .split(area);
+        for (i, (title, color, cards)) in columns.into_iter().enumerate() {
+            let selected = i == self.board_col;
+            let items: Vec<ListItem> = cards.into_iter().map(|(_, label)| ListItem::new(label)).collect();
+            let border = if selected { Style::default().fg(color) } else { Style::default().dim() };
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).border_style(border)
+                    .title(Span::styled(format!(" {} ", title), Style::default().fg(color).add_modifier(Modifier::BOLD))))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            let mut state = ListState::default();
+            if selected { state.select(Some(self.board_rows[i])); }
+            f.render_stateful_widget(list, chunks[i], &mut state);
+        }
+    }
+
     fn draw_artifacts(&mut self, f: &mut Frame, area: Rect) {
         let chunks = Layout::default()
             .direction(Direction::Horizontal)
@@ -646,16 +1740,79 @@ impl App {
             .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
         f.render_stateful_widget(list, chunks[0], &mut self.artifact_state);
 
+        let mut detail = chunks[1];
+        if let Some((_, lines)) = &self.artifact_preview {
+            let split = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(lines.len() as u16 + 2), Constraint::Min(0)])
+                .split(detail);
+            f.render_widget(Paragraph::new(lines.clone()).block(Block::default().borders(Borders::ALL).title(" Preview ")), split[0]);
+            detail = split[1];
+        }
         let content = self.artifact_content.as_deref().unwrap_or("Select an artifact to view");
         let scroll_info = if self.artifact_scroll > 0 { format!(" Content [^{}] ", self.artifact_scroll) } else { " Content [h/l] ".into() };
         let p = Paragraph::new(content)
             .block(Block::default().borders(Borders::ALL).title(scroll_info))
             .wrap(Wrap { trim: false })
             .scroll((self.artifact_scroll, 0));
+        f.render_widget(p, detail);
+    }
+
+    fn draw_search(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+            .split(area);
+
+        let items: Vec<ListItem> = self.saved_searches.iter()
+            .map(|s| ListItem::new(Line::from(vec![
+                Span::styled("📌 ", Style::default().fg(Color::Magenta)),
+                Span::raw(&s.name),
+            ])))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Saved "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(list, chunks[0], &mut self.saved_state);
+
+        let title = if self.search_mode {
+            format!(" [/{}] ", self.search_query)
+        } else if self.search_label.is_empty() {
+            " [/] search ".into()
+        } else {
+            format!(" {} ({} results) ", self.search_label, self.search_results.len())
+        };
+        let mut lines = Vec::new();
+        for (i, r) in self.search_results.iter().enumerate() {
+            let icon = match r.result_type.as_str() { "artifact" => "📄", "memory" => "💡", _ => "🔗" };
+            let title_style = if i == self.result_sel {
+                Style::default().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+            } else {
+                Style::default().add_modifier(Modifier::BOLD)
+            };
+            let priority = format!("{}{}", if r.pinned { " 📌" } else { "" },
+                if r.importance > 0 { format!(" ★{}", r.importance) } else { String::new() });
+            lines.push(Line::from(vec![
+                Span::styled(format!("{icon} "), Style::default().fg(Color::Cyan)),
+                Span::styled(r.title.clone(), title_style),
+                Span::styled(r.page.map(|p| format!(" p.{p}")).unwrap_or_default(), Style::default().fg(Color::Yellow)),
+                Span::styled(priority, Style::default().fg(Color::Magenta)),
+                Span::styled(format!(" ({:.2})", r.score), Style::default().dim()),
+            ]));
+            lines.push(highlighted(&r.preview, &r.highlights));
+            lines.push(Line::from(""));
+        }
+        let p = Paragraph::new(lines)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false });
         f.render_widget(p, chunks[1]);
     }
 
     fn draw_history(&mut self, f: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(area);
         let items: Vec<ListItem> = self
             .history
             .iter()
@@ -663,35 +1820,84 @@ impl App {
                 let name = s.name.as_deref().unwrap_or(&s.id);
                 let last_msg = self.last_msgs.get(&s.id).map(|m| m.as_str()).unwrap_or("");
                 ListItem::new(vec![
-                    Line::from(format!("○ {name}")),
+                    Line::from(vec![Span::raw("○ "), agent_span(&s.agent), Span::raw(name)]),
                     Line::from(Span::styled(last_msg, Style::default().dim())),
                 ])
             })
             .collect();
-        let list = List::new(items).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" History (recent 20) "),
-        );
-        f.render_widget(list, area);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" History ({} most recent, {}{}{}, PgDn for older) ", self.history.len(), self.history_period.label(),
+                        self.agent_filter.as_ref().map(|a| format!(", {a}")).unwrap_or_default(),
+                        if self.history_search.is_empty() || self.search_mode { String::new() } else { format!(", ~{}", self.history_search) })),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        f.render_stateful_widget(list, chunks[0], &mut self.history_state);
+
+        // Written by --summarize-url once a session is done
+        let selected = self.history_state.selected().and_then(|i| self.history.get(i));
+        let summary = match (selected, selected.and_then(|s| s.summary.as_deref())) {
+            (_, Some(summary)) => Text::from(summary),
+            (Some(_), None) => Text::styled("No summary for this session", Style::default().dim()),
+            (None, None) => Text::default(),
+        };
+        let pane = Paragraph::new(summary)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title(" Summary "));
+        f.render_widget(pane, chunks[1]);
     }
 
     fn draw_status(&self, f: &mut Frame, area: Rect) {
         let search_text = match self.tab {
+            Tab::Active => &self.session_search,
             Tab::Chains => &self.chain_search,
             Tab::Artifacts => &self.artifact_search,
+            Tab::Search => &self.search_query,
+            Tab::History => &self.history_search,
             _ => "",
         };
-        let help = if self.search_mode {
+        let help = if let Some(id) = &self.confirm_delete {
+            format!(" Delete session {id} and all its data? [y] yes | any other key cancels ")
+        } else if self.comparison.is_some() {
+            " Comparing sessions | [Esc] close ".into()
+        } else if let Some(id) = &self.compare_pick {
+            format!(" Compare with {id}: select another session and press [c] | [Esc] cancel ")
+        } else if self.palette.is_some() {
+            " Command: type to filter | [↑/↓] choose | [Enter] run | [Esc] close ".into()
+        } else if let (true, Some((command, _))) = (self.input_mode, &self.command_input) {
+            format!(" {}: {}_ | [Enter] {} | [Esc] cancel ", command.prompt().unwrap_or_default(), self.input, command.label())
+        } else if self.search_mode {
             format!(" Search: {}_ | [Enter] select | [Esc] clear ", search_text)
+        } else if self.answer_choice.is_some() {
+            " Answer: [j/k] choose option | [Enter] submit | [Esc] cancel ".into()
+        } else if let (true, Some(target)) = (self.input_mode, &self.todo_input) {
+            let what = match target { TodoInput::New(chain) => format!("New todo for {chain}"), TodoInput::Assign(_) => "Assign to (empty unassigns)".into() };
+            format!(" {what}: {}_ | [Enter] save | [Esc] cancel ", self.input)
+        } else if self.input_mode && self.broadcasting {
+            format!(" Broadcast to all active sessions: {}_ | [Enter] send | [Esc] cancel ", self.input)
         } else if self.input_mode {
             format!(" Input: {}_ | [Enter] submit | [Esc] cancel ", self.input)
-        } else if matches!(self.tab, Tab::Chains | Tab::Artifacts) {
+        } else if self.tab == Tab::Chains {
+            " [/] search | [j/k] navigate | [s]tatus | [t]odo | [d]elete | [r]efresh | [q]uit ".into()
+        } else if self.tab == Tab::Board {
+            let other = if self.board_todos { "chains" } else { "todos" };
+            format!(" [h/l] column | [j/k] card | [H/L] move card | [v] show {other} | [r]efresh | [q]uit ")
+        } else if self.tab == Tab::Todos {
+            " [j/k] navigate | [x] done/undo | [a]ssign | [d]elete | [r]efresh | [q]uit ".into()
+        } else if self.tab == Tab::Artifacts {
             " [/] search | [j/k] navigate | [d]elete | [r]efresh | [q]uit ".into()
+        } else if self.tab == Tab::Search {
+            " [/] search | [s]ave query | [j/k] saved | [J/K] result | [p]in | [+/-] importance | [d]elete saved | [q]uit ".into()
+        } else if self.tab == Tab::History {
+            " [Tab] switch | [j/k] navigate | [/] name | [f] period | [g] agent | [a] resume | [c]ompare | [PgDn] older | [D]elete | [q]uit ".into()
         } else {
-            " [Tab] switch | [j/k] navigate | [d]one | [r]efresh | [q]uit ".into()
+            " [Tab] switch | [j/k] navigate | [/] search | [g] agent | [a]nswer | [A]pprovals | [B]roadcast | [v] hooks | [c]ompare | [d]one | [D]elete | [r]efresh | [q]uit ".into()
         };
-        let style = if self.search_mode || self.input_mode {
+        let style = if self.confirm_delete.is_some() {
+            Style::default().fg(Color::Red)
+        } else if self.compare_pick.is_some() || self.search_mode || self.input_mode || self.answer_choice.is_some() || self.palette.is_some() {
             Style::default().fg(Color::Yellow)
         } else {
             Style::default().dim()
@@ -702,3 +1908,274 @@ impl App {
         f.render_widget(p, area);
     }
 }
+
+// Sessions of `agent` (any agent when None) whose name (or id), agent, cwd or last hook preview
+// fuzzy-matches `query`, best first; all of them in their usual order when it is empty
+fn filter_sessions<'a>(sessions: &'a [Session], last_msgs: &HashMap<String, String>, agent: Option<&str>, query: &str) -> Vec<&'a Session> {
+    let of_agent = sessions.iter().filter(|s| agent.is_none_or(|a| s.agent.eq_ignore_ascii_case(a)));
+    if query.is_empty() { return of_agent.collect(); }
+    let query = query.to_lowercase();
+    let mut scored: Vec<(&Session, f64)> = of_agent
+        .filter_map(|s| {
+            let fields = [s.name.as_deref().unwrap_or(&s.id), &s.agent, &s.cwd, last_msgs.get(&s.id).map_or("", |m| m.as_str())];
+            let score = fields.iter().map(|field| {
+                let field = field.to_lowercase();
+                let boost = if field.contains(&query) { 0.3 } else { 0.0 };
+                (jaro_winkler(&field, &query) + boost).min(1.0)
+            }).fold(0.0, f64::max);
+            if score > 0.4 { Some((s, score)) } else { None }
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    scored.into_iter().map(|(s, _)| s).collect()
+}
+
+// Preview with matched byte ranges emphasised
+fn highlighted<'a>(preview: &'a str, ranges: &[[usize; 2]]) -> Line<'a> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for &[start, end] in ranges {
+        if start < last || end > preview.len() || !preview.is_char_boundary(start) || !preview.is_char_boundary(end) { continue; }
+        spans.push(Span::styled(&preview[last..start], Style::default().dim()));
+        spans.push(Span::styled(&preview[start..end], Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+        last = end;
+    }
+    spans.push(Span::styled(&preview[last..], Style::default().dim()));
+    Line::from(spans)
+}
+
+// "12.3k in / 4.5k out · ~$0.42"
+fn format_usage(u: &Usage) -> String {
+    let k = |n: u64| if n >= 1000 { format!("{:.1}k", n as f64 / 1000.0) } else { n.to_string() };
+    format!("{} in / {} out · ~${:.2}", k(u.input_tokens), k(u.output_tokens), u.cost_usd)
+}
+
+fn is_stuck(tool: &ActiveTool, after_secs: i64) -> bool {
+    now() - tool.since > after_secs
+}
+
+// 45s, 2m13s, 1h05m
+fn format_elapsed(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+// One bar per minute, scaled to the busiest minute in the window; idle minutes render as spaces
+fn sparkline(counts: &[u64]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = counts.iter().copied().max().unwrap_or(0);
+    if max == 0 { return String::new(); }
+    counts.iter().map(|&c| if c == 0 { ' ' } else { BARS[((c * 8 - 1) / max).min(7) as usize] }).collect()
+}
+
+fn set<T>(map: &mut HashMap<String, T>, id: &str, value: Option<T>) {
+    match value {
+        Some(v) => { map.insert(id.to_string(), v); }
+        None => { map.remove(id); }
+    }
+}
+
+// Background loader: batches whatever was requested since the last pass, so a burst of hooks on one
+// session costs one reload, and reloads only the sessions named unless a full reload was asked for
+async fn load(store: Store, mut requests: UnboundedReceiver<Load>, updates: UnboundedSender<Update>) {
+    // How much history the UI has paged in, and through which filters, so full reloads keep it
+    let mut history_len = HISTORY_PAGE;
+    let mut filter = HistoryQuery::default();
+    while let Some(first) = requests.recv().await {
+        let mut batch = vec![first];
+        while let Ok(more) = requests.try_recv() { batch.push(more); }
+        let update_all = batch.iter().any(|l| matches!(l, Load::All));
+        let todos = batch.iter().any(|l| matches!(l, Load::Todos));
+        let handoffs = batch.iter().any(|l| matches!(l, Load::Handoffs));
+        let older = batch.iter().filter(|l| matches!(l, Load::OlderHistory)).count() * HISTORY_PAGE;
+        if let Some(q) = batch.iter().rev().find_map(|l| match l { Load::History(q) => Some(q.clone()), _ => None }) {
+            (filter, history_len) = (q, HISTORY_PAGE);
+            let first = HistoryQuery { limit: history_len, offset: 0, ..filter.clone() };
+            if let Ok(page) = store.history_page(&first).await {
+                if updates.send(Update::History(sessions_data(&store, page, false).await, true)).is_err() { return; }
+            }
+        }
+        let mut ids: Vec<String> = batch.into_iter().filter_map(|l| match l { Load::Session(id) => Some(id), _ => None }).collect();
+        ids.sort();
+        ids.dedup();
+        if todos && !update_all {
+            if let Ok(todos) = store.list_todos(&TodoQuery::default()).await {
+                if updates.send(Update::Todos(todos)).is_err() { return; }
+            }
+        }
+        if handoffs && !update_all {
+            if let Ok(handoffs) = store.list_handoffs(false).await {
+                if updates.send(Update::Handoffs(handoffs)).is_err() { return; }
+            }
+        }
+        if older > 0 && !update_all {
+            if let Ok(page) = store.history_page(&HistoryQuery { limit: older, offset: history_len, ..filter.clone() }).await {
+                history_len += older;
+                if updates.send(Update::History(sessions_data(&store, page, false).await, false)).is_err() { return; }
+            }
+        } else {
+            history_len += older;
+        }
+        let sent = if update_all {
+            match load_all(&store, &HistoryQuery { limit: history_len, offset: 0, ..filter.clone() }).await {
+                Ok(snapshot) => updates.send(Update::All(Box::new(snapshot))).is_ok(),
+                Err(_) => true, // keep showing the last data; the header reports Redis outages
+            }
+        } else {
+            let mut open = true;
+            for id in ids {
+                if let Ok(update) = load_one(&store, id).await { open &= updates.send(update).is_ok(); }
+            }
+            open
+        };
+        if !sent { return; }
+    }
+}
+
+/// One full reload, as on start and on `r`, for `tinymem bench`
+pub(crate) async fn reload(store: &Store) -> Result<()> { load_all(store, &HistoryQuery::default()).await.map(|_| ()) }
+
+async fn load_all(store: &Store, history: &HistoryQuery) -> Result<Snapshot> {
+    let sessions = sessions_data(store, store.list_sessions(&Status::Active, usize::MAX, 0).await?, true).await;
+    let history = sessions_data(store, store.history_page(history).await?, false).await;
+    let mut chains = Vec::new();
+    for name in store.list_chain_names().await.unwrap_or_default() {
+        let count = store.get_chain_links(&name).await.map(|l| l.len()).unwrap_or(0);
+        chains.push((name, count));
+    }
+    Ok(Snapshot {
+        sessions,
+        history,
+        usage_today: usage_today(store).await,
+        chains,
+        chain_statuses: store.chain_statuses().await.unwrap_or_default(),
+        todos: store.list_todos(&TodoQuery::default()).await.unwrap_or_default(),
+        handoffs: store.list_handoffs(false).await.unwrap_or_default(),
+        artifacts: store.list_artifacts().await.unwrap_or_default(),
+        saved_searches: store.list_saved_searches().await.unwrap_or_default(),
+    })
+}
+
+async fn load_one(store: &Store, id: String) -> Result<Update> {
+    let Some(session) = store.get_session(&id).await? else { return Ok(Update::Gone(id)) };
+    let active = store.list_active().await?.contains(&id);
+    let data = load_session(store, session, active).await;
+    Ok(Update::Session(Box::new(data), active, usage_today(store).await))
+}
+
+async fn sessions_data(store: &Store, sessions: Vec<Session>, active: bool) -> Vec<SessionData> {
+    futures::future::join_all(sessions.into_iter().map(|s| load_session(store, s, active))).await
+}
+
+async fn usage_today(store: &Store) -> Usage {
+    store.daily_usage(1).await.ok().and_then(|d| d.into_iter().next()).map(|(_, u)| u).unwrap_or_default()
+}
+
+async fn load_session(store: &Store, session: Session, active: bool) -> SessionData {
+    let mut d = SessionData {
+        active_tools: None, pending_ask: None, approvals: None, related: None, activity: None, usage: None, transcript: None, errors: None,
+        last_hook: last_hook(store, &session.id).await,
+        hooks: None,
+        session,
+    };
+    if !active { return d; }
+    let id = d.session.id.as_str();
+    d.hooks = store.get_hooks(id, HOOK_FEED).await.ok();
+    d.active_tools = store.get_active_tools(id).await.ok().filter(|t| !t.is_empty());
+    d.errors = store.recent_errors(id, 20).await.ok().filter(|e| !e.is_empty());
+    d.pending_ask = store.get_pending_ask(id).await.ok().flatten();
+    d.approvals = store.pending_approvals(id).await.ok().filter(|a| !a.is_empty());
+    if let Ok((chains, artifacts)) = store.session_related(id).await {
+        let mut lines = Vec::new();
+        if !chains.is_empty() { lines.push(format!("Chains: {}", chains.join(", "))); }
+        if !artifacts.is_empty() {
+            lines.push(format!("Artifacts: {}", artifacts.iter().map(|a| a.title.as_str()).collect::<Vec<_>>().join(", ")));
+        }
+        if !lines.is_empty() { d.related = Some(format!("\n\nRelated\n{}", lines.join("\n"))); }
+    }
+    d.activity = store.activity(id, SPARKLINE_MINUTES).await.ok();
+    d.usage = store.session_usage(id).await.ok().filter(|u| u.input_tokens + u.output_tokens > 0);
+    // Recent conversation turns, so the detail pane shows what the agent was asked
+    if let Ok(entries) = store.get_transcript(id, 6).await {
+        if !entries.is_empty() {
+            let lines: Vec<String> = entries.iter().map(|e| {
+                let who = match e.role.as_str() { "user" => "▶ user", "operator" => "✉ operator", _ => "◀ agent" };
+                let text = e.text.replace('\n', " ");
+                let text = preview(&text, 300);
+                format!("{who}: {text}")
+            }).collect();
+            d.transcript = Some(format!("\n\nTranscript\n{}", lines.join("\n")));
+        }
+    }
+    d
+}
+
+// Last hook as (list preview, detail pane text)
+async fn last_hook(store: &Store, id: &str) -> Option<(String, String)> {
+    let hooks = store.get_hooks(id, 1).await.ok()?;
+    let hook = hooks.last()?;
+    let full_meta = serde_json::to_string_pretty(&hook.meta).unwrap_or_default();
+    let detail = format!("Last: {} {}\n\n{}", hook_icon(&hook.kind), hook.task,
+        preview(&full_meta, 1000));
+    Some((hook_summary(hook), detail))
+}
+
+// Agents tinymem ships glue for get fixed badges; any other name hashes to one of the rest, the same
+// one every run (FNV-1a, not the std hasher, whose output may change between releases)
+const AGENT_BADGES: &[(&str, &str, Color)] = &[
+    ("claude", "✻", Color::LightRed), ("cursor", "▲", Color::LightBlue), ("codex", "◆", Color::LightGreen), ("aider", "◇", Color::LightYellow),
+];
+const OTHER_ICONS: &[&str] = &["■", "▼", "◀", "▶", "★", "♦", "♣", "♠"];
+const OTHER_COLORS: &[Color] = &[Color::Cyan, Color::Magenta, Color::Yellow, Color::Green, Color::Blue, Color::LightCyan, Color::LightMagenta];
+
+fn agent_badge(agent: &str) -> (&'static str, Color) {
+    let agent = agent.to_lowercase();
+    if let Some((_, icon, color)) = AGENT_BADGES.iter().find(|(name, _, _)| agent.starts_with(name)) { return (icon, *color); }
+    let hash = agent.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)) as usize;
+    (OTHER_ICONS[hash % OTHER_ICONS.len()], OTHER_COLORS[hash / OTHER_ICONS.len() % OTHER_COLORS.len()])
+}
+
+fn agent_span(agent: &str) -> Span<'static> {
+    let (icon, color) = agent_badge(agent);
+    Span::styled(format!("{icon} "), Style::default().fg(color))
+}
+
+fn hook_icon(kind: &HookKind) -> &'static str {
+    match kind {
+        HookKind::Pre => "→",
+        HookKind::Post => "✓",
+        HookKind::Error => "✗",
+        HookKind::Notification => "!",
+        HookKind::UserPrompt => "▶",
+        HookKind::Stop => "■",
+        HookKind::Other(_) => "·",
+    }
+}
+
+// Icon, tool and its most telling argument, on one line
+fn hook_summary(hook: &Hook) -> String {
+    let kind = hook_icon(&hook.kind);
+    let meta_str = if let Some(obj) = hook.meta.as_object() {
+        let priority_keys = ["file_path", "command", "pattern", "query", "url", "skill", "prompt"];
+        let mut found = None;
+        for key in priority_keys {
+            if let Some(serde_json::Value::String(val)) = obj.get(key) {
+                let val = val.replace('\n', " ");
+                found = Some(preview(&val, 42));
+                break;
+            }
+        }
+        found.unwrap_or_default()
+    } else {
+        String::new()
+    };
+    if meta_str.is_empty() {
+        format!("{} {}", kind, hook.task)
+    } else {
+        format!("{} {} ({})", kind, hook.task, meta_str)
+    }
+}
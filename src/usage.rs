@@ -0,0 +1,22 @@
+//! Token cost estimates for the usage totals kept by [`crate::Store::record_usage`].
+
+// USD per million (input, output) tokens, matched by substring of the model name; first match wins
+const PRICES: &[(&str, f64, f64)] = &[
+    ("opus", 15.0, 75.0),
+    ("sonnet", 3.0, 15.0),
+    ("haiku", 0.8, 4.0),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("o3", 2.0, 8.0),
+    ("gemini-2.5-pro", 1.25, 10.0),
+    ("gemini-2.5-flash", 0.3, 2.5),
+];
+
+/// Estimated USD cost of a request; 0.0 for models without a known price
+pub fn estimate_cost(model: &str, input_tokens: u64, output_tokens: u64) -> f64 {
+    let model = model.to_lowercase();
+    PRICES.iter().find(|(name, _, _)| model.contains(name))
+        .map_or(0.0, |(_, i, o)| (input_tokens as f64 * i + output_tokens as f64 * o) / 1_000_000.0)
+}
@@ -0,0 +1,312 @@
+//! Request validation for the HTTP API. POST bodies are extracted with [`Valid`], which turns malformed
+//! JSON and failed [`Validate`] checks into an RFC 7807 `application/problem+json` response:
+//!
+//! ```json
+//! {"type": "about:blank", "title": "Invalid request", "status": 400, "detail": "1 invalid field",
+//!  "errors": [{"field": "slug", "message": "may only contain letters, digits, '-', '_' and '.'"}]}
+//! ```
+
+use axum::{async_trait, extract::{FromRef, FromRequest, Request}, http::{header, StatusCode}, response::{IntoResponse, Response}, Json};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use crate::models::{AnswerReq, ApproveReq, ArtifactSaveReq, AskReq, BroadcastReq, ChainSaveReq, ChainSearchReq, ChainStatusReq, CreateSessionReq,
+    DedupeReq, GlobalSearchReq, HookReq, MemorySaveReq, PriorityReq, SavedSearch, StartReq, TodoReq, AssignReq, HandoffReq, HandoffAcceptReq, TranscriptReq, UsageReq};
+
+/// Names, slugs, titles, questions and other one-line fields
+pub const MAX_FIELD_BYTES: usize = 1024;
+const MAX_LIMIT: usize = 1000;
+const MAX_ASK_OPTIONS: usize = 20;
+const MAX_LIST_ENTRIES: usize = 100;
+
+/// Size limits for request bodies, configurable per server (`--max-body-bytes`, `--max-content-bytes`)
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    /// Whole request body; larger ones are refused with 413 before they are read
+    pub max_body_bytes: usize,
+    /// Chain link, memory and transcript text, artifact descriptions and serialized hook `meta`
+    /// (which carries whole files for writes)
+    pub max_content_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self { Self { max_body_bytes: 4 * 1024 * 1024, max_content_bytes: 1024 * 1024 } }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError { pub field: String, pub message: String }
+
+/// An RFC 7807 problem; `errors` is only sent when there are field-level details, and `extensions` are
+/// extra members sent next to `detail`
+#[derive(Debug)]
+pub struct Problem {
+    pub status: StatusCode, pub title: String, pub detail: String, pub errors: Vec<FieldError>,
+    pub extensions: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        let title = status.canonical_reason().unwrap_or("Error").to_string();
+        Self { status, title, detail: detail.into(), errors: vec![], extensions: Default::default() }
+    }
+
+    pub fn with(mut self, member: &str, value: impl Serialize) -> Self {
+        self.extensions.insert(member.into(), json!(value));
+        self
+    }
+
+    /// 400 for a single bad field
+    pub fn field(field: &str, message: impl Into<String>) -> Self {
+        Self::invalid(vec![FieldError { field: field.into(), message: message.into() }])
+    }
+
+    pub fn invalid(errors: Vec<FieldError>) -> Self {
+        let detail = format!("{} invalid field{}", errors.len(), if errors.len() == 1 { "" } else { "s" });
+        Self { status: StatusCode::BAD_REQUEST, title: "Invalid request".into(), detail, errors, extensions: Default::default() }
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response {
+        let mut body = json!({ "type": "about:blank", "title": self.title, "status": self.status.as_u16(), "detail": self.detail });
+        if !self.errors.is_empty() { body["errors"] = json!(self.errors); }
+        for (member, value) in self.extensions { body[member] = value; }
+        (self.status, [(header::CONTENT_TYPE, "application/problem+json")], Json(body)).into_response()
+    }
+}
+
+/// Collects every failed check so one response reports all bad fields
+pub struct Checks { errors: Vec<FieldError>, limits: Limits }
+
+impl Checks {
+    pub fn new(limits: Limits) -> Self { Self { errors: vec![], limits } }
+
+    pub fn check(&mut self, field: &str, ok: bool, message: &str) -> &mut Self {
+        if !ok { self.errors.push(FieldError { field: field.into(), message: message.into() }); }
+        self
+    }
+
+    pub fn non_empty(&mut self, field: &str, value: &str) -> &mut Self {
+        self.check(field, !value.trim().is_empty(), "must not be empty")
+    }
+
+    pub fn max_bytes(&mut self, field: &str, value: &str, max: usize) -> &mut Self {
+        self.check(field, value.len() <= max, &format!("must be at most {max} bytes"))
+    }
+
+    /// Within the configured content limit
+    pub fn content(&mut self, field: &str, value: &str) -> &mut Self {
+        self.max_bytes(field, value, self.limits.max_content_bytes)
+    }
+
+    /// Non-empty, short, and free of ':' (the separator in `chain:name:slug` ids and Redis keys)
+    pub fn name(&mut self, field: &str, value: &str) -> &mut Self {
+        self.non_empty(field, value).max_bytes(field, value, MAX_FIELD_BYTES)
+            .check(field, !value.contains(':'), "must not contain ':'")
+    }
+
+    pub fn slug(&mut self, field: &str, value: &str) -> &mut Self {
+        let ok = value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        self.non_empty(field, value).max_bytes(field, value, MAX_FIELD_BYTES)
+            .check(field, ok, "may only contain letters, digits, '-', '_' and '.'")
+    }
+
+    pub fn limit(&mut self, field: &str, value: usize) -> &mut Self {
+        self.check(field, (1..=MAX_LIMIT).contains(&value), &format!("must be between 1 and {MAX_LIMIT}"))
+    }
+
+    /// At most [`MAX_LIST_ENTRIES`] non-empty entries of up to `max` bytes each
+    pub fn entries(&mut self, field: &str, values: &[String], max: usize) -> &mut Self {
+        self.check(field, values.len() <= MAX_LIST_ENTRIES, &format!("must have at most {MAX_LIST_ENTRIES} entries"))
+            .check(field, values.iter().all(|v| !v.trim().is_empty() && v.len() <= max), &format!("entries must be non-empty and at most {max} bytes"))
+    }
+
+    pub fn importance(&mut self, field: &str, value: Option<u8>) -> &mut Self {
+        self.check(field, value.is_none_or(|i| i <= 10), "must be between 0 and 10")
+    }
+}
+
+pub trait Validate {
+    fn validate(&self, c: &mut Checks);
+}
+
+/// `Json<T>` that also runs `T`'s checks; both failures answer with a [`Problem`]
+pub struct Valid<T>(pub T);
+
+#[async_trait]
+impl<S: Send + Sync, T: DeserializeOwned + Validate> FromRequest<S> for Valid<T> where Limits: FromRef<S> {
+    type Rejection = Problem;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Problem> {
+        let Json(value) = Json::<T>::from_request(req, state).await.map_err(|e| Problem::new(e.status(), e.body_text()))?;
+        let mut checks = Checks::new(Limits::from_ref(state));
+        value.validate(&mut checks);
+        if checks.errors.is_empty() { Ok(Valid(value)) } else { Err(Problem::invalid(checks.errors)) }
+    }
+}
+
+impl Validate for CreateSessionReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("agent", &self.agent).max_bytes("agent", &self.agent, MAX_FIELD_BYTES);
+        if let Some(name) = &self.name { c.name("name", name); }
+    }
+}
+
+impl Validate for StartReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("agent", &self.agent).max_bytes("agent", &self.agent, MAX_FIELD_BYTES);
+        if let Some((provider, _)) = self.external_key() { c.name("provider", &provider); }
+    }
+}
+
+impl Validate for HookReq {
+    fn validate(&self, c: &mut Checks) {
+        c.max_bytes("task", &self.task, MAX_FIELD_BYTES).content("meta", &self.meta.to_string());
+    }
+}
+
+impl Validate for TranscriptReq {
+    fn validate(&self, c: &mut Checks) {
+        c.check("role", self.role == "user" || self.role == "assistant", "must be user or assistant")
+            .content("text", &self.text);
+    }
+}
+
+impl Validate for UsageReq {
+    fn validate(&self, c: &mut Checks) {
+        c.max_bytes("model", &self.model, MAX_FIELD_BYTES)
+            .check("cost_usd", self.cost_usd.is_none_or(|v| v >= 0.0), "must not be negative");
+    }
+}
+
+impl Validate for AskReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("question", &self.question).content("question", &self.question)
+            .check("options", self.options.len() <= MAX_ASK_OPTIONS, &format!("must have at most {MAX_ASK_OPTIONS} entries"))
+            .check("options", self.options.iter().all(|o| !o.trim().is_empty() && o.len() <= MAX_FIELD_BYTES),
+                &format!("entries must be non-empty and at most {MAX_FIELD_BYTES} bytes"));
+    }
+}
+
+impl Validate for AnswerReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("answer", &self.answer).content("answer", &self.answer);
+    }
+}
+
+impl Validate for BroadcastReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("text", &self.text).content("text", &self.text);
+    }
+}
+
+impl Validate for ApproveReq {
+    fn validate(&self, c: &mut Checks) {
+        c.max_bytes("reason", &self.reason, MAX_FIELD_BYTES);
+    }
+}
+
+impl Validate for ChainSaveReq {
+    fn validate(&self, c: &mut Checks) {
+        c.name("chain_name", &self.chain_name)
+            .slug("slug", &self.slug)
+            .check("content", self.has_sections() || !self.content.trim().is_empty(), "must not be empty without completed, next_steps, decisions or files_touched")
+            .content("content", &self.content)
+            .importance("importance", Some(self.importance));
+        let max = c.limits.max_content_bytes;
+        c.entries("completed", &self.completed, max).entries("next_steps", &self.next_steps, max)
+            .entries("decisions", &self.decisions, max).entries("files_touched", &self.files_touched, MAX_FIELD_BYTES);
+        if let Some(git) = &self.git {
+            c.non_empty("git.repo", &git.repo).max_bytes("git.repo", &git.repo, MAX_FIELD_BYTES)
+                .max_bytes("git.branch", git.branch.as_deref().unwrap_or_default(), MAX_FIELD_BYTES)
+                .check("git.commit", (4..=64).contains(&git.commit.len()) && git.commit.chars().all(|c| c.is_ascii_hexdigit()),
+                    "must be a hex commit id");
+        }
+    }
+}
+
+impl Validate for ChainStatusReq {
+    fn validate(&self, c: &mut Checks) {
+        if let Some(slug) = &self.slug { c.non_empty("slug", slug).max_bytes("slug", slug, MAX_FIELD_BYTES); }
+    }
+}
+
+impl Validate for ChainSearchReq {
+    fn validate(&self, c: &mut Checks) {
+        c.max_bytes("query", &self.query, MAX_FIELD_BYTES).limit("limit", self.limit);
+    }
+}
+
+impl Validate for GlobalSearchReq {
+    fn validate(&self, c: &mut Checks) {
+        c.max_bytes("query", &self.query, MAX_FIELD_BYTES).limit("limit", self.limit);
+    }
+}
+
+impl Validate for SavedSearch {
+    fn validate(&self, c: &mut Checks) {
+        c.name("name", &self.name).max_bytes("query", &self.query, MAX_FIELD_BYTES).limit("limit", self.limit);
+    }
+}
+
+impl Validate for ArtifactSaveReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("file_path", &self.file_path)
+            .non_empty("title", &self.title).max_bytes("title", &self.title, MAX_FIELD_BYTES)
+            .content("description", &self.description);
+    }
+}
+
+impl Validate for MemorySaveReq {
+    fn validate(&self, c: &mut Checks) {
+        c.max_bytes("title", &self.title, MAX_FIELD_BYTES)
+            .non_empty("content", &self.content).content("content", &self.content)
+            .check("ttl_secs", self.ttl_secs.is_none_or(|t| t > 0), "must be positive")
+            .check("decay_days", self.decay_days.is_none_or(|d| d > 0), "must be positive")
+            .importance("importance", Some(self.importance));
+    }
+}
+
+impl Validate for TodoReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("text", &self.text).content("text", &self.text)
+            .check("chain_name", self.chain_name.is_some() || self.session_id.is_some(), "a todo needs a chain_name or session_id");
+        if let Some(name) = &self.chain_name { c.name("chain_name", name); }
+        if let Some(id) = &self.session_id { c.non_empty("session_id", id).max_bytes("session_id", id, MAX_FIELD_BYTES); }
+        if let Some(a) = &self.assignee { c.non_empty("assignee", a).max_bytes("assignee", a, MAX_FIELD_BYTES); }
+    }
+}
+
+impl Validate for AssignReq {
+    fn validate(&self, c: &mut Checks) {
+        if let Some(a) = &self.assignee { c.non_empty("assignee", a).max_bytes("assignee", a, MAX_FIELD_BYTES); }
+    }
+}
+
+impl Validate for HandoffReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("session_id", &self.session_id).max_bytes("session_id", &self.session_id, MAX_FIELD_BYTES)
+            .non_empty("to", &self.to).max_bytes("to", &self.to, MAX_FIELD_BYTES)
+            .content("note", &self.note);
+        if let Some(name) = &self.chain_name { c.name("chain_name", name); }
+    }
+}
+
+impl Validate for HandoffAcceptReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("session_id", &self.session_id).max_bytes("session_id", &self.session_id, MAX_FIELD_BYTES);
+        if let Some(id) = &self.id { c.non_empty("id", id); }
+    }
+}
+
+impl Validate for PriorityReq {
+    fn validate(&self, c: &mut Checks) {
+        c.non_empty("id", &self.id).importance("importance", self.importance);
+    }
+}
+
+impl Validate for DedupeReq {
+    fn validate(&self, c: &mut Checks) {
+        c.check("threshold", (0.0..=1.0).contains(&self.threshold), "must be between 0 and 1")
+            .check("merge_above", self.merge_above.is_none_or(|m| (0.0..=1.0).contains(&m)), "must be between 0 and 1");
+    }
+}
@@ -0,0 +1,82 @@
+//! `--watch-dir`: files matching `--watch-glob` that appear or change under a watched directory are
+//! saved as artifacts of the `watcher` session (tagged `watched`), so reports agents drop into e.g.
+//! `./reports/` are captured without a tool call. A file that changes again re-indexes the artifact
+//! already saved for it rather than adding another.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use ::notify::{Event, EventKind, RecursiveMode, Watcher};
+use anyhow::{Context, Result};
+use crate::git;
+use crate::models::{Artifact, IndexState, IndexStatus};
+use crate::project::project_id;
+use crate::events::EventBus;
+use crate::server::index_artifact;
+use crate::store::Store;
+
+pub const SESSION: &str = "watcher";
+// Writers often touch a file several times; it is saved once it has been quiet this long
+const SETTLE: Duration = Duration::from_secs(1);
+
+/// Starts watching `dir` recursively; fails at once on a missing directory or a bad glob
+pub fn watch(store: Store, events: EventBus, dir: &Path, glob: &str) -> Result<()> {
+    let dir = std::fs::canonicalize(dir).with_context(|| format!("watching {}", dir.display()))?;
+    let pattern = glob::Pattern::new(glob).with_context(|| format!("invalid --watch-glob {glob}"))?;
+    let dir_str = dir.to_string_lossy();
+    let project = project_id(&dir_str, git::detect(&dir_str).as_ref());
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = ::notify::recommended_watcher(move |res: ::notify::Result<Event>| match res {
+        Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+            for path in event.paths { let _ = tx.send(path); }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("watcher: {e}"),
+    })?;
+    watcher.watch(&dir, RecursiveMode::Recursive).with_context(|| format!("watching {}", dir.display()))?;
+
+    tokio::spawn(async move {
+        let _watcher = watcher; // dropping it stops the events
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+        let mut tick = tokio::time::interval(Duration::from_millis(250));
+        loop {
+            tokio::select! {
+                Some(path) = rx.recv() => { pending.insert(path, Instant::now()); }
+                _ = tick.tick() => {
+                    let settled: Vec<PathBuf> = pending.iter().filter(|(_, t)| t.elapsed() >= SETTLE).map(|(p, _)| p.clone()).collect();
+                    for path in settled {
+                        pending.remove(&path);
+                        if !wanted(&dir, &path, &pattern) { continue; }
+                        if let Err(e) = ingest(&store, &events, &path, &project).await { eprintln!("watcher: {}: {e}", path.display()); }
+                    }
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+// Regular files outside hidden directories (editor swap files, .git) whose path relative to the
+// watched directory matches the glob
+fn wanted(dir: &Path, path: &Path, pattern: &glob::Pattern) -> bool {
+    let Ok(rel) = path.strip_prefix(dir) else { return false };
+    let hidden = rel.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+    !hidden && path.is_file() && pattern.matches_path(rel)
+}
+
+async fn ingest(store: &Store, events: &EventBus, path: &Path, project: &Option<String>) -> Result<()> {
+    let file_path = path.to_string_lossy().to_string();
+    let artifact = match store.list_artifacts().await?.into_iter().find(|a| a.file_path == file_path) {
+        Some(existing) => existing,
+        None => {
+            let title = path.file_name().map_or_else(|| file_path.clone(), |n| n.to_string_lossy().to_string());
+            let artifact = Artifact::new(file_path, title, "Saved by the directory watcher".into(), SESSION.into(), vec!["watched".into()]);
+            let artifact = Artifact { project: project.clone(), ..artifact };
+            store.save_artifact(&artifact).await?;
+            artifact
+        }
+    };
+    store.set_artifact_status(&artifact.id, &IndexStatus::new(IndexState::Indexing, 0, None)).await?;
+    index_artifact(store.clone(), events.clone(), artifact).await;
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use anyhow::{bail, Result};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError};
+use crate::events::{Event, EventKind};
+use crate::models::now;
+
+const MAX_ATTEMPTS: u32 = 4;
+// Per attempt, so a target that never answers can't hold a blocking-pool thread
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebhookEvent { SessionStarted, SessionDone, SessionRejected, AskPending, ChainLinkSaved }
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::SessionStarted => "session.started",
+            WebhookEvent::SessionDone => "session.done",
+            WebhookEvent::SessionRejected => "session.rejected",
+            WebhookEvent::AskPending => "ask.pending",
+            WebhookEvent::ChainLinkSaved => "chain_link.saved",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "session.started" => Some(WebhookEvent::SessionStarted),
+            "session.done" => Some(WebhookEvent::SessionDone),
+            "session.rejected" => Some(WebhookEvent::SessionRejected),
+            "ask.pending" => Some(WebhookEvent::AskPending),
+            "chain_link.saved" => Some(WebhookEvent::ChainLinkSaved),
+            _ => None,
+        }
+    }
+
+    fn from_kind(kind: EventKind) -> Option<Self> {
+        match kind {
+            EventKind::SessionStarted => Some(WebhookEvent::SessionStarted),
+            EventKind::SessionDone => Some(WebhookEvent::SessionDone),
+            EventKind::SessionRejected => Some(WebhookEvent::SessionRejected),
+            EventKind::AskPending => Some(WebhookEvent::AskPending),
+            EventKind::ChainLinkSaved => Some(WebhookEvent::ChainLinkSaved),
+            _ => None,
+        }
+    }
+}
+
+// Outbound webhook targets: each URL either listens to one event type or to all (None)
+#[derive(Clone, Default)]
+pub struct Webhooks { targets: Vec<(Option<WebhookEvent>, String)>, secret: String }
+
+impl Webhooks {
+    /// Parses `EVENT=URL` or bare `URL` specs (bare URLs receive every event)
+    pub fn new(specs: &[String], secret: &str) -> Result<Self> {
+        let mut targets = Vec::new();
+        for spec in specs.iter().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            match spec.split_once('=') {
+                Some((event, url)) if !event.contains("://") => match WebhookEvent::parse(event) {
+                    Some(ev) => targets.push((Some(ev), url.to_string())),
+                    None => bail!("unknown webhook event '{event}' (expected session.started, session.done, session.rejected, ask.pending or chain_link.saved)"),
+                },
+                _ => targets.push((None, spec.to_string())),
+            }
+        }
+        Ok(Self { targets, secret: secret.to_string() })
+    }
+
+    /// Fire-and-forget delivery of `data` to every target subscribed to `event`
+    pub fn emit(&self, event: WebhookEvent, data: Value) {
+        let urls: Vec<String> = self.targets.iter()
+            .filter(|(ev, _)| ev.is_none_or(|e| e == event))
+            .map(|(_, url)| url.clone())
+            .collect();
+        if urls.is_empty() { return; }
+        let body = json!({ "event": event.as_str(), "ts": now(), "data": data }).to_string();
+        let signature = (!self.secret.is_empty()).then(|| sign(&self.secret, &body));
+        for url in urls {
+            let (body, signature) = (body.clone(), signature.clone());
+            tokio::spawn(async move { deliver(url, event, body, signature).await });
+        }
+    }
+
+    /// Emits every bus event that has a webhook counterpart, with the event's data as payload
+    pub fn listen(self, mut events: broadcast::Receiver<Event>) {
+        if self.targets.is_empty() { return; }
+        tokio::spawn(async move {
+            loop {
+                match events.recv().await {
+                    Ok(ev) => if let Some(event) = WebhookEvent::from_kind(ev.kind) { self.emit(event, ev.data) },
+                    Err(RecvError::Lagged(n)) => eprintln!("webhooks: fell behind, {n} events not delivered"),
+                    Err(RecvError::Closed) => return,
+                }
+            }
+        });
+    }
+}
+
+// POST with exponential backoff (1s, 2s, 4s) between attempts
+async fn deliver(url: String, event: WebhookEvent, body: String, signature: Option<String>) {
+    let agent: ureq::Agent = ureq::Agent::config_builder().timeout_global(Some(TIMEOUT)).build().into();
+    for attempt in 0..MAX_ATTEMPTS {
+        let (agent, u, b, sig) = (agent.clone(), url.clone(), body.clone(), signature.clone());
+        let sent = tokio::task::spawn_blocking(move || {
+            let mut req = agent.post(&u)
+                .header("Content-Type", "application/json")
+                .header("X-Tinymem-Event", event.as_str());
+            if let Some(sig) = &sig { req = req.header("X-Tinymem-Signature", &format!("sha256={sig}")); }
+            req.send(&b).map(|_| ()).map_err(|e| e.to_string())
+        }).await;
+        match sent {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) if attempt + 1 == MAX_ATTEMPTS => eprintln!("webhook {url} failed: {e}"),
+            _ => tokio::time::sleep(Duration::from_secs(1 << attempt)).await,
+        }
+    }
+}
+
+fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts any key length");
+    mac.update(body.as_bytes());
+    mac.finalize().into_bytes().iter().map(|b| format!("{b:02x}")).collect()
+}